@@ -0,0 +1,300 @@
+use std::fmt::Write;
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use unicode_width::UnicodeWidthStr;
+
+const ESC: char = '\x1b';
+
+/// Converts text containing ANSI escape sequences into styled lines.
+///
+/// SGR sequences (colors and text attributes) are applied on top of `base`;
+/// any other escape sequence (cursor movement, OSC titles, ...) is dropped.
+pub fn parse_ansi(text: &str, base: Style) -> Vec<Line<'static>> {
+    text.lines().map(|line| parse_line(line, base)).collect()
+}
+
+pub fn contains_ansi(text: &str) -> bool {
+    text.contains(ESC)
+}
+
+/// Writes a rendered buffer as text with SGR sequences, the inverse of [`parse_ansi`].
+///
+/// Trailing blanks are trimmed from every row and blank rows from the end.
+pub fn write_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut rows = vec![];
+    for y in area.top()..area.bottom() {
+        let cells: Vec<_> = (area.left()..area.right())
+            .map(|x| &buffer[(x, y)])
+            .collect();
+        let end = cells
+            .iter()
+            .rposition(|cell| {
+                cell.symbol() != " " || cell.bg != Color::Reset || !cell.modifier.is_empty()
+            })
+            .map_or(0, |i| i + 1);
+
+        let plain = (Color::Reset, Color::Reset, Modifier::empty());
+        let mut row = String::new();
+        let mut style = plain;
+        let mut skip = 0;
+        for cell in &cells[..end] {
+            // The cells behind a wide character are covered by it
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            if (cell.fg, cell.bg, cell.modifier) != style {
+                style = (cell.fg, cell.bg, cell.modifier);
+                row.push_str(&sgr(cell.fg, cell.bg, cell.modifier));
+            }
+            row.push_str(cell.symbol());
+            skip = cell.symbol().width().saturating_sub(1);
+        }
+        if style != plain {
+            row.push_str("\x1b[0m");
+        }
+        rows.push(row);
+    }
+    while rows.last().is_some_and(String::is_empty) {
+        rows.pop();
+    }
+    rows.into_iter().map(|row| row + "\n").collect()
+}
+
+/// A sequence that resets the terminal's style, then applies the given one
+fn sgr(fg: Color, bg: Color, modifier: Modifier) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (flag, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if modifier.contains(flag) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.extend(color_code(fg, 30));
+    codes.extend(color_code(bg, 40));
+    let mut sequence = String::new();
+    let _ = write!(sequence, "{}[{}m", ESC, codes.join(";"));
+    sequence
+}
+
+/// SGR parameters for a color, with `base` 30 for the foreground and 40 for the
+/// background
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let named = |offset: u8| Some((base + offset).to_string());
+    let bright = |offset: u8| Some((base + 60 + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => named(0),
+        Color::Red => named(1),
+        Color::Green => named(2),
+        Color::Yellow => named(3),
+        Color::Blue => named(4),
+        Color::Magenta => named(5),
+        Color::Cyan => named(6),
+        Color::Gray => named(7),
+        Color::DarkGray => bright(0),
+        Color::LightRed => bright(1),
+        Color::LightGreen => bright(2),
+        Color::LightYellow => bright(3),
+        Color::LightBlue => bright(4),
+        Color::LightMagenta => bright(5),
+        Color::LightCyan => bright(6),
+        Color::White => bright(7),
+        Color::Indexed(index) => Some(format!("{};5;{}", base + 8, index)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    }
+}
+
+fn parse_line(line: &str, base: Style) -> Line<'static> {
+    let mut spans = vec![];
+    let mut style = base;
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut final_byte = None;
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            final_byte = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+
+                    if final_byte == Some('m') {
+                        if !current.is_empty() {
+                            spans.push(Span::styled(std::mem::take(&mut current), style));
+                        }
+                        style = apply_sgr(style, base, &params);
+                    }
+                }
+                Some(']') => {
+                    // OSC sequences end with BEL or ST (ESC \)
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == ESC && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {}
+            _ => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        spans.push(Span::styled(current, style));
+    }
+
+    Line::from(spans)
+}
+
+fn apply_sgr(mut style: Style, base: Style, params: &str) -> Style {
+    let codes: Vec<u16> = if params.is_empty() {
+        vec![0]
+    } else {
+        params
+            .split([';', ':'])
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+
+    let mut iter = codes.into_iter();
+    while let Some(code) = iter.next() {
+        style = match code {
+            0 => base,
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed((code - 30) as u8)),
+            38 => match extended_color(&mut iter) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => style.fg(base.fg.unwrap_or(Color::Reset)),
+            40..=47 => style.bg(Color::Indexed((code - 40) as u8)),
+            48 => match extended_color(&mut iter) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => style.bg(base.bg.unwrap_or(Color::Reset)),
+            90..=97 => style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            100..=107 => style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            _ => style,
+        };
+    }
+
+    style
+}
+
+fn extended_color(iter: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match iter.next()? {
+        5 => Some(Color::Indexed(iter.next()? as u8)),
+        2 => {
+            let r = iter.next()? as u8;
+            let g = iter.next()? as u8;
+            let b = iter.next()? as u8;
+            Some(Color::Rgb(r, g, b))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_written_buffer_parses_back() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 12, 3));
+        buffer.set_string(0, 0, "plain", Style::default());
+        buffer.set_string(0, 1, "字 bold", Style::default().fg(Color::Red));
+        buffer.set_string(3, 1, "bold", Style::default().add_modifier(Modifier::BOLD));
+        let written = write_ansi(&buffer);
+        assert_eq!(written.lines().count(), 2);
+        assert!(written.starts_with("plain\n"));
+
+        let line = &parse_ansi(&written, Style::default())[1];
+        assert_eq!(line.to_string(), "字 bold");
+        assert_eq!(line.spans[0].style.fg, Some(Color::Indexed(1)));
+        assert!(line.spans[1].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        let lines = parse_ansi("hello\nworld", Style::default());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].spans[0].content, "hello");
+    }
+
+    #[test]
+    fn test_basic_colors_and_reset() {
+        let lines = parse_ansi("\x1b[31mred\x1b[0m plain", Style::default());
+        let spans = &lines[0].spans;
+        assert_eq!(spans[0].content, "red");
+        assert_eq!(spans[0].style.fg, Some(Color::Indexed(1)));
+        assert_eq!(spans[1].content, " plain");
+        assert_eq!(spans[1].style, Style::default());
+    }
+
+    #[test]
+    fn test_bold_and_bright_colors_combine() {
+        let lines = parse_ansi("\x1b[1;94mdir\x1b[m", Style::default());
+        let span = &lines[0].spans[0];
+        assert_eq!(span.style.fg, Some(Color::Indexed(12)));
+        assert!(span.style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_256_and_truecolor() {
+        let lines = parse_ansi("\x1b[38;5;208ma\x1b[48;2;1;2;3mb", Style::default());
+        let spans = &lines[0].spans;
+        assert_eq!(spans[0].style.fg, Some(Color::Indexed(208)));
+        assert_eq!(spans[1].style.bg, Some(Color::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_non_sgr_sequences_are_stripped() {
+        let lines = parse_ansi("\x1b]0;title\x07\x1b[2Kdone\r", Style::default());
+        let text: String = lines[0].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "done");
+    }
+
+    #[test]
+    fn test_reset_returns_to_base_style() {
+        let base = Style::default().fg(Color::Gray);
+        let lines = parse_ansi("\x1b[32mok\x1b[0m rest", base);
+        assert_eq!(lines[0].spans[1].style, base);
+    }
+}
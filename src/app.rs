@@ -1,83 +1,661 @@
 use anyhow::{Result, anyhow};
-use markdown::{ParseOptions, mdast::Node, to_mdast};
+use markdown::{
+    Constructs, ParseOptions,
+    mdast::{Node, Table},
+    to_mdast,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
 use ratatui::{
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
 };
 use tui_scrollview::ScrollViewState;
+use unicode_width::UnicodeWidthChar;
+
+use crate::ansi::{contains_ansi, parse_ansi};
+use crate::capabilities::EnabledFeatures;
+use crate::config::LayoutConfig;
+use crate::copy::CopyTarget;
+use crate::cursor::{Cursor, next_row, scroll_to};
+use crate::decrypt::read_deck_source;
+use crate::diagram::{as_diagram_paragraph, is_protected, protect_diagram};
+use crate::directives::{expand_toc, pause_count};
+use crate::fallback::{Problem, as_error_block, error_block, parse_in_pieces};
+use crate::feedback::Feedback;
+use crate::fence::FenceAttributes;
+use crate::frontmatter::{Canvas, Frontmatter, Split};
+use crate::goto::GotoInput;
+use crate::hyperlink::LinkRun;
+use crate::images::{Images, Placement};
+use crate::jumplist::{JumpList, JumpPosition};
+use crate::kbd::{key_cap, mark_keys};
+use crate::macros::{MacroPrefix, Macros};
+use crate::meta::DeckMeta;
+use crate::numbering::SlideNumbers;
+use crate::overview::Overview;
+use crate::prefetch::AssetCache;
+use crate::presenter::{self, is_presenter_block};
+use crate::profile::Profiler;
+use crate::references::Definitions;
+use crate::rehearsal::Rehearsal;
+use crate::reload_diff::ReloadDiff;
+use crate::render_cache::RenderCache;
+use crate::run;
+use crate::screenshot::insert_placeholders;
+use crate::search::{Search, SearchInput};
+use crate::slide_loop::SlideLoop;
+use crate::syntax::{Languages, highlight_code};
+use crate::table;
+use crate::table::TableView;
+use crate::theme::{Theme, ThemeChoice, TitleFit};
+use crate::timer::Timer;
 
 pub struct App {
     pub slides: Vec<Vec<Node>>,
     pub current_slide: usize,
     pub scroll_view_state: ScrollViewState,
     pub viewport_height: u16,
+    /// Height of the current slide once rendered at the viewport width
+    pub content_height: u16,
+    /// Rows of the pinned heading above the scrolling part of the slide
+    pub pinned_rows: u16,
+    /// Presenter cursor, when turned on
+    pub cursor: Option<Cursor>,
+    pub render_cache: RenderCache,
+    pub theme: Theme,
+    /// Themes from `--theme` and the config, picked again on reload
+    pub theme_choice: ThemeChoice,
+    /// Theme from the deck's frontmatter
+    pub deck_theme: Option<Theme>,
+    /// The frontmatter's fixed canvas, which the whole screen is laid out in
+    pub canvas: Option<Canvas>,
+    /// The frontmatter's feedback link, with the responses counted so far
+    pub feedback: Option<Feedback>,
+    /// Fence languages from the config's `[code]`, applied to every theme
+    pub languages: Languages,
+    /// The config's `[layout]`, applied to every theme
+    pub layout: LayoutConfig,
+    /// Table being sorted and filtered in table mode
+    pub table_view: Option<TableView>,
+    /// Where `copy_slide` sends the slide's text
+    pub copy_target: CopyTarget,
+    /// Set by `--low-bandwidth`: ASCII borders, and no syntax colors or reload highlight
+    pub low_bandwidth: bool,
+    pub blanked: bool,
+    pub flash_started: Option<Instant>,
+    /// Output of the last shell command, shown until the next key press
+    pub command_output: Option<String>,
+    /// Whether the cheat sheet of keys is shown, until the next key press
+    pub cheat_sheet: bool,
+    pub macros: Macros,
+    /// Set after `q` or `@` while waiting for the register letter
+    pub pending_macro: Option<MacroPrefix>,
+    /// Slide number typed as a count before `G` or after `:`
+    pub goto_input: Option<GotoInput>,
+    pub should_quit: bool,
+    pub overview: Option<Overview>,
+    pub search_input: Option<SearchInput>,
+    pub search: Option<Search>,
+    /// Whether matches of the last search are highlighted
+    pub search_highlight: bool,
+    /// Slide and row of the match `n`/`N` last moved to
+    pub search_match: Option<(usize, u16)>,
+    /// Direction of a search that moved to another slide, whose first match that way
+    /// is scrolled to once the slide is rendered
+    pub search_pending: Option<bool>,
+    /// What the last live reload changed on the current slide
+    pub reload_diff: Option<ReloadDiff>,
+    /// One-off status message, cleared by the next key press
+    pub message: Option<String>,
+    pub jumps: JumpList,
+    /// Presentation lock: disruptive commands are disabled and quitting needs confirmation
+    pub presenting: bool,
+    /// Speaker notes and the next slide are shown beside the current one
+    pub speaker_view: bool,
+    /// Slide numbers shown on screen, which may skip slides or start elsewhere than 1
+    pub numbers: SlideNumbers,
+    pub timer: Timer,
+    /// Footer line with the slide counter and a progress bar
+    pub show_progress: bool,
+    pub confirm_quit: bool,
+    /// Attached to another presenter: only they move between slides
+    pub read_only: bool,
+    /// Viewers attached to this presentation, when serving it
+    pub viewers: Option<usize>,
+    pub features: EnabledFeatures,
+    /// Directory of the deck file, where `screenshot` commands run
+    pub base_dir: PathBuf,
+    /// Links in the last frame, drawn clickable after it when hyperlinks are enabled
+    pub hyperlinks: Vec<LinkRun>,
+    pub images: Images,
+    /// Remote images downloaded before presenting
+    pub assets: AssetCache,
+    /// Images in the last frame, drawn after it
+    pub image_placements: Vec<Placement>,
+    /// Frame timings, collected when started with `--profile`
+    pub profiler: Option<Profiler>,
+    /// Slide shown condensed by `zoom_out`; moving to another slide ends it
+    pub zoomed_out: Option<usize>,
+    /// Slides whose `<details>` sections are expanded
+    pub expanded_details: HashSet<usize>,
+    pub meta: DeckMeta,
+    /// Option picked on each quiz slide, 1-based
+    pub quiz_answers: HashMap<usize, usize>,
+    /// Checklist steps ticked off on each slide
+    pub checklists: HashMap<usize, usize>,
+    /// Pauses passed on each slide
+    pub revealed: HashMap<usize, usize>,
+    /// Stopwatch of a `--rehearse` run
+    pub rehearsal: Option<Rehearsal>,
+    /// Slides the slide keys go round in, see `loop_slides`
+    pub slide_loop: Option<SlideLoop>,
 }
 
 impl App {
     pub fn new(slides: Vec<Vec<Node>>) -> Self {
         Self {
+            numbers: SlideNumbers::new(&slides, 1),
             slides,
             current_slide: 0,
             scroll_view_state: ScrollViewState::default(),
             viewport_height: 0,
+            content_height: 0,
+            pinned_rows: 0,
+            cursor: None,
+            render_cache: RenderCache::default(),
+            theme: Theme::default(),
+            theme_choice: ThemeChoice::default(),
+            deck_theme: None,
+            canvas: None,
+            feedback: None,
+            languages: Languages::default(),
+            layout: LayoutConfig::default(),
+            low_bandwidth: false,
+            copy_target: CopyTarget::default(),
+            table_view: None,
+            blanked: false,
+            flash_started: None,
+            command_output: None,
+            cheat_sheet: false,
+            macros: Macros::default(),
+            pending_macro: None,
+            goto_input: None,
+            should_quit: false,
+            overview: None,
+            search_input: None,
+            search: None,
+            search_highlight: false,
+            search_match: None,
+            search_pending: None,
+            reload_diff: None,
+            message: None,
+            jumps: JumpList::default(),
+            presenting: false,
+            speaker_view: false,
+            show_progress: false,
+            timer: Timer::default(),
+            confirm_quit: false,
+            profiler: None,
+            zoomed_out: None,
+            expanded_details: HashSet::new(),
+            meta: DeckMeta::default(),
+            quiz_answers: HashMap::new(),
+            checklists: HashMap::new(),
+            revealed: HashMap::new(),
+            rehearsal: None,
+            slide_loop: None,
+            read_only: false,
+            viewers: None,
+            features: EnabledFeatures::default(),
+            base_dir: PathBuf::from("."),
+            hyperlinks: vec![],
+            images: Images::default(),
+            assets: AssetCache::default(),
+            image_placements: vec![],
+        }
+    }
+
+    /// App for a loaded deck, with the deck's frontmatter applied
+    pub fn from_deck(deck: Deck) -> Self {
+        let mut app = App::new(deck.slides);
+        app.numbers = SlideNumbers::new(&app.slides, deck.frontmatter.number_from.unwrap_or(1));
+        app.meta = deck.meta;
+        app.deck_theme = deck.frontmatter.theme;
+        app.canvas = deck.frontmatter.canvas;
+        app.feedback = deck.frontmatter.feedback;
+        app.apply_theme();
+        app
+    }
+
+    /// The table mode view, if it's on the current slide
+    pub fn table_view(&self) -> Option<TableView> {
+        self.table_view
+            .clone()
+            .filter(|view| view.slide == self.current_slide)
+    }
+
+    /// Switches to the theme picked from the deck's own and the `theme_choice`; syntax
+    /// colors change color on almost every token, so they're left out over a slow link
+    pub fn apply_theme(&mut self) {
+        self.theme = self.theme_choice.pick(self.deck_theme.clone());
+        self.theme.code.languages = self.languages.clone();
+        self.layout.apply(&mut self.theme.layout);
+        if self.low_bandwidth {
+            self.theme.code.syntax_theme = "none".to_string();
+        }
+        self.render_cache.invalidate();
+    }
+
+    /// Swaps in an edited version of the deck, staying on the current slide and scroll
+    /// position, or on the last slide when the deck got shorter. What's kept for each
+    /// slide follows it to where it is now, and is dropped when it can't be found
+    pub fn reload(&mut self, deck: Deck) {
+        if self.render_cache.slide() == Some(self.current_slide) && !self.low_bandwidth {
+            self.reload_diff = Some(ReloadDiff::new(
+                self.current_slide,
+                self.render_cache.buffer(),
+                Instant::now(),
+            ));
+        }
+        let moved = moved_slides(&self.slides, &deck.slides);
+        let slide = |index: usize| moved.get(index).copied().flatten();
+        for state in [
+            &mut self.revealed,
+            &mut self.quiz_answers,
+            &mut self.checklists,
+        ] {
+            *state = state
+                .drain()
+                .filter_map(|(index, value)| Some((slide(index)?, value)))
+                .collect();
+        }
+        self.expanded_details = self.expanded_details.drain().filter_map(slide).collect();
+        self.zoomed_out = self.zoomed_out.and_then(slide);
+        self.table_view = self.table_view.take().and_then(|mut view| {
+            view.slide = slide(view.slide)?;
+            (view.table < table::tables(&deck.slides[view.slide]).len()).then_some(view)
+        });
+        self.slide_loop = self.slide_loop.take().and_then(|slide_loop| {
+            Some(SlideLoop {
+                first: slide(slide_loop.first)?,
+                last: match slide_loop.last {
+                    Some(last) => Some(slide(last)?),
+                    None => None,
+                },
+            })
+        });
+        self.slides = deck.slides;
+        self.numbers = SlideNumbers::new(&self.slides, deck.frontmatter.number_from.unwrap_or(1));
+        if let Some(rehearsal) = &mut self.rehearsal {
+            rehearsal.reload(&self.slides, &deck.frontmatter.timing);
+        }
+        self.meta = deck.meta;
+        self.deck_theme = deck.frontmatter.theme;
+        self.canvas = deck.frontmatter.canvas;
+        let count = self.feedback.as_ref().and_then(|feedback| feedback.count);
+        self.feedback = deck.frontmatter.feedback;
+        if let Some(feedback) = &mut self.feedback {
+            feedback.count = count;
+        }
+        self.apply_theme();
+        if self.current_slide >= self.slides.len() {
+            self.current_slide = self.slides.len().saturating_sub(1);
+            self.scroll_view_state = ScrollViewState::default();
+        }
+        if let Some(protocol) = self.features.images {
+            self.images = Images::load(&self.slides, &self.base_dir, &self.assets, protocol);
+        }
+        self.render_cache.invalidate();
+    }
+
+    /// Pauses passed on the current slide; read-only viewers see slides in full
+    pub fn revealed(&self) -> usize {
+        let pauses = pause_count(&self.slides[self.current_slide]);
+        if self.read_only {
+            return pauses;
+        }
+        self.revealed
+            .get(&self.current_slide)
+            .map_or(0, |&revealed| revealed.min(pauses))
+    }
+
+    pub fn is_zoomed_out(&self) -> bool {
+        self.zoomed_out == Some(self.current_slide)
+    }
+
+    pub fn position(&self) -> JumpPosition {
+        JumpPosition {
+            slide: self.current_slide,
+            offset: self.scroll_view_state.offset().y,
+        }
+    }
+
+    /// Moves to a position recorded in the jump list
+    pub fn restore_position(&mut self, position: JumpPosition) {
+        self.goto_slide(position.slide);
+        let mut offset = self.scroll_view_state.offset();
+        offset.y = position.offset;
+        self.scroll_view_state.set_offset(offset);
+    }
+
+    /// Goes to another slide, remembering the current position in the jump list
+    pub fn jump_to_slide(&mut self, index: usize) {
+        if index < self.slides.len() && index != self.current_slide {
+            let position = self.position();
+            self.jumps.push(position);
+            self.goto_slide(index);
+        }
+    }
+
+    pub fn goto_slide(&mut self, index: usize) {
+        if index < self.slides.len() && index != self.current_slide {
+            self.current_slide = index;
+            self.scroll_view_state = ScrollViewState::default();
         }
     }
+
+    /// Scrolls to the next row with a search match on the current slide, or the
+    /// previous one; false when there's none left that way
+    pub fn next_match_row(&mut self, forward: bool) -> bool {
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let rows = search.match_rows(self.render_cache.buffer(), self.pinned_rows);
+        let current = self
+            .search_match
+            .filter(|(slide, _)| *slide == self.current_slide)
+            .map(|(_, row)| row);
+        let row = if forward {
+            rows.into_iter()
+                .find(|&row| current.is_none_or(|c| row > c))
+        } else {
+            rows.into_iter()
+                .rev()
+                .find(|&row| current.is_none_or(|c| row < c))
+        };
+        let Some(row) = row else {
+            return false;
+        };
+        self.search_match = Some((self.current_slide, row));
+        let mut offset = self.scroll_view_state.offset();
+        offset.y = scroll_to(row, offset.y, self.viewport_height);
+        self.scroll_view_state.set_offset(offset);
+        true
+    }
+
+    /// Row of the match `n`/`N` last moved to, when it's on the current slide
+    pub fn search_match_row(&self) -> Option<u16> {
+        self.search_match
+            .filter(|(slide, _)| self.search_highlight && *slide == self.current_slide)
+            .map(|(_, row)| row)
+    }
+
+    /// Row of the presenter cursor, which starts on the first line of a slide
+    pub fn cursor_row(&self) -> Option<u16> {
+        let cursor = self.cursor?;
+        cursor.row(self.current_slide).or_else(|| {
+            Some(next_row(self.render_cache.buffer(), self.pinned_rows, None, true).unwrap_or(0))
+        })
+    }
+
+    /// Moves the presenter cursor to the next or previous line with something on it,
+    /// scrolling only when it leaves the viewport
+    pub fn move_cursor(&mut self, down: bool) {
+        let current = self.cursor_row();
+        let row = next_row(self.render_cache.buffer(), self.pinned_rows, current, down)
+            .or(current)
+            .unwrap_or(0);
+        self.cursor = Some(Cursor::new(self.current_slide, row));
+        let mut offset = self.scroll_view_state.offset();
+        offset.y = scroll_to(row, offset.y, self.viewport_height);
+        self.scroll_view_state.set_offset(offset);
+    }
+
+    /// Time left on the position flash shown after returning to the slide
+    pub fn flash_remaining(&self, duration: Duration) -> Option<Duration> {
+        let elapsed = self.flash_started?.elapsed();
+        duration.checked_sub(elapsed).filter(|d| !d.is_zero())
+    }
+}
+
+pub struct Deck {
+    pub frontmatter: Frontmatter,
+    pub meta: DeckMeta,
+    pub slides: Vec<Vec<Node>>,
+    /// Parts that failed to parse and are shown as error blocks instead
+    pub problems: Vec<Problem>,
+}
+
+pub fn load_deck(path: &str, split: &Split) -> Result<Deck> {
+    let content = read_deck_source(Path::new(path))?;
+    parse_deck_with(&content, split)
 }
 
-pub fn load_slides(path: &str) -> Result<Vec<Vec<Node>>> {
-    let content = std::fs::read_to_string(path)?;
-    let mut mdast =
-        to_mdast(content.as_str(), &ParseOptions::default()).map_err(|e| anyhow!("{}", e))?;
+/// Parses deck markdown into its syntax tree, frontmatter included
+pub fn parse_markdown(content: &str) -> Result<Node> {
+    let options = ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            gfm_footnote_definition: true,
+            gfm_label_start_footnote: true,
+            gfm_strikethrough: true,
+            gfm_table: true,
+            gfm_task_list_item: true,
+            ..Constructs::default()
+        },
+        ..ParseOptions::default()
+    };
+    to_mdast(&presenter::desugar(content), &options).map_err(|e| anyhow!("{}", e))
+}
 
+/// Parses a deck with the default split, at H1 and H2 headings
+#[cfg(test)]
+pub fn parse_deck(content: &str) -> Result<Deck> {
+    parse_deck_with(content, &Split::default())
+}
+
+/// Parses a deck split into slides as `split` says, unless its frontmatter says otherwise
+pub fn parse_deck_with(content: &str, split: &Split) -> Result<Deck> {
+    let mut split = split.clone();
+    let mut problems = vec![];
+    let mut mdast = match parse_markdown(content) {
+        Ok(mdast) => mdast,
+        Err(_) => parse_in_pieces(content, parse_markdown, &mut problems),
+    };
+
+    let mut frontmatter = Frontmatter::default();
     let mut current_slide_content = vec![];
     let mut slides = vec![];
     let children = mdast.children_mut().ok_or(anyhow!("No children"))?;
 
-    for node in children {
-        if !current_slide_content.is_empty()
-            && let Node::Heading(heading) = node
-            && (heading.depth == 1 || heading.depth == 2)
-        {
+    // Definitions may sit on any slide, usually the last, so resolve them up front
+    let definitions = Definitions::extract(children);
+    for node in children.iter_mut() {
+        definitions.resolve(node);
+        protect_diagram(node, content);
+        mark_keys(node);
+    }
+
+    // Nodes are moved out of the tree rather than cloned, so a deck is held in memory once
+    for mut node in std::mem::take(children) {
+        let declared = match &node {
+            Node::Toml(toml) => Some(Frontmatter::from_toml(&toml.value)),
+            Node::Yaml(yaml) => Some(Frontmatter::from_yaml(&yaml.value)),
+            _ => None,
+        };
+        match declared {
+            Some(Ok(declared)) => {
+                frontmatter = declared;
+                if let Some(deck_split) = &frontmatter.split {
+                    split = deck_split.clone();
+                }
+                continue;
+            }
+            // Presented with the defaults, the block shown on a slide of its own
+            Some(Err(e)) => {
+                let message = format!("{:#}", e);
+                let position = node.position().cloned();
+                let source = position
+                    .as_ref()
+                    .and_then(|p| content.get(p.start.offset..p.end.offset))
+                    .unwrap_or_default();
+                problems.push(Problem {
+                    line: position.as_ref().map_or(1, |p| p.start.line),
+                    message: message.clone(),
+                });
+                node = error_block(source, &message, position);
+            }
+            None => {}
+        }
+
+        let delimiter = split.is_delimiter(&node);
+        let starts_slide = split.starts_slide(&node) || as_error_block(&node).is_some();
+        if !current_slide_content.is_empty() && (delimiter || starts_slide) {
             // Move the current slide into the slides list
             slides.push(std::mem::take(&mut current_slide_content));
         }
 
-        current_slide_content.push(node.clone());
+        if !delimiter {
+            current_slide_content.push(node);
+        }
+    }
+
+    // Push the last slide, unless a delimiter already closed it
+    if !current_slide_content.is_empty() || slides.is_empty() {
+        slides.push(current_slide_content);
+    }
+
+    let title_slide = frontmatter.title_slide();
+    let toc_slide = frontmatter.toc_slide();
+    if (title_slide.is_some() || toc_slide.is_some()) && slides.len() == 1 && slides[0].is_empty() {
+        slides.clear();
+    }
+    if let Some(toc_slide) = toc_slide {
+        slides.insert(0, toc_slide);
+    }
+    if let Some(title_slide) = title_slide {
+        slides.insert(0, title_slide);
     }
 
-    // Push the last slide
-    slides.push(current_slide_content);
+    for slide in slides.iter_mut() {
+        definitions.attach_footnotes(slide);
+    }
+    let numbers = SlideNumbers::new(&slides, frontmatter.number_from.unwrap_or(1));
+    expand_toc(&mut slides, &numbers);
+    insert_placeholders(&mut slides);
+
+    Ok(Deck {
+        meta: DeckMeta::new(&frontmatter, &slides),
+        frontmatter,
+        slides,
+        problems,
+    })
+}
+
+/// Where each slide went in an edited deck: found by its title when that's unique in
+/// both versions, or else left in place as long as no slides were added or removed
+fn moved_slides(old: &[Vec<Node>], new: &[Vec<Node>]) -> Vec<Option<usize>> {
+    let unique_titles = |slides: &[Vec<Node>]| {
+        let mut titles: HashMap<String, Option<usize>> = HashMap::new();
+        for (index, slide) in slides.iter().enumerate() {
+            titles
+                .entry(slide_title(slide))
+                .and_modify(|found| *found = None)
+                .or_insert(Some(index));
+        }
+        titles
+    };
+    let (before, after) = (unique_titles(old), unique_titles(new));
+    old.iter()
+        .enumerate()
+        .map(|(index, slide)| {
+            let title = slide_title(slide);
+            match (before.get(&title), after.get(&title)) {
+                (Some(Some(_)), Some(Some(moved))) => Some(*moved),
+                _ => (old.len() == new.len()).then_some(index),
+            }
+        })
+        .collect()
+}
 
-    Ok(slides)
+/// Text of the slide's first heading, or its first line when it has none
+pub fn slide_title(slide: &[Node]) -> String {
+    slide
+        .iter()
+        .find(|node| matches!(node, Node::Heading(_)))
+        .or_else(|| slide.iter().find(|node| !matches!(node, Node::Html(_))))
+        .map(|node| node.to_string().lines().next().unwrap_or("").to_string())
+        .unwrap_or_default()
 }
 
-pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style) {
+/// Index of the slide holding source `line`, counting from 1: the last slide that
+/// starts at or before it
+pub fn slide_at_line(slides: &[Vec<Node>], line: usize) -> usize {
+    slides
+        .iter()
+        .enumerate()
+        // Generated slides, such as the title slide, have no position to compare
+        .filter_map(|(index, slide)| {
+            let start = slide.iter().find_map(|node| node.position())?.start.line;
+            Some((index, start))
+        })
+        .take_while(|(_, start)| *start <= line)
+        .last()
+        .map_or(0, |(index, _)| index)
+}
+
+/// Title of the H1 section a slide starting at an H2 belongs to
+pub fn section_title(slides: &[Vec<Node>], index: usize) -> Option<String> {
+    let starts_with = |slide: &[Node], depth: u8| matches!(slide.first(), Some(Node::Heading(heading)) if heading.depth == depth);
+    if !starts_with(slides.get(index)?, 2) {
+        return None;
+    }
+    slides[..index]
+        .iter()
+        .rev()
+        .find(|slide| starts_with(slide, 1))
+        .map(|slide| slide_title(slide))
+}
+
+/// Plain text of everything shown on the slide
+pub fn slide_text(slide: &[Node]) -> String {
+    slide
+        .iter()
+        .filter(|node| !matches!(node, Node::Html(_)) && !is_presenter_block(node))
+        .map(|node| node.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders a node into styled lines that borrow its text rather than copying it
+pub fn node_to_lines<'a>(node: &'a Node, lines: &mut Vec<Line<'a>>, style: Style, theme: &Theme) {
     match node {
         Node::Root(root) => {
             for child in &root.children {
-                node_to_lines(child, lines, style);
+                node_to_lines(child, lines, style, theme);
             }
         }
         Node::Heading(heading) => {
-            let level = heading.depth;
-            let heading_style = Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD);
+            let level = theme.headings.level(heading.depth);
+            let heading_style = level.style(&theme.colors);
 
-            let prefix = "#".repeat(level as usize) + " ";
-            let mut spans = vec![Span::styled(prefix, heading_style)];
+            let mut spans = vec![Span::styled(level.prefix(heading.depth), heading_style)];
 
             for child in &heading.children {
-                collect_inline_spans(child, &mut spans, heading_style);
+                collect_inline_spans(child, &mut spans, heading_style, theme);
             }
 
-            lines.push(Line::from(spans));
+            let line = Line::from(spans);
+            lines.push(if level.center { line.centered() } else { line });
             lines.push(Line::raw(""));
         }
         Node::Paragraph(paragraph) => {
             let mut spans = vec![];
             for child in &paragraph.children {
-                collect_inline_spans(child, &mut spans, style);
+                collect_inline_spans(child, &mut spans, style, theme);
             }
             lines.push(Line::from(spans));
             lines.push(Line::raw(""));
@@ -86,22 +664,66 @@ pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style)
             for (i, child) in list.children.iter().enumerate() {
                 if let Node::ListItem(item) = child {
                     let bullet = if list.ordered {
-                        format!("{}. ", i + 1)
+                        format!("{}{} ", i + 1, theme.bullets.ordered)
                     } else {
-                        "- ".to_string()
+                        format!("{} ", theme.bullets.unordered)
                     };
 
                     let mut item_spans = vec![Span::raw(bullet)];
+                    match item.checked {
+                        Some(true) => item_spans.push(Span::raw("[x] ")),
+                        Some(false) => item_spans.push(Span::raw("[ ] ")),
+                        None => {}
+                    }
                     for item_child in &item.children {
-                        collect_inline_spans(item_child, &mut item_spans, style);
+                        collect_inline_spans(item_child, &mut item_spans, style, theme);
                     }
                     lines.push(Line::from(item_spans));
                 }
             }
             lines.push(Line::raw(""));
         }
+        Node::Code(_) if as_error_block(node).is_some() => {
+            let (message, source) = as_error_block(node).unwrap_or_default();
+            let error_style = Style::default()
+                .fg(theme.colors.incorrect)
+                .add_modifier(Modifier::BOLD);
+            lines.push(Line::styled(
+                format!("✗ Could not parse this part: {}", message),
+                error_style,
+            ));
+            let source_style = Style::default().fg(theme.colors.code);
+            lines.extend(source.lines().map(|line| {
+                Line::from(vec![
+                    Span::styled("│ ", error_style),
+                    Span::styled(line, source_style),
+                ])
+            }));
+            lines.push(Line::raw(""));
+        }
+        Node::Code(code) if as_diagram_paragraph(node).is_some() => {
+            lines.extend(code.value.lines().map(|line| Line::styled(line, style)));
+            lines.push(Line::raw(""));
+        }
+        Node::Code(code) if run::is_output(node) => {
+            // A panel set apart from the code above it by a labeled rule
+            let rule_style = Style::default().fg(theme.colors.accent);
+            lines.push(Line::styled("── output ──", rule_style));
+            let code_style = Style::default().fg(theme.colors.code);
+            if contains_ansi(&code.value) {
+                lines.extend(parse_ansi(&code.value, code_style));
+            } else {
+                lines.extend(
+                    code.value
+                        .lines()
+                        .map(|line| Line::styled(line, code_style)),
+                );
+            }
+            lines.push(Line::styled("──", rule_style));
+            lines.push(Line::raw(""));
+        }
         Node::Code(code) => {
-            let code_style = Style::default().fg(Color::Gray);
+            let code_style = Style::default().fg(theme.colors.code);
 
             if let Some(lang) = &code.lang {
                 lines.push(Line::styled(format!("```{}", lang), code_style));
@@ -109,8 +731,35 @@ pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style)
                 lines.push(Line::styled("```", code_style));
             }
 
-            for line in code.value.lines() {
-                lines.push(Line::styled(line.to_string(), code_style));
+            let highlighted = code
+                .lang
+                .as_ref()
+                .filter(|_| !is_protected(node))
+                .and_then(|lang| {
+                    highlight_code(
+                        &code.value,
+                        lang,
+                        &theme.code.syntax_theme,
+                        &theme.code.languages,
+                    )
+                });
+            let start = lines.len();
+            if contains_ansi(&code.value) {
+                // Captured terminal output keeps its own colors
+                lines.extend(parse_ansi(&code.value, code_style));
+            } else if let Some(highlighted) = highlighted {
+                lines.extend(highlighted);
+            } else {
+                for line in code.value.lines() {
+                    lines.push(Line::styled(line, code_style));
+                }
+            }
+            let attributes = FenceAttributes::parse(code);
+            if theme.code.languages.line_numbers || attributes.numbers {
+                number_lines(&mut lines[start..], theme);
+            }
+            if !attributes.highlighted.is_empty() {
+                highlight_lines(&mut lines[start..], &attributes, theme);
             }
             lines.push(Line::styled("```", code_style));
             lines.push(Line::raw(""));
@@ -118,11 +767,11 @@ pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style)
         Node::Blockquote(quote) => {
             for child in &quote.children {
                 let quote_style = Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme.colors.blockquote)
                     .add_modifier(Modifier::ITALIC);
 
                 let mut quote_lines = vec![];
-                node_to_lines(child, &mut quote_lines, quote_style);
+                node_to_lines(child, &mut quote_lines, quote_style, theme);
 
                 for line in quote_lines {
                     let mut spans = vec![Span::raw("> ")];
@@ -131,6 +780,23 @@ pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style)
                 }
             }
         }
+        Node::FootnoteDefinition(footnote) => {
+            let label_style = Style::default().fg(theme.colors.link);
+            let label = footnote.label.as_ref().unwrap_or(&footnote.identifier);
+            let mut spans = vec![Span::styled(format!("[{}] ", label), label_style)];
+            for child in &footnote.children {
+                collect_inline_spans(child, &mut spans, style, theme);
+            }
+            lines.push(Line::from(spans));
+        }
+        Node::Table(table) => {
+            lines.extend(table::layout(
+                table_rows(table, style, theme),
+                &table.align,
+                style,
+            ));
+            lines.push(Line::raw(""));
+        }
         Node::ThematicBreak(_) => {
             lines.push(Line::raw("─".repeat(40)));
             lines.push(Line::raw(""));
@@ -138,43 +804,186 @@ pub fn node_to_lines(node: &Node, lines: &mut Vec<Line<'static>>, style: Style)
         _ => {
             if let Some(children) = node.children() {
                 for child in children {
-                    node_to_lines(child, lines, style);
+                    node_to_lines(child, lines, style, theme);
                 }
             }
         }
     }
 }
 
-fn collect_inline_spans(node: &Node, spans: &mut Vec<Span<'static>>, base_style: Style) {
+/// Condenses rendered lines to fit more on screen: blank lines are dropped and code
+/// blocks longer than two lines are cut to their first and last line
+pub fn condense_lines(lines: Vec<Line<'_>>) -> Vec<Line<'_>> {
+    let is_fence = |line: &Line| {
+        line.spans
+            .first()
+            .is_some_and(|s| s.content.starts_with("```"))
+    };
+
+    let mut condensed = vec![];
+    let mut code: Option<Vec<Line>> = None;
+    for line in lines {
+        match &mut code {
+            Some(block) if is_fence(&line) => {
+                if block.len() > 2 {
+                    let style = block[0].style;
+                    let last = block.pop();
+                    condensed.push(block.swap_remove(0));
+                    condensed.push(Line::styled("…", style));
+                    condensed.extend(last);
+                } else {
+                    condensed.append(block);
+                }
+                condensed.push(line);
+                code = None;
+            }
+            Some(block) => block.push(line),
+            None if is_fence(&line) => {
+                condensed.push(line);
+                code = Some(vec![]);
+            }
+            None if line.width() == 0 => {}
+            None => condensed.push(line),
+        }
+    }
+    condensed.extend(code.into_iter().flatten());
+    condensed
+}
+
+/// The table's cells rendered as inline text, the header row first
+pub fn table_rows<'a>(table: &'a Table, style: Style, theme: &Theme) -> Vec<Vec<Line<'a>>> {
+    table
+        .children
+        .iter()
+        .map(|row| {
+            row.children()
+                .into_iter()
+                .flatten()
+                .map(|cell| {
+                    let mut spans = vec![];
+                    for child in cell.children().into_iter().flatten() {
+                        collect_inline_spans(child, &mut spans, style, theme);
+                    }
+                    Line::from(spans)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Puts the code lines' numbers in a gutter before them, right-aligned
+fn number_lines(lines: &mut [Line], theme: &Theme) {
+    let width = lines.len().to_string().len();
+    let style = Style::default().fg(theme.colors.line_number);
+    for (index, line) in lines.iter_mut().enumerate() {
+        let gutter = format!("{:>width$} │ ", index + 1);
+        line.spans.insert(0, Span::styled(gutter, style));
+    }
+}
+
+/// Puts the picked lines on a background as wide as the block's widest line
+fn highlight_lines(lines: &mut [Line], attributes: &FenceAttributes, theme: &Theme) {
+    let width = lines.iter().map(Line::width).max().unwrap_or(0);
+    let style = Style::default().bg(theme.colors.line_highlight);
+    for (index, line) in lines.iter_mut().enumerate() {
+        if attributes.is_highlighted(index + 1) {
+            let padding = width - line.width();
+            line.spans.push(Span::raw(" ".repeat(padding)));
+            for span in &mut line.spans {
+                span.style = span.style.patch(style);
+            }
+        }
+    }
+}
+
+/// Makes a rendered heading line fit within `width` columns according to the theme policy
+pub fn fit_heading(line: Line<'_>, width: usize, fit: TitleFit) -> Line<'_> {
+    if fit == TitleFit::Wrap || line.width() <= width {
+        return line;
+    }
+
+    // The first span holds the `#` markers
+    let alignment = line.alignment;
+    let mut spans: Vec<Span> = line.spans.into_iter().skip(1).collect();
+    if Line::from(spans.clone()).width() <= width {
+        return Line {
+            alignment,
+            ..Line::from(spans)
+        };
+    }
+
+    let ellipsis_style = spans.last().map(|s| s.style).unwrap_or_default();
+    let mut remaining = width.saturating_sub(1);
+    for span in spans.iter_mut() {
+        let mut kept = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if char_width > remaining {
+                remaining = 0;
+                break;
+            }
+            remaining -= char_width;
+            kept.push(c);
+        }
+        span.content = kept.into();
+    }
+    spans.retain(|s| !s.content.is_empty());
+    if width > 0 {
+        spans.push(Span::styled("…", ellipsis_style));
+    }
+
+    Line {
+        alignment,
+        ..Line::from(spans)
+    }
+}
+
+fn collect_inline_spans<'a>(
+    node: &'a Node,
+    spans: &mut Vec<Span<'a>>,
+    base_style: Style,
+    theme: &Theme,
+) {
     match node {
         Node::Text(text) => {
-            let sanitized = text.value.replace('\n', " ");
-            spans.push(Span::styled(sanitized, base_style));
+            if text.value.contains('\n') {
+                spans.push(Span::styled(text.value.replace('\n', " "), base_style));
+            } else {
+                spans.push(Span::styled(text.value.as_str(), base_style));
+            }
         }
         Node::Strong(strong) => {
             let bold_style = base_style.add_modifier(Modifier::BOLD);
             for child in &strong.children {
-                collect_inline_spans(child, spans, bold_style);
+                collect_inline_spans(child, spans, bold_style, theme);
             }
         }
         Node::Emphasis(emphasis) => {
             let italic_style = base_style.add_modifier(Modifier::ITALIC);
             for child in &emphasis.children {
-                collect_inline_spans(child, spans, italic_style);
+                collect_inline_spans(child, spans, italic_style, theme);
+            }
+        }
+        Node::Delete(delete) => {
+            let crossed_out_style = base_style.add_modifier(Modifier::CROSSED_OUT);
+            for child in &delete.children {
+                collect_inline_spans(child, spans, crossed_out_style, theme);
             }
         }
         Node::InlineCode(code) => {
-            let code_style = base_style.fg(Color::Green).add_modifier(Modifier::BOLD);
-            spans.push(Span::styled(code.value.clone(), code_style));
+            let code_style = base_style
+                .fg(theme.colors.inline_code)
+                .add_modifier(Modifier::BOLD);
+            spans.push(Span::styled(code.value.as_str(), code_style));
         }
         Node::Image(image) => {
             let link_style = base_style
-                .fg(Color::Blue)
+                .fg(theme.colors.link)
                 .add_modifier(Modifier::UNDERLINED);
             let alt_text = if image.alt.is_empty() {
-                image.url.clone()
+                image.url.as_str()
             } else {
-                image.alt.clone()
+                image.alt.as_str()
             };
 
             spans.push(Span::styled(alt_text, link_style));
@@ -186,19 +995,32 @@ fn collect_inline_spans(node: &Node, spans: &mut Vec<Span<'static>>, base_style:
         }
         Node::Link(link) => {
             let link_style = base_style
-                .fg(Color::Blue)
+                .fg(theme.colors.link)
                 .add_modifier(Modifier::UNDERLINED);
             for child in &link.children {
-                collect_inline_spans(child, spans, link_style);
+                collect_inline_spans(child, spans, link_style, theme);
             }
         }
+        Node::FootnoteReference(reference) => {
+            let label = reference.label.as_ref().unwrap_or(&reference.identifier);
+            spans.push(Span::styled(
+                format!("[{}]", label),
+                base_style.fg(theme.colors.link),
+            ));
+        }
         Node::Break(_) => {
             spans.push(Span::raw("\n"));
         }
+        Node::MdxJsxTextElement(_) if let Some(key) = key_cap(node) => {
+            spans.push(Span::styled(
+                format!(" {} ", key),
+                base_style.add_modifier(Modifier::REVERSED),
+            ));
+        }
         _ => {
             if let Some(children) = node.children() {
                 for child in children {
-                    collect_inline_spans(child, spans, base_style);
+                    collect_inline_spans(child, spans, base_style, theme);
                 }
             }
         }
@@ -208,6 +1030,7 @@ fn collect_inline_spans(node: &Node, spans: &mut Vec<Span<'static>>, base_style:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::directives::{Center, centering};
     use ratatui::style::Style;
     use std::io::Write;
     use tempfile::NamedTempFile;
@@ -219,11 +1042,137 @@ mod tests {
         file
     }
 
+    #[test]
+    fn test_flash_remaining() {
+        let mut app = App::new(vec![vec![]]);
+        assert_eq!(app.flash_remaining(Duration::from_secs(1)), None);
+
+        app.flash_started = Some(Instant::now());
+        assert!(app.flash_remaining(Duration::from_secs(60)).is_some());
+        assert_eq!(app.flash_remaining(Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_goto_slide_resets_scroll() {
+        let mut app = App::new(vec![vec![], vec![], vec![]]);
+        app.scroll_view_state
+            .set_offset(ratatui::layout::Position::new(0, 5));
+        app.goto_slide(2);
+        assert_eq!(app.current_slide, 2);
+        assert_eq!(app.scroll_view_state.offset().y, 0);
+
+        app.goto_slide(7);
+        assert_eq!(app.current_slide, 2);
+    }
+
+    #[test]
+    fn test_reload_keeps_slide_and_scroll() {
+        let mut app = App::from_deck(parse_deck("# One\n\n# Two\n\n# Three\n").unwrap());
+        app.goto_slide(1);
+        app.scroll_view_state
+            .set_offset(ratatui::layout::Position::new(0, 3));
+        app.reload(parse_deck("# One\n\n# Two, edited\n\n# Three\n").unwrap());
+        assert_eq!(app.current_slide, 1);
+        assert_eq!(app.scroll_view_state.offset().y, 3);
+        assert_eq!(app.slides[1][0].to_string(), "Two, edited");
+
+        // Deleting the slide being shown moves to the new last one
+        app.goto_slide(2);
+        app.reload(parse_deck("# One\n").unwrap());
+        assert_eq!(app.current_slide, 0);
+        assert_eq!(app.scroll_view_state.offset().y, 0);
+    }
+
+    #[test]
+    fn test_reload_moves_slide_state_with_its_slide() {
+        let quiz = "# Quiz\n<!-- quiz -->\n- [ ] A\n- [x] B\n";
+        let table = "# Data\n| a |\n|---|\n| 1 |\n";
+        let mut app = App::from_deck(
+            parse_deck(&format!("# Intro\n{}{}# Next\n# Next\n", quiz, table)).unwrap(),
+        );
+        app.quiz_answers.insert(1, 2);
+        app.table_view = Some(TableView::new(2));
+        app.revealed.insert(3, 1);
+
+        // An inserted slide moves the answer along, and state on a slide that can't be
+        // told apart from another is dropped
+        app.reload(
+            parse_deck(&format!(
+                "# Intro\n# New\n{}{}# Next\n# Next\n",
+                quiz, table
+            ))
+            .unwrap(),
+        );
+        assert_eq!(app.quiz_answers, HashMap::from([(2, 2)]));
+        assert_eq!(app.table_view.as_ref().map(|view| view.slide), Some(3));
+        assert!(app.revealed.is_empty());
+
+        // Deleting the slide drops what was kept for it
+        app.reload(parse_deck(&format!("# Intro\n{}# Next\n", table)).unwrap());
+        assert!(app.quiz_answers.is_empty());
+        assert_eq!(app.table_view.as_ref().map(|view| view.slide), Some(1));
+        app.reload(parse_deck("# Intro\n# Data\nNo table any more\n").unwrap());
+        assert!(app.table_view.is_none());
+    }
+
+    #[test]
+    fn test_reload_keeps_the_forced_theme() {
+        let mut app = App::from_deck(parse_deck("# One\n").unwrap());
+        let mut forced = Theme::default();
+        forced.colors.background = ratatui::style::Color::Black;
+        app.theme_choice.forced = Some(forced.clone());
+        let edited = "+++\n[theme.colors]\nheading = \"red\"\n+++\n\n# One\n";
+        app.reload(parse_deck(edited).unwrap());
+        assert_eq!(app.theme, forced);
+
+        app.theme_choice.forced = None;
+        app.reload(parse_deck(edited).unwrap());
+        assert_eq!(app.theme.colors.heading, ratatui::style::Color::Red);
+    }
+
+    #[test]
+    fn test_jump_back_restores_scroll_offset() {
+        let mut app = App::new(vec![vec![]; 4]);
+        app.scroll_view_state
+            .set_offset(ratatui::layout::Position::new(0, 12));
+        app.jump_to_slide(3);
+        assert_eq!(app.scroll_view_state.offset().y, 0);
+
+        let current = app.position();
+        let back = app.jumps.back(current).unwrap();
+        app.restore_position(back);
+        assert_eq!(app.current_slide, 0);
+        assert_eq!(app.scroll_view_state.offset().y, 12);
+    }
+
+    #[test]
+    fn test_slide_title_prefers_heading() {
+        let deck = parse_deck("<!-- emphasize: x -->\n\nIntro text\n\n## Title\n").unwrap();
+        assert_eq!(slide_title(&deck.slides[0]), "Intro text");
+        assert_eq!(slide_title(&deck.slides[1]), "Title");
+        assert_eq!(slide_title(&[]), "");
+    }
+
+    #[test]
+    fn test_slide_at_line_finds_the_containing_slide() {
+        let deck =
+            parse_deck("---\ntitle: Talk\ntitle_slide: true\n---\n# A\n\ntext\n\n## B\n").unwrap();
+        assert_eq!(deck.slides.len(), 3);
+        // Lines of the frontmatter belong to the generated title slide
+        assert_eq!(slide_at_line(&deck.slides, 2), 0);
+        assert_eq!(slide_at_line(&deck.slides, 5), 1);
+        assert_eq!(slide_at_line(&deck.slides, 8), 1);
+        assert_eq!(slide_at_line(&deck.slides, 9), 2);
+        assert_eq!(slide_at_line(&deck.slides, 100), 2);
+    }
+
     #[test]
     fn test_h1_creates_new_slide() {
         let content = "# Slide 1\nContent 1\n\n# Slide 2\nContent 2";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 2);
     }
 
@@ -231,7 +1180,9 @@ mod tests {
     fn test_h2_creates_new_slide() {
         let content = "## Slide 1\nContent 1\n\n## Slide 2\nContent 2";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 2);
     }
 
@@ -239,7 +1190,9 @@ mod tests {
     fn test_h3_does_not_split_slide() {
         let content = "# Slide 1\n### Subsection\nMore content";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 1);
     }
 
@@ -247,7 +1200,9 @@ mod tests {
     fn test_no_headings_creates_single_slide() {
         let content = "Just some content\nWith multiple lines\nBut no headings";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 1);
     }
 
@@ -255,15 +1210,51 @@ mod tests {
     fn test_mixed_h1_and_h2_split_slides() {
         let content = "# Slide 1\nContent\n\n## Slide 2\nMore content\n\n# Slide 3\nFinal";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 3);
     }
 
+    #[test]
+    fn test_rules_and_markers_split_slides() {
+        let content = "# One\n\n---\n\nStill one\n\n---\n\nTwo\n<!-- end_slide -->\nThree\n\n---\n";
+        let split = Split {
+            headings: false,
+            rules: true,
+            marker: Some("<!-- end_slide -->".to_string()),
+        };
+        let slides = parse_deck_with(content, &split).unwrap().slides;
+        let texts: Vec<String> = slides.iter().map(|slide| slide_text(slide)).collect();
+        assert_eq!(texts, vec!["One", "Still one", "Two", "Three"]);
+
+        // The frontmatter overrides the configured split
+        let content = format!("+++\n[split]\nheadings = false\n+++\n{}", content);
+        let slides = parse_deck_with(&content, &split).unwrap().slides;
+        assert_eq!(slides.len(), 1);
+    }
+
+    #[test]
+    fn test_yaml_frontmatter_title_slide() {
+        let content = "---\ntitle: Ownership\nauthor: Jane Doe\ntitle_slide: true\n---\n# Moves\n";
+        let deck = parse_deck(content).unwrap();
+        assert_eq!(deck.slides.len(), 2);
+        assert_eq!(centering(&deck.slides[0], Center::None), Center::Both);
+        assert_eq!(slide_text(&deck.slides[0]), "Ownership\nJane Doe");
+        assert_eq!(deck.meta.author.as_deref(), Some("Jane Doe"));
+
+        // A deck that is only frontmatter still gets its title slide alone
+        let deck = parse_deck("---\ntitle: Ownership\ntitle_slide: true\n---\n").unwrap();
+        assert_eq!(deck.slides.len(), 1);
+    }
+
     #[test]
     fn test_content_before_first_heading() {
         let content = "Intro content\n\n# Slide 1\nContent";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 2);
     }
 
@@ -271,19 +1262,271 @@ mod tests {
     fn test_empty_file() {
         let content = "";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         assert_eq!(slides.len(), 1);
     }
 
+    #[test]
+    fn test_frontmatter_is_not_a_slide() {
+        let content = "+++\n[theme.colors]\nheading = \"red\"\n+++\n\n# Slide 1\nContent";
+        let deck = parse_deck(content).unwrap();
+        assert_eq!(deck.slides.len(), 1);
+        let theme = deck.frontmatter.theme.unwrap();
+        assert_eq!(theme.colors.heading, ratatui::style::Color::Red);
+    }
+
+    #[test]
+    fn test_broken_frontmatter_is_shown_instead_of_failing() {
+        let content = "+++\ntitle = \"Unclosed\n+++\n# Slide 1\nContent";
+        let deck = parse_deck(content).unwrap();
+        assert_eq!(deck.slides.len(), 2);
+        assert_eq!(deck.problems.len(), 1);
+        assert_eq!(deck.problems[0].line, 1);
+        assert!(
+            deck.problems[0]
+                .message
+                .starts_with("Failed to parse deck frontmatter")
+        );
+
+        let mut lines = vec![];
+        node_to_lines(
+            &deck.slides[0][0],
+            &mut lines,
+            Style::default(),
+            &Theme::default(),
+        );
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert!(text[0].starts_with("✗ Could not parse this part: Failed to parse"));
+        assert_eq!(text[1], "│ +++");
+        assert_eq!(slide_title(&deck.slides[1]), "Slide 1");
+    }
+
+    #[test]
+    fn test_theme_bullets_are_used_for_lists() {
+        let deck = parse_deck("- one\n- two").unwrap();
+        let mut theme = Theme::default();
+        theme.bullets.unordered = "•".to_string();
+        let mut lines = vec![];
+
+        for node in &deck.slides[0] {
+            node_to_lines(node, &mut lines, Style::default(), &theme);
+        }
+
+        assert_eq!(lines[0].spans[0].content, "• ");
+    }
+
+    #[test]
+    fn test_code_block_ansi_output_is_styled() {
+        let deck = parse_deck("```console\n\x1b[32mok\x1b[0m passed\n```").unwrap();
+        let mut lines = vec![];
+
+        for node in &deck.slides[0] {
+            node_to_lines(node, &mut lines, Style::default(), &Theme::default());
+        }
+
+        assert_eq!(lines[1].spans[0].content, "ok");
+        assert_eq!(
+            lines[1].spans[0].style.fg,
+            Some(ratatui::style::Color::Indexed(2))
+        );
+        assert_eq!(lines[1].spans[1].content, " passed");
+    }
+
+    #[test]
+    fn test_code_block_is_highlighted_by_language() {
+        let deck = parse_deck("```rust\nlet x = 1;\n```\n\n```klingon\nQapla'\n```").unwrap();
+        let theme = Theme::default();
+        let mut lines = vec![];
+        for node in &deck.slides[0] {
+            node_to_lines(node, &mut lines, Style::default(), &theme);
+        }
+
+        assert_eq!(lines[1].to_string(), "let x = 1;");
+        assert!(lines[1].spans.len() > 1);
+        // Unknown languages keep the flat code color
+        assert_eq!(lines[5].to_string(), "Qapla'");
+        assert_eq!(lines[5].style.fg, Some(theme.colors.code));
+    }
+
+    #[test]
+    fn test_numbered_code_block_has_a_gutter() {
+        let source: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        let deck = parse_deck(&format!("```text {{numbers}}\n{}```\n", source)).unwrap();
+        let mut theme = Theme::default();
+        let mut lines = vec![];
+        node_to_lines(&deck.slides[0][0], &mut lines, Style::default(), &theme);
+        assert_eq!(lines[0].to_string(), "```text");
+        assert_eq!(lines[1].to_string(), " 1 │ line 1");
+        assert_eq!(lines[10].to_string(), "10 │ line 10");
+        assert_eq!(lines[11].to_string(), "```");
+
+        // Numbering every block from the config
+        let deck = parse_deck("```\nplain\n```\n").unwrap();
+        theme.code.languages.line_numbers = true;
+        let mut lines = vec![];
+        node_to_lines(&deck.slides[0][0], &mut lines, Style::default(), &theme);
+        assert_eq!(lines[1].to_string(), "1 │ plain");
+    }
+
+    #[test]
+    fn test_highlighted_code_lines_get_a_background() {
+        let deck = parse_deck("```text {1,3-4}\na\nbbb\nc\nd\n```\n").unwrap();
+        let theme = Theme::default();
+        let mut lines = vec![];
+        node_to_lines(&deck.slides[0][0], &mut lines, Style::default(), &theme);
+
+        let background = |line: &Line| {
+            line.spans
+                .iter()
+                .map(|span| span.style.bg)
+                .collect::<Vec<_>>()
+        };
+        // Padded to the widest line so the highlight is one even bar
+        assert_eq!(lines[1].to_string(), "a  ");
+        assert_eq!(
+            background(&lines[1]),
+            [Some(theme.colors.line_highlight); 2]
+        );
+        assert_eq!(lines[2].to_string(), "bbb");
+        assert_eq!(background(&lines[2]), [None]);
+        assert_eq!(lines[4].to_string(), "d  ");
+    }
+
+    fn heading_line() -> Line<'static> {
+        let deck = parse_deck("## A rather long title").unwrap();
+        let mut lines = vec![];
+        node_to_lines(
+            &deck.slides[0][0],
+            &mut lines,
+            Style::default(),
+            &Theme::default(),
+        );
+        // Spans borrow from the deck, so copy them out before it's dropped
+        let spans: Vec<Span<'static>> = lines
+            .remove(0)
+            .spans
+            .into_iter()
+            .map(|span| Span::styled(span.content.into_owned(), span.style))
+            .collect();
+        Line::from(spans)
+    }
+
+    fn rendered(markdown: &str) -> Vec<String> {
+        let deck = parse_deck(markdown).unwrap();
+        let mut lines = vec![];
+        for node in &deck.slides[0] {
+            node_to_lines(node, &mut lines, Style::default(), &Theme::default());
+        }
+        condense_lines(lines)
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_condense_drops_blank_lines_and_cuts_code() {
+        let lines = rendered("# Title\n\nText\n\n```rust\na\nb\nc\nd\n```\n");
+        assert_eq!(
+            lines,
+            vec!["# Title", "Text", "```rust", "a", "…", "d", "```"]
+        );
+    }
+
+    #[test]
+    fn test_condense_keeps_short_code_blocks() {
+        let lines = rendered("```\nfirst\n\nlast\n```\n");
+        assert_eq!(lines, vec!["```", "first", "…", "last", "```"]);
+        let lines = rendered("```\none\ntwo\n```\n");
+        assert_eq!(lines, vec!["```", "one", "two", "```"]);
+    }
+
+    #[test]
+    fn test_section_title_of_h2_slides() {
+        let deck =
+            parse_deck("Intro\n\n## Early\n\n# Part one\n\n## A\n\n## B\n\n# Part two\n").unwrap();
+        let titles: Vec<Option<String>> = (0..deck.slides.len())
+            .map(|i| section_title(&deck.slides, i))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                None,
+                None,
+                None,
+                Some("Part one".to_string()),
+                Some("Part one".to_string()),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_footnotes_render_with_labels() {
+        let deck = parse_deck("Claim[^1]\n\n[^1]: Source\n").unwrap();
+        let mut lines = vec![];
+        for node in &deck.slides[0] {
+            node_to_lines(node, &mut lines, Style::default(), &Theme::default());
+        }
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text, vec!["Claim[1]", "", "[1] Source"]);
+    }
+
+    #[test]
+    fn test_rendered_text_borrows_from_slide() {
+        let deck = parse_deck("Some `code` and text").unwrap();
+        let mut lines = vec![];
+        node_to_lines(
+            &deck.slides[0][0],
+            &mut lines,
+            Style::default(),
+            &Theme::default(),
+        );
+        assert!(
+            lines[0]
+                .spans
+                .iter()
+                .all(|span| matches!(span.content, std::borrow::Cow::Borrowed(_)))
+        );
+    }
+
+    #[test]
+    fn test_fit_heading_keeps_heading_that_fits() {
+        let line = fit_heading(heading_line(), 80, TitleFit::Shrink);
+        assert_eq!(line.to_string(), "## A rather long title");
+    }
+
+    #[test]
+    fn test_fit_heading_drops_markers_first() {
+        let line = fit_heading(heading_line(), 20, TitleFit::Shrink);
+        assert_eq!(line.to_string(), "A rather long title");
+    }
+
+    #[test]
+    fn test_fit_heading_truncates_as_last_resort() {
+        let line = fit_heading(heading_line(), 10, TitleFit::Shrink);
+        assert_eq!(line.to_string(), "A rather …");
+        assert_eq!(line.width(), 10);
+    }
+
+    #[test]
+    fn test_fit_heading_wrap_policy_leaves_line_alone() {
+        let line = fit_heading(heading_line(), 10, TitleFit::Wrap);
+        assert_eq!(line.to_string(), "## A rather long title");
+    }
+
     #[test]
     fn test_image_is_rendered_as_link_text() {
         let content = "![demo](demo.gif)";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         let mut lines = vec![];
 
         for node in &slides[0] {
-            node_to_lines(node, &mut lines, Style::default());
+            node_to_lines(node, &mut lines, Style::default(), &Theme::default());
         }
 
         let rendered = lines[0]
@@ -296,15 +1539,38 @@ mod tests {
         assert!(rendered.contains("(demo.gif)"));
     }
 
+    #[test]
+    fn test_strikethrough_is_crossed_out() {
+        let deck = parse_deck("Use ~~unwrap~~ `?` instead").unwrap();
+        let mut lines = vec![];
+        node_to_lines(
+            &deck.slides[0][0],
+            &mut lines,
+            Style::default(),
+            &Theme::default(),
+        );
+        let struck = &lines[0].spans[1];
+        assert_eq!(struck.content, "unwrap");
+        assert!(struck.style.add_modifier.contains(Modifier::CROSSED_OUT));
+        assert!(
+            !lines[0].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::CROSSED_OUT)
+        );
+    }
+
     #[test]
     fn test_paragraph_newlines_render_as_spaces() {
         let content = "# Slide\nLine one\nLine two";
         let file = create_temp_md_file(content);
-        let slides = load_slides(file.path().to_str().unwrap()).unwrap();
+        let slides = load_deck(file.path().to_str().unwrap(), &Split::default())
+            .unwrap()
+            .slides;
         let mut lines = vec![];
 
         for node in &slides[0] {
-            node_to_lines(node, &mut lines, Style::default());
+            node_to_lines(node, &mut lines, Style::default(), &Theme::default());
         }
 
         let rendered = lines[2]
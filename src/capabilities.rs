@@ -0,0 +1,606 @@
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Read, Write};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::terminal,
+    style::{Color, Modifier},
+};
+
+use crate::config::{Features, Support};
+use crate::images::ImageProtocol;
+
+/// What the terminal markdeck runs in can do, as far as it can be told
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Capabilities {
+    /// `TERM`, followed by `TERM_PROGRAM` when set
+    pub terminal: String,
+    pub colors: ColorDepth,
+    pub italics: bool,
+    /// The kitty graphics protocol
+    pub kitty_graphics: bool,
+    /// iTerm2's inline images, which WezTerm speaks as well
+    pub iterm_images: bool,
+    pub sixel: bool,
+    /// OSC 8 hyperlinks
+    pub hyperlinks: bool,
+    /// Setting the clipboard with OSC 52
+    pub clipboard: bool,
+    /// The kitty keyboard protocol, which tells apart keys like Tab and Ctrl-i
+    pub keyboard_enhancement: bool,
+    /// Whether the terminal answered the queries, so the graphics and keyboard
+    /// results are probed rather than guessed
+    pub probed: bool,
+}
+
+/// How many colors the terminal can show
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// `NO_COLOR` is set or the terminal is dumb: bold, italics and reverse video only
+    None,
+    /// The 16 ANSI colors
+    #[default]
+    Ansi16,
+    Ansi256,
+    /// 24-bit colors
+    TrueColor,
+}
+
+impl ColorDepth {
+    fn from_env(var: &impl Fn(&str) -> Option<String>, term: &str, modern: bool) -> Self {
+        // https://no-color.org asks for any non-empty value to disable colors
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) || term == "dumb" {
+            ColorDepth::None
+        } else if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")) {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") || modern {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Terminals known to support OSC 8 hyperlinks and OSC 52 clipboard access
+const MODERN_TERMINALS: [&str; 7] = [
+    "kitty",
+    "wezterm",
+    "iterm",
+    "ghostty",
+    "foot",
+    "alacritty",
+    "contour",
+];
+
+impl Capabilities {
+    /// Guesses capabilities from environment variables alone
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        let names = format!("{} {}", term, program).to_lowercase();
+        let modern = MODERN_TERMINALS.iter().any(|name| names.contains(name))
+            || var("KITTY_WINDOW_ID").is_some()
+            || var("WT_SESSION").is_some();
+        let vte = var("VTE_VERSION")
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5000);
+
+        Capabilities {
+            terminal: if program.is_empty() {
+                term.clone()
+            } else {
+                format!("{} ({})", term, program)
+            },
+            colors: ColorDepth::from_env(&var, &term, modern),
+            // The Linux console and screen's terminfo lack italics
+            italics: !(matches!(term.as_str(), "linux" | "vt100" | "vt220" | "dumb")
+                || term.starts_with("screen")),
+            kitty_graphics: names.contains("kitty") || names.contains("ghostty"),
+            iterm_images: matches!(program.as_str(), "iTerm.app" | "WezTerm")
+                || var("LC_TERMINAL").as_deref() == Some("iTerm2"),
+            sixel: false,
+            hyperlinks: modern || vte || program == "vscode",
+            clipboard: modern,
+            keyboard_enhancement: false,
+            probed: false,
+        }
+    }
+
+    /// Guesses capabilities from this process's environment, which is instant
+    /// unlike querying the terminal
+    pub fn guess() -> Self {
+        Capabilities::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Detects capabilities from the environment and by querying the terminal
+    ///
+    /// Queries need an interactive terminal; otherwise only the environment is used.
+    pub fn detect() -> Result<Self> {
+        let mut capabilities = Capabilities::guess();
+        if std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+            let responses = query_terminal()?;
+            capabilities.apply_responses(&responses);
+        }
+        Ok(capabilities)
+    }
+
+    /// Updates the probed capabilities from the terminal's replies to `QUERIES`
+    pub fn apply_responses(&mut self, responses: &[u8]) {
+        let responses = String::from_utf8_lossy(responses);
+        let Some(attributes) = device_attributes(&responses) else {
+            return;
+        };
+        self.probed = true;
+        self.sixel = attributes.split(';').any(|attribute| attribute == "4");
+        self.kitty_graphics = responses.contains("\x1b_Gi=31;OK");
+        self.keyboard_enhancement = responses
+            .match_indices("\x1b[?")
+            .any(|(i, _)| keyboard_flags(&responses[i + 3..]));
+    }
+
+    /// The best supported protocol for images
+    pub fn image_protocol(&self) -> Option<ImageProtocol> {
+        [
+            (self.kitty_graphics, ImageProtocol::Kitty),
+            (self.iterm_images, ImageProtocol::Iterm2),
+            (self.sixel, ImageProtocol::Sixel),
+        ]
+        .into_iter()
+        .find_map(|(supported, protocol)| supported.then_some(protocol))
+    }
+
+    /// Which features to use, given the user's overrides
+    pub fn enabled(&self, features: &Features) -> EnabledFeatures {
+        let protocol = self.image_protocol();
+        EnabledFeatures {
+            // Forcing images on without a known protocol tries kitty's, the most common
+            images: features
+                .images
+                .resolve(protocol.is_some())
+                .then(|| protocol.unwrap_or(ImageProtocol::Kitty)),
+            hyperlinks: features.hyperlinks.resolve(self.hyperlinks),
+            // Forcing truecolor off keeps the 256-color palette when the terminal has it
+            colors: match features.truecolor {
+                Support::Auto => self.colors,
+                Support::ForceOn => ColorDepth::TrueColor,
+                Support::ForceOff => self.colors.min(ColorDepth::Ansi256),
+            },
+            italics: features.italics.resolve(self.italics),
+        }
+    }
+
+    /// One line per capability with what markdeck does with it, given the user's
+    /// overrides
+    pub fn report(&self, features: &Features) -> String {
+        let mut report = format!("Terminal: {}\n", self.terminal);
+        if !self.probed {
+            report.push_str(
+                "The terminal didn't answer queries; graphics and keyboard support are guessed\n",
+            );
+        }
+        report.push('\n');
+
+        let colors = match features.truecolor {
+            Support::Auto => match self.colors {
+                ColorDepth::None => "none",
+                ColorDepth::Ansi16 => "16",
+                ColorDepth::Ansi256 => "256",
+                ColorDepth::TrueColor => "24-bit",
+            },
+            Support::ForceOn => "forced on",
+            Support::ForceOff => "forced off",
+        };
+        let effect = match self.enabled(features).colors {
+            ColorDepth::None => "text is shown without colors, highlights in reverse video",
+            ColorDepth::Ansi16 => "colors are reduced to the 16 ANSI colors",
+            ColorDepth::Ansi256 => "24-bit colors are reduced to the 256-color palette",
+            ColorDepth::TrueColor => "24-bit theme and code colors are shown exactly",
+        };
+        let _ = writeln!(report, "{:<18} {:<10} {}", "colors", colors, effect);
+
+        let rows = [
+            (
+                "italics",
+                self.italics,
+                features.italics,
+                "emphasis and quotes are italic",
+                "italic text is shown upright",
+            ),
+            (
+                "kitty graphics",
+                self.kitty_graphics,
+                features.images,
+                "PNG images are drawn inline",
+                "images show their alt text",
+            ),
+            (
+                "iTerm2 images",
+                self.iterm_images,
+                features.images,
+                "PNG images are drawn inline",
+                "images show their alt text",
+            ),
+            (
+                "sixel",
+                self.sixel,
+                features.images,
+                "PNG images are drawn inline",
+                "images show their alt text",
+            ),
+            (
+                "OSC 8 hyperlinks",
+                self.hyperlinks,
+                features.hyperlinks,
+                "links are clickable",
+                "links are underlined text",
+            ),
+            (
+                "OSC 52 clipboard",
+                self.clipboard,
+                Support::Auto,
+                "supported, not used yet",
+                "copying to the clipboard is unavailable",
+            ),
+            (
+                "enhanced keyboard",
+                self.keyboard_enhancement,
+                Support::Auto,
+                "supported, not used yet",
+                "Tab and Ctrl-i, Enter and Ctrl-m can't be bound separately",
+            ),
+        ];
+        for (name, detected, support, enabled, degraded) in rows {
+            let state = match support {
+                Support::Auto if detected => "yes",
+                Support::Auto => "no",
+                Support::ForceOn => "forced on",
+                Support::ForceOff => "forced off",
+            };
+            let _ = writeln!(
+                report,
+                "{:<18} {:<10} {}",
+                name,
+                state,
+                if support.resolve(detected) {
+                    enabled
+                } else {
+                    degraded
+                },
+            );
+        }
+        report
+    }
+}
+
+/// Features markdeck uses, once detection and overrides are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnabledFeatures {
+    /// How images are drawn, if at all
+    pub images: Option<ImageProtocol>,
+    pub hyperlinks: bool,
+    pub colors: ColorDepth,
+    pub italics: bool,
+}
+
+/// Renders as if the terminal supported everything markdeck draws without escape
+/// sequences of its own
+impl Default for EnabledFeatures {
+    fn default() -> Self {
+        EnabledFeatures {
+            images: None,
+            hyperlinks: false,
+            colors: ColorDepth::TrueColor,
+            italics: true,
+        }
+    }
+}
+
+impl EnabledFeatures {
+    /// The color as the terminal gets it
+    pub fn color(&self, color: Color) -> Color {
+        match self.colors {
+            ColorDepth::None => Color::Reset,
+            ColorDepth::Ansi16 => to_ansi16(color),
+            ColorDepth::Ansi256 => to_indexed(color),
+            ColorDepth::TrueColor => color,
+        }
+    }
+
+    /// Rewrites a rendered frame for a terminal with fewer colors or without italics
+    ///
+    /// Without colors, cells highlighted with another background than the theme's,
+    /// like search matches and the cursor line, are reversed instead.
+    pub fn degrade(&self, buffer: &mut Buffer, background: Color) {
+        if self.colors == ColorDepth::TrueColor && self.italics {
+            return;
+        }
+        for cell in &mut buffer.content {
+            if self.colors == ColorDepth::None && cell.bg != background && cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            cell.fg = self.color(cell.fg);
+            cell.bg = self.color(cell.bg);
+            cell.underline_color = self.color(cell.underline_color);
+            if !self.italics {
+                cell.modifier.remove(Modifier::ITALIC);
+            }
+        }
+    }
+}
+
+/// Nearest color of the 256-color palette's 6×6×6 cube or gray ramp
+fn to_indexed(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| (0..6).min_by_key(|&i| LEVELS[i].abs_diff(c)).unwrap_or(0);
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let average = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_index;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    if distance((gray, gray, gray)) < distance(cube) {
+        Color::Indexed(232 + gray_index)
+    } else {
+        Color::Indexed(16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8)
+    }
+}
+
+/// The 16 ANSI colors with xterm's default values
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Nearest of the 16 ANSI colors, for RGB and 256-palette colors
+fn to_ansi16(color: Color) -> Color {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(i) if i < 16 => return ANSI16[i as usize].0,
+        Color::Indexed(i) if i >= 232 => {
+            let gray = 8 + 10 * (i - 232);
+            (gray, gray, gray)
+        }
+        Color::Indexed(i) => {
+            const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+            let i = (i - 16) as usize;
+            (LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6])
+        }
+        _ => return color,
+    };
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map_or(color, |(ansi, _)| *ansi)
+}
+
+/// Kitty graphics and keyboard protocol queries, then primary device attributes
+///
+/// Every terminal answers the device attributes query, so its reply marks the end
+/// of the replies to expect.
+const QUERIES: &str = "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[?u\x1b[c";
+
+fn query_terminal() -> Result<Vec<u8>> {
+    terminal::enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    let written = stdout
+        .write_all(QUERIES.as_bytes())
+        .and_then(|_| stdout.flush());
+
+    // Reading blocks, so a terminal that never answers can't hang the report
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0; 256];
+        while let Ok(n) = std::io::stdin().read(&mut buf) {
+            if n == 0 || sender.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut responses = vec![];
+    while device_attributes(&String::from_utf8_lossy(&responses)).is_none() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok(bytes) => responses.extend(bytes),
+            Err(_) => break,
+        }
+    }
+    terminal::disable_raw_mode()?;
+    written?;
+    Ok(responses)
+}
+
+/// Parameters of the primary device attributes reply, `ESC [ ? params c`
+fn device_attributes(responses: &str) -> Option<&str> {
+    responses.match_indices("\x1b[?").find_map(|(i, _)| {
+        let params = &responses[i + 3..];
+        let end = params.find(|c: char| !c.is_ascii_digit() && c != ';')?;
+        (params.as_bytes()[end] == b'c').then(|| &params[..end])
+    })
+}
+
+/// Whether a reply continues as the keyboard flags, `ESC [ ? flags u`
+fn keyboard_flags(params: &str) -> bool {
+    let digits = params.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && params[digits..].starts_with('u')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> Capabilities {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        Capabilities::from_env(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_environment_heuristics() {
+        let kitty = env(&[("TERM", "xterm-kitty"), ("COLORTERM", "truecolor")]);
+        assert_eq!(kitty.colors, ColorDepth::TrueColor);
+        assert!(kitty.kitty_graphics && kitty.hyperlinks && kitty.clipboard);
+        assert_eq!(kitty.terminal, "xterm-kitty");
+
+        let plain = env(&[
+            ("TERM", "xterm-256color"),
+            ("TERM_PROGRAM", "Apple_Terminal"),
+        ]);
+        assert_eq!(plain.colors, ColorDepth::Ansi256);
+        assert!(!plain.hyperlinks && !plain.clipboard);
+        assert_eq!(plain.terminal, "xterm-256color (Apple_Terminal)");
+
+        let gnome = env(&[("TERM", "xterm-256color"), ("VTE_VERSION", "7600")]);
+        assert!(gnome.hyperlinks && !gnome.clipboard);
+        assert_eq!(gnome.image_protocol(), None);
+
+        let iterm = env(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")]);
+        assert_eq!(iterm.image_protocol(), Some(ImageProtocol::Iterm2));
+    }
+
+    #[test]
+    fn test_responses_are_parsed() {
+        let mut capabilities = Capabilities::default();
+        capabilities.apply_responses(b"\x1b_Gi=31;OK\x1b\\\x1b[?1u\x1b[?62;4;22c");
+        assert!(capabilities.probed);
+        assert!(capabilities.kitty_graphics && capabilities.sixel);
+        assert!(capabilities.keyboard_enhancement);
+
+        let mut capabilities = Capabilities::default();
+        capabilities.apply_responses(b"\x1b[?1;2c");
+        assert!(capabilities.probed);
+        assert!(!capabilities.kitty_graphics && !capabilities.sixel);
+        assert!(!capabilities.keyboard_enhancement);
+    }
+
+    #[test]
+    fn test_unanswered_queries_keep_guesses() {
+        let mut capabilities = env(&[("TERM", "xterm-kitty")]);
+        capabilities.apply_responses(b"");
+        assert!(!capabilities.probed);
+        assert!(capabilities.kitty_graphics);
+    }
+
+    #[test]
+    fn test_report_states_degradations_and_overrides() {
+        let capabilities = env(&[("TERM", "dumb")]);
+        let report = capabilities.report(&Features::default());
+        assert!(report.starts_with("Terminal: dumb\n"));
+        assert!(report.contains("colors             none       text is shown without colors"));
+
+        let features = Features {
+            truecolor: Support::ForceOn,
+            ..Features::default()
+        };
+        assert!(capabilities.report(&features).contains(
+            "colors             forced on  24-bit theme and code colors are shown exactly"
+        ));
+    }
+
+    #[test]
+    fn test_overrides_win_over_detection() {
+        let capabilities = env(&[("TERM", "xterm-kitty"), ("COLORTERM", "truecolor")]);
+        let features = Features {
+            hyperlinks: Support::ForceOff,
+            italics: Support::ForceOn,
+            ..Features::default()
+        };
+        let enabled = capabilities.enabled(&features);
+        assert_eq!(enabled.colors, ColorDepth::TrueColor);
+        assert!(enabled.italics);
+        assert_eq!(enabled.images, Some(ImageProtocol::Kitty));
+        assert!(!enabled.hyperlinks);
+
+        let linux = env(&[("TERM", "linux")]).enabled(&features);
+        assert_eq!(linux.colors, ColorDepth::Ansi16);
+        assert!(linux.italics);
+    }
+
+    #[test]
+    fn test_degrade_reduces_colors_and_drops_italics() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 2, 1));
+        buffer[(0, 0)].fg = Color::Rgb(255, 0, 0);
+        buffer[(0, 0)].modifier = Modifier::ITALIC | Modifier::BOLD;
+        buffer[(1, 0)].fg = Color::Rgb(128, 128, 128);
+        buffer[(1, 0)].bg = Color::Blue;
+
+        let enabled = EnabledFeatures {
+            colors: ColorDepth::Ansi256,
+            italics: false,
+            ..EnabledFeatures::default()
+        };
+        enabled.degrade(&mut buffer, Color::Reset);
+        assert_eq!(buffer[(0, 0)].fg, Color::Indexed(196));
+        assert_eq!(buffer[(0, 0)].modifier, Modifier::BOLD);
+        assert_eq!(buffer[(1, 0)].fg, Color::Indexed(244));
+        assert_eq!(buffer[(1, 0)].bg, Color::Blue);
+    }
+
+    #[test]
+    fn test_no_color_and_16_color_terminals() {
+        let no_color = env(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]);
+        assert_eq!(no_color.colors, ColorDepth::None);
+        let empty = env(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]);
+        assert_eq!(empty.colors, ColorDepth::Ansi256);
+        assert_eq!(env(&[("TERM", "xterm")]).colors, ColorDepth::Ansi16);
+
+        assert_eq!(to_ansi16(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(to_ansi16(Color::Indexed(3)), Color::Yellow);
+        assert_eq!(to_ansi16(Color::Indexed(196)), Color::LightRed);
+        assert_eq!(to_ansi16(Color::Indexed(244)), Color::DarkGray);
+        assert_eq!(to_ansi16(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn test_degrade_without_colors_reverses_highlights() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 3, 1));
+        buffer[(0, 0)].fg = Color::Black;
+        buffer[(0, 0)].bg = Color::Yellow;
+        buffer[(1, 0)].bg = Color::Rgb(0, 43, 54);
+        buffer[(2, 0)].fg = Color::Cyan;
+
+        let enabled = EnabledFeatures {
+            colors: ColorDepth::None,
+            ..EnabledFeatures::default()
+        };
+        enabled.degrade(&mut buffer, Color::Rgb(0, 43, 54));
+        assert_eq!(buffer[(0, 0)].modifier, Modifier::REVERSED);
+        assert_eq!(buffer[(0, 0)].bg, Color::Reset);
+        assert_eq!(buffer[(1, 0)].modifier, Modifier::empty());
+        assert_eq!(buffer[(1, 0)].bg, Color::Reset);
+        assert_eq!(buffer[(2, 0)].fg, Color::Reset);
+    }
+}
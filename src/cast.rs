@@ -0,0 +1,195 @@
+//! `markdeck export cast`: the deck navigated by a script of key presses, recorded as
+//! an asciinema cast to embed a preview of the talk in a repo or blog post
+//!
+//! GIFs are made from the cast by `agg`, like copying goes through the platform's
+//! clipboard tool.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use ratatui::{Terminal, backend::TestBackend};
+use serde::Deserialize;
+
+use crate::ansi::write_ansi;
+use crate::app::App;
+use crate::config::{Config, FontConfig, parse_key_script};
+use crate::fonts::DEFAULT_FAMILIES;
+use crate::{handle_key, render};
+
+/// Seconds between the keys of one step, so typing a search can be followed
+const KEY_INTERVAL: f64 = 0.15;
+
+/// What the recording does, read from a TOML file
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Script {
+    /// Size of the recorded terminal
+    pub width: u16,
+    pub height: u16,
+    /// Seconds each step is shown for, unless it sets its own `wait`
+    pub delay: f64,
+    pub steps: Vec<Step>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Script {
+            width: 100,
+            height: 30,
+            delay: 2.0,
+            steps: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    /// Keys to press, written like `l`, `/borrow<Enter>` or `<C-f>`
+    pub keys: String,
+    pub wait: Option<f64>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script: {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid script: {}", path.display()))
+    }
+}
+
+/// `talk.md` is recorded to `talk.cast`
+pub fn default_output(path: &Path) -> PathBuf {
+    path.with_extension("cast")
+}
+
+/// The asciicast v2 recording of the script played on the deck
+pub fn record(
+    app: &mut App,
+    config: &Config,
+    script: &Script,
+    title: Option<&str>,
+) -> Result<String> {
+    // Bad keys are reported before anything is recorded
+    let steps = script
+        .steps
+        .iter()
+        .map(|step| {
+            Ok((
+                parse_key_script(&step.keys)?,
+                step.wait.unwrap_or(script.delay),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut terminal = Terminal::new(TestBackend::new(script.width, script.height))?;
+    let mut header = serde_json::json!({
+        "version": 2,
+        "width": script.width,
+        "height": script.height,
+        "env": { "TERM": "xterm-256color" },
+    });
+    if let Some(title) = title {
+        header["title"] = title.into();
+    }
+    let mut cast = header.to_string() + "\n";
+    let mut time = 0.0;
+    cast.push_str(&event(time, &frame(&mut terminal, app, config)?));
+    time += script.delay;
+    for (keys, wait) in steps {
+        for (index, (code, modifiers)) in keys.into_iter().enumerate() {
+            if index > 0 {
+                time += KEY_INTERVAL;
+            }
+            handle_key(app, code, modifiers, config);
+            // A still frame would keep the slide change flash on for the whole step
+            app.flash_started = None;
+            if app.should_quit {
+                return Ok(cast);
+            }
+            cast.push_str(&event(time, &frame(&mut terminal, app, config)?));
+        }
+        time += wait;
+    }
+    // Holds the last frame for the last step's wait
+    cast.push_str(&event(time, ""));
+    Ok(cast)
+}
+
+/// The screen as the output that draws it from the top left
+fn frame(terminal: &mut Terminal<TestBackend>, app: &mut App, config: &Config) -> Result<String> {
+    terminal.draw(|frame| render(app, frame, config))?;
+    let screen = write_ansi(terminal.backend().buffer());
+    // Ending the last row in a newline would scroll the recording by one
+    Ok(format!(
+        "\x1b[H\x1b[2J{}",
+        screen.trim_end_matches('\n').replace('\n', "\r\n")
+    ))
+}
+
+fn event(time: f64, output: &str) -> String {
+    let time = (time * 1000.0).round() / 1000.0;
+    serde_json::json!([time, "o", output]).to_string() + "\n"
+}
+
+/// Converts the cast to a GIF with `agg`, in the configured font
+pub fn to_gif(cast: &Path, gif: &Path, font: &FontConfig) -> Result<()> {
+    let mut command = Command::new("agg");
+    command.arg("--font-family").arg(font_families(font));
+    if let Some(size) = font.size {
+        command.arg("--font-size").arg(size.to_string());
+    }
+    let output = command.arg(cast).arg(gif).stdin(Stdio::null()).output();
+    let output = match output {
+        Ok(output) => output,
+        Err(_) => bail!(
+            "agg not found; install it from https://github.com/asciinema/agg or export a .cast"
+        ),
+    };
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+pub fn font_families(font: &FontConfig) -> &str {
+    font.family.as_deref().unwrap_or(DEFAULT_FAMILIES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_recording_follows_the_script() {
+        let mut app =
+            App::from_deck(parse_deck("# Intro\n\nHello\n\n# Ownership\n\nMoves\n").unwrap());
+        let script: Script = toml::from_str(
+            "width = 40\nheight = 10\ndelay = 1.5\n[[steps]]\nkeys = \"l\"\n[[steps]]\nkeys = \"/mo<Enter>\"\nwait = 3",
+        )
+        .unwrap();
+        let cast = record(&mut app, &Config::default(), &script, Some("Talk")).unwrap();
+        let lines: Vec<serde_json::Value> = cast
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines[0]["width"], 40);
+        assert_eq!(lines[0]["title"], "Talk");
+        let times: Vec<f64> = lines[1..]
+            .iter()
+            .map(|event| event[0].as_f64().unwrap())
+            .collect();
+        // The first slide, l, then the four keys of the search a key interval apart
+        assert_eq!(times, [0.0, 1.5, 3.0, 3.15, 3.3, 3.45, 6.45]);
+        let second = lines[2][2].as_str().unwrap();
+        assert!(second.starts_with("\x1b[H\x1b[2J"));
+        assert!(second.contains("Ownership"));
+        assert!(!second.ends_with('\n'));
+
+        let bad: Script = toml::from_str("[[steps]]\nkeys = \"<Nope>\"").unwrap();
+        let error = record(&mut app, &Config::default(), &bad, None).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown key <Nope> in \"<Nope>\"");
+    }
+}
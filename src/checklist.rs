@@ -0,0 +1,101 @@
+//! Workshop checklists from fenced `checklist` blocks
+//!
+//! Every line of the block is a step. Steps are ticked off in order with a key as the
+//! room progresses, counting across all checklist blocks on the slide.
+
+use markdown::mdast::{Code, Node};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::theme::Theme;
+
+pub fn as_checklist(node: &Node) -> Option<&Code> {
+    match node {
+        Node::Code(code) if code.lang.as_deref() == Some("checklist") => Some(code),
+        _ => None,
+    }
+}
+
+fn items(code: &Code) -> impl Iterator<Item = &str> {
+    code.value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+}
+
+/// Steps across every checklist on the slide
+pub fn item_count(slide: &[Node]) -> usize {
+    slide
+        .iter()
+        .filter_map(as_checklist)
+        .map(|code| items(code).count())
+        .sum()
+}
+
+/// Lines of one checklist whose first step is number `first` on the slide, with the
+/// first `ticked` steps of the slide done
+pub fn checklist_lines(
+    code: &Code,
+    first: usize,
+    ticked: usize,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    for (i, item) in items(code).enumerate() {
+        let line = if first + i < ticked {
+            let style = Style::default().fg(theme.colors.correct);
+            Line::from(vec![
+                Span::styled("[✔] ", style),
+                Span::styled(item.to_string(), style.add_modifier(Modifier::DIM)),
+            ])
+        } else if first + i == ticked {
+            // The step the room is working on
+            Line::styled(
+                format!("[ ] {}", item),
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Line::raw(format!("[ ] {}", item))
+        };
+        lines.push(line);
+    }
+    lines.push(Line::raw(""));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    const LAB: &str = "# Lab\n```checklist\nClone the repo\n\nRun the tests\n```\n\n```checklist\nOpen a PR\n```\n";
+
+    #[test]
+    fn test_items_count_across_blocks() {
+        let deck = parse_deck(LAB).unwrap();
+        assert_eq!(item_count(&deck.slides[0]), 3);
+        assert_eq!(
+            item_count(&parse_deck("```rust\nx\n```").unwrap().slides[0]),
+            0
+        );
+    }
+
+    #[test]
+    fn test_ticked_steps_are_marked() {
+        let deck = parse_deck(LAB).unwrap();
+        let blocks: Vec<&Code> = deck.slides[0].iter().filter_map(as_checklist).collect();
+        let text = |code, first, ticked| -> Vec<String> {
+            checklist_lines(code, first, ticked, &Theme::default())
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        };
+        assert_eq!(
+            text(blocks[0], 0, 1),
+            vec!["[✔] Clone the repo", "[ ] Run the tests", ""]
+        );
+        assert_eq!(text(blocks[1], 2, 3), vec!["[✔] Open a PR", ""]);
+    }
+}
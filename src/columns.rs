@@ -0,0 +1,66 @@
+//! Side-by-side content: every `<!-- column -->` on a slide starts a column, and what
+//! comes before the first one spans the whole slide above them
+//!
+//! Columns wrap on their own, each to an equal share of the slide's width.
+
+use markdown::mdast::Node;
+
+use crate::directives::Directive;
+
+/// Blank columns between two columns
+pub const GAP: u16 = 4;
+
+fn is_column(node: &Node) -> bool {
+    matches!(node, Node::Html(html) if Directive::parse(&html.value) == Some(Directive::Column))
+}
+
+/// Columns the slide is laid out in, none for a slide without `column` directives
+pub fn count(slide: &[Node]) -> usize {
+    slide.iter().filter(|node| is_column(node)).count()
+}
+
+/// The nodes spanning the slide, then those of each column
+pub fn split(slide: &[Node]) -> (&[Node], Vec<&[Node]>) {
+    let mut parts = slide.split(is_column);
+    let lead = parts.next().unwrap_or_default();
+    (lead, parts.collect())
+}
+
+/// Width of each of `count` columns side by side in `width`
+pub fn width(width: u16, count: usize) -> u16 {
+    let count = count.max(1) as u16;
+    (width.saturating_sub(GAP * (count - 1)) / count).max(1)
+}
+
+/// Column each of `count` columns starts at
+pub fn offset(width: u16, count: usize, index: usize) -> u16 {
+    (self::width(width, count) + GAP) * index as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_split_at_column_directives() {
+        let deck = parse_deck(
+            "# Before and after\n\n<!-- column -->\n\n- a\n- b\n\n<!-- column -->\n\n- c\n",
+        )
+        .unwrap();
+        let slide = &deck.slides[0];
+        assert_eq!(count(slide), 2);
+        let (lead, columns) = split(slide);
+        assert_eq!(lead.len(), 1);
+        let text: Vec<String> = columns
+            .iter()
+            .map(|column| column.iter().map(|node| node.to_string()).collect())
+            .collect();
+        assert_eq!(text, ["ab", "c"]);
+
+        assert_eq!(width(80, 2), 38);
+        assert_eq!(offset(80, 2, 1), 42);
+        assert_eq!(width(80, 3), 24);
+        assert_eq!(width(80, 0), 80);
+    }
+}
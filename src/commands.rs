@@ -1,7 +1,22 @@
 use crate::app::App;
+use crate::checklist;
+use crate::copy::{code_block, slide_text};
+use crate::cursor::{Cursor, next_row, scroll_to};
+use crate::details::has_details;
+use crate::directives::{has_toc, pause_count, toc_target};
+use crate::overview::Overview;
+use crate::run;
+use crate::screenshot;
+use crate::search::SearchInput;
+use crate::slide_loop::SlideLoop;
+use crate::table::{TableView, tables};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
 use tui_scrollview::ScrollViewState;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Command {
     ScrollDown,
     ScrollUp,
@@ -13,11 +28,238 @@ pub enum Command {
     JumpToBottom,
     NextSlide,
     PreviousSlide,
+    ToggleBlank,
+    Overview,
+    StartSearch,
+    SearchNext,
+    SearchPrevious,
+    ClearSearch,
+    JumpBack,
+    JumpForward,
+    TogglePresenting,
+    ToggleSpeakerView,
+    ToggleProgress,
+    /// Switches to the next built-in theme, and back to the deck's own after the last
+    CycleTheme,
+    /// Starts the talk timer, or pauses and resumes it
+    ToggleTimer,
+    /// Turns the presenter cursor on or off; while on, scrolling moves it instead
+    ToggleCursor,
+    /// Shows every command and its keys until the next key press
+    CheatSheet,
+    /// Sends the slide as plain text to the clipboard or the configured FIFO
+    CopySlide,
+    /// Sends the slide's nth code block there, counting from 1, as typed with `2Y`
+    CopyCode(usize),
+    /// Lets the slide's tables be sorted by column and filtered
+    TableMode,
+    /// Jumps to the table of contents entry under the cursor, turning the cursor on at
+    /// the first entry if it's off
+    OpenTocEntry,
+    ZoomOut,
+    ToggleDetails,
+    /// Re-runs the `screenshot` directives on the current slide
+    RefreshScreenshots,
+    /// Ticks off the next step of the current slide's checklists
+    CheckItem,
+    UncheckItem,
+    /// Marks the first slide of a rehearsal loop, then its last, then stops looping
+    LoopSlides,
+    /// Runs the slide's nth code block that has an interpreter, counting from 1, as
+    /// typed with `2x`
+    RunCode(usize),
+    Quit,
+    /// Jumps to the slide shown with this number, as typed with `12G` or `:12`
+    GotoSlide(usize),
+    /// A user-defined command from the config's `[commands]` section
+    Shell {
+        name: String,
+        run: String,
+    },
 }
 
 impl Command {
+    pub const ALL: [Command; 37] = [
+        Command::ScrollDown,
+        Command::ScrollUp,
+        Command::PageDown,
+        Command::PageUp,
+        Command::HalfPageDown,
+        Command::HalfPageUp,
+        Command::JumpToTop,
+        Command::JumpToBottom,
+        Command::NextSlide,
+        Command::PreviousSlide,
+        Command::ToggleBlank,
+        Command::Overview,
+        Command::StartSearch,
+        Command::SearchNext,
+        Command::SearchPrevious,
+        Command::ClearSearch,
+        Command::JumpBack,
+        Command::JumpForward,
+        Command::TogglePresenting,
+        Command::ToggleSpeakerView,
+        Command::ToggleProgress,
+        Command::CycleTheme,
+        Command::ToggleTimer,
+        Command::ToggleCursor,
+        Command::CheatSheet,
+        Command::CopySlide,
+        Command::CopyCode(1),
+        Command::TableMode,
+        Command::OpenTocEntry,
+        Command::ZoomOut,
+        Command::ToggleDetails,
+        Command::RefreshScreenshots,
+        Command::CheckItem,
+        Command::UncheckItem,
+        Command::LoopSlides,
+        Command::RunCode(1),
+        Command::Quit,
+    ];
+
+    /// Name of the command's `[keymaps]` entry
+    pub fn name(&self) -> &str {
+        match self {
+            Command::ScrollDown => "scroll_down",
+            Command::ScrollUp => "scroll_up",
+            Command::PageDown => "page_down",
+            Command::PageUp => "page_up",
+            Command::HalfPageDown => "half_page_down",
+            Command::HalfPageUp => "half_page_up",
+            Command::JumpToTop => "jump_to_top",
+            Command::JumpToBottom => "jump_to_bottom",
+            Command::NextSlide => "next_slide",
+            Command::PreviousSlide => "previous_slide",
+            Command::ToggleBlank => "blank_screen",
+            Command::Overview => "overview",
+            Command::StartSearch => "search",
+            Command::SearchNext => "search_next",
+            Command::SearchPrevious => "search_previous",
+            Command::ClearSearch => "clear_search",
+            Command::JumpBack => "jump_back",
+            Command::JumpForward => "jump_forward",
+            Command::TogglePresenting => "presenting",
+            Command::ToggleSpeakerView => "speaker_view",
+            Command::ToggleProgress => "progress_bar",
+            Command::CycleTheme => "cycle_theme",
+            Command::ToggleTimer => "timer",
+            Command::ToggleCursor => "cursor",
+            Command::CheatSheet => "cheat_sheet",
+            Command::CopySlide => "copy_slide",
+            Command::CopyCode(_) => "copy_code",
+            Command::TableMode => "table_mode",
+            Command::OpenTocEntry => "open_toc_entry",
+            Command::ZoomOut => "zoom_out",
+            Command::ToggleDetails => "toggle_details",
+            Command::RefreshScreenshots => "refresh_screenshots",
+            Command::CheckItem => "check_item",
+            Command::UncheckItem => "uncheck_item",
+            Command::LoopSlides => "loop_slides",
+            Command::RunCode(_) => "run_code",
+            Command::Quit => "quit",
+            Command::GotoSlide(_) => "goto_slide",
+            Command::Shell { name, .. } => name,
+        }
+    }
+
+    /// What the command does, for the cheat sheet
+    pub fn description(&self) -> &str {
+        match self {
+            Command::ScrollDown => "Scroll down, or move the cursor down",
+            Command::ScrollUp => "Scroll up, or move the cursor up",
+            Command::PageDown => "Scroll down a page",
+            Command::PageUp => "Scroll up a page",
+            Command::HalfPageDown => "Scroll down half a page",
+            Command::HalfPageUp => "Scroll up half a page",
+            Command::JumpToTop => "Jump to the top of the slide",
+            Command::JumpToBottom => "Jump to the bottom of the slide, or to slide N after a count",
+            Command::NextSlide => "Next slide, or reveal the next pause",
+            Command::PreviousSlide => "Previous slide, or hide the last pause",
+            Command::ToggleBlank => "Blank the screen",
+            Command::Overview => "Overview of all slides",
+            Command::StartSearch => "Search the deck",
+            Command::SearchNext => "Next search match",
+            Command::SearchPrevious => "Previous search match",
+            Command::ClearSearch => "Clear the search highlights",
+            Command::JumpBack => "Back to where a jump started",
+            Command::JumpForward => "Forward again after jumping back",
+            Command::TogglePresenting => "Lock or unlock presenting mode",
+            Command::ToggleSpeakerView => "Show or hide the speaker view",
+            Command::ToggleProgress => "Show or hide the progress bar",
+            Command::CycleTheme => "Switch to the next built-in theme",
+            Command::ToggleTimer => "Start, pause or resume the timer",
+            Command::ToggleCursor => "Show or hide the presenter cursor",
+            Command::CheatSheet => "Show this cheat sheet",
+            Command::CopySlide => "Copy the slide as plain text, e.g. for a chat",
+            Command::CopyCode(_) => "Copy the slide's code block, or block N after a count",
+            Command::TableMode => "Sort and filter the slide's tables",
+            Command::OpenTocEntry => "Jump to the contents entry under the cursor",
+            Command::ZoomOut => "Condense the slide to fit the screen",
+            Command::ToggleDetails => "Expand or collapse details sections",
+            Command::RefreshScreenshots => "Re-run the slide's screenshot commands",
+            Command::CheckItem => "Tick off the next checklist step",
+            Command::UncheckItem => "Untick the last checklist step",
+            Command::LoopSlides => "Mark the first, then the last slide to rehearse in a loop",
+            Command::RunCode(_) => "Run the slide's code block, or block N after a count",
+            Command::Quit => "Quit",
+            Command::GotoSlide(_) => "Jump to a slide by number",
+            Command::Shell { run, .. } => run,
+        }
+    }
+
+    /// Commands that are blocked while presenting so a stray key press can't derail the talk
+    pub fn is_disruptive(&self) -> bool {
+        matches!(
+            self,
+            Command::Shell { .. } | Command::RefreshScreenshots | Command::CycleTheme
+        )
+    }
+
+    /// Commands read-only viewers may not run: the speaker view is the presenter's, and
+    /// the others would run code from the presenter's deck on the viewer's machine
+    pub fn is_refused_to_viewers(&self) -> bool {
+        matches!(
+            self,
+            Command::RunCode(_) | Command::RefreshScreenshots | Command::ToggleSpeakerView
+        )
+    }
+
+    /// Commands that move to another slide, which read-only viewers leave to the presenter
+    pub fn changes_slide(&self) -> bool {
+        matches!(
+            self,
+            Command::NextSlide
+                | Command::PreviousSlide
+                | Command::Overview
+                | Command::StartSearch
+                | Command::SearchNext
+                | Command::SearchPrevious
+                | Command::JumpBack
+                | Command::JumpForward
+                | Command::GotoSlide(_)
+                | Command::OpenTocEntry
+        )
+    }
+
     pub fn execute(&self, app: &mut App) {
+        if app.presenting && self.is_disruptive() {
+            app.message = Some(format!("{} is disabled while presenting", self.name()));
+            return;
+        }
+        if app.read_only && self.changes_slide() {
+            app.message = Some("Following the presenter".to_string());
+            return;
+        }
+        if app.read_only && self.is_refused_to_viewers() {
+            app.message = Some(format!("{} is disabled for viewers", self.name()));
+            return;
+        }
+
         match self {
+            Command::ScrollDown if app.cursor.is_some() => app.move_cursor(true),
+            Command::ScrollUp if app.cursor.is_some() => app.move_cursor(false),
             Command::ScrollDown => {
                 app.scroll_view_state.scroll_down();
             }
@@ -25,10 +267,21 @@ impl Command {
                 app.scroll_view_state.scroll_up();
             }
             Command::PageDown => {
-                app.scroll_view_state.scroll_page_down();
+                // Pages overlap by one line
+                let mut offset = app.scroll_view_state.offset();
+                offset.y = offset
+                    .y
+                    .saturating_add(app.viewport_height.max(1))
+                    .saturating_sub(1);
+                app.scroll_view_state.set_offset(offset);
             }
             Command::PageUp => {
-                app.scroll_view_state.scroll_page_up();
+                let mut offset = app.scroll_view_state.offset();
+                offset.y = offset
+                    .y
+                    .saturating_add(1)
+                    .saturating_sub(app.viewport_height.max(1));
+                app.scroll_view_state.set_offset(offset);
             }
             Command::HalfPageDown => {
                 let mut offset = app.scroll_view_state.offset();
@@ -43,15 +296,47 @@ impl Command {
                 app.scroll_view_state.set_offset(offset);
             }
             Command::JumpToTop => {
+                let position = app.position();
+                app.jumps.push(position);
                 let mut offset = app.scroll_view_state.offset();
                 offset.y = 0;
                 app.scroll_view_state.set_offset(offset);
             }
             Command::JumpToBottom => {
-                app.scroll_view_state.scroll_to_bottom();
+                let position = app.position();
+                app.jumps.push(position);
+                // Rendering clamps the offset to the last page
+                let mut offset = app.scroll_view_state.offset();
+                offset.y = app.content_height.saturating_sub(1);
+                app.scroll_view_state.set_offset(offset);
+            }
+            // Pauses are stepped through before moving on, in either direction
+            Command::NextSlide if app.revealed() < pause_count(&app.slides[app.current_slide]) => {
+                app.revealed.insert(app.current_slide, app.revealed() + 1);
+            }
+            Command::PreviousSlide if app.revealed() > 0 => {
+                app.revealed.insert(app.current_slide, app.revealed() - 1);
+            }
+            Command::NextSlide
+                if let Some(slide_loop) = app.slide_loop
+                    && let Some(first) = slide_loop.after(app.current_slide) =>
+            {
+                // Going round again hides the loop's pauses
+                app.revealed.retain(|slide, _| !slide_loop.contains(*slide));
+                app.goto_slide(first);
+            }
+            Command::PreviousSlide
+                if let Some(last) = app
+                    .slide_loop
+                    .and_then(|slide_loop| slide_loop.before(app.current_slide)) =>
+            {
+                app.goto_slide(last);
             }
             Command::NextSlide => {
-                if app.current_slide + 1 < app.slides.len() {
+                // Backup slides are only reached by jumping to them
+                if app.current_slide + 1 < app.slides.len()
+                    && !app.numbers.ends_main_deck(app.current_slide)
+                {
                     app.current_slide += 1;
                     app.scroll_view_state = ScrollViewState::default();
                 }
@@ -62,7 +347,283 @@ impl Command {
                     app.scroll_view_state = ScrollViewState::default();
                 }
             }
+            Command::ToggleBlank => {
+                app.blanked = !app.blanked;
+                if !app.blanked {
+                    // Help the audience find their place again
+                    app.flash_started = Some(Instant::now());
+                }
+            }
+            Command::Overview => {
+                app.overview = Some(Overview {
+                    selected: app.current_slide,
+                    ..Overview::default()
+                });
+            }
+            Command::StartSearch => {
+                let options = app.search.as_ref().map(|s| s.options).unwrap_or_default();
+                app.search_input = Some(SearchInput {
+                    text: String::new(),
+                    options,
+                });
+            }
+            Command::SearchNext | Command::SearchPrevious => {
+                let forward = matches!(self, Command::SearchNext);
+                if app.search.is_none() {
+                    app.message = Some("No previous search".to_string());
+                } else {
+                    app.search_highlight = true;
+                    // Matches further along the current slide come before other slides
+                    if !app.next_match_row(forward)
+                        && let Some(search) = &app.search
+                    {
+                        match search.next_slide(&app.slides, app.current_slide, forward) {
+                            Some(slide) => {
+                                app.jump_to_slide(slide);
+                                app.search_match = None;
+                                app.search_pending = Some(forward);
+                            }
+                            None => {
+                                app.message = Some(format!("Pattern not found: {}", search.query))
+                            }
+                        }
+                    }
+                }
+            }
+            Command::ClearSearch => {
+                // n/N bring the highlights back
+                app.search_highlight = false;
+            }
+            Command::JumpBack => {
+                let current = app.position();
+                if let Some(position) = app.jumps.back(current) {
+                    app.restore_position(position);
+                }
+            }
+            Command::JumpForward => {
+                let current = app.position();
+                if let Some(position) = app.jumps.forward(current) {
+                    app.restore_position(position);
+                }
+            }
+            Command::GotoSlide(number) => match app.numbers.index(*number) {
+                Some(index) if index < app.slides.len() => app.jump_to_slide(index),
+                _ => {
+                    app.message = Some(format!(
+                        "No slide {}: the deck has {} slides",
+                        number,
+                        app.slides.len()
+                    ))
+                }
+            },
+            Command::TogglePresenting => {
+                app.presenting = !app.presenting;
+            }
+            Command::ToggleSpeakerView => {
+                app.speaker_view = !app.speaker_view;
+            }
+            Command::ToggleProgress => {
+                app.show_progress = !app.show_progress;
+            }
+            Command::CycleTheme => {
+                let name = app.theme_choice.cycle();
+                app.apply_theme();
+                app.message = Some(format!("Theme: {}", name));
+            }
+            Command::ToggleTimer => app.timer.toggle(Instant::now()),
+            Command::ToggleCursor => {
+                app.cursor = match app.cursor {
+                    Some(_) => None,
+                    None => {
+                        // Start on the first line in view
+                        let above = app.scroll_view_state.offset().y.checked_sub(1);
+                        let row = next_row(app.render_cache.buffer(), app.pinned_rows, above, true);
+                        Some(Cursor::new(app.current_slide, row.unwrap_or(0)))
+                    }
+                };
+            }
+            Command::CheatSheet => app.cheat_sheet = true,
+            Command::LoopSlides => {
+                app.slide_loop = match app.slide_loop {
+                    None => Some(SlideLoop::mark(app.current_slide)),
+                    Some(marking) if marking.last.is_none() => {
+                        Some(marking.close(app.current_slide))
+                    }
+                    Some(_) => None,
+                };
+                app.message = Some(match app.slide_loop {
+                    Some(SlideLoop { last: None, .. }) => {
+                        "Loop starts here; go to its last slide and press again".to_string()
+                    }
+                    Some(slide_loop) => {
+                        format!("Rehearsing slides {} in a loop", slide_loop.range())
+                    }
+                    None => "Stopped looping".to_string(),
+                });
+            }
+            Command::TableMode => {
+                if tables(&app.slides[app.current_slide]).is_empty() {
+                    app.message = Some("No table on this slide".to_string());
+                } else {
+                    app.table_view = Some(TableView::new(app.current_slide));
+                }
+            }
+            Command::CopySlide => {
+                let slide = &app.slides[app.current_slide];
+                let text = slide_text(slide, &app.theme, app.table_view());
+                app.message = Some(match app.copy_target.send(text, "the slide") {
+                    Ok(sent) => sent,
+                    Err(e) => e.to_string(),
+                });
+            }
+            Command::CopyCode(nth) => {
+                let slide = &app.slides[app.current_slide];
+                let sent = code_block(slide, *nth)
+                    .and_then(|code| app.copy_target.send(code, "the code block"));
+                app.message = Some(match sent {
+                    Ok(sent) => sent,
+                    Err(e) => e.to_string(),
+                });
+            }
+            Command::OpenTocEntry => {
+                if !has_toc(&app.slides[app.current_slide]) {
+                    app.message = Some("No table of contents on this slide".to_string());
+                    return;
+                }
+                let content = app.render_cache.buffer();
+                let line = |row: u16| -> String {
+                    let y = app.pinned_rows + row;
+                    if y >= content.area.height {
+                        return String::new();
+                    }
+                    (0..content.area.width)
+                        .map(|x| content[(x, y)].symbol())
+                        .collect()
+                };
+                match app.cursor_row().filter(|_| app.cursor.is_some()) {
+                    Some(row) => match toc_target(&line(row), &app.numbers) {
+                        Some(slide) => app.jump_to_slide(slide),
+                        None => app.message = Some("No entry under the cursor".to_string()),
+                    },
+                    None => {
+                        let rows = content.area.height.saturating_sub(app.pinned_rows);
+                        let first = (0..rows)
+                            .find(|&row| toc_target(&line(row), &app.numbers).is_some())
+                            .unwrap_or(0);
+                        app.cursor = Some(Cursor::new(app.current_slide, first));
+                        let mut offset = app.scroll_view_state.offset();
+                        offset.y = scroll_to(first, offset.y, app.viewport_height);
+                        app.scroll_view_state.set_offset(offset);
+                    }
+                }
+            }
+            Command::ZoomOut => {
+                app.zoomed_out = if app.is_zoomed_out() {
+                    None
+                } else {
+                    Some(app.current_slide)
+                };
+                app.scroll_view_state = ScrollViewState::default();
+            }
+            Command::ToggleDetails => {
+                if !has_details(&app.slides[app.current_slide]) {
+                    app.message = Some("No collapsible sections on this slide".to_string());
+                } else if !app.expanded_details.remove(&app.current_slide) {
+                    app.expanded_details.insert(app.current_slide);
+                }
+            }
+            Command::RefreshScreenshots => {
+                let slide = &mut app.slides[app.current_slide];
+                match screenshot::capture(slide, &app.base_dir) {
+                    0 => app.message = Some("No screenshots on this slide".to_string()),
+                    _ => app.render_cache.invalidate(),
+                }
+            }
+            Command::RunCode(nth) => {
+                let slide = &mut app.slides[app.current_slide];
+                match run::run(slide, *nth, &app.languages, &app.base_dir) {
+                    Ok(()) => app.render_cache.invalidate(),
+                    Err(e) => app.message = Some(e.to_string()),
+                }
+            }
+            Command::CheckItem | Command::UncheckItem => {
+                let total = checklist::item_count(&app.slides[app.current_slide]);
+                if total == 0 {
+                    app.message = Some("No checklist on this slide".to_string());
+                    return;
+                }
+                let ticked = app.checklists.entry(app.current_slide).or_default();
+                *ticked = match self {
+                    Command::CheckItem => (*ticked + 1).min(total),
+                    _ => ticked.saturating_sub(1),
+                };
+            }
+            Command::Quit => {
+                if app.presenting && !app.confirm_quit {
+                    app.confirm_quit = true;
+                    app.message = Some("Presenting: quit again to exit".to_string());
+                } else {
+                    app.should_quit = true;
+                }
+            }
+            Command::Shell { run, .. } => {
+                app.command_output = Some(run_shell(run, None));
+            }
+        }
+    }
+}
+
+/// Runs a shell command, in `dir` if given, and returns its combined output, keeping
+/// ANSI colors
+pub fn run_shell(run: &str, dir: Option<&Path>) -> String {
+    let mut command = process::Command::new("sh");
+    command.arg("-c").arg(run);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    match command.output() {
+        Ok(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            if !output.status.success() {
+                text.push_str(&format!("\n[{}]", output.status));
+            }
+            text
         }
+        Err(e) => format!("Failed to run `{}`: {}", run, e),
+    }
+}
+
+/// Maps the names used in `[keymaps]` to the commands they run
+#[derive(Debug)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, Command>,
+}
+
+impl CommandRegistry {
+    pub fn register(&mut self, command: Command) {
+        self.commands.insert(command.name().to_string(), command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+}
+
+impl Default for CommandRegistry {
+    /// A registry holding every built-in command
+    fn default() -> Self {
+        let mut registry = CommandRegistry {
+            commands: BTreeMap::new(),
+        };
+        for command in Command::ALL {
+            registry.register(command);
+        }
+        registry
     }
 }
 
@@ -101,6 +662,133 @@ mod tests {
         assert_eq!(app.current_slide, 0);
     }
 
+    #[test]
+    fn test_pauses_are_revealed_before_moving_on() {
+        let deck = crate::app::parse_deck("# One\n- a\n<!-- pause -->\n- b\n\n# Two\n").unwrap();
+        let mut app = App::from_deck(deck);
+        Command::NextSlide.execute(&mut app);
+        assert_eq!((app.current_slide, app.revealed()), (0, 1));
+        Command::NextSlide.execute(&mut app);
+        assert_eq!(app.current_slide, 1);
+
+        // Coming back shows the slide as it was left, then steps back through it
+        Command::PreviousSlide.execute(&mut app);
+        assert_eq!((app.current_slide, app.revealed()), (0, 1));
+        Command::PreviousSlide.execute(&mut app);
+        assert_eq!((app.current_slide, app.revealed()), (0, 0));
+    }
+
+    #[test]
+    fn test_next_slide_stops_before_the_appendix() {
+        let deck =
+            crate::app::parse_deck("# One\n\n# Backup\n<!-- appendix -->\n\n# More\n").unwrap();
+        let mut app = App::from_deck(deck);
+        Command::NextSlide.execute(&mut app);
+        assert_eq!(app.current_slide, 0);
+
+        // Once jumped to, the backup slides are walked through as usual
+        app.goto_slide(1);
+        Command::NextSlide.execute(&mut app);
+        assert_eq!(app.current_slide, 2);
+    }
+
+    #[test]
+    fn test_zoom_out_only_applies_to_its_slide() {
+        let mut app = App::new(vec![vec![], vec![]]);
+        Command::ZoomOut.execute(&mut app);
+        assert!(app.is_zoomed_out());
+
+        Command::NextSlide.execute(&mut app);
+        assert!(!app.is_zoomed_out());
+        Command::PreviousSlide.execute(&mut app);
+        Command::ZoomOut.execute(&mut app);
+        assert!(!app.is_zoomed_out());
+    }
+
+    #[test]
+    fn test_toggle_details() {
+        let deck = crate::app::parse_deck("# A\n\n# B\n<details>\n\nx\n\n</details>\n").unwrap();
+        let mut app = App::new(deck.slides);
+        Command::ToggleDetails.execute(&mut app);
+        assert!(app.message.is_some());
+
+        app.goto_slide(1);
+        Command::ToggleDetails.execute(&mut app);
+        assert!(app.expanded_details.contains(&1));
+        Command::ToggleDetails.execute(&mut app);
+        assert!(app.expanded_details.is_empty());
+    }
+
+    #[test]
+    fn test_refresh_screenshots() {
+        let deck = crate::app::parse_deck("# A\n\n# B\n<!-- screenshot: echo hi -->\n").unwrap();
+        let mut app = App::new(deck.slides);
+        Command::RefreshScreenshots.execute(&mut app);
+        assert_eq!(app.message.as_deref(), Some("No screenshots on this slide"));
+
+        app.goto_slide(1);
+        Command::RefreshScreenshots.execute(&mut app);
+        assert_eq!(app.slides[1][2].to_string(), "$ echo hi\nhi");
+    }
+
+    #[test]
+    fn test_check_items_in_order() {
+        let deck = crate::app::parse_deck("# A\n```checklist\none\ntwo\n```\n").unwrap();
+        let mut app = App::new(deck.slides);
+        Command::UncheckItem.execute(&mut app);
+        assert_eq!(app.checklists[&0], 0);
+        for _ in 0..3 {
+            Command::CheckItem.execute(&mut app);
+        }
+        assert_eq!(app.checklists[&0], 2);
+        Command::UncheckItem.execute(&mut app);
+        assert_eq!(app.checklists[&0], 1);
+    }
+
+    #[test]
+    fn test_pages_overlap_by_one_line() {
+        let mut app = App::new(vec![vec![]]);
+        app.viewport_height = 10;
+        Command::PageDown.execute(&mut app);
+        assert_eq!(app.scroll_view_state.offset().y, 9);
+        Command::PageUp.execute(&mut app);
+        assert_eq!(app.scroll_view_state.offset().y, 0);
+    }
+
+    #[test]
+    fn test_cursor_moves_before_scrolling() {
+        let mut app = App::new(vec![vec![]]);
+        let key = crate::render_cache::RenderKey {
+            slide: 0,
+            width: 5,
+            search: None,
+            flash: false,
+            condensed: false,
+            expanded: false,
+            answer: None,
+            ticked: 0,
+            revealed: 0,
+            table: None,
+        };
+        let content = ratatui::buffer::Buffer::with_lines(["a", "", "b", "c", "d"]);
+        app.render_cache.store(key, content, 0);
+        app.viewport_height = 3;
+
+        Command::ToggleCursor.execute(&mut app);
+        assert_eq!(app.cursor_row(), Some(0));
+        Command::ScrollDown.execute(&mut app);
+        assert_eq!(app.cursor_row(), Some(2));
+        assert_eq!(app.scroll_view_state.offset().y, 0);
+        Command::ScrollDown.execute(&mut app);
+        assert_eq!(app.cursor_row(), Some(3));
+        assert_eq!(app.scroll_view_state.offset().y, 1);
+
+        Command::ToggleCursor.execute(&mut app);
+        Command::ScrollDown.execute(&mut app);
+        assert_eq!(app.cursor_row(), None);
+        assert_eq!(app.scroll_view_state.offset().y, 2);
+    }
+
     #[test]
     fn test_next_slide_resets_scroll_state() {
         let mut app = App::new(vec![vec![], vec![]]);
@@ -127,4 +815,175 @@ mod tests {
         let new_offset = app.scroll_view_state.offset();
         assert_eq!(new_offset.y, 0);
     }
+
+    #[test]
+    fn test_registry_contains_builtins_by_name() {
+        let registry = CommandRegistry::default();
+        assert!(matches!(
+            registry.get("next_slide"),
+            Some(Command::NextSlide)
+        ));
+        assert!(registry.get("unknown").is_none());
+        assert_eq!(registry.commands().count(), Command::ALL.len());
+    }
+
+    #[test]
+    fn test_shell_command_output_is_captured() {
+        let mut app = App::new(vec![vec![]]);
+        let command = Command::Shell {
+            name: "greet".to_string(),
+            run: "echo hello".to_string(),
+        };
+        command.execute(&mut app);
+        assert_eq!(app.command_output.as_deref(), Some("hello\n"));
+    }
+
+    #[test]
+    fn test_search_next_and_previous_cycle_matching_slides() {
+        let deck = crate::app::parse_deck("# A\nfoo\n\n# B\n\n# C\nfoo\n").unwrap();
+        let mut app = App::new(deck.slides);
+        app.search = Some(crate::search::Search::parse("foo", Default::default()).unwrap());
+
+        Command::SearchNext.execute(&mut app);
+        assert_eq!(app.current_slide, 2);
+        Command::SearchNext.execute(&mut app);
+        assert_eq!(app.current_slide, 0);
+        Command::SearchPrevious.execute(&mut app);
+        assert_eq!(app.current_slide, 2);
+    }
+
+    #[test]
+    fn test_clear_search_hides_highlights_until_next_jump() {
+        let deck = crate::app::parse_deck("# A\nfoo\n\n# B\nfoo\n").unwrap();
+        let mut app = App::new(deck.slides);
+        app.search = Some(crate::search::Search::parse("foo", Default::default()).unwrap());
+        app.search_highlight = true;
+
+        Command::ClearSearch.execute(&mut app);
+        assert!(!app.search_highlight);
+        assert!(app.search.is_some());
+
+        Command::SearchNext.execute(&mut app);
+        assert!(app.search_highlight);
+    }
+
+    #[test]
+    fn test_jump_back_returns_to_scrolled_position_before_search() {
+        let deck = crate::app::parse_deck("# A\n\n# B\nfoo\n").unwrap();
+        let mut app = App::new(deck.slides);
+        app.scroll_view_state
+            .set_offset(ratatui::layout::Position::new(0, 8));
+        app.search = Some(crate::search::Search::parse("foo", Default::default()).unwrap());
+
+        Command::SearchNext.execute(&mut app);
+        assert_eq!(app.current_slide, 1);
+
+        Command::JumpBack.execute(&mut app);
+        assert_eq!(app.current_slide, 0);
+        assert_eq!(app.scroll_view_state.offset().y, 8);
+
+        Command::JumpForward.execute(&mut app);
+        assert_eq!(app.current_slide, 1);
+    }
+
+    #[test]
+    fn test_presenting_requires_quit_confirmation() {
+        let mut app = App::new(vec![vec![]]);
+        Command::TogglePresenting.execute(&mut app);
+        Command::Quit.execute(&mut app);
+        assert!(!app.should_quit);
+        assert!(app.confirm_quit);
+
+        Command::Quit.execute(&mut app);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_presenting_blocks_shell_commands() {
+        let mut app = App::new(vec![vec![]]);
+        app.presenting = true;
+        let command = Command::Shell {
+            name: "greet".to_string(),
+            run: "echo hello".to_string(),
+        };
+        command.execute(&mut app);
+        assert!(app.command_output.is_none());
+        assert!(app.message.is_some());
+
+        let theme = app.theme.clone();
+        Command::CycleTheme.execute(&mut app);
+        assert_eq!(app.theme, theme);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("cycle_theme is disabled while presenting")
+        );
+    }
+
+    #[test]
+    fn test_read_only_viewer_cannot_change_slides() {
+        let mut app = App::new(vec![vec![], vec![]]);
+        app.read_only = true;
+        Command::NextSlide.execute(&mut app);
+        Command::Overview.execute(&mut app);
+        assert_eq!(app.current_slide, 0);
+        assert!(app.overview.is_none());
+        assert_eq!(app.message.as_deref(), Some("Following the presenter"));
+    }
+
+    #[test]
+    fn test_read_only_viewer_cannot_run_code() {
+        let mut deck = crate::app::parse_deck("# Demo\n\n```sh\necho hi\n```\n").unwrap();
+        let mut app = App::new(std::mem::take(&mut deck.slides));
+        app.read_only = true;
+        Command::RunCode(1).execute(&mut app);
+        assert_eq!(app.slides[0].len(), 2);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("run_code is disabled for viewers")
+        );
+    }
+
+    #[test]
+    fn test_read_only_viewer_cannot_open_speaker_view() {
+        let mut app = App::new(vec![vec![]]);
+        app.read_only = true;
+        Command::ToggleSpeakerView.execute(&mut app);
+        assert!(!app.speaker_view);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("speaker_view is disabled for viewers")
+        );
+    }
+
+    #[test]
+    fn test_read_only_viewer_cannot_run_screenshots() {
+        let deck = crate::app::parse_deck("# A\n<!-- screenshot: echo hi -->\n").unwrap();
+        let mut app = App::from_deck(deck);
+        app.read_only = true;
+        Command::RefreshScreenshots.execute(&mut app);
+        assert!(app.slides[0][2].to_string().contains("not captured yet"));
+        assert_eq!(
+            app.message.as_deref(),
+            Some("refresh_screenshots is disabled for viewers")
+        );
+    }
+
+    #[test]
+    fn test_search_next_without_search_shows_message() {
+        let mut app = App::new(vec![vec![]]);
+        Command::SearchNext.execute(&mut app);
+        assert!(app.message.is_some());
+    }
+
+    #[test]
+    fn test_toggle_blank_flashes_on_return() {
+        let mut app = App::new(vec![vec![]]);
+        Command::ToggleBlank.execute(&mut app);
+        assert!(app.blanked);
+        assert!(app.flash_started.is_none());
+
+        Command::ToggleBlank.execute(&mut app);
+        assert!(!app.blanked);
+        assert!(app.flash_started.is_some());
+    }
 }
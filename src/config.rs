@@ -1,39 +1,332 @@
 use anyhow::{Result, anyhow};
-use ratatui::crossterm::event::{KeyCode, KeyModifiers};
-use serde::Deserialize;
+use ratatui::crossterm::event::{KeyCode, KeyModifiers, MouseEventKind};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::commands::Command;
+use crate::commands::{Command, CommandRegistry};
+use crate::copy::CopyTarget;
+use crate::directives::Center;
+use crate::frontmatter::Split;
+use crate::modes::{Action, Mode};
+use crate::mouse;
+use crate::syntax::Languages;
+use crate::theme;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub keymaps: Keymaps,
-}
-
-#[derive(Debug, Deserialize, Default)]
-pub struct Keymaps {
+    /// User-defined shell commands, bindable by name in `[keymaps]`
+    #[serde(default)]
+    pub commands: BTreeMap<String, String>,
     #[serde(default)]
-    pub scroll_down: Vec<String>,
+    pub flash: Flash,
     #[serde(default)]
-    pub scroll_up: Vec<String>,
+    pub help: Help,
     #[serde(default)]
-    pub previous_slide: Vec<String>,
+    pub keepalive: KeepAliveConfig,
     #[serde(default)]
-    pub next_slide: Vec<String>,
+    pub features: Features,
     #[serde(default)]
-    pub page_down: Vec<String>,
+    pub split: Split,
     #[serde(default)]
-    pub page_up: Vec<String>,
+    pub progress: Progress,
     #[serde(default)]
-    pub half_page_down: Vec<String>,
+    pub timer: TimerConfig,
     #[serde(default)]
-    pub half_page_up: Vec<String>,
+    pub copy: CopyConfig,
+    /// Fence language aliases and what to do with unknown languages
     #[serde(default)]
-    pub jump_to_top: Vec<String>,
+    pub code: Languages,
+    /// Layout settings that override the theme's
     #[serde(default)]
-    pub jump_to_bottom: Vec<String>,
+    pub layout: LayoutConfig,
+    /// Font GIF exports are drawn in
+    #[serde(default)]
+    pub font: FontConfig,
+    /// Built-in theme name or theme file, relative to the config file, for decks
+    /// without a theme of their own
+    pub theme: Option<PathBuf>,
+    #[serde(skip)]
+    pub registry: CommandRegistry,
+}
+
+/// Key bindings per command name, and per action for each mode with a table of its
+/// own, like `[keymaps.overview]`, where `open` binds the command opening the mode
+#[derive(Debug, Clone, Default)]
+pub struct Keymaps {
+    pub normal: BTreeMap<String, Vec<String>>,
+    pub modes: BTreeMap<Mode, BTreeMap<String, Vec<String>>>,
+}
+
+impl<'de> Deserialize<'de> for Keymaps {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Entry {
+            Keys(Vec<String>),
+            Mode(BTreeMap<String, Vec<String>>),
+        }
+
+        let mut keymaps = Keymaps::default();
+        let mut opened = vec![];
+        for (name, entry) in BTreeMap::<String, Entry>::deserialize(deserializer)? {
+            match entry {
+                Entry::Keys(keys) => {
+                    keymaps.normal.insert(name, keys);
+                }
+                Entry::Mode(mut actions) => {
+                    let mode = Mode::from_name(&name).ok_or_else(|| {
+                        D::Error::custom(format!("Unknown mode keymaps.{}", name))
+                    })?;
+                    if let Some(keys) = actions.remove("open") {
+                        opened.push((mode, keys));
+                    }
+                    keymaps.modes.insert(mode, actions);
+                }
+            }
+        }
+        for (mode, keys) in opened {
+            if keymaps.normal.contains_key(mode.command()) {
+                return Err(D::Error::custom(format!(
+                    "keymaps.{}.open and keymaps.{} both bind the same command",
+                    mode.name(),
+                    mode.command()
+                )));
+            }
+            keymaps.normal.insert(mode.command().to_string(), keys);
+        }
+        Ok(keymaps)
+    }
+}
+
+/// Which hints the footer shows, in order, and how much detail they include
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Help {
+    /// Hint groups (`slides`, `scroll`, ...), single command names, `custom` or `quit`
+    pub hints: Vec<String>,
+    pub mode: HelpMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HelpMode {
+    /// First binding of each command with a short label
+    Compact,
+    /// Every binding of each command with a descriptive label
+    Verbose,
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Help {
+            hints: [
+                "slides",
+                "scroll",
+                "half_page",
+                "full_page",
+                "top_bottom",
+                "blank",
+                "overview",
+                "custom",
+                "quit",
+            ]
+            .map(String::from)
+            .to_vec(),
+            mode: HelpMode::Compact,
+        }
+    }
+}
+
+/// Highlight shown on the current slide after leaving the blank screen
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Flash {
+    pub effect: FlashEffect,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashEffect {
+    /// Invert the slide title
+    Heading,
+    /// Draw a border around the slide content
+    Border,
+    Off,
+}
+
+impl Flash {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+impl Default for Flash {
+    fn default() -> Self {
+        Flash {
+            effect: FlashEffect::Heading,
+            duration_ms: 800,
+        }
+    }
+}
+
+/// Keeps the display awake while presenting
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KeepAliveConfig {
+    /// Seconds between keep-alive writes to the terminal, 0 disables them
+    pub interval_secs: u64,
+    /// Also inhibit the system screensaver via `caffeinate` or `systemd-inhibit`
+    pub inhibit: bool,
+}
+
+impl KeepAliveConfig {
+    pub fn interval(&self) -> Option<Duration> {
+        (self.interval_secs > 0).then(|| Duration::from_secs(self.interval_secs))
+    }
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        KeepAliveConfig {
+            interval_secs: 60,
+            inhibit: true,
+        }
+    }
+}
+
+/// Footer line showing `slide 3/12` and how far along the deck is
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    /// Shown from the start; the `progress_bar` key toggles it either way
+    pub show: bool,
+    pub style: ProgressStyle,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressStyle {
+    /// A bar filling up over the deck
+    #[default]
+    Bar,
+    /// A dot per slide, grouped by section; the bar when they don't fit
+    Dots,
+}
+
+/// Where the `copy_slide` key sends the slide's text
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CopyConfig {
+    /// FIFO to write to instead of the clipboard, for a script posting to a chat
+    pub fifo: Option<PathBuf>,
+    /// Copies through the terminal with OSC 52 rather than a clipboard tool, which
+    /// over SSH would copy on the remote machine
+    pub osc52: bool,
+}
+
+impl CopyConfig {
+    pub fn target(&self) -> CopyTarget {
+        match &self.fifo {
+            Some(fifo) => CopyTarget::Fifo(fifo.clone()),
+            None if self.osc52 => CopyTarget::Terminal,
+            None => CopyTarget::Clipboard,
+        }
+    }
+}
+
+/// `[font]`: how `markdeck export cast` draws a GIF
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FontConfig {
+    /// Comma-separated families, each drawing the glyphs the ones before it lack
+    pub family: Option<String>,
+    /// Size in pixels
+    pub size: Option<u16>,
+}
+
+/// `[layout]`: what's set here wins over the theme, for every deck
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    pub center: Option<Center>,
+    /// Widest the content gets, e.g. 100 to keep slides readable on ultrawide terminals
+    pub max_width: Option<u16>,
+    pub margin_x: Option<u16>,
+    pub margin_y: Option<u16>,
+}
+
+impl LayoutConfig {
+    pub fn apply(&self, layout: &mut theme::Layout) {
+        if let Some(center) = self.center {
+            layout.center = center;
+        }
+        if self.max_width.is_some() {
+            layout.max_width = self.max_width;
+        }
+        if let Some(margin_x) = self.margin_x {
+            layout.margin_x = margin_x;
+        }
+        if let Some(margin_y) = self.margin_y {
+            layout.margin_y = margin_y;
+        }
+    }
+}
+
+/// Stopwatch for the talk, shown in the header
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    pub start: TimerStart,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerStart {
+    /// As soon as the deck opens
+    #[default]
+    Launch,
+    /// On the first press of the timer key
+    Key,
+}
+
+/// Overrides for what the terminal is detected to support, per visual feature
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Features {
+    pub images: Support,
+    /// Clickable OSC 8 links
+    pub hyperlinks: Support,
+    /// 24-bit colors, otherwise reduced to what the terminal shows; forcing it on also
+    /// overrides `NO_COLOR`
+    pub truecolor: Support,
+    pub italics: Support,
+}
+
+/// Whether a feature follows detection or is forced, for terminals that advertise
+/// their capabilities incorrectly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Support {
+    #[default]
+    Auto,
+    ForceOn,
+    ForceOff,
+}
+
+impl Support {
+    pub fn resolve(self, detected: bool) -> bool {
+        match self {
+            Support::Auto => detected,
+            Support::ForceOn => true,
+            Support::ForceOff => false,
+        }
+    }
 }
 
 impl Config {
@@ -49,8 +342,12 @@ impl Config {
         };
 
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            let config: Config = toml::from_str(&content)?;
+            let mut config = Config::parse(&fs::read_to_string(&config_path)?)?;
+            if let (Some(theme), Some(dir)) = (&mut config.theme, config_path.parent())
+                && !theme::is_builtin(theme)
+            {
+                *theme = dir.join(&theme);
+            }
             Ok(config)
         } else if let Some(p) = path {
             anyhow::bail!("Failed to find config at: {}", p)
@@ -59,164 +356,554 @@ impl Config {
         }
     }
 
-    pub fn get_command(&self, key_code: KeyCode, modifiers: KeyModifiers) -> Option<Command> {
-        let key_str = keycode_to_string(key_code, modifiers);
+    fn parse(content: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(content)?;
+        config.merge_default_keymaps();
+        config.register_commands()?;
+        config.validate()?;
+        Ok(config)
+    }
 
-        for binding in &self.keymaps.scroll_down {
-            if binding == &key_str {
-                return Some(Command::ScrollDown);
-            }
-        }
-        for binding in &self.keymaps.scroll_up {
-            if binding == &key_str {
-                return Some(Command::ScrollUp);
+    /// Commands left out of `[keymaps]` keep their default keys, except the ones the
+    /// config binds to another command; `name = []` unbinds a command
+    fn merge_default_keymaps(&mut self) {
+        let taken: HashSet<String> = self
+            .keymaps
+            .normal
+            .values()
+            .flatten()
+            .filter_map(|binding| canonical_binding(binding))
+            .collect();
+        for (name, bindings) in DEFAULT_KEYMAPS {
+            if self.keymaps.normal.contains_key(name) {
+                continue;
             }
+            let bindings = bindings
+                .iter()
+                .filter(|binding| {
+                    canonical_binding(binding).is_none_or(|key| !taken.contains(&key))
+                })
+                .map(|binding| binding.to_string())
+                .collect();
+            self.keymaps.normal.insert(name.to_string(), bindings);
         }
-        for binding in &self.keymaps.previous_slide {
-            if binding == &key_str {
-                return Some(Command::PreviousSlide);
-            }
-        }
-        for binding in &self.keymaps.next_slide {
-            if binding == &key_str {
-                return Some(Command::NextSlide);
-            }
-        }
-        for binding in &self.keymaps.page_down {
-            if binding == &key_str {
-                return Some(Command::PageDown);
-            }
+        for mode in Mode::ALL {
+            self.merge_mode_keymap(mode);
         }
-        for binding in &self.keymaps.page_up {
-            if binding == &key_str {
-                return Some(Command::PageUp);
+    }
+
+    /// Like the commands, actions left out of a mode's table keep their default keys;
+    /// actions named after a command, like table mode's `scroll_down`, get its keys
+    fn merge_mode_keymap(&mut self, mode: Mode) {
+        let inherited: Vec<(Action, Vec<String>)> = mode
+            .actions()
+            .iter()
+            .filter_map(|(action, _)| {
+                let keys = self
+                    .bindings(action.inherits()?)
+                    .iter()
+                    .filter(|binding| parse_key_binding(binding).is_some())
+                    .cloned()
+                    .collect();
+                Some((*action, keys))
+            })
+            .collect();
+        let keymap = self.keymaps.modes.entry(mode).or_default();
+        let taken: HashSet<String> = keymap
+            .values()
+            .flatten()
+            .filter_map(|binding| canonical_binding(binding))
+            .collect();
+        for (action, defaults) in mode.actions() {
+            if keymap.contains_key(action.name()) {
+                continue;
             }
+            let bindings = match inherited.iter().find(|(other, _)| other == action) {
+                Some((_, keys)) => keys.clone(),
+                None => defaults.iter().map(|binding| binding.to_string()).collect(),
+            };
+            let bindings = bindings
+                .into_iter()
+                .filter(|binding| {
+                    canonical_binding(binding).is_none_or(|key| !taken.contains(&key))
+                })
+                .collect();
+            keymap.insert(action.name().to_string(), bindings);
         }
-        for binding in &self.keymaps.half_page_down {
-            if binding == &key_str {
-                return Some(Command::HalfPageDown);
+    }
+
+    /// Every command with its keys, built-ins first in their usual order
+    pub fn key_table(&self) -> Vec<(String, &Command)> {
+        let builtins = Command::ALL
+            .iter()
+            .filter_map(|command| self.registry.get(command.name()));
+        let custom = self
+            .registry
+            .commands()
+            .filter(|command| matches!(command, Command::Shell { .. }));
+        builtins
+            .chain(custom)
+            .map(|command| (self.bindings(command.name()).join(", "), command))
+            .collect()
+    }
+
+    /// Adds the `[commands]` section to the registry so they can be bound like built-ins
+    fn register_commands(&mut self) -> Result<()> {
+        for (name, run) in &self.commands {
+            if self.registry.get(name).is_some() {
+                anyhow::bail!("Command \"{}\" is already defined", name);
             }
+            self.registry.register(Command::Shell {
+                name: name.clone(),
+                run: run.clone(),
+            });
         }
-        for binding in &self.keymaps.half_page_up {
-            if binding == &key_str {
-                return Some(Command::HalfPageUp);
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut bound: HashMap<String, &str> = HashMap::new();
+
+        for (name, bindings) in &self.keymaps.normal {
+            let name = name.as_str();
+            if self.registry.get(name).is_none() {
+                anyhow::bail!("Unknown command keymaps.{}", name);
             }
-        }
-        for binding in &self.keymaps.jump_to_top {
-            if binding == &key_str {
-                return Some(Command::JumpToTop);
+
+            for binding in bindings {
+                // Compare canonical forms so equivalent spellings also conflict
+                let Some(key) = canonical_binding(binding) else {
+                    anyhow::bail!("Invalid key binding \"{}\" for keymaps.{}", binding, name);
+                };
+                if let Some(reserved) = reserved_key(&key) {
+                    anyhow::bail!(
+                        "Key \"{}\" for keymaps.{} is reserved for {}",
+                        binding,
+                        name,
+                        reserved
+                    );
+                }
+                match bound.get(key.as_str()) {
+                    Some(&other) if other != name => anyhow::bail!(
+                        "Key \"{}\" is bound to both keymaps.{} and keymaps.{}",
+                        binding,
+                        other,
+                        name
+                    ),
+                    _ => {
+                        bound.insert(key, name);
+                    }
+                }
             }
         }
-        for binding in &self.keymaps.jump_to_bottom {
-            if binding == &key_str {
-                return Some(Command::JumpToBottom);
+
+        for (&mode, keymap) in &self.keymaps.modes {
+            let mut bound: HashMap<(KeyCode, KeyModifiers), &str> = HashMap::new();
+            for (name, bindings) in keymap {
+                let name = name.as_str();
+                if mode.action(name).is_none() {
+                    anyhow::bail!("Unknown action keymaps.{}.{}", mode.name(), name);
+                }
+                for binding in bindings {
+                    // Modes take keys only: the mouse is left to the slide underneath
+                    let Some(key) = parse_key_binding(binding) else {
+                        anyhow::bail!(
+                            "Invalid key binding \"{}\" for keymaps.{}.{}",
+                            binding,
+                            mode.name(),
+                            name
+                        );
+                    };
+                    match bound.get(&key) {
+                        Some(&other) if other != name => anyhow::bail!(
+                            "Key \"{}\" is bound to both keymaps.{}.{} and keymaps.{}.{}",
+                            binding,
+                            mode.name(),
+                            other,
+                            mode.name(),
+                            name
+                        ),
+                        _ => {
+                            bound.insert(key, name);
+                        }
+                    }
+                }
             }
         }
+        Ok(())
+    }
 
-        None
-    }
-
-    pub fn get_keys_for_command(&self, command: Command) -> Option<&str> {
-        let bindings = match command {
-            Command::ScrollDown => &self.keymaps.scroll_down,
-            Command::ScrollUp => &self.keymaps.scroll_up,
-            Command::PreviousSlide => &self.keymaps.previous_slide,
-            Command::NextSlide => &self.keymaps.next_slide,
-            Command::PageDown => &self.keymaps.page_down,
-            Command::PageUp => &self.keymaps.page_up,
-            Command::HalfPageDown => &self.keymaps.half_page_down,
-            Command::HalfPageUp => &self.keymaps.half_page_up,
-            Command::JumpToTop => &self.keymaps.jump_to_top,
-            Command::JumpToBottom => &self.keymaps.jump_to_bottom,
-        };
+    pub fn get_command(&self, key_code: KeyCode, modifiers: KeyModifiers) -> Option<&Command> {
+        self.bound_to(&keycode_to_string(key_code, modifiers))
+    }
 
-        bindings.first().map(|s| s.as_str())
+    pub fn get_mouse_command(&self, kind: MouseEventKind) -> Option<&Command> {
+        self.bound_to(mouse::binding(kind)?)
     }
 
-    pub fn format_help_text(&self) -> String {
-        let mut parts = vec![];
+    /// The action a key runs in a mode, if any; unbound keys may still type text
+    pub fn get_mode_action(
+        &self,
+        mode: Mode,
+        key_code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Option<Action> {
+        let key = keycode_to_string(key_code, modifiers);
+        mode.actions()
+            .iter()
+            .map(|(action, _)| *action)
+            .find(|action| self.mode_bindings(mode, *action).contains(&key))
+    }
 
-        if let (Some(prev), Some(next)) = (
-            self.get_keys_for_command(Command::PreviousSlide),
-            self.get_keys_for_command(Command::NextSlide),
-        ) {
-            parts.push(format!("{}/{}: slides", prev, next));
-        }
+    pub fn mode_bindings(&self, mode: Mode, action: Action) -> &[String] {
+        self.keymaps
+            .modes
+            .get(&mode)
+            .and_then(|keymap| keymap.get(action.name()))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
 
-        if let (Some(down), Some(up)) = (
-            self.get_keys_for_command(Command::ScrollDown),
-            self.get_keys_for_command(Command::ScrollUp),
-        ) {
-            parts.push(format!("{}/{}: scroll", down, up));
-        }
+    /// A footer hint for a mode, like `A-r/A-c/A-w: regex/case/word`; None while any of
+    /// the actions is unbound
+    pub fn mode_hint(&self, mode: Mode, actions: &[Action], label: &str) -> Option<String> {
+        let keys = actions
+            .iter()
+            .map(|action| self.mode_bindings(mode, *action).first().cloned())
+            .collect::<Option<Vec<_>>>()?;
+        Some(format!("{}: {}", keys.join("/"), label))
+    }
 
-        if let (Some(down), Some(up)) = (
-            self.get_keys_for_command(Command::HalfPageDown),
-            self.get_keys_for_command(Command::HalfPageUp),
-        ) {
-            parts.push(format!("{}/{}: half page", down, up));
-        }
+    /// Whether any command is bound to the mouse, which then gets captured
+    pub fn binds_mouse(&self) -> bool {
+        self.keymaps
+            .normal
+            .values()
+            .flatten()
+            .any(|binding| mouse::BINDINGS.contains(&binding.as_str()))
+    }
+
+    fn bound_to(&self, binding: &str) -> Option<&Command> {
+        let (name, _) = self
+            .keymaps
+            .normal
+            .iter()
+            .find(|(_, bindings)| bindings.iter().any(|b| b == binding))?;
+        self.registry.get(name)
+    }
+
+    pub fn bindings(&self, name: &str) -> &[String] {
+        self.keymaps
+            .normal
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
 
-        if let (Some(down), Some(up)) = (
-            self.get_keys_for_command(Command::PageDown),
-            self.get_keys_for_command(Command::PageUp),
-        ) {
-            parts.push(format!("{}/{}: full page", down, up));
+    pub fn get_keys_for_command(&self, command: &Command) -> Option<&str> {
+        self.bindings(command.name()).first().map(|s| s.as_str())
+    }
+
+    pub fn format_help_text(&self) -> String {
+        self.help
+            .hints
+            .iter()
+            .filter_map(|hint| self.format_hint(hint))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    fn format_hint(&self, hint: &str) -> Option<String> {
+        if hint == "quit" && self.bindings("quit").is_empty() {
+            // Ctrl-C quits even when no key is bound
+            return Some("C-c: quit".to_string());
         }
 
-        if let (Some(top), Some(bottom)) = (
-            self.get_keys_for_command(Command::JumpToTop),
-            self.get_keys_for_command(Command::JumpToBottom),
-        ) {
-            parts.push(format!("{}/{}: top/bottom", top, bottom));
+        if hint == "custom" {
+            let hints: Vec<String> = self
+                .registry
+                .commands()
+                .filter(|command| matches!(command, Command::Shell { .. }))
+                .filter_map(|command| self.format_hint(command.name()))
+                .collect();
+            return (!hints.is_empty()).then(|| hints.join("  "));
         }
 
-        parts.push("q: quit".to_string());
+        let (names, label) = if let Some((_, names, compact, verbose)) =
+            HINT_GROUPS.iter().find(|(name, ..)| *name == hint)
+        {
+            let label = match self.help.mode {
+                HelpMode::Compact => compact.to_string(),
+                HelpMode::Verbose => verbose.to_string(),
+            };
+            (names.to_vec(), label)
+        } else {
+            self.registry.get(hint)?;
+            (vec![hint], hint.replace('_', " "))
+        };
 
-        parts.join("  ")
+        let mut keys = vec![];
+        for name in names {
+            // The footer hints at keys; the mouse is listed in the cheat sheet
+            let bindings: Vec<String> = self
+                .bindings(name)
+                .iter()
+                .filter(|binding| !mouse::BINDINGS.contains(&binding.as_str()))
+                .cloned()
+                .collect();
+            let key = match self.help.mode {
+                HelpMode::Compact => bindings.first()?.clone(),
+                HelpMode::Verbose if bindings.is_empty() => return None,
+                HelpMode::Verbose => bindings.join(","),
+            };
+            keys.push(key);
+        }
+
+        Some(format!("{}: {}", keys.join("/"), label))
     }
 }
 
+/// Footer hints covering several commands: name, command names, compact label, verbose label
+const HINT_GROUPS: [(&str, &[&str], &str, &str); 6] = [
+    (
+        "slides",
+        &["previous_slide", "next_slide"],
+        "slides",
+        "previous/next slide",
+    ),
+    (
+        "scroll",
+        &["scroll_down", "scroll_up"],
+        "scroll",
+        "scroll down/up",
+    ),
+    (
+        "half_page",
+        &["half_page_down", "half_page_up"],
+        "half page",
+        "half page down/up",
+    ),
+    (
+        "full_page",
+        &["page_down", "page_up"],
+        "full page",
+        "full page down/up",
+    ),
+    (
+        "top_bottom",
+        &["jump_to_top", "jump_to_bottom"],
+        "top/bottom",
+        "jump to top/bottom",
+    ),
+    ("blank", &["blank_screen"], "blank", "blank screen"),
+];
+
+/// Bindings used when no config file exists
+const DEFAULT_KEYMAPS: [(&str, &[&str]); 37] = [
+    ("scroll_down", &["j", "Down", "WheelDown"]),
+    ("scroll_up", &["k", "Up", "WheelUp"]),
+    ("previous_slide", &["h", "RightClick"]),
+    ("next_slide", &["l", "LeftClick"]),
+    ("page_down", &["C-f"]),
+    ("page_up", &["C-b"]),
+    ("half_page_down", &["C-d"]),
+    ("half_page_up", &["C-u"]),
+    ("jump_to_top", &["g"]),
+    ("jump_to_bottom", &["G"]),
+    ("blank_screen", &["b"]),
+    ("overview", &["o"]),
+    ("search", &["/"]),
+    ("search_next", &["n"]),
+    ("search_previous", &["N"]),
+    ("clear_search", &["Esc"]),
+    ("jump_back", &["C-o"]),
+    ("jump_forward", &["Tab"]),
+    ("presenting", &["P"]),
+    ("speaker_view", &["s"]),
+    ("progress_bar", &["p"]),
+    ("cycle_theme", &["T"]),
+    ("timer", &["t"]),
+    ("cursor", &["v"]),
+    ("cheat_sheet", &["?"]),
+    ("copy_slide", &["y"]),
+    ("copy_code", &["Y"]),
+    ("table_mode", &["S"]),
+    ("open_toc_entry", &["Enter"]),
+    ("zoom_out", &["z"]),
+    ("toggle_details", &["d"]),
+    ("refresh_screenshots", &["R"]),
+    ("check_item", &["c"]),
+    ("uncheck_item", &["C"]),
+    ("loop_slides", &["L"]),
+    ("run_code", &["x"]),
+    ("quit", &["Q"]),
+];
+
 impl Default for Config {
     fn default() -> Self {
-        Config {
+        let normal = DEFAULT_KEYMAPS
+            .iter()
+            .map(|(name, bindings)| {
+                let bindings = bindings.iter().map(|b| b.to_string()).collect();
+                (name.to_string(), bindings)
+            })
+            .collect();
+
+        let mut config = Config {
             keymaps: Keymaps {
-                scroll_down: vec!["j".to_string(), "Down".to_string()],
-                scroll_up: vec!["k".to_string(), "Up".to_string()],
-                previous_slide: vec!["h".to_string()],
-                next_slide: vec!["l".to_string()],
-                page_down: vec!["C-f".to_string()],
-                page_up: vec!["C-b".to_string()],
-                half_page_down: vec!["C-d".to_string()],
-                half_page_up: vec!["C-u".to_string()],
-                jump_to_top: vec!["g".to_string()],
-                jump_to_bottom: vec!["G".to_string()],
+                normal,
+                modes: BTreeMap::new(),
             },
+            commands: BTreeMap::new(),
+            flash: Flash::default(),
+            keepalive: KeepAliveConfig::default(),
+            features: Features::default(),
+            split: Split::default(),
+            progress: Progress::default(),
+            timer: TimerConfig::default(),
+            copy: CopyConfig::default(),
+            code: Languages::default(),
+            layout: LayoutConfig::default(),
+            font: FontConfig::default(),
+            theme: None,
+            help: Help::default(),
+            registry: CommandRegistry::default(),
+        };
+        for mode in Mode::ALL {
+            config.merge_mode_keymap(mode);
         }
+        config
     }
 }
 
 fn keycode_to_string(key_code: KeyCode, modifiers: KeyModifiers) -> String {
     let base = match key_code {
         KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{}", n),
         KeyCode::Up => "Up".to_string(),
         KeyCode::Down => "Down".to_string(),
         KeyCode::Left => "Left".to_string(),
         KeyCode::Right => "Right".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
         KeyCode::Enter => "Enter".to_string(),
         KeyCode::Esc => "Esc".to_string(),
         KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => return "S-Tab".to_string(),
         KeyCode::Backspace => "Backspace".to_string(),
         _ => return String::new(),
     };
 
+    let mut prefix = String::new();
     if modifiers.contains(KeyModifiers::CONTROL) {
-        format!("C-{}", base)
-    } else if modifiers.contains(KeyModifiers::ALT) {
-        format!("A-{}", base)
-    } else {
-        base
+        prefix.push_str("C-");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("A-");
+    }
+    // Shift is already part of the character for printable keys
+    if modifiers.contains(KeyModifiers::SHIFT) && !matches!(key_code, KeyCode::Char(_)) {
+        prefix.push_str("S-");
+    }
+
+    format!("{}{}", prefix, base)
+}
+
+/// A key binding spelled the way `keycode_to_string` spells it, or a mouse action
+fn canonical_binding(binding: &str) -> Option<String> {
+    if mouse::BINDINGS.contains(&binding) {
+        return Some(binding.to_string());
+    }
+    parse_key_binding(binding).map(|(key_code, modifiers)| keycode_to_string(key_code, modifiers))
+}
+
+/// What a key handled before the keymap is looked up is used for, so it can't be bound
+fn reserved_key(key: &str) -> Option<&'static str> {
+    match key {
+        "q" => Some("recording macros"),
+        "@" => Some("replaying macros"),
+        ":" => Some("going to a slide"),
+        "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => Some("counts"),
+        _ => None,
+    }
+}
+
+/// Parses a script of key presses: characters are typed as is, and key names in angle
+/// brackets use the binding syntax, e.g. `/foo<Enter>n<C-o>`
+pub fn parse_key_script(script: &str) -> Result<Vec<(KeyCode, KeyModifiers)>> {
+    let mut keys = vec![];
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+        if c == '<' {
+            let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+            let key = parse_key_binding(&name)
+                .ok_or_else(|| anyhow!("Unknown key <{}> in \"{}\"", name, script))?;
+            keys.push(key);
+        } else {
+            keys.push((KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+    Ok(keys)
+}
+
+/// Parses a binding string such as `C-f`, `S-Up` or `F5` into the key it describes
+pub fn parse_key_binding(binding: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = binding;
+
+    loop {
+        if let Some(stripped) = rest.strip_prefix("C-").filter(|r| !r.is_empty()) {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("A-").filter(|r| !r.is_empty()) {
+            modifiers |= KeyModifiers::ALT;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("S-").filter(|r| !r.is_empty()) {
+            modifiers |= KeyModifiers::SHIFT;
+            rest = stripped;
+        } else {
+            break;
+        }
     }
+
+    let key_code = match rest {
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "Insert" => KeyCode::Insert,
+        "Delete" => KeyCode::Delete,
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    if modifiers.contains(KeyModifiers::SHIFT) {
+                        return None;
+                    }
+                    KeyCode::Char(c)
+                }
+                (Some('F'), Some(_)) => match rest[1..].parse::<u8>() {
+                    Ok(n @ 1..=12) => KeyCode::F(n),
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    };
+
+    Some((key_code, modifiers))
 }
 
 #[cfg(test)]
@@ -262,6 +949,139 @@ mod tests {
         assert_eq!(s, "Down");
     }
 
+    #[test]
+    fn test_keycode_to_string_function_and_navigation_keys() {
+        assert_eq!(keycode_to_string(KeyCode::F(5), KeyModifiers::NONE), "F5");
+        assert_eq!(
+            keycode_to_string(KeyCode::PageDown, KeyModifiers::NONE),
+            "PageDown"
+        );
+        assert_eq!(keycode_to_string(KeyCode::Home, KeyModifiers::NONE), "Home");
+        assert_eq!(
+            keycode_to_string(KeyCode::Delete, KeyModifiers::NONE),
+            "Delete"
+        );
+    }
+
+    #[test]
+    fn test_keycode_to_string_modified_arrows() {
+        assert_eq!(
+            keycode_to_string(KeyCode::Right, KeyModifiers::SHIFT),
+            "S-Right"
+        );
+        assert_eq!(
+            keycode_to_string(KeyCode::Left, KeyModifiers::CONTROL),
+            "C-Left"
+        );
+        assert_eq!(
+            keycode_to_string(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+            "C-S-Up"
+        );
+    }
+
+    #[test]
+    fn test_shift_is_implicit_for_characters() {
+        assert_eq!(
+            keycode_to_string(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            "G"
+        );
+    }
+
+    #[test]
+    fn test_binding_strings_round_trip() {
+        let bindings = [
+            "j",
+            "G",
+            "C-f",
+            "A-x",
+            "C-A-x",
+            "F1",
+            "F12",
+            "PageUp",
+            "PageDown",
+            "Home",
+            "End",
+            "Insert",
+            "Delete",
+            "S-Up",
+            "C-Left",
+            "C-S-Down",
+            "Enter",
+            "Esc",
+            "Tab",
+            "S-Tab",
+            "Backspace",
+            "-",
+        ];
+        for binding in bindings {
+            let (key_code, modifiers) = parse_key_binding(binding).unwrap();
+            assert_eq!(keycode_to_string(key_code, modifiers), binding);
+        }
+    }
+
+    #[test]
+    fn test_invalid_binding_strings_are_rejected() {
+        for binding in ["", "F13", "F0", "Foo", "S-a", "C-"] {
+            assert_eq!(parse_key_binding(binding), None, "{}", binding);
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_invalid_binding() {
+        let config: Config = toml::from_str("[keymaps]\nnext_slide = [\"PgDn\"]").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("PgDn"));
+        assert!(err.contains("next_slide"));
+    }
+
+    #[test]
+    fn test_validate_reports_conflicting_bindings() {
+        let content = "[keymaps]\nscroll_down = [\"j\"]\nnext_slide = [\"l\", \"j\"]";
+        let config: Config = toml::from_str(content).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("keymaps.scroll_down"));
+        assert!(err.contains("keymaps.next_slide"));
+    }
+
+    #[test]
+    fn test_validate_rejects_macro_keys() {
+        let config: Config = toml::from_str("[keymaps]\nquit = [\"q\"]").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Key \"q\" for keymaps.quit is reserved for recording macros"
+        );
+        let config: Config = toml::from_str("[keymaps]\nquit = [\"@\", \"Q\"]").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_goto_keys() {
+        let config: Config = toml::from_str("[keymaps]\nnext_slide = [\"1\"]").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Key \"1\" for keymaps.next_slide is reserved for counts"
+        );
+        let config: Config = toml::from_str("[keymaps]\noverview = [\":\"]").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Key \":\" for keymaps.overview is reserved for going to a slide"
+        );
+    }
+
+    #[test]
+    fn test_validate_allows_repeated_binding_for_same_command() {
+        let config: Config = toml::from_str("[keymaps]\nscroll_down = [\"j\", \"j\"]").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_default_config_has_no_conflicts() {
+        assert!(Config::default().validate().is_ok());
+    }
+
     #[test]
     fn test_format_help_text_default_config() {
         let config = Config::default();
@@ -271,14 +1091,234 @@ mod tests {
         assert!(help_text.contains("C-d/C-u: half page"));
         assert!(help_text.contains("C-f/C-b: full page"));
         assert!(help_text.contains("g/G: top/bottom"));
-        assert!(help_text.contains("q: quit"));
+        assert!(help_text.contains("Q: quit"));
+    }
+
+    #[test]
+    fn test_default_config_b_blanks_screen() {
+        let config = Config::default();
+        let cmd = config.get_command(KeyCode::Char('b'), KeyModifiers::NONE);
+        assert!(matches!(cmd, Some(Command::ToggleBlank)));
+    }
+
+    #[test]
+    fn test_flash_config() {
+        let config: Config = toml::from_str("[flash]\neffect = \"border\"").unwrap();
+        assert_eq!(config.flash.effect, FlashEffect::Border);
+        assert_eq!(config.flash.duration(), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_layout_config_overrides_the_theme() {
+        let config: Config = toml::from_str("[layout]\nmax_width = 100\nmargin_x = 0").unwrap();
+        let mut layout = theme::Layout {
+            margin_x: 4,
+            margin_y: 2,
+            ..Default::default()
+        };
+        config.layout.apply(&mut layout);
+        assert_eq!(layout.max_width, Some(100));
+        assert_eq!(layout.margin_x, 0);
+        assert_eq!(layout.margin_y, 2);
+    }
+
+    #[test]
+    fn test_keepalive_config() {
+        let config: Config = toml::from_str("[keepalive]\ninterval_secs = 0").unwrap();
+        assert_eq!(config.keepalive.interval(), None);
+        assert!(config.keepalive.inhibit);
+        assert_eq!(
+            Config::default().keepalive.interval(),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_help_hints_follow_configured_order() {
+        let mut config = Config::default();
+        config.help.hints = vec!["quit".to_string(), "slides".to_string()];
+        assert_eq!(config.format_help_text(), "Q: quit  h/l: slides");
+    }
+
+    #[test]
+    fn test_help_hint_for_single_command() {
+        let mut config = Config::default();
+        config.help.hints = vec!["jump_to_bottom".to_string()];
+        assert_eq!(config.format_help_text(), "G: jump to bottom");
+    }
+
+    #[test]
+    fn test_help_verbose_mode_lists_every_binding() {
+        let mut config = Config::default();
+        config.help.mode = HelpMode::Verbose;
+        config.help.hints = vec!["scroll".to_string()];
+        assert_eq!(config.format_help_text(), "j,Down/k,Up: scroll down/up");
+    }
+
+    #[test]
+    fn test_help_skips_unknown_and_unbound_hints() {
+        let mut config = Config::default();
+        config.keymaps.normal.remove("blank_screen");
+        config.help.hints = vec!["nonsense".to_string(), "blank".to_string()];
+        assert_eq!(config.format_help_text(), "");
+    }
+
+    fn load_str(content: &str) -> Result<Config> {
+        Config::parse(content)
+    }
+
+    #[test]
+    fn test_theme_path_is_relative_to_the_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "theme = \"themes/dark.toml\"").unwrap();
+        let config = Config::load(path.to_str()).unwrap();
+        assert_eq!(config.theme, Some(dir.path().join("themes/dark.toml")));
+    }
+
+    #[test]
+    fn test_default_keymaps_fill_in_missing_commands() {
+        let config = load_str(
+            "[keymaps]
+next_slide = [\"j\"]
+quit = []",
+        )
+        .unwrap();
+        assert_eq!(config.bindings("next_slide"), ["j"]);
+        // j went to next_slide, Down stays with scroll_down
+        assert_eq!(config.bindings("scroll_down"), ["Down", "WheelDown"]);
+        assert_eq!(config.bindings("previous_slide"), ["h", "RightClick"]);
+        assert!(config.bindings("quit").is_empty());
+    }
+
+    #[test]
+    fn test_custom_shell_command_can_be_bound() {
+        let config =
+            load_str("[commands]\nopen_repo = \"xdg-open .\"\n[keymaps]\nopen_repo = [\"C-o\"]")
+                .unwrap();
+        let cmd = config.get_command(KeyCode::Char('o'), KeyModifiers::CONTROL);
+        assert!(matches!(cmd, Some(Command::Shell { run, .. }) if run == "xdg-open ."));
+    }
+
+    #[test]
+    fn test_custom_commands_appear_in_help() {
+        let config =
+            load_str("[commands]\nopen_repo = \"xdg-open .\"\n[keymaps]\nopen_repo = [\"C-o\"]")
+                .unwrap();
+        // The default keys fill in the rest
+        assert!(
+            config
+                .format_help_text()
+                .ends_with("o: overview  C-o: open repo  Q: quit")
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_in_keymaps_is_rejected() {
+        let err = load_str("[keymaps]\nfly = [\"f\"]")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("keymaps.fly"));
+    }
+
+    #[test]
+    fn test_custom_command_cannot_shadow_builtin() {
+        assert!(load_str("[commands]\nnext_slide = \"true\"").is_err());
     }
 
     #[test]
     fn test_get_keys_for_command() {
         let config = Config::default();
-        assert_eq!(config.get_keys_for_command(Command::ScrollDown), Some("j"));
-        assert_eq!(config.get_keys_for_command(Command::ScrollUp), Some("k"));
-        assert_eq!(config.get_keys_for_command(Command::NextSlide), Some("l"));
+        assert_eq!(config.get_keys_for_command(&Command::ScrollDown), Some("j"));
+        assert_eq!(config.get_keys_for_command(&Command::ScrollUp), Some("k"));
+        assert_eq!(config.get_keys_for_command(&Command::NextSlide), Some("l"));
+    }
+
+    #[test]
+    fn test_mouse_actions_bind_like_keys() {
+        let config = load_str("[keymaps]\nnext_slide = [\"l\", \"WheelDown\"]").unwrap();
+        assert_eq!(
+            config
+                .get_mouse_command(MouseEventKind::ScrollDown)
+                .map(Command::name),
+            Some("next_slide")
+        );
+        // The wheel went to next_slide and the clicks keep their defaults
+        assert_eq!(config.bindings("scroll_down"), ["j", "Down"]);
+        assert!(config.binds_mouse());
+
+        let error =
+            load_str("[keymaps]\nnext_slide = [\"WheelDown\"]\nscroll_down = [\"WheelDown\"]")
+                .unwrap_err();
+        assert!(error.to_string().contains("bound to both"));
+
+        let keyboard_only = load_str(
+            "[keymaps]\nscroll_down = [\"j\"]\nscroll_up = [\"k\"]\nnext_slide = [\"l\"]\nprevious_slide = [\"h\"]",
+        )
+        .unwrap();
+        assert!(!keyboard_only.binds_mouse());
+    }
+
+    #[test]
+    fn test_mode_keymaps_default_and_inherit() {
+        let config = load_str(
+            "[keymaps]\nscroll_down = [\"J\"]\n[keymaps.overview]\nconfirm = [\"Tab\"]\nselect_below = [\"j\", \"Enter\"]",
+        )
+        .unwrap();
+        let overview = |key| config.get_mode_action(Mode::Overview, key, KeyModifiers::NONE);
+        assert_eq!(overview(KeyCode::Tab), Some(Action::Confirm));
+        assert_eq!(overview(KeyCode::Enter), Some(Action::SelectBelow));
+        // Down was left to select_below's default, which the table replaced
+        assert_eq!(overview(KeyCode::Down), None);
+        assert_eq!(overview(KeyCode::Esc), Some(Action::Cancel));
+
+        // Table mode scrolls with the keys of the scroll commands, but not the wheel
+        assert_eq!(config.mode_bindings(Mode::Table, Action::ScrollDown), ["J"]);
+        assert_eq!(
+            config.mode_bindings(Mode::Table, Action::ScrollUp),
+            ["k", "Up"]
+        );
+        assert_eq!(
+            config.get_mode_action(Mode::Search, KeyCode::Char('r'), KeyModifiers::ALT),
+            Some(Action::ToggleRegex)
+        );
+    }
+
+    #[test]
+    fn test_mode_keymaps_are_validated() {
+        let error = |content| load_str(content).unwrap_err().to_string();
+        assert!(
+            error("[keymaps.presenter]\nconfirm = [\"Enter\"]")
+                .contains("Unknown mode keymaps.presenter")
+        );
+        assert_eq!(
+            error("[keymaps.search]\nsort = [\"s\"]"),
+            "Unknown action keymaps.search.sort"
+        );
+        assert_eq!(
+            error("[keymaps.table]\nsort = [\"s\"]\nfilter = [\"s\"]"),
+            "Key \"s\" is bound to both keymaps.table.filter and keymaps.table.sort"
+        );
+        assert_eq!(
+            error("[keymaps.table]\nscroll_down = [\"WheelDown\"]"),
+            "Invalid key binding \"WheelDown\" for keymaps.table.scroll_down"
+        );
+        // The same key may do different things in different modes
+        load_str("[keymaps.table]\nsort = [\"Enter\"]\nconfirm = []").unwrap();
+    }
+
+    #[test]
+    fn test_mode_table_binds_the_command_opening_it() {
+        let config = load_str("[keymaps.overview]\nopen = [\"O\"]\ncancel = [\"q\"]").unwrap();
+        assert_eq!(config.bindings("overview"), ["O"]);
+        assert_eq!(config.mode_bindings(Mode::Overview, Action::Cancel), ["q"]);
+
+        let error = load_str("[keymaps]\ntable_mode = [\"S\"]\n[keymaps.table]\nopen = [\"s\"]")
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("keymaps.table.open and keymaps.table_mode both bind the same command")
+        );
     }
 }
@@ -0,0 +1,236 @@
+//! Copies the current slide as plain text, to paste into a chat when remote attendees
+//! ask for it, or one of its code blocks for viewers following along
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use markdown::mdast::Node;
+
+use crate::checklist::as_checklist;
+use crate::directives::{centering, pause_count};
+use crate::fallback::as_error_block;
+use crate::images::Images;
+use crate::presenter::is_presenter_block;
+use crate::render_cache::RenderKey;
+use crate::render_slide;
+use crate::run;
+use crate::table::TableView;
+use crate::theme::Theme;
+
+/// Wide enough to keep paragraphs on one line, leaving the wrapping to the chat
+const WIDTH: u16 = 1000;
+
+/// Clipboard tools tried in order, with their arguments
+const CLIPBOARD_COMMANDS: [(&str, &[&str]); 5] = [
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// The slide as rendered with every pause passed, without colors or wrapping, and
+/// with a table sorted and filtered as in table mode
+pub fn slide_text(slide: &[Node], theme: &Theme, table: Option<TableView>) -> String {
+    let key = RenderKey {
+        slide: 0,
+        width: WIDTH,
+        search: None,
+        flash: false,
+        condensed: false,
+        expanded: true,
+        answer: None,
+        ticked: 0,
+        revealed: pause_count(slide),
+        table,
+    };
+    let (buffer, _) = render_slide(slide, &key, None, None, theme, &Images::default(), None);
+    let centered = centering(slide, theme.layout.center).horizontal();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            if centered {
+                row.trim().to_string()
+            } else {
+                row.trim_end().to_string()
+            }
+        })
+        .collect();
+    rows.join("\n").trim_matches('\n').to_string() + "\n"
+}
+
+/// The code of the slide's `nth` code block, counting from 1, leaving out the blocks
+/// markdeck draws something else from
+pub fn code_block(slide: &[Node], nth: usize) -> Result<String> {
+    let blocks: Vec<&str> = slide
+        .iter()
+        .filter(|node| {
+            as_error_block(node).is_none()
+                && as_checklist(node).is_none()
+                && !is_presenter_block(node)
+                && !run::is_output(node)
+        })
+        .filter_map(|node| match node {
+            Node::Code(code) => Some(code.value.as_str()),
+            _ => None,
+        })
+        .collect();
+    match blocks.get(nth.saturating_sub(1)) {
+        Some(code) => Ok(format!("{}\n", code)),
+        None if blocks.is_empty() => bail!("No code block on this slide"),
+        None => bail!("This slide has {} code blocks", blocks.len()),
+    }
+}
+
+/// Where copied slides go
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CopyTarget {
+    #[default]
+    Clipboard,
+    /// The clipboard of the terminal markdeck is shown in, through OSC 52, which
+    /// reaches the presenter's machine over SSH
+    Terminal,
+    /// A FIFO, or any file, that a chat helper script reads from
+    Fifo(PathBuf),
+}
+
+impl CopyTarget {
+    /// Hands the text over, and describes where `what` went
+    pub fn send(&self, text: String, what: &str) -> Result<String> {
+        match self {
+            CopyTarget::Clipboard => {
+                for (program, args) in CLIPBOARD_COMMANDS {
+                    if pipe_to(program, args, &text) {
+                        return Ok(format!("Copied {} to the clipboard", what));
+                    }
+                }
+                // Terminals that don't support OSC 52 ignore it, so this is a last resort
+                osc52(&text)?;
+                Ok(format!(
+                    "No clipboard tool found; asked the terminal to copy {}",
+                    what
+                ))
+            }
+            CopyTarget::Terminal => {
+                osc52(&text)?;
+                Ok(format!("Asked the terminal to copy {}", what))
+            }
+            CopyTarget::Fifo(path) => {
+                if !path.exists() {
+                    bail!("No FIFO at {}; create it with mkfifo", path.display());
+                }
+                write_in_background(path.clone(), text);
+                Ok(format!("Sent {} to {}", what, path.display()))
+            }
+        }
+    }
+}
+
+/// The escape sequence asking the terminal to put `text` on the clipboard
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+fn osc52(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    Ok(stdout.flush()?)
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Opening a FIFO blocks until something reads it, which mustn't hold up the slides
+fn write_in_background(path: PathBuf, text: String) {
+    std::thread::spawn(move || write_to(&path, &text));
+}
+
+fn write_to(path: &Path, text: &str) -> std::io::Result<()> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .write_all(text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_slide_text_is_plain_and_unwrapped() {
+        let long = "word ".repeat(50);
+        let markdown = format!(
+            "# Ownership\n\n{}\n\n<!-- pause -->\n\n- **Move** semantics\n\n```rust\nlet s = String::new();\n```\n",
+            long.trim()
+        );
+        let deck = parse_deck(&markdown).unwrap();
+        let text = slide_text(&deck.slides[0], &Theme::default(), None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "# Ownership");
+        assert_eq!(lines[2], long.trim());
+        assert!(lines.contains(&"- Move semantics"));
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.contains("let s = String::new();"))
+        );
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_centered_slides_are_not_padded() {
+        let deck = parse_deck("# Thanks\n<!-- center -->\n\nQuestions?\n").unwrap();
+        let text = slide_text(&deck.slides[0], &Theme::default(), None);
+        assert_eq!(text, "# Thanks\n\nQuestions?\n");
+    }
+
+    #[test]
+    fn test_missing_fifo_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = CopyTarget::Fifo(dir.path().join("slide"));
+        let error = target.send("text".to_string(), "the slide").unwrap_err();
+        assert!(error.to_string().starts_with("No FIFO at "));
+
+        let path = dir.path().join("slide.txt");
+        std::fs::write(&path, "").unwrap();
+        write_to(&path, "# Slide\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Slide\n");
+    }
+
+    #[test]
+    fn test_code_block_by_number() {
+        let deck = parse_deck(
+            "# Demo\n\n```presenter\nMention the borrow checker\n```\n\n```rust\nlet a = 1;\n```\n\n```sh\ncargo run\n```\n",
+        )
+        .unwrap();
+        let slide = &deck.slides[0];
+        assert_eq!(code_block(slide, 1).unwrap(), "let a = 1;\n");
+        assert_eq!(code_block(slide, 2).unwrap(), "cargo run\n");
+        let error = code_block(slide, 3).unwrap_err();
+        assert_eq!(error.to_string(), "This slide has 2 code blocks");
+        let error = code_block(&deck.slides[0][..1], 1).unwrap_err();
+        assert_eq!(error.to_string(), "No code block on this slide");
+
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
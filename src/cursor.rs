@@ -0,0 +1,80 @@
+//! Presenter cursor: a highlighted line moved with the scroll keys, so long slides
+//! are walked through line by line and only scroll when the cursor reaches an edge
+
+use ratatui::buffer::Buffer;
+
+/// Row of the cursor among the scrolling rows of a slide
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor {
+    slide: usize,
+    row: u16,
+}
+
+impl Cursor {
+    pub fn new(slide: usize, row: u16) -> Self {
+        Cursor { slide, row }
+    }
+
+    /// The row on `slide`, if the cursor was last moved there
+    pub fn row(&self, slide: usize) -> Option<u16> {
+        (self.slide == slide).then_some(self.row)
+    }
+}
+
+/// The next row after `from` with something on it, among the rows of `content` from
+/// `first` on, counted from `first`
+pub fn next_row(content: &Buffer, first: u16, from: Option<u16>, down: bool) -> Option<u16> {
+    let rows = content.area.height.saturating_sub(first);
+    let is_blank = |row: u16| {
+        (0..content.area.width).all(|x| content[(x, first + row)].symbol().trim().is_empty())
+    };
+    match (from, down) {
+        (None, _) => (0..rows).find(|&row| !is_blank(row)),
+        (Some(from), true) => (from + 1..rows).find(|&row| !is_blank(row)),
+        (Some(from), false) => (0..from.min(rows)).rev().find(|&row| !is_blank(row)),
+    }
+}
+
+/// Scroll offset that brings `row` into a viewport `height` rows tall, moving as
+/// little as possible from `offset`
+pub fn scroll_to(row: u16, offset: u16, height: u16) -> u16 {
+    if row < offset {
+        row
+    } else if row >= offset + height.max(1) {
+        row + 1 - height.max(1)
+    } else {
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_rows_are_skipped() {
+        let content = Buffer::with_lines(["Title", "", "- one", "", "- two"]);
+        assert_eq!(next_row(&content, 0, None, true), Some(0));
+        assert_eq!(next_row(&content, 0, Some(0), true), Some(2));
+        assert_eq!(next_row(&content, 0, Some(2), true), Some(4));
+        assert_eq!(next_row(&content, 0, Some(4), true), None);
+        assert_eq!(next_row(&content, 0, Some(4), false), Some(2));
+        // Rows under a pinned heading are counted from below it
+        assert_eq!(next_row(&content, 2, None, true), Some(0));
+        assert_eq!(next_row(&content, 2, Some(0), true), Some(2));
+    }
+
+    #[test]
+    fn test_scrolls_only_at_the_edges() {
+        assert_eq!(scroll_to(3, 0, 10), 0);
+        assert_eq!(scroll_to(12, 0, 10), 3);
+        assert_eq!(scroll_to(2, 5, 10), 2);
+    }
+
+    #[test]
+    fn test_cursor_belongs_to_its_slide() {
+        let cursor = Cursor::new(1, 4);
+        assert_eq!(cursor.row(1), Some(4));
+        assert_eq!(cursor.row(2), None);
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+
+/// How a deck file is encrypted, recognized by its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encryption {
+    /// `talk.md.age`
+    Age,
+    /// `talk.md.gpg` or ASCII-armored `talk.md.asc`
+    Gpg,
+}
+
+impl Encryption {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "age" => Some(Encryption::Age),
+            "gpg" | "asc" => Some(Encryption::Gpg),
+            _ => None,
+        }
+    }
+
+    fn command(self, path: &Path) -> Command {
+        let mut command = match self {
+            Encryption::Age => Command::new("age"),
+            Encryption::Gpg => {
+                let mut command = Command::new("gpg");
+                command.arg("--quiet");
+                command
+            }
+        };
+        command.arg("--decrypt").arg(path);
+        command
+    }
+
+    fn tool(self) -> &'static str {
+        match self {
+            Encryption::Age => "age",
+            Encryption::Gpg => "gpg",
+        }
+    }
+}
+
+/// Reads a deck's markdown, decrypting it in memory when the file is encrypted
+///
+/// Decryption is left to `age` or `gpg`, which prompt for the passphrase on the
+/// terminal, so this has to run before the TUI takes over the screen. The plaintext
+/// never touches the disk.
+pub fn read_deck_source(path: &Path) -> Result<String> {
+    let Some(encryption) = Encryption::detect(path) else {
+        return std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read deck: {}", path.display()));
+    };
+
+    let output = encryption
+        .command(path)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| {
+            format!(
+                "{} is required to open {}",
+                encryption.tool(),
+                path.display()
+            )
+        })?;
+    if !output.status.success() {
+        bail!(
+            "Failed to decrypt {}: {} exited with {}",
+            path.display(),
+            encryption.tool(),
+            output.status
+        );
+    }
+
+    String::from_utf8(output.stdout).context("Decrypted deck is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encryption_from_extension() {
+        assert_eq!(
+            Encryption::detect(Path::new("roadmap.md.age")),
+            Some(Encryption::Age)
+        );
+        assert_eq!(
+            Encryption::detect(Path::new("roadmap.md.gpg")),
+            Some(Encryption::Gpg)
+        );
+        assert_eq!(
+            Encryption::detect(Path::new("roadmap.md.asc")),
+            Some(Encryption::Gpg)
+        );
+        assert_eq!(Encryption::detect(Path::new("roadmap.md")), None);
+    }
+
+    #[test]
+    fn test_plain_decks_are_read_directly() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk.md");
+        std::fs::write(&path, "# Hello").unwrap();
+        assert_eq!(read_deck_source(&path).unwrap(), "# Hello");
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::LazyLock;
+
+use markdown::mdast::Node;
+use regex::Regex;
+
+static SUMMARY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<summary>(.*?)</summary>").unwrap());
+
+/// Part of a slide: a regular node, or a `<details>` block shown folded or expanded
+#[derive(Debug)]
+pub enum Section<'a> {
+    Node(&'a Node),
+    Fold {
+        summary: String,
+        /// Markdown between the opening and closing tags
+        body: Vec<&'a Node>,
+        /// Text written inside the HTML block itself, e.g. `<details>…text</details>`
+        inline: String,
+    },
+}
+
+/// Splits a slide into sections, grouping everything inside `<details>` blocks
+pub fn sections(slide: &[Node]) -> Vec<Section<'_>> {
+    let mut sections = vec![];
+    let mut nodes = slide.iter();
+    while let Some(node) = nodes.next() {
+        let Node::Html(html) = node else {
+            sections.push(Section::Node(node));
+            continue;
+        };
+        let opening = html.value.trim_start();
+        if !opening.starts_with("<details") {
+            sections.push(Section::Node(node));
+            continue;
+        }
+
+        let summary = SUMMARY
+            .captures(opening)
+            .map(|c| c[1].trim().to_string())
+            .unwrap_or_else(|| "Details".to_string());
+        let after_summary = match opening.find("</summary>") {
+            Some(i) => &opening[i + "</summary>".len()..],
+            None => opening.split_once('>').map_or("", |(_, rest)| rest),
+        };
+
+        let mut body = vec![];
+        let inline = if let Some((inline, _)) = after_summary.split_once("</details>") {
+            inline.trim().to_string()
+        } else {
+            for node in nodes.by_ref() {
+                if let Node::Html(html) = node
+                    && html.value.contains("</details>")
+                {
+                    break;
+                }
+                body.push(node);
+            }
+            after_summary.trim().to_string()
+        };
+
+        sections.push(Section::Fold {
+            summary,
+            body,
+            inline,
+        });
+    }
+    sections
+}
+
+pub fn has_details(slide: &[Node]) -> bool {
+    sections(slide)
+        .iter()
+        .any(|section| matches!(section, Section::Fold { .. }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_details_block_groups_its_body() {
+        let deck = parse_deck(
+            "# A\n\n<details>\n<summary>Deep dive</summary>\n\nHidden\n\n- item\n\n</details>\n\nAfter\n",
+        )
+        .unwrap();
+        let sections = sections(&deck.slides[0]);
+
+        assert_eq!(sections.len(), 3);
+        let Section::Fold { summary, body, .. } = &sections[1] else {
+            panic!("expected a fold");
+        };
+        assert_eq!(summary, "Deep dive");
+        assert_eq!(body.len(), 2);
+        assert!(matches!(sections[2], Section::Node(node) if node.to_string() == "After"));
+    }
+
+    #[test]
+    fn test_single_line_details() {
+        let deck = parse_deck("<details><summary>X</summary>inline body</details>\n").unwrap();
+        let sections = sections(&deck.slides[0]);
+        assert!(matches!(
+            &sections[0],
+            Section::Fold { summary, body, inline } if summary == "X" && body.is_empty() && inline == "inline body"
+        ));
+    }
+
+    #[test]
+    fn test_has_details() {
+        let deck = parse_deck("# A\n<!-- toc -->\n\n# B\n<details>\n\nx\n\n</details>\n").unwrap();
+        assert!(!has_details(&deck.slides[0]));
+        assert!(has_details(&deck.slides[1]));
+    }
+}
@@ -0,0 +1,141 @@
+//! Hand-drawn diagrams in box-drawing characters or ASCII boxes, which are shown
+//! exactly as written
+//!
+//! Markdown would join a diagram paragraph's lines and read `*` or `_` as emphasis,
+//! so such paragraphs become plain code blocks. Diagram lines are cut off at the edge
+//! of the slide rather than wrapped onto the next row.
+
+use markdown::mdast::{Code, Node};
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthChar;
+
+/// Meta of the code blocks made from diagram paragraphs
+const META: &str = "diagram";
+
+/// Box drawing, block elements, arrows and geometric shapes
+fn is_drawing(c: char) -> bool {
+    matches!(c, '\u{2190}'..='\u{21FF}' | '\u{2500}'..='\u{25FF}')
+}
+
+/// Whether text of several lines is mostly drawing: at least a fifth of its visible
+/// characters are box-drawing characters, or two lines have `+--` style corners
+pub fn is_diagram(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    let visible = text.chars().filter(|c| !c.is_whitespace()).count();
+    let drawing = text.chars().filter(|&c| is_drawing(c)).count();
+    let corners = lines
+        .iter()
+        .filter(|line| line.contains("+-") || line.contains("-+"))
+        .count();
+    drawing * 5 >= visible || corners >= 2
+}
+
+/// Replaces a diagram paragraph with a code block of its source lines
+pub fn protect_diagram(node: &mut Node, source: &str) {
+    let Node::Paragraph(paragraph) = node else {
+        return;
+    };
+    let Some(text) = paragraph
+        .position
+        .as_ref()
+        .and_then(|position| source.get(position.start.offset..position.end.offset))
+    else {
+        return;
+    };
+    if is_diagram(text) {
+        *node = Node::Code(Code {
+            value: text.to_string(),
+            position: paragraph.position.clone(),
+            lang: None,
+            meta: Some(META.to_string()),
+        });
+    }
+}
+
+/// Diagrams written as paragraphs, shown without code fences
+pub fn as_diagram_paragraph(node: &Node) -> Option<&Code> {
+    match node {
+        Node::Code(code) if code.meta.as_deref() == Some(META) => Some(code),
+        _ => None,
+    }
+}
+
+/// Nodes whose lines are cut off rather than wrapped: diagram paragraphs and code
+/// blocks without a language that hold a diagram
+pub fn is_protected(node: &Node) -> bool {
+    match node {
+        Node::Code(code) => {
+            as_diagram_paragraph(node).is_some()
+                || matches!(code.lang.as_deref(), None | Some("text" | "txt"))
+                    && is_diagram(&code.value)
+        }
+        _ => false,
+    }
+}
+
+/// The part of `line` that fits into `width` columns
+pub fn clip_line(line: Line<'_>, width: usize) -> Line<'_> {
+    if line.width() <= width {
+        return line;
+    }
+    let mut remaining = width;
+    let mut spans = vec![];
+    for span in line.spans {
+        let mut kept = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if char_width > remaining {
+                remaining = 0;
+                break;
+            }
+            remaining -= char_width;
+            kept.push(c);
+        }
+        spans.push(Span::styled(kept, span.style));
+        if remaining == 0 {
+            break;
+        }
+    }
+    Line::from(spans).style(line.style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_diagrams_are_detected() {
+        assert!(is_diagram("┌──────┐\n│ api  │──▶ db\n└──────┘"));
+        assert!(is_diagram(
+            "+-----+    +----+\n| web | -> | db |\n+-----+    +----+"
+        ));
+        assert!(!is_diagram(
+            "Prose with a dash - and an arrow → here\nand a second line"
+        ));
+        assert!(!is_diagram("┌──────┐"));
+    }
+
+    #[test]
+    fn test_diagram_paragraphs_keep_their_source() {
+        let source = "# Arch\n\n+--*--+\n| _a_ |\n+-----+\n\nText *stays*\n";
+        let deck = parse_deck(source).unwrap();
+        let slide = &deck.slides[0];
+        let diagram = as_diagram_paragraph(&slide[1]).unwrap();
+        assert_eq!(diagram.value, "+--*--+\n| _a_ |\n+-----+");
+        assert!(is_protected(&slide[1]));
+        assert!(!is_protected(&slide[2]));
+    }
+
+    #[test]
+    fn test_clip_line_cuts_at_width() {
+        let line = Line::from(vec![Span::raw("┌──"), Span::raw("──┐")]);
+        assert_eq!(clip_line(line, 4).to_string(), "┌───");
+    }
+}
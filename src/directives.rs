@@ -0,0 +1,372 @@
+use markdown::mdast::{List, ListItem, Node, Paragraph, Text};
+use serde::Deserialize;
+
+use crate::numbering::SlideNumbers;
+use crate::presenter::as_presenter_block;
+
+/// Presentation instructions written as HTML comments, e.g. `<!-- emphasize: a, b -->`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Directive {
+    Emphasize(Vec<String>),
+    Toc,
+    /// Turns the slide's first list into answer options, see `quiz::Quiz`
+    Quiz,
+    /// Speaker notes, kept off the slide
+    Notes(String),
+    /// Shell command whose captured output is shown as a static block, see `screenshot`
+    Screenshot(String),
+    /// Centers the slide's content, as on a generated title slide, or only one way
+    Center(Center),
+    /// Starts a column of content beside the others, see `columns`
+    Column,
+    /// Hides the rest of the slide until the next slide key reveals it
+    Pause,
+    /// Leaves the slide out of the slide numbers, e.g. a title or backup slide
+    Unnumbered,
+    /// Starts the backup slides, which forward navigation and the progress leave out
+    Appendix,
+    /// Shows the frontmatter's feedback link as a QR code, see `feedback`
+    Feedback,
+}
+
+impl Directive {
+    pub fn parse(html: &str) -> Option<Self> {
+        let body = html
+            .trim()
+            .strip_prefix("<!--")?
+            .strip_suffix("-->")?
+            .trim();
+        let (name, args) = match body.split_once(':') {
+            Some((name, args)) => (name.trim(), args.trim()),
+            None => (body, ""),
+        };
+
+        match name {
+            "emphasize" => Some(Directive::Emphasize(split_list(args))),
+            "toc" => Some(Directive::Toc),
+            "quiz" => Some(Directive::Quiz),
+            "notes" => Some(Directive::Notes(args.to_string())),
+            "screenshot" => Some(Directive::Screenshot(args.to_string())),
+            "center" => Center::parse(args).map(Directive::Center),
+            "column" => Some(Directive::Column),
+            "pause" => Some(Directive::Pause),
+            "unnumbered" => Some(Directive::Unnumbered),
+            "appendix" => Some(Directive::Appendix),
+            "feedback" => Some(Directive::Feedback),
+            _ => None,
+        }
+    }
+}
+
+/// Directives placed directly on a slide
+pub fn slide_directives(slide: &[Node]) -> Vec<Directive> {
+    slide
+        .iter()
+        .filter_map(|node| match node {
+            Node::Html(html) => Directive::parse(&html.value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Terms listed by every `emphasize` directive on the slide
+pub fn emphasized_terms(slide: &[Node]) -> Vec<String> {
+    slide_directives(slide)
+        .into_iter()
+        .flat_map(|directive| match directive {
+            Directive::Emphasize(terms) => terms,
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Which ways a slide's content is centered: lines in the middle of the screen, and
+/// a slide shorter than the screen halfway down it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Center {
+    #[default]
+    None,
+    Vertical,
+    Horizontal,
+    Both,
+}
+
+impl Center {
+    /// `<!-- center -->` centers both ways
+    fn parse(args: &str) -> Option<Self> {
+        match args {
+            "" | "both" => Some(Center::Both),
+            "vertical" => Some(Center::Vertical),
+            "horizontal" => Some(Center::Horizontal),
+            "none" => Some(Center::None),
+            _ => None,
+        }
+    }
+
+    pub fn horizontal(self) -> bool {
+        matches!(self, Center::Horizontal | Center::Both)
+    }
+
+    pub fn vertical(self) -> bool {
+        matches!(self, Center::Vertical | Center::Both)
+    }
+}
+
+/// How the slide is centered: as its `center` directive says, or else as every slide is
+pub fn centering(slide: &[Node], default: Center) -> Center {
+    slide_directives(slide)
+        .into_iter()
+        .find_map(|directive| match directive {
+            Directive::Center(center) => Some(center),
+            _ => None,
+        })
+        .unwrap_or(default)
+}
+
+pub fn is_unnumbered(slide: &[Node]) -> bool {
+    slide_directives(slide).contains(&Directive::Unnumbered)
+}
+
+pub fn is_appendix(slide: &[Node]) -> bool {
+    slide_directives(slide).contains(&Directive::Appendix)
+}
+
+fn is_pause(node: &Node) -> bool {
+    matches!(node, Node::Html(html) if Directive::parse(&html.value) == Some(Directive::Pause))
+}
+
+/// Pauses on the slide, each one a step before the slide is fully shown
+pub fn pause_count(slide: &[Node]) -> usize {
+    slide.iter().filter(|node| is_pause(node)).count()
+}
+
+/// The part of the slide shown once `revealed` of its pauses were passed
+pub fn revealed_part(slide: &[Node], revealed: usize) -> &[Node] {
+    let end = slide
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| is_pause(node))
+        .nth(revealed)
+        .map_or(slide.len(), |(i, _)| i);
+    &slide[..end]
+}
+
+/// Text of every `notes` directive and presenter block on the slide, in order
+pub fn speaker_notes(slide: &[Node]) -> Vec<String> {
+    slide
+        .iter()
+        .filter_map(|node| match node {
+            Node::Html(html) => match Directive::parse(&html.value)? {
+                Directive::Notes(notes) => Some(notes),
+                _ => None,
+            },
+            _ => Some(as_presenter_block(node)?.value.clone()),
+        })
+        .collect()
+}
+
+fn is_toc(node: &Node) -> bool {
+    matches!(node, Node::Html(html) if Directive::parse(&html.value) == Some(Directive::Toc))
+}
+
+pub fn has_toc(slide: &[Node]) -> bool {
+    slide.iter().any(is_toc)
+}
+
+/// Follows every `toc` directive with a list of the deck's section titles and slide
+/// numbers as shown on screen; the directive stays to mark the slide as a table of
+/// contents
+pub fn expand_toc(slides: &mut [Vec<Node>], numbers: &SlideNumbers) {
+    let entries: Vec<String> = slides
+        .iter()
+        .enumerate()
+        .filter(|(_, slide)| !slide.iter().any(is_toc))
+        .filter_map(|(i, slide)| match slide.first() {
+            Some(heading @ Node::Heading(_)) => Some(match numbers.label(i) {
+                Some(label) => format!("{} — {}", heading.to_string(), label),
+                None => heading.to_string(),
+            }),
+            _ => None,
+        })
+        .collect();
+
+    for slide in slides.iter_mut() {
+        if let Some(index) = slide.iter().position(is_toc) {
+            slide.insert(index + 1, toc_list(&entries));
+        }
+    }
+}
+
+/// Index of the slide a rendered table of contents line points to
+pub fn toc_target(line: &str, numbers: &SlideNumbers) -> Option<usize> {
+    let (_, label) = line.trim_end().rsplit_once(" — ")?;
+    numbers.find_label(label)
+}
+
+fn toc_list(entries: &[String]) -> Node {
+    let children = entries
+        .iter()
+        .map(|entry| {
+            Node::ListItem(ListItem {
+                children: vec![Node::Paragraph(Paragraph {
+                    children: vec![Node::Text(Text {
+                        value: entry.clone(),
+                        position: None,
+                    })],
+                    position: None,
+                })],
+                position: None,
+                spread: false,
+                checked: None,
+            })
+        })
+        .collect();
+
+    Node::List(List {
+        children,
+        position: None,
+        ordered: false,
+        start: None,
+        spread: false,
+    })
+}
+
+fn split_list(args: &str) -> Vec<String> {
+    args.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_parse_emphasize() {
+        let directive = Directive::parse("<!-- emphasize: zero-copy, lifetimes -->");
+        assert_eq!(
+            directive,
+            Some(Directive::Emphasize(vec![
+                "zero-copy".to_string(),
+                "lifetimes".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_center_directive_overrides_the_default() {
+        let deck = parse_deck(
+            "# One\n<!-- center -->\n\n# Two\n<!-- center: vertical -->\n\n# Three\n<!-- center: none -->\n\n# Four\n",
+        )
+        .unwrap();
+        let centered: Vec<Center> = deck
+            .slides
+            .iter()
+            .map(|slide| centering(slide, Center::Horizontal))
+            .collect();
+        assert_eq!(
+            centered,
+            [
+                Center::Both,
+                Center::Vertical,
+                Center::None,
+                Center::Horizontal
+            ]
+        );
+        assert_eq!(Directive::parse("<!-- center: diagonal -->"), None);
+    }
+
+    #[test]
+    fn test_unknown_comment_is_not_a_directive() {
+        assert_eq!(Directive::parse("<!-- just a comment -->"), None);
+        assert_eq!(Directive::parse("<div>emphasize: x</div>"), None);
+    }
+
+    #[test]
+    fn test_emphasized_terms_from_slide() {
+        let deck = parse_deck("# Slide\n<!-- emphasize: borrow -->\n\nText\n").unwrap();
+        assert_eq!(
+            emphasized_terms(&deck.slides[0]),
+            vec!["borrow".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_revealed_part_stops_at_the_next_pause() {
+        let deck = parse_deck("# Slide\n- a\n<!-- pause -->\n- b\n<!-- pause -->\n- c\n").unwrap();
+        let slide = &deck.slides[0];
+        assert_eq!(pause_count(slide), 2);
+        let text = |revealed| {
+            revealed_part(slide, revealed)
+                .iter()
+                .filter(|node| !matches!(node, Node::Html(_)))
+                .map(|node| node.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(0), vec!["Slide", "a"]);
+        assert_eq!(text(1), vec!["Slide", "a", "b"]);
+        assert_eq!(text(2), vec!["Slide", "a", "b", "c"]);
+        assert_eq!(text(5), text(2));
+    }
+
+    #[test]
+    fn test_multiline_speaker_notes() {
+        let deck = parse_deck(
+            "# Slide\n<!-- notes: Start with the story.\n\nThen the demo. -->\n\nText\n<!-- notes: Ask for questions -->\n",
+        )
+        .unwrap();
+        assert_eq!(
+            speaker_notes(&deck.slides[0]),
+            vec![
+                "Start with the story.\n\nThen the demo.",
+                "Ask for questions"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toc_lists_sections_with_slide_numbers() {
+        let content = "# Talk\n\n## Agenda\n<!-- toc -->\n\n## Intro\n\n## Deep dive\n";
+        let deck = parse_deck(content).unwrap();
+        let Node::List(list) = &deck.slides[1][2] else {
+            panic!("toc directive was not expanded");
+        };
+        let entries: Vec<String> = list.children.iter().map(|item| item.to_string()).collect();
+        assert_eq!(entries, vec!["Talk — 1", "Intro — 3", "Deep dive — 4"]);
+        assert!(has_toc(&deck.slides[1]));
+        let numbers = SlideNumbers::new(&deck.slides, 1);
+        assert_eq!(toc_target("- Intro — 3   ", &numbers), Some(2));
+        assert_eq!(toc_target("- Intro", &numbers), None);
+    }
+
+    #[test]
+    fn test_toc_follows_the_slide_numbers() {
+        let content = "# Talk
+<!-- unnumbered -->
+
+## Agenda
+<!-- toc -->
+
+# A
+
+# B
+
+# Backup
+<!-- appendix -->
+";
+        let deck = parse_deck(content).unwrap();
+        let Node::List(list) = &deck.slides[1][2] else {
+            panic!("toc directive was not expanded");
+        };
+        let entries: Vec<String> = list.children.iter().map(|item| item.to_string()).collect();
+        assert_eq!(entries, ["Talk", "A — 2", "B — 3", "Backup — appendix 1"]);
+        let numbers = SlideNumbers::new(&deck.slides, 1);
+        assert_eq!(toc_target("- A — 2", &numbers), Some(2));
+        assert_eq!(toc_target("- Backup — appendix 1", &numbers), Some(4));
+        assert_eq!(toc_target("- Talk", &numbers), None);
+    }
+}
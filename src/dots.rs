@@ -0,0 +1,88 @@
+//! Progress as one dot per slide, grouped by section like web slide frameworks do, so
+//! the audience can tell how far along each section the talk is
+
+use markdown::mdast::Node;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+
+/// Slides in each section of the first `main` slides, a section starting at every
+/// slide that opens with an H1
+pub fn sections(slides: &[Vec<Node>], main: usize) -> Vec<usize> {
+    let mut sections: Vec<usize> = vec![];
+    for (index, slide) in slides[..main.min(slides.len())].iter().enumerate() {
+        let opens_section =
+            matches!(slide.first(), Some(Node::Heading(heading)) if heading.depth == 1);
+        match sections.last_mut() {
+            Some(count) if index > 0 && !opens_section => *count += 1,
+            _ => sections.push(1),
+        }
+    }
+    sections
+}
+
+/// What the dots are drawn with
+pub struct Symbols<'a> {
+    pub passed: &'a str,
+    pub ahead: &'a str,
+}
+
+pub const SYMBOLS: Symbols = Symbols {
+    passed: "●",
+    ahead: "○",
+};
+
+/// A dot for each slide, filled up to and including the one at `current`, with the
+/// sections two columns apart
+pub fn line(
+    sections: &[usize],
+    current: usize,
+    symbols: &Symbols,
+    passed: Style,
+    ahead: Style,
+) -> Line<'static> {
+    let mut spans = vec![];
+    let mut index = 0;
+    for (section, &count) in sections.iter().enumerate() {
+        if section > 0 {
+            spans.push(Span::raw("  "));
+        }
+        for _ in 0..count {
+            spans.push(if index <= current {
+                Span::styled(symbols.passed.to_string(), passed)
+            } else {
+                Span::styled(symbols.ahead.to_string(), ahead)
+            });
+            index += 1;
+        }
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_dots_are_grouped_by_section() {
+        let deck = parse_deck(
+            "## Title\n\n# Intro\n\n## Why\n\n# Demo\n\n## Setup\n\n## Run\n\n# Backup\n",
+        )
+        .unwrap();
+        let sections = sections(&deck.slides, 6);
+        assert_eq!(sections, [1, 2, 3]);
+
+        let text = |current| {
+            line(
+                &sections,
+                current,
+                &SYMBOLS,
+                Style::default(),
+                Style::default(),
+            )
+            .to_string()
+        };
+        assert_eq!(text(0), "●  ○○  ○○○");
+        assert_eq!(text(3), "●  ●●  ●○○");
+    }
+}
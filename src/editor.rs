@@ -0,0 +1,116 @@
+//! Live preview for editors with `--listen-editor`: a plugin streams the buffer and
+//! cursor to markdeck, which shows the slide under the cursor
+//!
+//! Messages are JSON objects, one per line, read from a TCP connection or from stdin
+//! when the address is `-`:
+//!
+//! ```text
+//! {"type": "set_source", "source": "# Intro\n..."}
+//! {"type": "cursor_moved", "line": 12}
+//! ```
+//!
+//! Lines count from 1. Unknown message types and malformed lines are skipped, so
+//! plugins can send messages newer markdeck versions understand.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::events::AppEvent;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    /// The unsaved buffer, which replaces the deck
+    SetSource { source: String },
+    /// The cursor moved to this source line
+    CursorMoved { line: usize },
+}
+
+/// The event a line of the protocol asks for, if it holds a message markdeck knows
+fn event(line: &str) -> Option<AppEvent> {
+    Some(match serde_json::from_str(line).ok()? {
+        Message::SetSource { source } => AppEvent::Reload(source),
+        Message::CursorMoved { line } => AppEvent::Cursor(line),
+    })
+}
+
+/// Starts accepting editor connections on `address`, one at a time
+pub async fn listen(address: &str, events: UnboundedSender<AppEvent>) -> Result<()> {
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {}", address))?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            if !forward(BufReader::new(stream), &events).await {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Reads messages from stdin, which the editor pipes into markdeck, on a dedicated
+/// thread since reading it blocks
+pub fn spawn_stdin(events: UnboundedSender<AppEvent>) {
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            if let Some(event) = event(&line)
+                && events.send(event).is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// Publishes the messages read until the editor disconnects; false once the loop
+/// stopped listening
+async fn forward(
+    mut reader: impl AsyncBufRead + Unpin,
+    events: &UnboundedSender<AppEvent>,
+) -> bool {
+    let mut line = String::new();
+    while matches!(reader.read_line(&mut line).await, Ok(n) if n > 0) {
+        if let Some(event) = event(&line)
+            && events.send(event).is_err()
+        {
+            return false;
+        }
+        line.clear();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn test_messages_become_events() {
+        let input = concat!(
+            "{\"type\": \"set_source\", \"source\": \"# A\\n\"}\n",
+            "not json\n",
+            "{\"type\": \"save\"}\n",
+            "{\"type\": \"cursor_moved\", \"line\": 3}\n",
+        );
+        let (events, mut received) = unbounded_channel();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let (mut editor, preview) = tokio::io::duplex(1024);
+            editor.write_all(input.as_bytes()).await.unwrap();
+            drop(editor);
+            assert!(forward(BufReader::new(preview), &events).await);
+        });
+        assert!(matches!(received.try_recv(), Ok(AppEvent::Reload(source)) if source == "# A\n"));
+        assert!(matches!(received.try_recv(), Ok(AppEvent::Cursor(3))));
+        assert!(received.try_recv().is_err());
+    }
+}
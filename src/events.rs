@@ -0,0 +1,105 @@
+use std::time::Instant;
+
+use ratatui::crossterm::event::{self, Event};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Everything the main loop reacts to
+///
+/// Sources such as the terminal, file watchers or a remote control publish into the
+/// [`EventBus`] from their own threads or tasks, and the loop handles one event at a time.
+#[derive(Debug)]
+pub enum AppEvent {
+    Terminal(Event),
+    /// A deadline requested by the loop passed, e.g. the flash ran out
+    Tick,
+    /// The presenter a read-only viewer is attached to moved to this slide
+    Follow(usize),
+    /// The presenter a read-only viewer is attached to ended the session
+    Disconnected,
+    /// Number of viewers attached to this presentation changed
+    Viewers(usize),
+    /// Feedback responses counted so far, see `feedback`
+    Responses(usize),
+    /// The deck changed to this source, saved with `--watch` or sent by an editor
+    Reload(String),
+    /// The theme file previewed with `markdeck theme preview` was saved with this source
+    Theme(String),
+    /// The editor's cursor moved to this source line, with `--listen-editor`
+    Cursor(usize),
+}
+
+pub struct EventBus {
+    sender: UnboundedSender<AppEvent>,
+    receiver: UnboundedReceiver<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+        EventBus { sender, receiver }
+    }
+
+    /// Handle for publishing events from another thread or task
+    pub fn sender(&self) -> UnboundedSender<AppEvent> {
+        self.sender.clone()
+    }
+
+    /// Forwards terminal input from a dedicated thread, since reading it blocks
+    pub fn spawn_terminal_reader(&self) {
+        let sender = self.sender();
+        std::thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                if sender.send(AppEvent::Terminal(event)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Waits for the next event, or a `Tick` once `deadline` passes
+    pub async fn next(&mut self, deadline: Option<Instant>) -> Option<AppEvent> {
+        let Some(deadline) = deadline else {
+            return self.receiver.recv().await;
+        };
+        tokio::select! {
+            event = self.receiver.recv() => event,
+            _ = tokio::time::sleep_until(deadline.into()) => Some(AppEvent::Tick),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent};
+    use std::time::Duration;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_published_events_are_received_in_order() {
+        let mut bus = EventBus::new();
+        let sender = bus.sender();
+        let key = Event::Key(KeyEvent::from(KeyCode::Char('j')));
+        sender.send(AppEvent::Terminal(key.clone())).unwrap();
+        sender.send(AppEvent::Tick).unwrap();
+
+        runtime().block_on(async {
+            assert!(matches!(bus.next(None).await, Some(AppEvent::Terminal(e)) if e == key));
+            assert!(matches!(bus.next(None).await, Some(AppEvent::Tick)));
+        });
+    }
+
+    #[test]
+    fn test_deadline_produces_tick() {
+        let mut bus = EventBus::new();
+        let deadline = Instant::now() + Duration::from_millis(5);
+        let event = runtime().block_on(bus.next(Some(deadline)));
+        assert!(matches!(event, Some(AppEvent::Tick)));
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use markdeck::model;
+use markdown::mdast::Node;
+
+use crate::app::{Deck, slide_title};
+use crate::directives::{Directive, speaker_notes};
+use crate::presenter::is_presenter_block;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ScriptFormat {
+    Markdown,
+    Text,
+}
+
+/// Slide titles and their speaker notes in order, to print and keep at the podium
+pub fn script(deck: &Deck, format: ScriptFormat) -> String {
+    let mut script = String::new();
+    if let Some(title) = &deck.meta.title {
+        match format {
+            ScriptFormat::Markdown => {
+                let _ = writeln!(script, "# {}\n", title);
+            }
+            ScriptFormat::Text => {
+                let _ = writeln!(script, "{}\n{}\n", title, "=".repeat(title.chars().count()));
+            }
+        }
+    }
+
+    for (index, slide) in deck.slides.iter().enumerate() {
+        let heading = format!("{}. {}", index + 1, slide_title(slide));
+        match format {
+            ScriptFormat::Markdown => {
+                let _ = writeln!(script, "## {}\n", heading);
+            }
+            ScriptFormat::Text => {
+                let _ = writeln!(
+                    script,
+                    "{}\n{}\n",
+                    heading,
+                    "-".repeat(heading.chars().count())
+                );
+            }
+        }
+        for notes in speaker_notes(slide) {
+            let _ = writeln!(script, "{}\n", notes);
+        }
+    }
+    script.truncate(script.trim_end().len());
+    script.push('\n');
+    script
+}
+
+/// The deck as the versioned model external tools read, with every block's markdown
+/// taken from `source`
+pub fn model(deck: &Deck, source: &str) -> model::Deck {
+    let slides = deck
+        .slides
+        .iter()
+        .enumerate()
+        .map(|(index, slide)| {
+            let mut fragments = vec![model::Fragment { blocks: vec![] }];
+            let mut directives = vec![];
+            let mut column = None;
+            for node in slide {
+                match node {
+                    Node::Html(html) if let Some(directive) = Directive::parse(&html.value) => {
+                        match directive {
+                            Directive::Pause => fragments.push(model::Fragment { blocks: vec![] }),
+                            Directive::Column => column = Some(column.unwrap_or(0) + 1),
+                            directive => directives.extend(model_directive(directive)),
+                        }
+                    }
+                    // Notes to self are kept to the script export
+                    _ if is_presenter_block(node) => {}
+                    _ => fragments.last_mut().unwrap().blocks.push(model::Block {
+                        kind: block_kind(node),
+                        markdown: node
+                            .position()
+                            .and_then(|position| {
+                                source.get(position.start.offset..position.end.offset)
+                            })
+                            .map_or_else(|| node.to_string(), str::to_string),
+                        column,
+                    }),
+                }
+            }
+            model::Slide {
+                number: index + 1,
+                title: slide_title(slide),
+                fragments,
+                directives,
+            }
+        })
+        .collect();
+    model::Deck {
+        version: model::VERSION,
+        title: deck.meta.title.clone(),
+        author: deck.meta.author.clone(),
+        date: deck.meta.date.clone(),
+        tags: deck.meta.tags.clone(),
+        slides,
+    }
+}
+
+fn block_kind(node: &Node) -> model::BlockKind {
+    match node {
+        Node::Heading(_) => model::BlockKind::Heading,
+        Node::Paragraph(_) => model::BlockKind::Paragraph,
+        Node::List(_) => model::BlockKind::List,
+        Node::Code(_) => model::BlockKind::Code,
+        Node::Blockquote(_) => model::BlockKind::Quote,
+        Node::Table(_) => model::BlockKind::Table,
+        Node::ThematicBreak(_) => model::BlockKind::Rule,
+        Node::Html(_) => model::BlockKind::Html,
+        _ => model::BlockKind::Other,
+    }
+}
+
+/// Pauses and columns aren't directives in the model but where blocks are placed
+fn model_directive(directive: Directive) -> Option<model::Directive> {
+    Some(match directive {
+        Directive::Emphasize(terms) => model::Directive::Emphasize { terms },
+        Directive::Toc => model::Directive::Toc,
+        Directive::Quiz => model::Directive::Quiz,
+        Directive::Notes(text) => model::Directive::Notes { text },
+        Directive::Screenshot(command) => model::Directive::Screenshot { command },
+        Directive::Center(center) => model::Directive::Center {
+            horizontal: center.horizontal(),
+            vertical: center.vertical(),
+        },
+        Directive::Unnumbered => model::Directive::Unnumbered,
+        Directive::Appendix => model::Directive::Appendix,
+        Directive::Feedback => model::Directive::Feedback,
+        Directive::Pause | Directive::Column => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    const DECK: &str = "+++\ntitle = \"Ownership\"\n+++\n# Intro\n<!-- notes: Welcome everyone -->\n\nHello\n\n# Borrowing\nNo notes here\n";
+
+    #[test]
+    fn test_markdown_script() {
+        let deck = parse_deck(DECK).unwrap();
+        assert_eq!(
+            script(&deck, ScriptFormat::Markdown),
+            "# Ownership\n\n## 1. Intro\n\nWelcome everyone\n\n## 2. Borrowing\n"
+        );
+    }
+
+    #[test]
+    fn test_model_keeps_block_markdown() {
+        let source =
+            "# Intro\n<!-- notes: Welcome -->\n\nSome **bold** text\n\n```rust\nlet x = 1;\n```\n";
+        let deck = model(&parse_deck(source).unwrap(), source);
+        assert_eq!(deck.version, model::VERSION);
+        let slide = &deck.slides[0];
+        assert_eq!(slide.title, "Intro");
+        assert_eq!(
+            slide.directives,
+            vec![model::Directive::Notes {
+                text: "Welcome".to_string()
+            }]
+        );
+        let blocks: Vec<(model::BlockKind, &str)> = slide.fragments[0]
+            .blocks
+            .iter()
+            .map(|block| (block.kind, block.markdown.as_str()))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (model::BlockKind::Heading, "# Intro"),
+                (model::BlockKind::Paragraph, "Some **bold** text"),
+                (model::BlockKind::Code, "```rust\nlet x = 1;\n```"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_model_splits_fragments_at_pauses() {
+        let source = "# Steps\n- one\n<!-- pause -->\n- two\n";
+        let deck = model(&parse_deck(source).unwrap(), source);
+        let fragments: Vec<usize> = deck.slides[0]
+            .fragments
+            .iter()
+            .map(|fragment| fragment.blocks.len())
+            .collect();
+        assert_eq!(fragments, vec![2, 1]);
+        assert!(deck.slides[0].directives.is_empty());
+    }
+
+    #[test]
+    fn test_model_places_blocks_in_columns() {
+        let source = "# Compare\n<!-- column -->\nBefore\n<!-- column -->\nAfter\n";
+        let deck = model(&parse_deck(source).unwrap(), source);
+        let columns: Vec<Option<usize>> = deck.slides[0].fragments[0]
+            .blocks
+            .iter()
+            .map(|block| block.column)
+            .collect();
+        assert_eq!(columns, [None, Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_presenter_blocks_only_reach_the_script() {
+        let source = "# Demo\n\n:::presenter\nCheck the wifi first\n:::\n\nLive coding\n";
+        let deck = parse_deck(source).unwrap();
+        assert_eq!(
+            script(&deck, ScriptFormat::Markdown),
+            "# Demo\n\n## 1. Demo\n\nCheck the wifi first\n"
+        );
+        let slide = &model(&deck, source).slides[0];
+        assert_eq!(slide.fragments[0].blocks.len(), 2);
+        assert!(slide.directives.is_empty());
+    }
+
+    #[test]
+    fn test_text_script_underlines_titles() {
+        let deck = parse_deck(DECK).unwrap();
+        assert_eq!(
+            script(&deck, ScriptFormat::Text),
+            "Ownership\n=========\n\n1. Intro\n--------\n\nWelcome everyone\n\n2. Borrowing\n------------\n"
+        );
+    }
+}
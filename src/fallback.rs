@@ -0,0 +1,192 @@
+//! Parts of a deck that fail to parse are shown as their raw source under the error,
+//! so one broken slide or frontmatter block doesn't take the rest of the talk down
+
+use std::fmt;
+
+use anyhow::Result;
+use markdown::mdast::{Code, Node, Root};
+use markdown::unist::Position;
+
+/// Marks the code blocks standing in for source that failed to parse
+const META: &str = "parse-error";
+
+/// Something wrong with the deck that it was presented without, for `markdeck check`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Source line the problem starts at, counting from 1
+    pub line: usize,
+    pub message: String,
+}
+
+/// `line: message`, which `markdeck check` prefixes with the file like a compiler would
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.line, self.message)
+    }
+}
+
+/// A block showing `source` as written, under the error it caused
+///
+/// Errors quoting the source over several lines, like TOML's, are cut to their first
+/// line and the reason on the last one.
+pub fn error_block(source: &str, message: &str, position: Option<Position>) -> Node {
+    let mut lines = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let first = lines.next().unwrap_or_default();
+    let message = match lines.next_back() {
+        Some(last) => format!("{}: {}", first.trim_end_matches(':'), last),
+        None => first.to_string(),
+    };
+    Node::Code(Code {
+        value: format!("{}\n{}", message, source.trim_end()),
+        position,
+        lang: None,
+        meta: Some(META.to_string()),
+    })
+}
+
+/// The error and the source of an error block
+pub fn as_error_block(node: &Node) -> Option<(&str, &str)> {
+    match node {
+        Node::Code(code) if code.meta.as_deref() == Some(META) => {
+            Some(code.value.split_once('\n').unwrap_or((&code.value, "")))
+        }
+        _ => None,
+    }
+}
+
+/// Parses a deck that doesn't parse as a whole one piece at a time, each from an H1 or
+/// H2 heading to the next, turning the pieces that still fail into error blocks
+pub fn parse_in_pieces(
+    content: &str,
+    parse: impl Fn(&str) -> Result<Node>,
+    problems: &mut Vec<Problem>,
+) -> Node {
+    let mut children = vec![];
+    for piece in pieces(content) {
+        let source = &content[piece.start..piece.end];
+        match parse(source) {
+            Ok(mut root) => {
+                for mut child in root.children_mut().map(std::mem::take).unwrap_or_default() {
+                    shift(&mut child, piece.line - 1, piece.start);
+                    children.push(child);
+                }
+            }
+            Err(e) => {
+                let message = format!("{:#}", e);
+                let lines = source.trim_end().lines().count().max(1);
+                let position = Position::new(
+                    piece.line,
+                    1,
+                    piece.start,
+                    piece.line + lines - 1,
+                    1,
+                    piece.end,
+                );
+                children.push(error_block(source, &message, Some(position)));
+                problems.push(Problem {
+                    line: piece.line,
+                    message,
+                });
+            }
+        }
+    }
+    Node::Root(Root {
+        children,
+        position: None,
+    })
+}
+
+struct Piece {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Byte ranges starting at H1 and H2 lines outside code fences and the frontmatter
+fn pieces(content: &str) -> Vec<Piece> {
+    let mut pieces = vec![Piece {
+        line: 1,
+        start: 0,
+        end: 0,
+    }];
+    // The closing line of the fence or frontmatter block being skipped
+    let mut until: Option<&str> = None;
+    let mut offset = 0;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if let Some(closing) = until {
+            if trimmed.starts_with(closing) {
+                until = None;
+            }
+        } else if index == 0 && (trimmed == "+++" || trimmed == "---") {
+            until = Some(if trimmed == "+++" { "+++" } else { "---" });
+        } else if trimmed.starts_with("```") {
+            until = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            until = Some("~~~");
+        } else if (trimmed.starts_with("# ") || trimmed.starts_with("## ")) && offset > 0 {
+            pieces.push(Piece {
+                line: index + 1,
+                start: offset,
+                end: offset,
+            });
+        }
+        offset += line.len();
+        if let Some(piece) = pieces.last_mut() {
+            piece.end = offset;
+        }
+    }
+    pieces
+}
+
+/// Moves the positions of a piece's nodes to where the piece sits in the deck
+fn shift(node: &mut Node, lines: usize, offset: usize) {
+    if let Some(position) = node.position_mut() {
+        position.start.line += lines;
+        position.end.line += lines;
+        position.start.offset += offset;
+        position.end.offset += offset;
+    }
+    for child in node.children_mut().into_iter().flatten() {
+        shift(child, lines, offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_markdown;
+
+    #[test]
+    fn test_pieces_that_fail_become_error_blocks() {
+        let content = "# One\n\nFine\n\n```md\n# Not a heading\n```\n\n# Two\n\nBROKEN\n\n## Three\n\nFine too\n";
+        let parse = |source: &str| {
+            if source.contains("BROKEN") {
+                anyhow::bail!("Unexpected closing tag");
+            }
+            parse_markdown(source)
+        };
+        let mut problems = vec![];
+        let root = parse_in_pieces(content, parse, &mut problems);
+        let children = root.children().unwrap();
+
+        assert_eq!(
+            problems,
+            [Problem {
+                line: 9,
+                message: "Unexpected closing tag".to_string()
+            }]
+        );
+        let (message, source) = children.iter().find_map(as_error_block).unwrap();
+        assert_eq!(message, "Unexpected closing tag");
+        assert_eq!(source, "# Two\n\nBROKEN");
+
+        // Nodes of later pieces keep their place in the whole deck
+        let three = children.iter().rev().nth(1).unwrap();
+        assert_eq!(three.to_string(), "Three");
+        assert_eq!(three.position().unwrap().start.line, 13);
+    }
+}
@@ -0,0 +1,160 @@
+//! Collecting feedback as the talk closes: `<!-- feedback -->` shows the link set in
+//! the frontmatter as a QR code and a short URL, with the responses counted so far
+//!
+//! The count is fetched with `curl`, like remote images are, from a URL answering with
+//! a plain number.
+
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use qrcode::QrCode;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use serde::Deserialize;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::events::AppEvent;
+use crate::theme::Theme;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Light modules around the code, which scanners need to find it
+const QUIET_ZONE: isize = 2;
+
+/// `[feedback]` in the frontmatter
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Feedback {
+    /// Where the audience leaves feedback
+    pub url: String,
+    /// Answers with the number of responses so far
+    pub responses: Option<String>,
+    /// Last count fetched from `responses`
+    #[serde(skip)]
+    pub count: Option<usize>,
+}
+
+/// The URL without its scheme or trailing slash, to type in by hand
+pub fn short_url(url: &str) -> &str {
+    url.trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_end_matches('/')
+}
+
+/// The QR code, two modules to a row, dark on light whatever the theme
+pub fn qr_lines(url: &str) -> Option<Vec<Line<'static>>> {
+    let code = QrCode::new(url.as_bytes()).ok()?;
+    let width = code.width() as isize;
+    let colors = code.to_colors();
+    let dark = |x: isize, y: isize| {
+        (0..width).contains(&x)
+            && (0..width).contains(&y)
+            && colors[(y * width + x) as usize] == qrcode::Color::Dark
+    };
+    let style = Style::default().fg(Color::Black).bg(Color::White);
+    let lines = (-QUIET_ZONE..width + QUIET_ZONE)
+        .step_by(2)
+        .map(|y| {
+            let row: String = (-QUIET_ZONE..width + QUIET_ZONE)
+                .map(|x| match (dark(x, y), dark(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect();
+            Line::styled(row, style)
+        })
+        .collect();
+    Some(lines)
+}
+
+/// What `<!-- feedback -->` shows: the code, the short URL and the response count
+pub fn lines(feedback: Option<&Feedback>, theme: &Theme) -> Vec<Line<'static>> {
+    let Some(feedback) = feedback else {
+        return vec![
+            Line::styled(
+                "Set url under [feedback] in the frontmatter to show a QR code here",
+                Style::default().fg(theme.colors.incorrect),
+            ),
+            Line::raw(""),
+        ];
+    };
+    let mut lines = qr_lines(&feedback.url).unwrap_or_default();
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        short_url(&feedback.url).to_string(),
+        Style::default()
+            .fg(theme.colors.link)
+            .add_modifier(Modifier::BOLD),
+    ));
+    if let Some(responses) = feedback.count {
+        lines.push(Line::styled(
+            match responses {
+                1 => "1 response so far".to_string(),
+                count => format!("{} responses so far", count),
+            },
+            Style::default().fg(theme.colors.accent),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines
+}
+
+/// Publishes the response count every little while, from a thread as `curl` blocks
+pub fn spawn_poller(url: String, events: UnboundedSender<AppEvent>) {
+    std::thread::spawn(move || {
+        loop {
+            if let Ok(count) = fetch_count(&url)
+                && events.send(AppEvent::Responses(count)).is_err()
+            {
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+fn fetch_count(url: &str) -> Result<usize> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--location", "--max-time", "10"])
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feedback_widget() {
+        assert_eq!(
+            short_url("https://www.forms.example/rustconf/"),
+            "forms.example/rustconf"
+        );
+
+        let feedback = Feedback {
+            url: "https://forms.example/rustconf".to_string(),
+            responses: None,
+            count: Some(12),
+        };
+        let lines = lines(Some(&feedback), &Theme::default());
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        // A version 3 code is 29 modules wide, plus the quiet zone on either side
+        assert_eq!(text[0], " ".repeat(33));
+        assert_eq!(text[1].chars().count(), 33);
+        assert!(text[1].starts_with("  █▀▀▀▀▀█"));
+        let rows = text.iter().position(String::is_empty).unwrap();
+        assert_eq!(rows, 17);
+        assert_eq!(
+            text[rows + 1..],
+            ["forms.example/rustconf", "12 responses so far", ""]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Attributes in braces after a fence's language, e.g. ```` ```rust {numbers} ```` or
+//! ```` ```rust {3,5-7} ````
+
+use std::ops::RangeInclusive;
+
+use markdown::mdast::Code;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FenceAttributes {
+    /// Lines are numbered in a gutter
+    pub numbers: bool,
+    /// Lines, counting from 1, shown on a highlighted background
+    pub highlighted: Vec<RangeInclusive<usize>>,
+}
+
+impl FenceAttributes {
+    /// Reads the braces of the fence's info string; other text and unknown attributes
+    /// are left alone
+    pub fn parse(code: &Code) -> Self {
+        let mut attributes = FenceAttributes::default();
+        let Some(braces) = code
+            .meta
+            .as_deref()
+            .and_then(|meta| meta.trim().strip_prefix('{')?.strip_suffix('}'))
+        else {
+            return attributes;
+        };
+        for attribute in braces.split([',', ' ']).map(str::trim) {
+            if attribute == "numbers" {
+                attributes.numbers = true;
+            } else if let Some(lines) = line_range(attribute) {
+                attributes.highlighted.push(lines);
+            }
+        }
+        attributes
+    }
+
+    pub fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted.iter().any(|lines| lines.contains(&line))
+    }
+}
+
+/// `5` or `5-7`
+fn line_range(attribute: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = attribute.split_once('-').unwrap_or((attribute, attribute));
+    Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+    use markdown::mdast::Node;
+
+    #[test]
+    fn test_attributes_in_braces() {
+        let deck = parse_deck(
+            "```rust {numbers}\nx\n```\n\n```rust numbers\nx\n```\n\n```rust {3, 5-7,numbers}\nx\n```\n",
+        )
+        .unwrap();
+        let attributes: Vec<FenceAttributes> = deck.slides[0]
+            .iter()
+            .filter_map(|node| match node {
+                Node::Code(code) => Some(FenceAttributes::parse(code)),
+                _ => None,
+            })
+            .collect();
+        assert!(attributes[0].numbers);
+        assert!(!attributes[1].numbers);
+        assert!(attributes[2].numbers);
+        assert_eq!(attributes[2].highlighted, [3..=3, 5..=7]);
+        assert!(attributes[2].is_highlighted(6));
+        assert!(!attributes[2].is_highlighted(4));
+    }
+}
@@ -0,0 +1,134 @@
+//! `markdeck check --widths`: renders every slide at the terminal sizes a deck may
+//! meet on an unknown projector, and reports the ones that don't fit
+//!
+//! Prose wraps to any width, so only code blocks and tables count as too wide: they
+//! get torn apart or cut off instead.
+
+use anyhow::Result;
+use markdown::mdast::Node;
+use ratatui::{Terminal, backend::TestBackend, layout::Rect};
+
+use crate::app::{App, node_to_lines};
+use crate::checklist::as_checklist;
+use crate::config::Config;
+use crate::fallback::{Problem, as_error_block};
+use crate::render;
+
+/// Slides that run off the bottom or a side of a `width` × `height` terminal, with
+/// every pause passed
+pub fn overflows(app: &mut App, config: &Config, width: u16, height: u16) -> Result<Vec<Problem>> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let columns = app
+        .theme
+        .layout
+        .content_area(Rect::new(0, 0, width, height))
+        .width as usize;
+    let mut problems = vec![];
+    for index in 0..app.slides.len() {
+        app.current_slide = index;
+        app.revealed.insert(index, usize::MAX);
+        terminal.draw(|frame| render(app, frame, config))?;
+
+        let slide = &app.slides[index];
+        if app.content_height > app.viewport_height {
+            problems.push(Problem {
+                line: first_line(slide),
+                message: format!(
+                    "Slide {} is {} rows too tall for {}x{}",
+                    index + 1,
+                    app.content_height - app.viewport_height,
+                    width,
+                    height
+                ),
+            });
+        }
+        let (lead, in_columns) = crate::columns::split(slide);
+        let column_width = crate::columns::width(columns as u16, in_columns.len()) as usize;
+        let nodes = lead.iter().map(|node| (node, columns)).chain(
+            in_columns
+                .into_iter()
+                .flatten()
+                .map(|node| (node, column_width)),
+        );
+        for (node, columns) in nodes.filter(|(node, _)| is_rigid(node)) {
+            let mut lines = vec![];
+            node_to_lines(node, &mut lines, Default::default(), &app.theme);
+            let widest = lines.iter().map(|line| line.width()).max().unwrap_or(0);
+            if widest > columns {
+                let what = match node {
+                    Node::Table(_) => "table",
+                    _ => "code block",
+                };
+                problems.push(Problem {
+                    line: node.position().map_or(0, |position| position.start.line),
+                    message: format!(
+                        "A {} on slide {} is {} columns too wide for {}x{}",
+                        what,
+                        index + 1,
+                        widest - columns,
+                        width,
+                        height
+                    ),
+                });
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Blocks that can't wrap without breaking apart
+fn is_rigid(node: &Node) -> bool {
+    match node {
+        Node::Table(_) => true,
+        Node::Code(_) => as_error_block(node).is_none() && as_checklist(node).is_none(),
+        _ => false,
+    }
+}
+
+/// Source line of the slide's first node
+pub fn first_line(slide: &[Node]) -> usize {
+    slide
+        .iter()
+        .find_map(|node| node.position())
+        .map_or(0, |position| position.start.line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_overflows_at_each_size() {
+        let long: String = (1..=30).map(|i| format!("- Point {}\n", i)).collect();
+        let markdown = format!(
+            "# Fits\n\nShort\n\n# Long\n\n{}\n# Wide\n\n```rust\nlet total = {};\n```\n\n# Columns\n<!-- column -->\n```\n{}\n```\n<!-- column -->\n",
+            long,
+            "1 + ".repeat(20) + "1",
+            "x".repeat(40)
+        );
+        let mut app = App::from_deck(parse_deck(&markdown).unwrap());
+        let config = Config::default();
+
+        let messages = |problems: Vec<Problem>| {
+            problems
+                .into_iter()
+                .map(|problem| problem.to_string())
+                .collect::<Vec<_>>()
+        };
+        let small = messages(overflows(&mut app, &config, 60, 24).unwrap());
+        assert_eq!(small.len(), 3, "{:?}", small);
+        assert!(small[0].starts_with("5: Slide 2 is "));
+        assert!(small[0].ends_with(" rows too tall for 60x24"));
+        assert!(
+            small[1].starts_with("40: A code block on slide 3 is "),
+            "{}",
+            small[1]
+        );
+
+        // Half of 60 columns is too narrow for the code block in a column
+        assert!(small[2].starts_with("46: A code block on slide 4 is "));
+
+        assert!(overflows(&mut app, &config, 120, 50).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! The font GIF exports are drawn in, and whether it has a glyph for everything the
+//! deck shows, so a missing one is reported instead of drawn as a box
+//!
+//! Coverage comes from fontconfig's `fc-match`, like copying goes through the
+//! platform's clipboard tool; without it the check is skipped.
+
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use ratatui::{Terminal, backend::TestBackend};
+
+use crate::app::App;
+use crate::config::Config;
+use crate::fallback::Problem;
+use crate::fit::first_line;
+use crate::render;
+
+/// The families `agg` draws in when none are configured
+pub const DEFAULT_FAMILIES: &str =
+    "JetBrains Mono,Fira Code,SF Mono,Menlo,Consolas,DejaVu Sans Mono,Liberation Mono";
+
+/// Characters a font has glyphs for, as ranges of code points
+#[derive(Debug, Default, PartialEq)]
+pub struct Coverage {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Coverage {
+    /// Reads fontconfig's charset format, e.g. `20-7e a0 2190-21ff`
+    pub fn parse(charset: &str) -> Self {
+        let ranges = charset
+            .split_whitespace()
+            .filter_map(|range| {
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                Some((
+                    u32::from_str_radix(start, 16).ok()?,
+                    u32::from_str_radix(end, 16).ok()?,
+                ))
+            })
+            .collect();
+        Coverage { ranges }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        let c = c as u32;
+        self.ranges
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&c))
+    }
+
+    fn extend(&mut self, other: Coverage) {
+        self.ranges.extend(other.ranges);
+    }
+}
+
+/// What the installed ones of the comma-separated `families` cover between them, or
+/// `None` without fontconfig
+pub fn coverage(families: &str) -> Result<Option<Coverage>> {
+    let mut coverage = Coverage::default();
+    let mut installed = false;
+    for family in families.split(',').map(str::trim) {
+        let output = Command::new("fc-match")
+            .arg("--format=%{family}\n%{charset}")
+            .arg(family)
+            .stdin(Stdio::null())
+            .output();
+        let Ok(output) = output else {
+            return Ok(None);
+        };
+        let output = String::from_utf8_lossy(&output.stdout);
+        let (matched, charset) = output.split_once('\n').unwrap_or((&output, ""));
+        // fontconfig falls back to another font for a family that isn't installed
+        if matched
+            .split(',')
+            .any(|name| name.eq_ignore_ascii_case(family))
+        {
+            installed = true;
+            coverage.extend(Coverage::parse(charset));
+        }
+    }
+    if !installed {
+        anyhow::bail!("None of the fonts {} are installed", families);
+    }
+    Ok(Some(coverage))
+}
+
+/// Slides showing characters the font has no glyph for, rendered at `width` ×
+/// `height` with every pause passed
+pub fn missing_glyphs(
+    app: &mut App,
+    config: &Config,
+    coverage: &Coverage,
+    width: u16,
+    height: u16,
+) -> Result<Vec<Problem>> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    let mut problems = vec![];
+    for index in 0..app.slides.len() {
+        app.current_slide = index;
+        app.revealed.insert(index, usize::MAX);
+        terminal.draw(|frame| render(app, frame, config))?;
+
+        let mut missing = vec![];
+        for cell in terminal.backend().buffer().content() {
+            for c in cell.symbol().chars() {
+                if c > ' ' && !coverage.contains(c) && !missing.contains(&c) {
+                    missing.push(c);
+                }
+            }
+        }
+        if !missing.is_empty() {
+            problems.push(Problem {
+                line: first_line(&app.slides[index]),
+                message: format!(
+                    "Slide {} has characters the font has no glyph for: {}",
+                    index + 1,
+                    missing
+                        .iter()
+                        .map(char::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                ),
+            });
+        }
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_missing_glyphs_are_reported_per_slide() {
+        let coverage = Coverage::parse("20-7e a0-ff 2500-257f 2588");
+        assert!(coverage.contains('é'));
+        assert!(!coverage.contains('→'));
+
+        let mut app =
+            App::from_deck(parse_deck("# Plain\n\nText\n\n# Arrows\n\nIn → out ✓\n").unwrap());
+        app.low_bandwidth = true;
+        app.apply_theme();
+        let problems = missing_glyphs(&mut app, &Config::default(), &coverage, 60, 20).unwrap();
+        let messages: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            messages,
+            ["5: Slide 2 has characters the font has no glyph for: → ✓"]
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use markdown::mdast::{Heading, Html, Node, Paragraph, Text};
+use ratatui::layout::Rect;
+use serde::{Deserialize, Deserializer};
+
+use crate::feedback::Feedback;
+use crate::rehearsal::Timing;
+use crate::theme::Theme;
+
+/// Deck settings declared in a TOML (`+++ ... +++`) or YAML (`--- ... ---`)
+/// frontmatter block
+#[derive(Debug, Default, Deserialize)]
+pub struct Frontmatter {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// Either a TOML date (`date = 2026-10-16`) or free text
+    #[serde(default, deserialize_with = "date_or_string")]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub theme: Option<Theme>,
+    /// Open the deck with a centered slide of the title, author and date
+    #[serde(default)]
+    pub title_slide: bool,
+    /// Add a contents slide listing every section, after the title slide if there's one
+    #[serde(default)]
+    pub toc_slide: bool,
+    #[serde(default)]
+    pub timing: Timing,
+    /// Number shown on the first numbered slide, 1 unless set
+    pub number_from: Option<usize>,
+    /// Replaces the config's `[split]` for this deck
+    pub split: Option<Split>,
+    /// Oldest markdeck that renders the deck as intended, e.g. `"0.3"`
+    pub min_markdeck_version: Option<Version>,
+    /// Fixed size the deck is laid out at, e.g. `"100x30"`, whatever the terminal
+    pub canvas: Option<Canvas>,
+    /// Link shown by the `feedback` directive
+    pub feedback: Option<Feedback>,
+}
+
+/// A `major.minor.patch` version, where left-out parts count as 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Version([u64; 3]);
+
+impl Version {
+    /// The markdeck that's running
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION")
+            .parse()
+            .expect("the package version is a version")
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let parts: Vec<&str> = text.trim().trim_start_matches('v').split('.').collect();
+        let mut version = [0; 3];
+        if parts.len() > 3 {
+            anyhow::bail!("Invalid version \"{}\", expected e.g. 0.3 or 1.2.0", text);
+        }
+        for (part, text_part) in version.iter_mut().zip(&parts) {
+            *part = text_part.parse().with_context(|| {
+                format!("Invalid version \"{}\", expected e.g. 0.3 or 1.2.0", text)
+            })?;
+        }
+        Ok(Version(version))
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [major, minor, patch] = self.0;
+        write!(f, "{}.{}.{}", major, minor, patch)
+    }
+}
+
+/// A virtual screen of `width` × `height` cells, centered in larger terminals with the
+/// rest left as a letterbox
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Canvas {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Canvas {
+    /// The canvas centered on the screen, cut to the screen where it's smaller
+    pub fn area(self, screen: Rect) -> Rect {
+        let width = self.width.min(screen.width);
+        let height = self.height.min(screen.height);
+        Rect::new(
+            screen.x + (screen.width - width) / 2,
+            screen.y + (screen.height - height) / 2,
+            width,
+            height,
+        )
+    }
+}
+
+impl TryFrom<String> for Canvas {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        let size = text.split_once(['x', '×']).and_then(|(width, height)| {
+            Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+        });
+        match size {
+            Some((width, height)) if width >= 20 && height >= 5 => Ok(Canvas { width, height }),
+            _ => anyhow::bail!(
+                "Invalid canvas \"{}\", expected columns x rows of at least 20x5, e.g. 100x30",
+                text
+            ),
+        }
+    }
+}
+
+/// What starts a new slide, set in the config or the frontmatter's `[split]` table
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Split {
+    /// H1 and H2 headings
+    pub headings: bool,
+    /// Horizontal rules (`---`), which are dropped from the slides
+    pub rules: bool,
+    /// An HTML comment on its own, e.g. `<!-- end_slide -->`, dropped as well
+    pub marker: Option<String>,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split {
+            headings: true,
+            rules: false,
+            marker: None,
+        }
+    }
+}
+
+impl Split {
+    /// Nodes that only mark a slide boundary and aren't shown
+    pub fn is_delimiter(&self, node: &Node) -> bool {
+        match node {
+            Node::ThematicBreak(_) => self.rules,
+            Node::Html(html) => self
+                .marker
+                .as_deref()
+                .is_some_and(|marker| html.value.trim() == marker.trim()),
+            _ => false,
+        }
+    }
+
+    pub fn starts_slide(&self, node: &Node) -> bool {
+        self.headings && matches!(node, Node::Heading(heading) if heading.depth <= 2)
+    }
+}
+
+fn date_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DateOrString {
+        Date(toml::value::Datetime),
+        String(String),
+    }
+
+    Ok(
+        Option::<DateOrString>::deserialize(deserializer)?.map(|date| match date {
+            DateOrString::Date(date) => date.to_string(),
+            DateOrString::String(text) => text,
+        }),
+    )
+}
+
+impl Frontmatter {
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).context("Failed to parse deck frontmatter")
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        if content.trim().is_empty() {
+            return Ok(Frontmatter::default());
+        }
+        serde_yaml_ng::from_str(content).context("Failed to parse deck frontmatter")
+    }
+
+    /// Why this markdeck may present the deck wrong, when it's older than the deck asks
+    pub fn version_warning(&self, running: Version) -> Option<String> {
+        let required = self
+            .min_markdeck_version
+            .filter(|required| running < *required)?;
+        Some(format!(
+            "This deck needs markdeck {} or newer, but this is {}; some slides may not show as intended",
+            required, running
+        ))
+    }
+
+    /// The slide `title_slide` asks for, when the deck has a title
+    pub fn title_slide(&self) -> Option<Vec<Node>> {
+        let title = self.title.as_ref().filter(|_| self.title_slide)?;
+        let text = |value: &str| {
+            vec![Node::Text(Text {
+                value: value.to_string(),
+                position: None,
+            })]
+        };
+        let mut slide = vec![
+            Node::Html(Html {
+                value: "<!-- center -->".to_string(),
+                position: None,
+            }),
+            Node::Heading(Heading {
+                children: text(title),
+                position: None,
+                depth: 1,
+            }),
+        ];
+        for line in [&self.author, &self.date].into_iter().flatten() {
+            slide.push(Node::Paragraph(Paragraph {
+                children: text(line),
+                position: None,
+            }));
+        }
+        Some(slide)
+    }
+
+    /// The slide `toc_slide` asks for, which `directives::expand_toc` fills in
+    pub fn toc_slide(&self) -> Option<Vec<Node>> {
+        self.toc_slide.then(|| {
+            vec![
+                Node::Heading(Heading {
+                    children: vec![Node::Text(Text {
+                        value: "Contents".to_string(),
+                        position: None,
+                    })],
+                    position: None,
+                    depth: 1,
+                }),
+                Node::Html(Html {
+                    value: "<!-- toc -->".to_string(),
+                    position: None,
+                }),
+            ]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_empty_frontmatter_has_no_theme() {
+        let frontmatter = Frontmatter::from_toml("").unwrap();
+        assert!(frontmatter.theme.is_none());
+    }
+
+    #[test]
+    fn test_frontmatter_theme_section() {
+        let content = "[theme.colors]\nheading = \"red\"\n[theme.layout]\nmargin_y = 3";
+        let frontmatter = Frontmatter::from_toml(content).unwrap();
+        let theme = frontmatter.theme.unwrap();
+        assert_eq!(theme.colors.heading, Color::Red);
+        assert_eq!(theme.layout.margin_y, 3);
+    }
+
+    #[test]
+    fn test_date_accepts_toml_dates_and_text() {
+        let frontmatter = Frontmatter::from_toml("date = 2026-10-16").unwrap();
+        assert_eq!(frontmatter.date.as_deref(), Some("2026-10-16"));
+        let frontmatter = Frontmatter::from_toml("date = \"Spring 2026\"").unwrap();
+        assert_eq!(frontmatter.date.as_deref(), Some("Spring 2026"));
+    }
+
+    #[test]
+    fn test_yaml_frontmatter() {
+        let frontmatter = Frontmatter::from_yaml(
+            "title: Ownership\nauthor: Jane Doe\ndate: 2026-10-16\ntheme:\n  colors:\n    heading: red",
+        )
+        .unwrap();
+        assert_eq!(frontmatter.title.as_deref(), Some("Ownership"));
+        assert_eq!(frontmatter.date.as_deref(), Some("2026-10-16"));
+        assert_eq!(frontmatter.theme.unwrap().colors.heading, Color::Red);
+        assert!(Frontmatter::from_yaml("").unwrap().title.is_none());
+    }
+
+    #[test]
+    fn test_title_slide_needs_a_title() {
+        let frontmatter = Frontmatter::from_toml("title_slide = true\nauthor = \"Jane\"").unwrap();
+        assert!(frontmatter.title_slide().is_none());
+        let frontmatter =
+            Frontmatter::from_toml("title_slide = true\ntitle = \"Ownership\"\nauthor = \"Jane\"")
+                .unwrap();
+        let slide = frontmatter.title_slide().unwrap();
+        let text: Vec<String> = slide.iter().map(|node| node.to_string()).collect();
+        assert_eq!(text, vec!["<!-- center -->", "Ownership", "Jane"]);
+    }
+
+    #[test]
+    fn test_split_section() {
+        let frontmatter = Frontmatter::from_toml("[split]\nrules = true").unwrap();
+        assert_eq!(
+            frontmatter.split,
+            Some(Split {
+                headings: true,
+                rules: true,
+                marker: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_frontmatter_is_an_error() {
+        assert!(Frontmatter::from_toml("[theme.colors]\nheading = \"nope\"").is_err());
+    }
+
+    #[test]
+    fn test_min_markdeck_version() {
+        let frontmatter = Frontmatter::from_toml("min_markdeck_version = \"0.3\"").unwrap();
+        let warning = frontmatter
+            .version_warning("0.2.9".parse().unwrap())
+            .unwrap();
+        assert!(warning.starts_with("This deck needs markdeck 0.3.0 or newer, but this is 0.2.9"));
+        assert_eq!(frontmatter.version_warning("0.3.0".parse().unwrap()), None);
+        assert_eq!(frontmatter.version_warning("1.0".parse().unwrap()), None);
+
+        let error = Frontmatter::from_toml("min_markdeck_version = \"0.x\"").unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid version \"0.x\""));
+    }
+
+    #[test]
+    fn test_canvas_is_centered_and_cut_to_the_screen() {
+        let canvas = Frontmatter::from_toml("canvas = \"100x30\"")
+            .unwrap()
+            .canvas
+            .unwrap();
+        assert_eq!(
+            canvas.area(Rect::new(0, 0, 160, 50)),
+            Rect::new(30, 10, 100, 30)
+        );
+        assert_eq!(
+            canvas.area(Rect::new(0, 0, 90, 31)),
+            Rect::new(0, 0, 90, 30)
+        );
+
+        let error = Frontmatter::from_toml("canvas = \"100\"").unwrap_err();
+        assert!(format!("{:#}", error).contains("Invalid canvas \"100\""));
+    }
+}
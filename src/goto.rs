@@ -0,0 +1,40 @@
+/// A slide number being typed, either as the count before `G` or after `:`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GotoInput {
+    pub digits: String,
+    /// Typed after `:`, so Enter jumps rather than `G`
+    pub prompt: bool,
+    /// The quiz answer a first digit replaced, put back if the digits turn out to be a
+    /// count
+    pub replaced_answer: Option<Option<usize>>,
+}
+
+impl GotoInput {
+    pub fn prompt() -> Self {
+        GotoInput {
+            digits: String::new(),
+            prompt: true,
+            replaced_answer: None,
+        }
+    }
+
+    /// The slide number typed so far, counting from 1
+    pub fn number(&self) -> Option<usize> {
+        self.digits.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_of_typed_digits() {
+        let mut input = GotoInput::prompt();
+        assert_eq!(input.number(), None);
+        input.digits.push_str("012");
+        assert_eq!(input.number(), Some(12));
+        input.digits = "9".repeat(40);
+        assert_eq!(input.number(), None);
+    }
+}
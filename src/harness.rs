@@ -0,0 +1,558 @@
+//! End-to-end tests that drive the real key handling and renderer against ratatui's
+//! `TestBackend`, asserting on what ends up on screen.
+
+use ratatui::{Terminal, backend::TestBackend, crossterm::event::MouseEventKind};
+
+use std::time::Instant;
+
+use crate::app::{App, parse_deck};
+use crate::config::{Config, ProgressStyle, parse_key_script};
+use crate::directives::Center;
+use crate::rehearsal::{Rehearsal, Timing};
+use crate::theme::Theme;
+use crate::{handle_key, handle_mouse, render};
+
+pub struct Harness {
+    pub app: App,
+    pub config: Config,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Harness {
+    pub fn new(markdown: &str) -> Self {
+        Harness::with_size(markdown, 60, 20)
+    }
+
+    pub fn with_size(markdown: &str, width: u16, height: u16) -> Self {
+        let deck = parse_deck(markdown).unwrap();
+        let mut harness = Harness {
+            app: App::from_deck(deck),
+            config: Config::default(),
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+        };
+        harness.draw();
+        harness
+    }
+
+    /// Presses a key script and redraws after every key, like the event loop does
+    ///
+    /// Characters are typed as is; key names in angle brackets use the config
+    /// binding syntax, e.g. `/foo<Enter>n<C-o>`.
+    pub fn press(&mut self, script: &str) -> &mut Self {
+        for (code, modifiers) in parse_key_script(script).unwrap() {
+            handle_key(&mut self.app, code, modifiers, &self.config);
+            self.draw();
+        }
+        self
+    }
+
+    /// Sends a mouse event and redraws
+    pub fn mouse(&mut self, kind: MouseEventKind) -> &mut Self {
+        handle_mouse(&mut self.app, kind, &self.config);
+        self.draw();
+        self
+    }
+
+    pub fn draw(&mut self) {
+        if let Some(rehearsal) = &mut self.app.rehearsal {
+            rehearsal.observe(self.app.current_slide, Instant::now());
+        }
+        let (app, config) = (&mut self.app, &self.config);
+        self.terminal
+            .draw(|frame| render(app, frame, config))
+            .unwrap();
+    }
+
+    /// Text of one screen row, without trailing spaces
+    pub fn row(&self, y: u16) -> String {
+        let buffer = self.terminal.backend().buffer();
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    pub fn screen(&self) -> String {
+        let height = self.terminal.backend().buffer().area.height;
+        (0..height)
+            .map(|y| self.row(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn shows(&self, text: &str) -> bool {
+        self.screen().contains(text)
+    }
+
+    pub fn header(&self) -> String {
+        self.row(0)
+    }
+
+    pub fn footer(&self) -> String {
+        self.row(self.terminal.backend().buffer().area.height - 1)
+    }
+}
+
+const DECK: &str = "# Intro\nWelcome to the talk\n\n# Ownership\nBorrowing rules\n\n# Async\nFutures and borrowing\n";
+
+#[test]
+fn test_navigation_updates_slide_and_indicator() {
+    let mut harness = Harness::new(DECK);
+    assert!(harness.header().ends_with("1/3"));
+    assert!(harness.shows("# Intro"));
+
+    harness.press("ll");
+    assert!(harness.header().ends_with("3/3"));
+    assert!(harness.shows("Futures and borrowing"));
+    assert!(!harness.shows("Welcome"));
+
+    harness.press("h");
+    assert!(harness.shows("# Ownership"));
+}
+
+#[test]
+fn test_search_jumps_highlights_and_clears() {
+    let mut harness = Harness::new(DECK);
+    harness.press("/borrowing");
+    assert!(harness.footer().starts_with("/borrowing"));
+
+    harness.press("<Enter>");
+    assert!(
+        harness
+            .header()
+            .starts_with("/borrowing  2 matches on 2 slides")
+    );
+    assert!(harness.shows("Borrowing rules"));
+
+    harness.press("n");
+    assert!(harness.shows("Futures and borrowing"));
+
+    harness.press("<Esc>");
+    assert!(!harness.header().contains("matches"));
+}
+
+#[test]
+fn test_n_steps_through_matches_on_a_tall_slide() {
+    let body: String = (1..=30)
+        .map(|i| match i {
+            12 | 25 => format!("- item {} needle\n", i),
+            _ => format!("- item {}\n", i),
+        })
+        .collect();
+    let deck = format!("# Long\n{}\n# Next\nneedle again\n", body);
+    let mut harness = Harness::with_size(&deck, 40, 12);
+    harness.press("/needle<Enter>");
+    assert!(harness.shows("- item 12 needle"));
+
+    harness.press("n");
+    assert!(harness.shows("- item 25 needle"));
+    assert!(!harness.shows("- item 12 needle"));
+
+    harness.press("n");
+    assert!(harness.shows("needle again"));
+
+    // Going back starts from the last match of the previous slide
+    harness.press("N");
+    assert!(harness.shows("- item 25 needle"));
+    harness.press("N");
+    assert!(harness.shows("- item 12 needle"));
+}
+
+#[test]
+fn test_overview_filters_and_opens_slide() {
+    let mut harness = Harness::new(DECK);
+    harness.press("o");
+    assert!(harness.shows(" 1 Intro "));
+    assert!(harness.shows(" 3 Async "));
+
+    harness.press("fut");
+    assert!(!harness.shows(" 1 Intro "));
+    assert!(harness.shows(" 3 Async "));
+
+    harness.press("<Enter>");
+    assert!(harness.header().ends_with("3/3"));
+    assert!(harness.shows("Futures and borrowing"));
+}
+
+#[test]
+fn test_wrapped_lines_scroll_into_view() {
+    let words: Vec<String> = (1..=60).map(|i| format!("word{}", i)).collect();
+    let mut harness = Harness::with_size(&format!("# Wrapped\n\n{}\n", words.join(" ")), 40, 12);
+    // Sixty words take far more rows than the paragraph's one line
+    assert!(harness.footer().contains('▼'));
+    assert!(!harness.shows("word60"));
+
+    harness.press("G");
+    assert!(harness.shows("word60"));
+    assert!(!harness.footer().contains('▼'));
+}
+
+#[test]
+fn test_vertical_centering_keeps_lines_left() {
+    let row = |harness: &Harness, text| (0..20).find(|&y| harness.row(y).contains(text));
+    let harness = Harness::new("# Thanks\n<!-- center: vertical -->\n\nQuestions?\n");
+    assert!(row(&harness, "# Thanks") > Some(5));
+    assert!(harness.shows("\n  # Thanks"));
+
+    // The config's [layout] centers slides without a directive of their own
+    let mut harness = Harness::new("# Plain\n\nText\n");
+    harness.app.layout.center = Some(Center::Horizontal);
+    harness.app.apply_theme();
+    harness.draw();
+    assert_eq!(row(&harness, "# Plain"), Some(2));
+    assert!(!harness.shows("\n  # Plain"));
+}
+
+#[test]
+fn test_slide_keys_go_round_a_loop() {
+    let mut harness = Harness::new(&format!("{}\n# Q&A\nThanks\n", DECK));
+    harness.press("lLlL");
+    assert!(harness.header().contains("loop 2–3  3/4"));
+    harness.press("l");
+    assert_eq!(harness.app.current_slide, 1);
+    harness.press("h");
+    assert_eq!(harness.app.current_slide, 2);
+
+    harness.press("Ll");
+    assert_eq!(harness.app.current_slide, 3);
+    assert!(!harness.header().contains("loop"));
+}
+
+#[test]
+fn test_heading_levels_follow_the_theme() {
+    let harness = Harness::with_size(
+        "+++\n[theme.headings.h1]\nprefix = \"\"\ncenter = true\n+++\n# Title\n\n### Part\n",
+        40,
+        12,
+    );
+    // Below the header, which shows the deck's title as well
+    let title = (1..12)
+        .map(|y| harness.row(y))
+        .find(|row| row.contains("Title"));
+    // Centered in the 36 columns between the margins, without the `#` marker
+    assert_eq!(title.unwrap(), format!("{}Title", " ".repeat(18)));
+    assert!(harness.shows("\n  ### Part"));
+}
+
+#[test]
+fn test_columns_sit_side_by_side() {
+    let mut harness = Harness::with_size(
+        "# Compare\n\n<!-- column -->\n\nLeft side that wraps in its column\n\n<!-- pause -->\n<!-- column -->\n\nRight side\n",
+        60,
+        20,
+    );
+    let row = |harness: &Harness, text| (0..20).find(|&y| harness.row(y).contains(text));
+    let left = row(&harness, "Left side").unwrap();
+    assert!(!harness.shows("Right side"));
+    assert!(harness.row(left + 1).contains("column"));
+
+    // The revealed column doesn't narrow the first one
+    harness.press("l");
+    assert_eq!(row(&harness, "Right side"), Some(left));
+    assert!(harness.row(left).starts_with("  Left side that wraps in  "));
+    assert_eq!(harness.row(left).find("Right side"), Some(32));
+}
+
+#[test]
+fn test_canvas_is_centered_in_a_larger_terminal() {
+    let deck = format!("+++\ncanvas = \"40x10\"\n+++\n{}", DECK);
+    let harness = Harness::with_size(&deck, 60, 20);
+    // The header ends at the canvas' right edge, ten columns in from the screen's
+    assert_eq!(harness.header(), "");
+    assert_eq!(harness.row(5).len(), 50);
+    assert!(harness.row(5).ends_with("1/3"));
+    // Below the header and the margin, and indented by the margin
+    assert!(harness.row(7).starts_with("            # Intro"));
+    assert_eq!(harness.footer(), "");
+}
+
+#[test]
+fn test_enter_follows_toc_entries() {
+    let deck = format!("+++\ntoc_slide = true\n+++\n{}", DECK);
+    let mut harness = Harness::new(&deck);
+    assert!(harness.shows("# Contents"));
+    assert!(harness.shows("- Ownership — 3"));
+
+    // The first Enter puts the cursor on the first entry, j moves it along
+    harness.press("<Enter>j<Enter>");
+    assert!(harness.header().ends_with("3/4"));
+    assert!(harness.shows("Borrowing rules"));
+
+    harness.press("<Enter>");
+    assert_eq!(
+        harness.app.message.as_deref(),
+        Some("No table of contents on this slide")
+    );
+}
+
+#[test]
+fn test_footer_counts_rows_below_the_fold() {
+    let body: String = (1..=30).map(|i| format!("- item {}\n", i)).collect();
+    let mut harness = Harness::new(&format!("# Long\n\n{}", body));
+    // 20 rows leave 16 for the slide, with the heading and a blank line above the list
+    assert!(harness.footer().ends_with(" ▼ +17"));
+
+    harness.press("j");
+    assert!(harness.footer().ends_with(" ▼ +16"));
+    harness.press("G");
+    assert!(!harness.footer().contains('▼'));
+}
+
+#[test]
+fn test_cheat_sheet_until_next_key() {
+    // Tall enough for the commands to fit in two columns
+    let mut harness = Harness::with_size(DECK, 60, 24);
+    harness.press("?");
+    assert!(harness.shows(" Keys "));
+    assert!(harness.shows("l, LeftClick  Next"));
+
+    // The key closing the sheet does nothing else
+    harness.press("l");
+    assert!(!harness.shows(" Keys "));
+    assert!(harness.header().ends_with("1/3"));
+}
+
+#[test]
+fn test_overview_grid_moves_between_rows() {
+    let mut harness = Harness::new(DECK);
+    harness.press("o");
+    // Two cards fit side by side, each showing the start of its slide
+    assert!(harness.shows("Welcome to the talk"));
+    assert!(harness.shows("Borrowing rules"));
+
+    harness.press("<Down>");
+    harness.press("<Enter>");
+    assert!(harness.header().ends_with("3/3"));
+
+    harness.press("o<Left><Enter>");
+    assert!(harness.header().ends_with("2/3"));
+}
+
+#[test]
+fn test_jump_back_returns_to_previous_slide() {
+    let mut harness = Harness::new(DECK);
+    harness.press("G");
+    harness.press("o").press("async<Enter>");
+    assert!(harness.header().ends_with("3/3"));
+
+    harness.press("<C-o>");
+    assert!(harness.header().ends_with("1/3"));
+}
+
+#[test]
+fn test_blank_screen_hides_everything() {
+    let mut harness = Harness::new(DECK);
+    harness.press("b");
+    assert_eq!(harness.screen().trim(), "");
+
+    harness.press("b");
+    assert!(harness.shows("# Intro"));
+}
+
+#[test]
+fn test_presenting_lock_is_shown_and_guards_quit() {
+    let mut harness = Harness::new(DECK);
+    harness.press("P");
+    assert!(harness.header().ends_with("locked  1/3"));
+
+    harness.press("Q");
+    assert!(!harness.app.should_quit);
+    assert!(
+        harness
+            .header()
+            .starts_with("Presenting: quit again to exit")
+    );
+}
+
+#[test]
+fn test_scrolling_tall_slide() {
+    let body: String = (1..=30).map(|i| format!("- item {}\n", i)).collect();
+    let mut harness = Harness::with_size(&format!("# Long\n{}", body), 40, 12);
+    assert!(harness.shows("- item 1"));
+    assert!(!harness.shows("item 30"));
+
+    harness.press("G");
+    assert!(harness.shows("- item 30"));
+    assert!(!harness.shows("# Long"));
+
+    harness.press("g");
+    assert!(harness.shows("# Long"));
+}
+
+#[test]
+fn test_zoom_out_fits_tall_code_block() {
+    let code: String = (1..=30).map(|i| format!("line {}\n", i)).collect();
+    let mut harness = Harness::with_size(&format!("# Code\n\n```\n{}```\n", code), 40, 12);
+    assert!(!harness.shows("line 30"));
+
+    harness.press("z");
+    assert!(harness.header().ends_with("zoomed out  1/1"));
+    assert!(harness.shows("line 1\n"));
+    assert!(harness.shows("…"));
+    assert!(harness.shows("line 30"));
+
+    harness.press("z");
+    assert!(!harness.shows("line 30"));
+}
+
+#[test]
+fn test_details_fold_and_expand() {
+    let mut harness = Harness::new(
+        "# Deep\n\n<details>\n<summary>Internals</summary>\n\nHidden detail\n\n</details>\n",
+    );
+    assert!(harness.shows("▸ Internals"));
+    assert!(!harness.shows("Hidden detail"));
+
+    harness.press("d");
+    assert!(harness.shows("▾ Internals"));
+    assert!(harness.shows("Hidden detail"));
+}
+
+#[test]
+fn test_breadcrumb_names_parent_section() {
+    let deck = "+++\n[theme.headings]\nbreadcrumb = true\n+++\n# Memory\n\n## Stack\n\n## Heap\n";
+    let mut harness = Harness::new(deck);
+    assert!(!harness.shows("›"));
+
+    harness.press("ll");
+    assert!(harness.shows("Memory ›"));
+    assert!(harness.shows("## Heap"));
+}
+
+#[test]
+fn test_header_shows_deck_title_until_a_status_replaces_it() {
+    let mut harness = Harness::new("+++\ntitle = \"Ownership\"\nauthor = \"Jane\"\n+++\n# Intro\n");
+    assert!(harness.header().starts_with("Ownership · Jane"));
+
+    harness.press("qa");
+    assert!(harness.header().starts_with("recording @a"));
+}
+
+#[test]
+fn test_quiz_reveals_answer_and_explanation() {
+    let mut harness = Harness::new(
+        "# Quiz\n<!-- quiz -->\nWhich type owns its data?\n\n- [ ] &str\n- [x] String\n\n> String owns a heap buffer.\n",
+    );
+    assert!(harness.shows("  1) &str"));
+    assert!(harness.shows("Press 1-2 to answer"));
+    assert!(!harness.shows("heap buffer"));
+
+    harness.press("1");
+    assert!(harness.shows("✘ 1) &str"));
+    assert!(harness.shows("> String owns a heap buffer."));
+
+    harness.press("2");
+    assert!(harness.shows("✔ 2) String"));
+}
+
+#[test]
+fn test_rehearsal_timer_in_header() {
+    let mut harness = Harness::new("# One\n# Two\n");
+    let rehearsal = Rehearsal::new(
+        &harness.app.slides,
+        &Timing {
+            default_secs: Some(90),
+            ..Timing::default()
+        },
+        Instant::now(),
+    );
+    harness.app.rehearsal = Some(rehearsal);
+    harness.draw();
+    assert!(harness.header().ends_with("0:00 / 1:30  1/2"));
+
+    // An edit adding a slide doesn't leave the stopwatch behind
+    harness.app.reload(
+        parse_deck("+++\n[timing]\ndefault_secs = 60\n+++\n# One\n# Two\n# Three\n").unwrap(),
+    );
+    harness.press("ll");
+    assert!(harness.header().ends_with("0:00 / 1:00  3/3"));
+}
+
+#[test]
+fn test_low_bandwidth_draws_ascii_borders() {
+    let mut harness = Harness::new("# One\n<!-- notes: Say hi -->\n# Two\n");
+    harness.app.low_bandwidth = true;
+    harness.app.speaker_view = true;
+    harness.app.show_progress = true;
+    harness.draw();
+    assert!(harness.shows("+ Notes -"));
+    assert!(harness.shows("|Say hi"));
+    assert!(harness.shows("slide 1/2   ===="));
+    assert!(!harness.screen().contains('│'));
+}
+
+#[test]
+fn test_progress_dots_fall_back_to_the_bar() {
+    let deck = "# Intro\n\n## Why\n\n# Demo\n\n## Setup\n\n## Run\n";
+    let mut harness = Harness::new(deck);
+    harness.config.progress.style = ProgressStyle::Dots;
+    harness.app.show_progress = true;
+    harness.press("ll");
+    assert!(harness.shows("slide 3/5  ●●  ●○○"));
+
+    // Too many slides for the screen's width
+    let mut harness = Harness::with_size(&deck.repeat(4), 30, 10);
+    harness.config.progress.style = ProgressStyle::Dots;
+    harness.app.show_progress = true;
+    harness.draw();
+    assert!(harness.shows("slide 1/20   ───"));
+}
+
+#[test]
+fn test_cycle_through_builtin_themes() {
+    let mut harness = Harness::new("# One\n");
+    harness.press("T");
+    assert!(harness.shows("Theme: dark"));
+    assert_eq!(harness.app.theme, Theme::builtin("dark").unwrap());
+
+    harness.press("TTTT");
+    assert!(harness.shows("Theme: original"));
+    assert_eq!(harness.app.theme, Theme::default());
+}
+
+#[test]
+fn test_table_mode_sorts_and_filters_rows() {
+    let mut harness = Harness::new(
+        "# Benchmarks\n\n| Server | Latency |\n|---|---:|\n| tokio | 12 ms |\n| actix | 9 ms |\n| axum | 30 ms |\n",
+    );
+    harness.press("S");
+    assert!(harness.shows("s: sort"));
+
+    harness.press("ls");
+    assert!(harness.shows("Server │ Latency ▲"));
+    let actix = harness.screen().find("actix").unwrap();
+    assert!(actix < harness.screen().find("tokio").unwrap());
+
+    harness.press("/ax<Enter>");
+    assert!(harness.shows("axum"));
+    assert!(!harness.shows("tokio"));
+
+    harness.press("<Esc>");
+    assert!(harness.shows("Server │ Latency"));
+    assert!(!harness.shows("▲"));
+    assert!(harness.shows("tokio"));
+}
+
+#[test]
+fn test_mouse_clicks_change_slides_and_the_wheel_scrolls() {
+    use ratatui::crossterm::event::MouseButton;
+
+    let body: String = (1..=40).map(|i| format!("Line {}\n\n", i)).collect();
+    let mut harness = Harness::with_size(&format!("# One\n\n{}# Two\n", body), 40, 12);
+    harness.mouse(MouseEventKind::ScrollDown);
+    assert_eq!(harness.app.scroll_view_state.offset().y, 1);
+    harness.mouse(MouseEventKind::ScrollUp);
+    assert_eq!(harness.app.scroll_view_state.offset().y, 0);
+
+    harness.mouse(MouseEventKind::Down(MouseButton::Left));
+    assert_eq!(harness.app.current_slide, 1);
+    harness.mouse(MouseEventKind::Down(MouseButton::Right));
+    assert_eq!(harness.app.current_slide, 0);
+
+    // Releasing a button isn't a click, and the overview is keyboard-only
+    harness.mouse(MouseEventKind::Up(MouseButton::Left));
+    harness.press("o");
+    harness.mouse(MouseEventKind::Down(MouseButton::Left));
+    assert_eq!(harness.app.current_slide, 0);
+}
@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+/// Applies `style` to every whole-word, case-insensitive occurrence of `terms`
+pub fn highlight_terms(lines: &mut [Line<'_>], terms: &[String], style: Style) {
+    if terms.is_empty() {
+        return;
+    }
+    highlight_with(lines, style, |text| find_terms(text, terms));
+}
+
+/// Restyles the byte ranges reported by `find` within each span
+pub fn highlight_with(
+    lines: &mut [Line<'_>],
+    style: Style,
+    find: impl Fn(&str) -> Vec<Range<usize>>,
+) {
+    for line in lines.iter_mut() {
+        let mut spans = vec![];
+        for span in line.spans.drain(..) {
+            let ranges = find(&span.content);
+            if ranges.is_empty() {
+                spans.push(span);
+                continue;
+            }
+
+            let mut last = 0;
+            for range in ranges {
+                if range.start > last {
+                    spans.push(Span::styled(
+                        slice(&span.content, last..range.start),
+                        span.style,
+                    ));
+                }
+                spans.push(Span::styled(
+                    slice(&span.content, range.clone()),
+                    span.style.patch(style),
+                ));
+                last = range.end;
+            }
+            if last < span.content.len() {
+                spans.push(Span::styled(
+                    slice(&span.content, last..span.content.len()),
+                    span.style,
+                ));
+            }
+        }
+        line.spans = spans;
+    }
+}
+
+/// Part of a span's text, still borrowing from the slide when the span did
+fn slice<'a>(content: &Cow<'a, str>, range: Range<usize>) -> Cow<'a, str> {
+    match content {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[range]),
+        Cow::Owned(text) => Cow::Owned(text[range].to_string()),
+    }
+}
+
+fn find_terms(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    // ASCII lowercasing keeps byte offsets aligned with the original text
+    let haystack = text.to_ascii_lowercase();
+    let mut ranges: Vec<Range<usize>> = vec![];
+
+    for term in terms {
+        let needle = term.to_ascii_lowercase();
+        for (start, _) in haystack.match_indices(&needle) {
+            let end = start + needle.len();
+            if is_word_boundary(text, start, end) {
+                ranges.push(start..end);
+            }
+        }
+    }
+
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start < last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+
+    fn accent() -> Style {
+        Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    #[test]
+    fn test_term_is_split_into_its_own_span() {
+        let mut lines = vec![Line::from("Rust has zero-copy parsing")];
+        highlight_terms(&mut lines, &["zero-copy".to_string()], accent());
+        let spans = &lines[0].spans;
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].content, "zero-copy");
+        assert_eq!(spans[1].style.fg, Some(Color::Magenta));
+        assert_eq!(spans[2].content, " parsing");
+    }
+
+    #[test]
+    fn test_terms_match_case_insensitively_on_word_boundaries() {
+        let mut lines = vec![Line::from("Lifetimes and lifetimesque")];
+        highlight_terms(&mut lines, &["lifetimes".to_string()], accent());
+        let highlighted: Vec<_> = lines[0]
+            .spans
+            .iter()
+            .filter(|s| s.style.fg == Some(Color::Magenta))
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(highlighted, vec!["Lifetimes"]);
+    }
+
+    #[test]
+    fn test_existing_span_style_is_kept() {
+        let base = Style::default().add_modifier(Modifier::ITALIC);
+        let mut lines = vec![Line::from(Span::styled("a key term", base))];
+        highlight_terms(&mut lines, &["key".to_string()], accent());
+        let style = lines[0].spans[1].style;
+        assert!(style.add_modifier.contains(Modifier::ITALIC));
+        assert!(style.add_modifier.contains(Modifier::BOLD));
+    }
+}
@@ -0,0 +1,174 @@
+//! Clickable links via OSC 8
+//!
+//! Ratatui measures a cell's symbol by its characters, so escape sequences can't
+//! live in the buffer. Instead the link cells are found after a frame is rendered
+//! and redrawn wrapped in OSC 8 once the frame is on screen.
+
+use std::io::{self, Write};
+
+use markdown::mdast::Node;
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::{Color, Modifier},
+};
+
+/// Cells of one link on one row, in screen coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRun {
+    pub url: String,
+    pub cells: Vec<(u16, u16, Cell)>,
+}
+
+/// Text and URL of every link on the slide, in order
+pub fn slide_links(slide: &[Node]) -> Vec<(String, String)> {
+    fn collect(node: &Node, links: &mut Vec<(String, String)>) {
+        if let Node::Link(link) = node {
+            links.push((node.to_string(), link.url.clone()));
+        } else if let Some(children) = node.children() {
+            for child in children {
+                collect(child, links);
+            }
+        }
+    }
+
+    let mut links = vec![];
+    for node in slide {
+        collect(node, &mut links);
+    }
+    links
+}
+
+/// Finds the cells drawn in the link style within `area` and matches them to the
+/// slide's links by their text, so links wrapped over several rows keep their URL
+pub fn find_links(
+    buffer: &Buffer,
+    area: Rect,
+    links: &[(String, String)],
+    link_color: Color,
+) -> Vec<LinkRun> {
+    let is_link =
+        |cell: &Cell| cell.fg == link_color && cell.modifier.contains(Modifier::UNDERLINED);
+    let mut runs = vec![];
+    // Link being matched and how much of its text was already found
+    let (mut current, mut found) = (0, 0);
+
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            if !is_link(&buffer[(x, y)]) {
+                x += 1;
+                continue;
+            }
+            let mut cells = vec![];
+            while x < area.right() && is_link(&buffer[(x, y)]) {
+                cells.push((x, y, buffer[(x, y)].clone()));
+                x += 1;
+            }
+            let text: String = cells.iter().map(|(_, _, cell)| cell.symbol()).collect();
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+
+            let continues = links
+                .get(current)
+                .and_then(|(link, _)| link.get(found..))
+                .and_then(|rest| rest.find(text));
+            let position = match continues {
+                Some(offset) => Some((current, found + offset)),
+                None => (0..links.len())
+                    .map(|i| (current + 1 + i) % links.len())
+                    .find_map(|i| links[i].0.find(text).map(|offset| (i, offset))),
+            };
+            if let Some((link, offset)) = position {
+                (current, found) = (link, offset + text.len());
+                runs.push(LinkRun {
+                    url: links[link].1.clone(),
+                    cells,
+                });
+            }
+        }
+    }
+    runs
+}
+
+/// Redraws the link cells of the frame on screen wrapped in OSC 8
+pub fn write<W: Write>(backend: &mut CrosstermBackend<W>, runs: &[LinkRun]) -> io::Result<()> {
+    for run in runs {
+        write!(backend, "\x1b]8;;{}\x1b\\", run.url)?;
+        backend.draw(run.cells.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+        write!(backend, "\x1b]8;;\x1b\\")?;
+    }
+    Backend::flush(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+    use ratatui::{
+        style::Style,
+        text::{Line, Span},
+        widgets::{Paragraph, Widget, Wrap},
+    };
+
+    fn links_on(markdown: &str, width: u16) -> Vec<(String, String)> {
+        let deck = parse_deck(markdown).unwrap();
+        let links = slide_links(&deck.slides[0]);
+        let mut spans = vec![];
+        for (i, (text, _)) in links.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" and "));
+            }
+            spans.push(Span::styled(text.clone(), Style::new().blue().underlined()));
+        }
+        let area = Rect::new(0, 0, width, 4);
+        let mut buffer = Buffer::empty(area);
+        Paragraph::new(Line::from(spans))
+            .wrap(Wrap { trim: true })
+            .render(area, &mut buffer);
+
+        find_links(&buffer, area, &links, Color::Blue)
+            .into_iter()
+            .map(|run| {
+                let text = run.cells.iter().map(|(_, _, cell)| cell.symbol()).collect();
+                (text, run.url)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_links_are_found_in_order() {
+        assert_eq!(
+            links_on("[docs](https://a.example) [site](https://b.example)", 40),
+            vec![
+                ("docs".to_string(), "https://a.example".to_string()),
+                ("site".to_string(), "https://b.example".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrapped_link_keeps_its_url_on_every_row() {
+        let runs = links_on("[the official docs](https://a.example)", 8);
+        assert_eq!(runs.len(), 3);
+        assert!(runs.iter().all(|(_, url)| url == "https://a.example"));
+    }
+
+    #[test]
+    fn test_osc8_wraps_link_cells() {
+        let mut output = vec![];
+        let mut backend = CrosstermBackend::new(&mut output);
+        let runs = [LinkRun {
+            url: "https://a.example".to_string(),
+            cells: vec![(0, 0, Cell::new("x"))],
+        }];
+        write(&mut backend, &runs).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("\x1b]8;;https://a.example\x1b\\"));
+        assert!(output.contains('x'));
+        assert!(output.ends_with("\x1b]8;;\x1b\\"));
+    }
+}
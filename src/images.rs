@@ -0,0 +1,496 @@
+//! Inline images via the kitty, iTerm2 and sixel graphics protocols
+//!
+//! An image on a paragraph of its own is laid out as rows of placeholder cells, one
+//! private-use character per cell that names the image. Once a frame is rendered the
+//! placeholders are found in it, skipped by ratatui and drawn over with the image after
+//! the frame is on screen, the same way links are made clickable. Images the terminal
+//! can't show, remote ones that weren't prefetched and anything but PNG keep showing
+//! their alt text.
+
+use std::fmt::Write as _;
+use std::io::{self, Cursor, Write};
+use std::path::Path;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use markdown::mdast::{self, Node};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    buffer::Buffer,
+    crossterm::{cursor::MoveTo, queue, terminal},
+    layout::Rect,
+    text::Line,
+};
+
+use crate::prefetch::AssetCache;
+
+/// How images are sent to the terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageProtocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+/// Cell size assumed for layout, as most terminal fonts are about twice as tall as wide
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+/// Tallest an image is laid out, so one image doesn't push the rest off the slide
+const MAX_ROWS: u32 = 20;
+/// Placeholder cells are `PLACEHOLDER + index` of the image
+const PLACEHOLDER: u32 = 0xF0000;
+
+/// A decoded PNG
+#[derive(Debug)]
+pub struct Image {
+    /// The file as read, which kitty and iTerm2 decode themselves
+    png: Vec<u8>,
+    width: u32,
+    height: u32,
+    /// RGBA pixels, for sixel
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn decode(png: Vec<u8>) -> Option<Self> {
+        let mut decoder = png::Decoder::new(Cursor::new(&png));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().ok()?;
+        let mut buffer = vec![0; reader.output_buffer_size()?];
+        let info = reader.next_frame(&mut buffer).ok()?;
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .flat_map(|pixel| match *pixel {
+                [gray] => [gray, gray, gray, 255],
+                [gray, alpha] => [gray, gray, gray, alpha],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => [0; 4],
+            })
+            .collect();
+        Some(Image {
+            width: info.width,
+            height: info.height,
+            png,
+            pixels,
+        })
+    }
+
+    /// Columns and rows the image takes at most `max_columns` wide: its own size,
+    /// shrunk to fit keeping its aspect ratio
+    pub fn cells(&self, max_columns: u16) -> (u16, u16) {
+        let (width, height) = (self.width.max(1), self.height.max(1));
+        let mut columns = width.div_ceil(CELL_WIDTH).min(max_columns.max(1) as u32);
+        let mut rows = (columns * CELL_WIDTH * height).div_ceil(width * CELL_HEIGHT);
+        if rows > MAX_ROWS {
+            rows = MAX_ROWS;
+            columns = (rows * CELL_HEIGHT * width).div_ceil(height * CELL_WIDTH);
+        }
+        (columns.max(1) as u16, rows.max(1) as u16)
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        let i = ((y * self.width + x) * 4) as usize;
+        &self.pixels[i..i + 4]
+    }
+}
+
+/// An image on a paragraph of its own
+pub fn image_paragraph(node: &Node) -> Option<&mdast::Image> {
+    match node {
+        Node::Paragraph(paragraph) => match paragraph.children.as_slice() {
+            [Node::Image(image)] => Some(image),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Where an image is drawn on screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub image: usize,
+    pub area: Rect,
+}
+
+/// The deck's images, loaded once so slides render without touching the disk
+#[derive(Debug, Default)]
+pub struct Images {
+    protocol: Option<ImageProtocol>,
+    images: Vec<(String, Image)>,
+    /// Placements on screen, so frames that didn't move them don't send them again
+    drawn: Vec<Placement>,
+    screen: Rect,
+}
+
+impl Images {
+    /// Loads the PNG images on slides of their own, from files relative to `dir`,
+    /// `data:` URIs of packed decks or prefetched downloads
+    pub fn load(
+        slides: &[Vec<Node>],
+        dir: &Path,
+        assets: &AssetCache,
+        protocol: ImageProtocol,
+    ) -> Self {
+        let mut images: Vec<(String, Image)> = vec![];
+        for image in slides.iter().flatten().filter_map(image_paragraph) {
+            if images.iter().any(|(url, _)| *url == image.url) {
+                continue;
+            }
+            let bytes = match image.url.strip_prefix("data:image/png;base64,") {
+                Some(data) => STANDARD.decode(data).ok(),
+                None if image.url.contains("://") => assets.get(&image.url),
+                None => std::fs::read(dir.join(&image.url)).ok(),
+            };
+            if let Some(decoded) = bytes.and_then(Image::decode) {
+                images.push((image.url.clone(), decoded));
+            }
+        }
+        Images {
+            protocol: Some(protocol),
+            images,
+            ..Images::default()
+        }
+    }
+
+    pub fn find(&self, url: &str) -> Option<(usize, &Image)> {
+        self.images
+            .iter()
+            .enumerate()
+            .find(|(_, (image_url, _))| image_url == url)
+            .map(|(i, (_, image))| (i, image))
+    }
+
+    /// Placeholder rows for the image, followed by a blank line
+    pub fn lines(&self, index: usize, max_columns: u16) -> Vec<Line<'static>> {
+        let (columns, rows) = self.images[index].1.cells(max_columns);
+        let cell = char::from_u32(PLACEHOLDER + index as u32).unwrap_or(' ');
+        let row = cell.to_string().repeat(columns as usize);
+        let mut lines = vec![Line::raw(row); rows as usize];
+        lines.push(Line::raw(""));
+        lines
+    }
+
+    /// Finds the images laid out within `area` of a rendered frame and keeps ratatui
+    /// from drawing over them
+    ///
+    /// Images scrolled partly out of view or covered by a popup are blanked instead,
+    /// as they can't be cropped.
+    pub fn place(&self, buffer: &mut Buffer, area: Rect) -> Vec<Placement> {
+        let mut found: Vec<(usize, Rect, u32)> = vec![];
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let Some(index) = placeholder(buffer[(x, y)].symbol()) else {
+                    continue;
+                };
+                let cell = Rect::new(x, y, 1, 1);
+                match found.iter_mut().find(|(image, _, _)| *image == index) {
+                    Some((_, bounds, count)) => {
+                        *bounds = bounds.union(cell);
+                        *count += 1;
+                    }
+                    None => found.push((index, cell, 1)),
+                }
+            }
+        }
+
+        let mut placements = vec![];
+        for (index, bounds, count) in found {
+            let whole = self.images.get(index).is_some_and(|(_, image)| {
+                image.cells(bounds.width) == (bounds.width, bounds.height) && count == bounds.area()
+            });
+            for position in bounds.positions() {
+                let cell = &mut buffer[position];
+                if placeholder(cell.symbol()) == Some(index) {
+                    if whole {
+                        cell.skip = true;
+                    } else {
+                        cell.set_symbol(" ");
+                    }
+                }
+            }
+            if whole {
+                placements.push(Placement {
+                    image: index,
+                    area: bounds,
+                });
+            }
+        }
+        placements
+    }
+
+    /// Draws the images of the frame on screen, unless they're already there
+    pub fn draw<W: Write>(
+        &mut self,
+        backend: &mut CrosstermBackend<W>,
+        placements: Vec<Placement>,
+        screen: Rect,
+    ) -> io::Result<()> {
+        // Resizing redraws the whole screen, images included
+        if placements == self.drawn && screen == self.screen {
+            return Ok(());
+        }
+        let Some(protocol) = self.protocol else {
+            return Ok(());
+        };
+        if protocol == ImageProtocol::Kitty {
+            // Kitty keeps images apart from the text, so moved ones have to go
+            write!(backend, "\x1b_Ga=d,q=2\x1b\\")?;
+        }
+        for placement in &placements {
+            let image = &self.images[placement.image].1;
+            queue!(backend, MoveTo(placement.area.x, placement.area.y))?;
+            let escape = match protocol {
+                ImageProtocol::Kitty => kitty(image, placement.area),
+                ImageProtocol::Iterm2 => iterm2(image, placement.area),
+                ImageProtocol::Sixel => sixel(image, placement.area),
+            };
+            backend.write_all(escape.as_bytes())?;
+        }
+        self.drawn = placements;
+        self.screen = screen;
+        Backend::flush(backend)
+    }
+}
+
+fn placeholder(symbol: &str) -> Option<usize> {
+    let mut chars = symbol.chars();
+    let code = chars.next()? as u32;
+    (chars.next().is_none() && (PLACEHOLDER..PLACEHOLDER + 0xFFFE).contains(&code))
+        .then(|| (code - PLACEHOLDER) as usize)
+}
+
+/// Transmits the PNG in chunks, scaled by the terminal to fill `area`
+fn kitty(image: &Image, area: Rect) -> String {
+    let data = STANDARD.encode(&image.png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    let mut escape = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = String::from_utf8_lossy(chunk);
+        if i == 0 {
+            let _ = write!(
+                escape,
+                "\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\",
+                area.width, area.height, more, chunk
+            );
+        } else {
+            let _ = write!(escape, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+    escape
+}
+
+fn iterm2(image: &Image, area: Rect) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        image.png.len(),
+        area.width,
+        area.height,
+        STANDARD.encode(&image.png)
+    )
+}
+
+/// Scales the image to the pixels of `area` and encodes it in the 6×6×6 color cube
+fn sixel(image: &Image, area: Rect) -> String {
+    let (cell_width, cell_height) = match terminal::window_size() {
+        Ok(size) if size.width > 0 && size.columns > 0 => (
+            size.width as u32 / size.columns as u32,
+            size.height as u32 / size.rows.max(1) as u32,
+        ),
+        _ => (CELL_WIDTH, CELL_HEIGHT),
+    };
+    let (box_width, box_height) = (
+        area.width as u32 * cell_width,
+        area.height as u32 * cell_height,
+    );
+    let scale = f64::min(
+        box_width as f64 / image.width as f64,
+        box_height as f64 / image.height as f64,
+    );
+    let width = ((image.width as f64 * scale) as u32).max(1);
+    let height = ((image.height as f64 * scale) as u32).max(1);
+    encode_sixel(image, width, height)
+}
+
+fn encode_sixel(image: &Image, width: u32, height: u32) -> String {
+    let color = |x: u32, y: u32| -> Option<usize> {
+        let pixel = image.pixel(x * image.width / width, y * image.height / height);
+        let level = |value: u8| (value as usize * 5 + 127) / 255;
+        (pixel[3] >= 128).then(|| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+    };
+
+    // Transparent background, so unset pixels keep the slide's
+    let mut escape = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for i in 0..216 {
+        let _ = write!(
+            escape,
+            "#{};2;{};{};{}",
+            i,
+            i / 36 * 20,
+            i / 6 % 6 * 20,
+            i % 6 * 20
+        );
+    }
+    for top in (0..height).step_by(6) {
+        // Sixels of every color used in this band of six rows
+        let mut band: Vec<(usize, Vec<u8>)> = vec![];
+        for x in 0..width {
+            for bit in 0..6.min(height - top) {
+                let Some(color) = color(x, top + bit) else {
+                    continue;
+                };
+                let sixels = match band.iter().position(|(c, _)| *c == color) {
+                    Some(i) => &mut band[i].1,
+                    None => {
+                        band.push((color, vec![0; width as usize]));
+                        &mut band.last_mut().unwrap().1
+                    }
+                };
+                sixels[x as usize] |= 1 << bit;
+            }
+        }
+        for (color, sixels) in band {
+            let _ = write!(escape, "#{}", color);
+            for run in sixels.chunk_by(|a, b| a == b) {
+                let symbol = (63 + run[0]) as char;
+                if run.len() > 3 {
+                    let _ = write!(escape, "!{}{}", run.len(), symbol);
+                } else {
+                    escape.extend(std::iter::repeat_n(symbol, run.len()));
+                }
+            }
+            escape.push('$');
+        }
+        escape.push('-');
+    }
+    escape.push_str("\x1b\\");
+    escape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    fn png(width: u32, height: u32, rgba: [u8; 4]) -> Vec<u8> {
+        let mut png = vec![];
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&rgba.repeat((width * height) as usize))
+            .unwrap();
+        writer.finish().unwrap();
+        png
+    }
+
+    #[test]
+    fn test_cells_keep_aspect_ratio() {
+        let image = Image::decode(png(200, 100, [0; 4])).unwrap();
+        assert_eq!(image.cells(80), (20, 5));
+        assert_eq!(image.cells(10), (10, 3));
+
+        let tall = Image::decode(png(10, 1000, [0; 4])).unwrap();
+        assert_eq!(tall.cells(80), (1, 20));
+    }
+
+    #[test]
+    fn test_load_files_and_data_uris() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.png"), png(20, 20, [255; 4])).unwrap();
+        std::fs::write(dir.path().join("b.jpg"), "not a png").unwrap();
+        let data = STANDARD.encode(png(4, 4, [0; 4]));
+        let markdown = format!(
+            "![A](a.png)\n\n![B](b.jpg)\n\n![C](https://x.example/c.png)\n\n![D](data:image/png;base64,{})\n\nInline ![E](a.png) image\n",
+            data
+        );
+        let deck = parse_deck(&markdown).unwrap();
+
+        let images = Images::load(
+            &deck.slides,
+            dir.path(),
+            &AssetCache::default(),
+            ImageProtocol::Kitty,
+        );
+        assert_eq!(images.images.len(), 2);
+        assert_eq!(images.find("a.png").unwrap().0, 0);
+        assert!(images.find("b.jpg").is_none());
+        assert!(images.find("data:image/png;base64,").is_none());
+    }
+
+    fn images_with(width: u32, height: u32) -> Images {
+        Images {
+            protocol: Some(ImageProtocol::Kitty),
+            images: vec![(
+                "a.png".to_string(),
+                Image::decode(png(width, height, [255; 4])).unwrap(),
+            )],
+            ..Images::default()
+        }
+    }
+
+    #[test]
+    fn test_placeholders_are_placed_and_skipped() {
+        let images = images_with(40, 40);
+        let area = Rect::new(0, 0, 10, 5);
+        let mut buffer = Buffer::empty(area);
+        for (y, line) in images.lines(0, 10).iter().enumerate() {
+            buffer.set_line(2, y as u16 + 1, line, 10);
+        }
+
+        let placements = images.place(&mut buffer, area);
+        assert_eq!(
+            placements,
+            vec![Placement {
+                image: 0,
+                area: Rect::new(2, 1, 4, 2)
+            }]
+        );
+        assert!(buffer[(2, 1)].skip && !buffer[(1, 1)].skip);
+    }
+
+    #[test]
+    fn test_partly_visible_images_are_blanked() {
+        let images = images_with(40, 40);
+        let area = Rect::new(0, 0, 10, 1);
+        let mut buffer = Buffer::empty(area);
+        buffer.set_line(0, 0, &images.lines(0, 10)[0], 10);
+
+        assert!(images.place(&mut buffer, area).is_empty());
+        assert_eq!(buffer[(0, 0)].symbol(), " ");
+        assert!(!buffer[(0, 0)].skip);
+    }
+
+    #[test]
+    fn test_unchanged_placements_are_not_sent_again() {
+        let mut images = images_with(4, 4);
+        let placements = vec![Placement {
+            image: 0,
+            area: Rect::new(0, 0, 1, 1),
+        }];
+        let screen = Rect::new(0, 0, 80, 24);
+        let mut output = vec![];
+        let mut backend = CrosstermBackend::new(&mut output);
+        images
+            .draw(&mut backend, placements.clone(), screen)
+            .unwrap();
+        let first = String::from_utf8_lossy(&output).to_string();
+        assert!(first.contains("\x1b_Ga=T,f=100"));
+
+        let mut output = vec![];
+        let mut backend = CrosstermBackend::new(&mut output);
+        images.draw(&mut backend, placements, screen).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_sixel_encoding() {
+        let image = Image::decode(png(2, 2, [255, 0, 0, 255])).unwrap();
+        let escape = encode_sixel(&image, 8, 2);
+        assert!(escape.starts_with("\x1bP0;1;0q\"1;1;8;2"));
+        // Red is color 180 of the cube; both rows set (bits 0 and 1) across 8 columns
+        assert!(escape.contains("#180!8B$-"));
+        assert!(escape.ends_with("\x1b\\"));
+    }
+}
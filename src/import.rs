@@ -0,0 +1,849 @@
+//! Best-effort import of reveal.js HTML decks into markdeck markdown
+//!
+//! Every innermost `<section>` becomes a slide and its `<aside class="notes">` the
+//! speaker notes. Common tags map to their markdown counterparts and anything else is
+//! unwrapped to its text; sections written in markdown (`data-markdown`) are kept as is.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+static TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<!--.*?-->|<!.*?>|<(/?)([a-zA-Z][a-zA-Z0-9-]*)((?:[^>"']|"[^"]*"|'[^']*')*)>"#,
+    )
+    .unwrap()
+});
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([^\s"'=/>]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap()
+});
+static HEADING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(#{1,6})\s").unwrap());
+
+/// Elements without content or a closing tag
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+/// Elements whose content is text rather than markup
+const RAW: &[&str] = &["script", "style", "textarea"];
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "script",
+    "section",
+    "style",
+    "table",
+    "template",
+    "textarea",
+    "ul",
+];
+
+#[derive(Debug)]
+enum Html {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Html>,
+}
+
+impl Element {
+    fn new(name: &str, attributes: Vec<(String, String)>) -> Self {
+        Element {
+            name: name.to_string(),
+            attributes,
+            children: vec![],
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Html::Element(element) => Some(element),
+            Html::Text(_) => None,
+        })
+    }
+
+    /// First element named `name` among the descendants, depth first
+    fn find(&self, name: &str) -> Option<&Element> {
+        self.elements().find_map(|element| {
+            (element.name == name)
+                .then_some(element)
+                .or_else(|| element.find(name))
+        })
+    }
+
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                Html::Text(t) => text.push_str(t),
+                Html::Element(element) if element.name == "br" => text.push('\n'),
+                Html::Element(element) => text.push_str(&element.text()),
+            }
+        }
+        text
+    }
+}
+
+/// An imported deck: markdown plus what made it in
+#[derive(Debug)]
+pub struct Imported {
+    pub markdown: String,
+    pub slides: usize,
+    /// Sections loading their markdown from another file, which are left out
+    pub external: usize,
+}
+
+pub fn import_file(path: &Path) -> Result<Imported> {
+    let html = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read HTML deck: {}", path.display()))?;
+    import_html(&html)
+}
+
+/// Default output path: `talk.html` imports into `talk.md` next to it
+pub fn default_output(path: &Path) -> PathBuf {
+    path.with_extension("md")
+}
+
+pub fn import_html(html: &str) -> Result<Imported> {
+    let root = parse(html);
+    let mut sections = vec![];
+    leaf_sections(&root, &mut sections);
+    if sections.is_empty() {
+        bail!("No <section> slides found; is this a reveal.js deck?");
+    }
+
+    let mut slides = vec![];
+    let mut external = 0;
+    for section in sections {
+        match section.attribute("data-markdown") {
+            Some(src) if !src.is_empty() => external += 1,
+            Some(_) => {
+                let source = section
+                    .find("textarea")
+                    .map(Element::text)
+                    .unwrap_or_default();
+                for slide in markdown_slides(&source, section) {
+                    slides.push(titled(&slide, slides.len() + 1));
+                }
+            }
+            None => {
+                let mut converter = Converter::default();
+                converter.blocks(&section.children);
+                if !converter.heading {
+                    // Without a heading the slide would run into the previous one
+                    converter
+                        .output
+                        .insert(0, format!("## Slide {}", slides.len() + 1));
+                }
+                if let Some(notes) = section.attribute("data-notes") {
+                    converter.output.push(notes_directive(notes));
+                }
+                slides.push(converter.output.join("\n\n"));
+            }
+        }
+    }
+
+    let mut markdown = frontmatter(&root);
+    markdown.push_str(&slides.join("\n\n"));
+    markdown.push('\n');
+    Ok(Imported {
+        markdown,
+        slides: slides.len(),
+        external,
+    })
+}
+
+/// Title and author from `<title>` and `<meta name="author">`
+fn frontmatter(root: &Element) -> String {
+    let mut fields = vec![];
+    if let Some(title) = root.find("title").map(|title| collapse(&title.text()))
+        && !title.is_empty()
+    {
+        fields.push(format!("title = {}", toml::Value::String(title)));
+    }
+    let mut metas = vec![];
+    collect(root, "meta", &mut metas);
+    if let Some(author) = metas
+        .iter()
+        .find(|meta| meta.attribute("name") == Some("author"))
+        .and_then(|meta| meta.attribute("content"))
+    {
+        fields.push(format!(
+            "author = {}",
+            toml::Value::String(author.to_string())
+        ));
+    }
+    if fields.is_empty() {
+        return String::new();
+    }
+    format!("+++\n{}\n+++\n\n", fields.join("\n"))
+}
+
+fn collect<'a>(element: &'a Element, name: &str, found: &mut Vec<&'a Element>) {
+    for child in element.elements() {
+        if child.name == name {
+            found.push(child);
+        }
+        collect(child, name, found);
+    }
+}
+
+/// Sections without nested sections, so vertical stacks become consecutive slides
+fn leaf_sections<'a>(element: &'a Element, sections: &mut Vec<&'a Element>) {
+    for child in element.elements() {
+        if child.name == "section" && child.find("section").is_none() {
+            sections.push(child);
+        } else {
+            leaf_sections(child, sections);
+        }
+    }
+}
+
+/// Slides of a `data-markdown` section, split at its separators, with `Note:` lines
+/// turned into speaker notes
+fn markdown_slides(source: &str, section: &Element) -> Vec<String> {
+    let source = dedent(source);
+    let vertical = section.attribute("data-separator-vertical").is_some();
+    let mut slides = vec![vec![]];
+    for line in source.lines() {
+        let separator = line.trim_end();
+        if separator == "---" || (vertical && separator == "--") {
+            slides.push(vec![]);
+        } else if let Some(last) = slides.last_mut() {
+            last.push(line);
+        }
+    }
+    slides
+        .into_iter()
+        .map(|lines| {
+            let notes_at = lines.iter().position(|line| {
+                let lower = line.to_lowercase();
+                lower.starts_with("note:") || lower.starts_with("notes:")
+            });
+            let (content, notes) = lines.split_at(notes_at.unwrap_or(lines.len()));
+            let mut slide = content.join("\n").trim().to_string();
+            if let Some((first, rest)) = notes.split_first() {
+                let mut text = vec![first.split_once(':').map_or("", |(_, t)| t).trim()];
+                text.extend(rest.iter().copied());
+                slide.push_str("\n\n");
+                slide.push_str(&notes_directive(text.join("\n").trim()));
+            }
+            slide
+        })
+        .filter(|slide| !slide.is_empty())
+        .collect()
+}
+
+/// Makes sure the slide opens a new slide: its first heading becomes `##` when deeper,
+/// and a slide without one gets a numbered heading
+fn titled(slide: &str, number: usize) -> String {
+    let mut lines: Vec<String> = slide.lines().map(str::to_string).collect();
+    match lines.iter().position(|line| HEADING.is_match(line)) {
+        Some(i) => {
+            let depth = lines[i].chars().take_while(|&c| c == '#').count();
+            if depth > 2 {
+                lines[i] = format!("##{}", &lines[i][depth..]);
+            }
+            lines.join("\n")
+        }
+        None => format!("## Slide {}\n\n{}", number, slide),
+    }
+}
+
+fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn notes_directive(notes: &str) -> String {
+    format!("<!-- notes: {} -->", notes.replace("-->", "-- >"))
+}
+
+#[derive(Default)]
+struct Converter {
+    output: Vec<String>,
+    /// The slide opens with its title; later headings are demoted below it
+    heading: bool,
+}
+
+impl Converter {
+    fn blocks(&mut self, nodes: &[Html]) {
+        let mut inline = String::new();
+        for node in nodes {
+            match node {
+                Html::Element(element) if BLOCKS.contains(&element.name.as_str()) => {
+                    self.paragraph(&std::mem::take(&mut inline));
+                    self.block(element);
+                }
+                _ => inline.push_str(&inline_markdown(node)),
+            }
+        }
+        self.paragraph(&inline);
+    }
+
+    fn paragraph(&mut self, inline: &str) {
+        let text = tidy(inline);
+        if !text.is_empty() {
+            self.output.push(text);
+        }
+    }
+
+    fn block(&mut self, element: &Element) {
+        match element.name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let depth: usize = element.name[1..].parse().unwrap_or(1);
+                // Only a heading opening the slide may start one in markdeck
+                let depth = if self.heading || !self.output.is_empty() {
+                    depth.max(3)
+                } else {
+                    self.heading = true;
+                    depth.min(2)
+                };
+                let text = tidy(&inline_children(element)).replace('\n', " ");
+                self.output.push(format!("{} {}", "#".repeat(depth), text));
+            }
+            "p" => self.paragraph(&inline_children(element)),
+            "ul" | "ol" => {
+                let mut lines = vec![];
+                list(element, "", &mut lines);
+                self.output.push(lines.join("\n"));
+            }
+            "pre" => {
+                let code = element.find("code");
+                let lang = code
+                    .and_then(language)
+                    .or_else(|| language(element))
+                    .unwrap_or_default();
+                let text = element.text();
+                let text = text.trim_matches('\n');
+                let mut fence = "```".to_string();
+                while text.contains(&fence) {
+                    fence.push('`');
+                }
+                self.output
+                    .push(format!("{}{}\n{}\n{}", fence, lang, text, fence));
+            }
+            "blockquote" => {
+                let mut quote = Converter {
+                    heading: true,
+                    ..Converter::default()
+                };
+                quote.blocks(&element.children);
+                let text = quote.output.join("\n\n");
+                let quoted: Vec<String> = text
+                    .lines()
+                    .map(|line| format!("> {}", line).trim_end().to_string())
+                    .collect();
+                self.output.push(quoted.join("\n"));
+            }
+            "aside" if element.has_class("notes") => {
+                let mut notes = Converter {
+                    heading: true,
+                    ..Converter::default()
+                };
+                notes.blocks(&element.children);
+                self.output
+                    .push(notes_directive(&notes.output.join("\n\n")));
+            }
+            "table" => self.table(element),
+            "hr" | "script" | "style" | "template" | "textarea" => {}
+            _ => self.blocks(&element.children),
+        }
+    }
+
+    fn table(&mut self, element: &Element) {
+        let mut rows = vec![];
+        collect(element, "tr", &mut rows);
+        let rows: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                row.elements()
+                    .filter(|cell| cell.name == "td" || cell.name == "th")
+                    .map(|cell| {
+                        tidy(&inline_children(cell))
+                            .replace('\n', " ")
+                            .replace('|', "\\|")
+                    })
+                    .collect()
+            })
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let line = |cells: &[String]| {
+            let mut cells = cells.to_vec();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut lines = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+        self.output.push(lines.join("\n"));
+    }
+}
+
+/// A list's items, with nested lists indented under the item they belong to
+fn list(element: &Element, indent: &str, lines: &mut Vec<String>) {
+    let start: usize = element
+        .attribute("start")
+        .and_then(|start| start.parse().ok())
+        .unwrap_or(1);
+    let items = element.elements().filter(|item| item.name == "li");
+    for (number, item) in (start..).zip(items) {
+        let marker = match element.name.as_str() {
+            "ol" => format!("{}. ", number),
+            _ => "- ".to_string(),
+        };
+        let mut text = String::new();
+        let mut nested = vec![];
+        for child in &item.children {
+            match child {
+                Html::Element(list) if list.name == "ul" || list.name == "ol" => nested.push(list),
+                Html::Element(paragraph) if BLOCKS.contains(&paragraph.name.as_str()) => {
+                    text.push(' ');
+                    text.push_str(&inline_children(paragraph));
+                }
+                _ => text.push_str(&inline_markdown(child)),
+            }
+        }
+        let continuation = format!("{}{}", indent, " ".repeat(marker.len()));
+        let text = tidy(&text).replace('\n', &format!("\n{}", continuation));
+        lines.push(format!("{}{}{}", indent, marker, text));
+        for list_element in nested {
+            list(list_element, &continuation, lines);
+        }
+    }
+}
+
+/// Language of a code block from a `language-rust` or `lang-rust` class
+fn language(element: &Element) -> Option<String> {
+    element
+        .attribute("data-lang")
+        .map(str::to_string)
+        .or_else(|| {
+            element
+                .attribute("class")?
+                .split_whitespace()
+                .find_map(|class| {
+                    class
+                        .strip_prefix("language-")
+                        .or_else(|| class.strip_prefix("lang-"))
+                        .map(str::to_string)
+                })
+        })
+}
+
+fn inline_children(element: &Element) -> String {
+    element.children.iter().map(inline_markdown).collect()
+}
+
+fn inline_markdown(node: &Html) -> String {
+    let element = match node {
+        Html::Text(text) => return escape(&collapse_keeping_edges(text)),
+        Html::Element(element) => element,
+    };
+    let inner = || inline_children(element);
+    let wrap = |marker: &str| {
+        let inner = inner();
+        match inner.trim() {
+            "" => inner,
+            text => format!("{}{}{}", marker, text, marker),
+        }
+    };
+    match element.name.as_str() {
+        "strong" | "b" => wrap("**"),
+        "em" | "i" => wrap("*"),
+        "code" => {
+            let code = element.text();
+            let ticks = if code.contains('`') { "`` " } else { "`" };
+            format!(
+                "{}{}{}",
+                ticks,
+                code,
+                ticks.chars().rev().collect::<String>()
+            )
+        }
+        "a" => match element.attribute("href") {
+            Some(href) => format!("[{}]({})", tidy(&inner()), href),
+            None => inner(),
+        },
+        "img" => format!(
+            "![{}]({})",
+            element.attribute("alt").unwrap_or(""),
+            element.attribute("src").unwrap_or("")
+        ),
+        "br" => "\\\n".to_string(),
+        "script" | "style" | "template" | "textarea" => String::new(),
+        _ => inner(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '`' | '<' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Collapses whitespace runs to single spaces, keeping a leading or trailing one so
+/// words on either side of a tag stay apart
+fn collapse_keeping_edges(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+        } else {
+            if space {
+                collapsed.push(' ');
+            }
+            space = false;
+            collapsed.push(c);
+        }
+    }
+    if space {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Trims every line of inline markdown and drops the spaces doubled up at tag edges
+fn tidy(inline: &str) -> String {
+    inline
+        .lines()
+        .map(|line| {
+            let mut line = line.trim().to_string();
+            while line.contains("  ") {
+                line = line.replace("  ", " ");
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn parse(html: &str) -> Element {
+    let mut stack = vec![Element::new("#root", vec![])];
+    let mut pos = 0;
+    while let Some(tag) = TAG.captures_at(html, pos) {
+        let whole = tag.get(0).unwrap();
+        push_text(&mut stack, &html[pos..whole.start()]);
+        pos = whole.end();
+        let Some(name) = tag.get(2) else {
+            // Comments and doctypes
+            continue;
+        };
+        let name = name.as_str().to_lowercase();
+
+        if !tag[1].is_empty() {
+            if let Some(open) = stack.iter().skip(1).rposition(|e| e.name == name) {
+                while stack.len() > open + 1 {
+                    close(&mut stack);
+                }
+            }
+            continue;
+        }
+
+        // Paragraphs and list items end where the next one starts
+        if matches!(name.as_str(), "p" | "li") && stack.last().is_some_and(|top| top.name == name) {
+            close(&mut stack);
+        }
+        let attributes = ATTRIBUTE
+            .captures_iter(&tag[3])
+            .map(|attribute| {
+                let value = (2..=4)
+                    .find_map(|i| attribute.get(i))
+                    .map_or(String::new(), |value| decode(value.as_str()));
+                (attribute[1].to_lowercase(), value)
+            })
+            .collect();
+        let mut element = Element::new(&name, attributes);
+
+        if RAW.contains(&name.as_str()) {
+            let end = find_ignore_ascii_case(&html[pos..], &format!("</{}", name))
+                .map_or(html.len(), |end| pos + end);
+            let text = &html[pos..end];
+            let text = if name == "textarea" {
+                decode(text)
+            } else {
+                text.to_string()
+            };
+            element.children.push(Html::Text(text));
+            push_element(&mut stack, element);
+            pos = html[end..]
+                .find('>')
+                .map_or(html.len(), |close| end + close + 1);
+        } else if VOID.contains(&name.as_str()) || tag[3].trim_end().ends_with('/') {
+            push_element(&mut stack, element);
+        } else {
+            stack.push(element);
+        }
+    }
+    push_text(&mut stack, &html[pos..]);
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    stack.pop().unwrap()
+}
+
+/// Byte offset of `needle` in `haystack`, ignoring ASCII case; unlike lowercasing
+/// first, this keeps offsets right when other characters change length
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn push_text(stack: &mut [Element], text: &str) {
+    if !text.is_empty()
+        && let Some(top) = stack.last_mut()
+    {
+        top.children.push(Html::Text(decode(text)));
+    }
+}
+
+fn push_element(stack: &mut [Element], element: Element) {
+    if let Some(top) = stack.last_mut() {
+        top.children.push(Html::Element(element));
+    }
+}
+
+fn close(stack: &mut Vec<Element>) {
+    if let Some(element) = stack.pop() {
+        push_element(stack, element);
+    }
+}
+
+/// Decodes the character references HTML decks commonly use
+fn decode(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = reference.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (character, reference) {
+            (Some(character), Some(reference)) => {
+                decoded.push(character);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+    use crate::directives::speaker_notes;
+
+    const REVEAL: &str = r#"<!doctype html>
+<html>
+<head>
+  <title>Ownership &amp; Borrowing</title>
+  <meta name="author" content="Jane Doe">
+</head>
+<body>
+<div class="reveal"><div class="slides">
+  <section>
+    <h1>Ownership</h1>
+    <p>Every value has <em>one</em> owner.<br>Even <code>Box&lt;T&gt;</code>.</p>
+    <aside class="notes">Start with the story.</aside>
+  </section>
+  <section>
+    <section>
+      <h2>Moves</h2>
+      <ul>
+        <li>Assignment moves
+          <ul><li>unless <a href="https://doc.rust-lang.org/std/marker/trait.Copy.html">Copy</a></li></ul>
+        </li>
+        <li>So do calls
+      </ul>
+      <pre><code class="language-rust">let a = vec![1];
+let b = a;</code></pre>
+    </section>
+    <section>
+      <p>A slide without a title</p>
+    </section>
+  </section>
+  <section data-markdown>
+    <textarea data-template>
+      ### Borrowing
+      Shared or unique.
+      ---
+      ## Lifetimes
+      Note: Skip if short on time.
+    </textarea>
+  </section>
+  <section data-markdown="extra.md"></section>
+</div></div>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_sections_become_slides() {
+        let imported = import_html(REVEAL).unwrap();
+        assert_eq!(imported.slides, 5);
+        assert_eq!(imported.external, 1);
+        assert_eq!(
+            imported.markdown,
+            r#"+++
+title = "Ownership & Borrowing"
+author = "Jane Doe"
++++
+
+# Ownership
+
+Every value has *one* owner.\
+Even `Box<T>`.
+
+<!-- notes: Start with the story. -->
+
+## Moves
+
+- Assignment moves
+  - unless [Copy](https://doc.rust-lang.org/std/marker/trait.Copy.html)
+- So do calls
+
+```rust
+let a = vec![1];
+let b = a;
+```
+
+## Slide 3
+
+A slide without a title
+
+## Borrowing
+Shared or unique.
+
+## Lifetimes
+
+<!-- notes: Skip if short on time. -->
+"#
+        );
+    }
+
+    #[test]
+    fn test_imported_deck_parses_into_the_same_slides() {
+        let deck = parse_deck(&import_html(REVEAL).unwrap().markdown).unwrap();
+        assert_eq!(deck.slides.len(), 5);
+        assert_eq!(deck.meta.title.as_deref(), Some("Ownership & Borrowing"));
+        assert_eq!(
+            speaker_notes(&deck.slides[0]),
+            vec!["Start with the story."]
+        );
+        assert_eq!(
+            speaker_notes(&deck.slides[4]),
+            vec!["Skip if short on time."]
+        );
+    }
+
+    #[test]
+    fn test_not_a_reveal_deck() {
+        assert!(import_html("<html><body><p>Hi</p></body></html>").is_err());
+    }
+
+    #[test]
+    fn test_raw_text_ends_at_its_closing_tag() {
+        let root = parse("<textarea>İstanbul</TEXTAREA><p>After</p>");
+        assert_eq!(root.find("textarea").unwrap().text(), "İstanbul");
+        assert_eq!(root.find("p").unwrap().text(), "After");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode("a &lt;b&gt; &#39;c&#x27; &copy; & d"),
+            "a <b> 'c' &copy; & d"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+/// A place in the deck: slide index and vertical scroll offset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JumpPosition {
+    pub slide: usize,
+    pub offset: u16,
+}
+
+/// Positions left by jumps, walked with `jump_back`/`jump_forward` like Vim's Ctrl-o/Tab
+#[derive(Debug, Default)]
+pub struct JumpList {
+    back: Vec<JumpPosition>,
+    forward: Vec<JumpPosition>,
+}
+
+const MAX_JUMPS: usize = 100;
+
+impl JumpList {
+    /// Records the position being left; a new jump discards the forward history
+    pub fn push(&mut self, position: JumpPosition) {
+        if self.back.last() != Some(&position) {
+            self.back.push(position);
+        }
+        if self.back.len() > MAX_JUMPS {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: JumpPosition) -> Option<JumpPosition> {
+        let target = self.back.pop()?;
+        self.forward.push(current);
+        Some(target)
+    }
+
+    pub fn forward(&mut self, current: JumpPosition) -> Option<JumpPosition> {
+        let target = self.forward.pop()?;
+        self.back.push(current);
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(slide: usize, offset: u16) -> JumpPosition {
+        JumpPosition { slide, offset }
+    }
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut jumps = JumpList::default();
+        jumps.push(at(0, 5));
+        jumps.push(at(3, 0));
+
+        assert_eq!(jumps.back(at(7, 2)), Some(at(3, 0)));
+        assert_eq!(jumps.back(at(3, 0)), Some(at(0, 5)));
+        assert_eq!(jumps.back(at(0, 5)), None);
+
+        assert_eq!(jumps.forward(at(0, 5)), Some(at(3, 0)));
+        assert_eq!(jumps.forward(at(3, 0)), Some(at(7, 2)));
+        assert_eq!(jumps.forward(at(7, 2)), None);
+    }
+
+    #[test]
+    fn test_new_jump_clears_forward_history() {
+        let mut jumps = JumpList::default();
+        jumps.push(at(1, 0));
+        jumps.back(at(2, 0));
+        jumps.push(at(1, 0));
+        assert_eq!(jumps.forward(at(4, 0)), None);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut jumps = JumpList::default();
+        for slide in 0..MAX_JUMPS + 10 {
+            jumps.push(at(slide, 0));
+        }
+        let mut count = 0;
+        let mut current = at(0, 0);
+        while let Some(position) = jumps.back(current) {
+            current = position;
+            count += 1;
+        }
+        assert_eq!(count, MAX_JUMPS);
+    }
+}
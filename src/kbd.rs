@@ -0,0 +1,138 @@
+//! Keyboard keys written as `<kbd>Ctrl</kbd>` or with the `[[Ctrl+C]]` shorthand,
+//! shown as key caps
+//!
+//! Keys become `kbd` elements in the tree, so their text reads as the key alone
+//! wherever the slide's text is used, e.g. by search.
+
+use markdown::mdast::{MdxJsxTextElement, Node, Text};
+
+const TAG: &str = "kbd";
+
+/// Turns the keys among `node`'s descendants into `kbd` elements
+pub fn mark_keys(node: &mut Node) {
+    let Some(children) = node.children_mut() else {
+        return;
+    };
+    let mut marked = Vec::with_capacity(children.len());
+    let mut open: Option<Vec<Node>> = None;
+    for mut child in std::mem::take(children) {
+        match (&child, &mut open) {
+            (Node::Html(html), None) if is_tag(&html.value, "<kbd>") => open = Some(vec![]),
+            (Node::Html(html), Some(_)) if is_tag(&html.value, "</kbd>") => {
+                marked.push(key(open.take().unwrap_or_default()));
+            }
+            (_, Some(inside)) => inside.push(child),
+            (Node::Text(text), None) if text.value.contains("[[") => {
+                marked.extend(shorthand(&text.value));
+            }
+            (_, None) => {
+                mark_keys(&mut child);
+                marked.push(child);
+            }
+        }
+    }
+    // An unclosed tag is left as written
+    if let Some(inside) = open {
+        marked.push(Node::Html(markdown::mdast::Html {
+            value: "<kbd>".to_string(),
+            position: None,
+        }));
+        marked.extend(inside);
+    }
+    *children = marked;
+}
+
+/// Text of a key cap
+pub fn key_cap(node: &Node) -> Option<String> {
+    match node {
+        Node::MdxJsxTextElement(element) if element.name.as_deref() == Some(TAG) => {
+            Some(node.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn is_tag(html: &str, tag: &str) -> bool {
+    html.trim().eq_ignore_ascii_case(tag)
+}
+
+fn key(children: Vec<Node>) -> Node {
+    Node::MdxJsxTextElement(MdxJsxTextElement {
+        children,
+        position: None,
+        name: Some(TAG.to_string()),
+        attributes: vec![],
+    })
+}
+
+fn text(value: &str) -> Node {
+    Node::Text(Text {
+        value: value.to_string(),
+        position: None,
+    })
+}
+
+/// Splits text at `[[Ctrl+C]]` into a key cap per key, joined by `+`
+fn shorthand(value: &str) -> Vec<Node> {
+    let mut nodes = vec![];
+    let mut rest = value;
+    while let Some(start) = rest.find("[[")
+        && let Some(len) = rest[start + 2..].find("]]")
+    {
+        let combo = &rest[start + 2..start + 2 + len];
+        if !rest[..start].is_empty() {
+            nodes.push(text(&rest[..start]));
+        }
+        // A lone `+` or a trailing one, as in `[[Ctrl++]]`, is a key itself
+        let keys: Vec<&str> = combo.split('+').collect();
+        if combo.is_empty() || keys.iter().any(|key| key.trim().is_empty()) {
+            nodes.push(key(vec![text(combo)]));
+        } else {
+            for (i, name) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    nodes.push(text("+"));
+                }
+                nodes.push(key(vec![text(name.trim())]));
+            }
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    if !rest.is_empty() {
+        nodes.push(text(rest));
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    fn keys(markdown: &str) -> Vec<String> {
+        let deck = parse_deck(markdown).unwrap();
+        let paragraph = deck.slides[0].last().unwrap();
+        paragraph
+            .children()
+            .unwrap()
+            .iter()
+            .map(|node| match key_cap(node) {
+                Some(key) => format!("[{}]", key),
+                None => node.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kbd_tags_and_shorthand_become_keys() {
+        assert_eq!(
+            keys("Press <kbd>Ctrl</kbd>+<kbd>C</kbd> to quit"),
+            ["Press ", "[Ctrl]", "+", "[C]", " to quit"]
+        );
+        assert_eq!(
+            keys("Press [[Ctrl + C]], then [[+]]"),
+            ["Press ", "[Ctrl]", "+", "[C]", ", then ", "[+]"]
+        );
+        assert_eq!(keys("Not [[closed"), ["Not [[closed"]);
+        assert_eq!(keys("`[[C]]` <kbd>x"), ["[[C]]", " ", "<kbd>", "x"]);
+    }
+}
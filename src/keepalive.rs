@@ -0,0 +1,134 @@
+use std::io::{self, Write};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Save and restore the cursor: output the terminal sees as activity but that changes nothing
+const KEEPALIVE_SEQUENCE: &[u8] = b"\x1b7\x1b8";
+
+/// Periodic terminal output while presenting so idle detection doesn't blank the screen
+#[derive(Debug)]
+pub struct KeepAlive {
+    interval: Option<Duration>,
+    last: Instant,
+}
+
+impl KeepAlive {
+    /// `None` disables the keep-alive
+    pub fn new(interval: Option<Duration>) -> Self {
+        KeepAlive {
+            interval,
+            last: Instant::now(),
+        }
+    }
+
+    /// Time left until the next keep-alive is due
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.interval
+            .map(|interval| interval.saturating_sub(now.saturating_duration_since(self.last)))
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.remaining(now) == Some(Duration::ZERO)
+    }
+
+    /// Any activity, such as a key press, restarts the interval
+    pub fn touch(&mut self, now: Instant) {
+        self.last = now;
+    }
+
+    pub fn ping(&mut self, out: &mut impl Write, now: Instant) -> io::Result<()> {
+        out.write_all(KEEPALIVE_SEQUENCE)?;
+        out.flush()?;
+        self.touch(now);
+        Ok(())
+    }
+}
+
+/// Holds off the system screensaver for as long as it's alive
+///
+/// Uses `caffeinate` on macOS and `systemd-inhibit` (which talks to logind over D-Bus)
+/// elsewhere. Inhibiting is best effort: without either tool only the keep-alive runs.
+#[derive(Debug)]
+pub struct Inhibitor {
+    child: Option<Child>,
+}
+
+impl Inhibitor {
+    pub fn start() -> Self {
+        let child = inhibit_command()
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok();
+        Inhibitor { child }
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn inhibit_command() -> Command {
+    let mut command = Command::new("caffeinate");
+    command.args(["-d", "-i"]);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn inhibit_command() -> Command {
+    let mut command = Command::new("systemd-inhibit");
+    command.args([
+        "--what=idle",
+        "--who=markdeck",
+        "--why=Presenting",
+        "sleep",
+        "infinity",
+    ]);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keepalive_is_due_after_interval() {
+        let start = Instant::now();
+        let mut keepalive = KeepAlive::new(Some(Duration::from_secs(60)));
+        keepalive.touch(start);
+
+        assert_eq!(
+            keepalive.remaining(start + Duration::from_secs(20)),
+            Some(Duration::from_secs(40))
+        );
+        assert!(!keepalive.is_due(start + Duration::from_secs(59)));
+        assert!(keepalive.is_due(start + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_ping_writes_sequence_and_restarts_interval() {
+        let start = Instant::now();
+        let mut keepalive = KeepAlive::new(Some(Duration::from_secs(60)));
+        let mut out = Vec::new();
+        let later = start + Duration::from_secs(61);
+        keepalive.ping(&mut out, later).unwrap();
+
+        assert_eq!(out, KEEPALIVE_SEQUENCE);
+        assert!(!keepalive.is_due(later));
+    }
+
+    #[test]
+    fn test_disabled_keepalive_is_never_due() {
+        let keepalive = KeepAlive::new(None);
+        let later = Instant::now() + Duration::from_secs(3600);
+        assert_eq!(keepalive.remaining(later), None);
+        assert!(!keepalive.is_due(later));
+    }
+}
@@ -0,0 +1,129 @@
+//! `markdeck keys`: the resolved keymap as a cheat sheet, to print or to standardize
+//! bindings across shared podium machines
+
+use clap::ValueEnum;
+use markdown::mdast::AlignKind;
+use ratatui::style::Style;
+use ratatui::text::Line;
+
+use crate::config::Config;
+use crate::modes::Mode;
+use crate::table;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum KeysFormat {
+    /// A markdown table, e.g. for a team wiki
+    Md,
+    /// Aligned plain text columns
+    Table,
+}
+
+const HEADER: [&str; 3] = ["Keys", "Command", "Description"];
+
+const MODE_HEADER: [&str; 3] = ["Keys", "Action", "Description"];
+
+/// Every command with its keys, description and `[keymaps]` name, followed by the
+/// actions of each mode with its own `[keymaps.<mode>]` table
+pub fn cheat_sheet(config: &Config, format: KeysFormat) -> String {
+    let rows: Vec<[String; 3]> = config
+        .key_table()
+        .into_iter()
+        .map(|(keys, command)| {
+            [
+                keys,
+                command.name().to_string(),
+                command.description().to_string(),
+            ]
+        })
+        .collect();
+    let mut sheet = render_table(HEADER, rows, format);
+    for mode in Mode::ALL {
+        let rows = mode
+            .actions()
+            .iter()
+            .map(|(action, _)| {
+                [
+                    config.mode_bindings(mode, *action).join(", "),
+                    action.name().to_string(),
+                    action.description(mode).to_string(),
+                ]
+            })
+            .collect();
+        let heading = format!("[keymaps.{}]", mode.name());
+        match format {
+            KeysFormat::Md => sheet.push_str(&format!("\n## `{}`\n\n", heading)),
+            KeysFormat::Table => sheet.push_str(&format!("\n{}\n", heading)),
+        }
+        sheet.push_str(&render_table(MODE_HEADER, rows, format));
+    }
+    sheet
+}
+
+fn render_table(header: [&str; 3], rows: Vec<[String; 3]>, format: KeysFormat) -> String {
+    match format {
+        KeysFormat::Md => {
+            let escape = |cell: &str| cell.replace('|', "\\|");
+            let mut markdown = format!("| {} |\n|---|---|---|\n", header.join(" | "));
+            for row in rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| match i {
+                        // Keys and names read as code, so `?` or `C-o` stand out
+                        0 | 1 if !cell.is_empty() => format!("`{}`", escape(cell)),
+                        _ => escape(cell),
+                    })
+                    .collect();
+                markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            markdown
+        }
+        KeysFormat::Table => {
+            let lines = table::layout(
+                std::iter::once(header.map(String::from))
+                    .chain(rows)
+                    .map(|row| row.into_iter().map(Line::raw).collect())
+                    .collect(),
+                &[AlignKind::None; 3],
+                Style::default(),
+            );
+            lines
+                .iter()
+                .map(|line| line.to_string().trim_end().to_string() + "\n")
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cheat_sheet_formats() {
+        let config = Config::default();
+        let markdown = cheat_sheet(&config, KeysFormat::Md);
+        assert!(markdown.starts_with("| Keys | Command | Description |\n|---|---|---|\n"));
+        assert!(markdown.contains(
+            "| `j, Down, WheelDown` | `scroll_down` | Scroll down, or move the cursor down |"
+        ));
+
+        let table = cheat_sheet(&config, KeysFormat::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("Keys               │ Command"));
+        assert!(lines[2].starts_with("j, Down, WheelDown │ scroll_down"));
+    }
+
+    #[test]
+    fn test_cheat_sheet_lists_each_mode() {
+        let config = Config::default();
+        let markdown = cheat_sheet(&config, KeysFormat::Md);
+        assert!(markdown.contains(
+            "## `[keymaps.table]`\n\n| Keys | Action | Description |\n|---|---|---|\n| `h, Left` | `previous_column` |"
+        ));
+        assert!(markdown.contains("| `j, Down` | `scroll_down` | Scroll down |"));
+
+        let table = cheat_sheet(&config, KeysFormat::Table);
+        assert!(table.contains("\n[keymaps.search]\nKeys      │ Action"));
+    }
+}
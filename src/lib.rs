@@ -0,0 +1,7 @@
+//! Types shared with tools that work with markdeck decks
+//!
+//! The markdeck binary renders decks in the terminal; this library holds the parts of
+//! it other programs can rely on, such as the [`model`] written by
+//! `markdeck export json`.
+
+pub mod model;
@@ -0,0 +1,63 @@
+//! `--low-bandwidth`: presenting over a slow SSH link, where every byte of redraw
+//! output adds latency
+//!
+//! Frames already go out as the cells that changed since the previous one. On top of
+//! that this mode drops what redraws on its own or costs many bytes per cell: the
+//! flash and reload highlights, syntax colors, 24-bit colors, images, OSC 8 links and
+//! box-drawing borders.
+
+use ratatui::symbols::border;
+
+use crate::capabilities::{ColorDepth, EnabledFeatures};
+use crate::dots;
+
+/// One byte per border cell, where box-drawing characters take three
+pub const BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Fill of the progress bar, for the same reason
+pub const GAUGE_FILLED: &str = "=";
+pub const GAUGE_UNFILLED: &str = "-";
+pub const DOTS: dots::Symbols = dots::Symbols {
+    passed: "*",
+    ahead: ".",
+};
+
+/// The detected features, minus the ones that send extra escape sequences
+pub fn features(enabled: EnabledFeatures) -> EnabledFeatures {
+    EnabledFeatures {
+        images: None,
+        hyperlinks: false,
+        colors: enabled.colors.min(ColorDepth::Ansi256),
+        ..enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::images::ImageProtocol;
+
+    #[test]
+    fn test_features_drop_images_links_and_truecolor() {
+        let enabled = EnabledFeatures {
+            images: Some(ImageProtocol::Kitty),
+            hyperlinks: true,
+            colors: ColorDepth::TrueColor,
+            italics: true,
+        };
+        let reduced = features(enabled);
+        assert_eq!(reduced.images, None);
+        assert!(!reduced.hyperlinks);
+        assert_eq!(reduced.colors, ColorDepth::Ansi256);
+        assert!(reduced.italics);
+    }
+}
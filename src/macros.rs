@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::commands::Command;
+
+/// Key that was pressed before a macro register letter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MacroPrefix {
+    /// `q`: record into the register that follows
+    Record,
+    /// `@`: replay the register that follows
+    Replay,
+}
+
+/// Command sequences recorded with `q<letter>` and replayed with `@<letter>`
+#[derive(Debug, Default)]
+pub struct Macros {
+    registers: HashMap<char, Vec<Command>>,
+    recording: Option<(char, Vec<Command>)>,
+    last_replayed: Option<char>,
+}
+
+impl Macros {
+    pub fn start_recording(&mut self, register: char) {
+        self.recording = Some((register, vec![]));
+    }
+
+    /// Stores the commands recorded so far into their register
+    pub fn stop_recording(&mut self) {
+        if let Some((register, commands)) = self.recording.take() {
+            self.registers.insert(register, commands);
+        }
+    }
+
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    pub fn record(&mut self, command: &Command) {
+        if let Some((_, commands)) = &mut self.recording {
+            commands.push(command.clone());
+        }
+    }
+
+    /// Commands stored in `register`, where `@` means the last replayed register
+    pub fn replay(&mut self, register: char) -> Vec<Command> {
+        let register = if register == '@' {
+            match self.last_replayed {
+                Some(last) => last,
+                None => return vec![],
+            }
+        } else {
+            register
+        };
+
+        self.last_replayed = Some(register);
+        self.registers.get(&register).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_commands_are_replayed() {
+        let mut macros = Macros::default();
+        macros.start_recording('a');
+        macros.record(&Command::NextSlide);
+        macros.record(&Command::ScrollDown);
+        macros.stop_recording();
+
+        let commands = macros.replay('a');
+        assert!(matches!(
+            commands.as_slice(),
+            [Command::NextSlide, Command::ScrollDown]
+        ));
+    }
+
+    #[test]
+    fn test_commands_are_ignored_when_not_recording() {
+        let mut macros = Macros::default();
+        macros.record(&Command::NextSlide);
+        assert!(macros.replay('a').is_empty());
+    }
+
+    #[test]
+    fn test_at_replays_last_register() {
+        let mut macros = Macros::default();
+        assert!(macros.replay('@').is_empty());
+
+        macros.start_recording('b');
+        macros.record(&Command::NextSlide);
+        macros.stop_recording();
+        macros.replay('b');
+
+        assert_eq!(macros.replay('@').len(), 1);
+    }
+
+    #[test]
+    fn test_recording_register() {
+        let mut macros = Macros::default();
+        assert_eq!(macros.recording_register(), None);
+        macros.start_recording('z');
+        assert_eq!(macros.recording_register(), Some('z'));
+        macros.stop_recording();
+        assert_eq!(macros.recording_register(), None);
+    }
+}
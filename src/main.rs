@@ -1,114 +1,2263 @@
+mod ansi;
 mod app;
+mod capabilities;
+mod cast;
+mod checklist;
+mod columns;
 mod commands;
 mod config;
+mod copy;
+mod cursor;
+mod decrypt;
+mod details;
+mod diagram;
+mod directives;
+mod dots;
+mod editor;
+mod events;
+mod export;
+mod fallback;
+mod feedback;
+mod fence;
+mod fit;
+mod fonts;
+mod frontmatter;
+mod goto;
+#[cfg(test)]
+mod harness;
+mod highlight;
+mod hyperlink;
+mod images;
+mod import;
+mod jumplist;
+mod kbd;
+mod keepalive;
+mod keys;
+mod low_bandwidth;
+mod macros;
+mod merge;
+mod meta;
+mod modes;
+mod mouse;
+mod numbering;
+mod overview;
+mod pack;
+mod pptx;
+mod prefetch;
+mod presenter;
+mod profile;
+mod quiz;
+mod references;
+mod rehearsal;
+mod reload_diff;
+mod render_cache;
+mod run;
+mod screenshot;
+mod search;
+mod share;
+mod slide_loop;
+mod storage;
+mod syntax;
+mod table;
+mod template;
+mod theme;
+mod timer;
+mod watch;
 
-use std::io::Stdout;
+use std::io::{IsTerminal, Stdout};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use anyhow::Result;
-use app::{App, load_slides, node_to_lines};
-use clap::Parser;
+use ansi::{parse_ansi, write_ansi};
+use anyhow::{Context, Result, bail};
+use app::{
+    App, condense_lines, fit_heading, load_deck, node_to_lines, parse_deck_with, section_title,
+    slide_at_line, slide_title, table_rows,
+};
+use capabilities::Capabilities;
+use checklist::{as_checklist, checklist_lines};
+use clap::{Parser, Subcommand};
+use commands::Command;
+use config::{FlashEffect, ProgressStyle, TimerStart};
+use decrypt::{Encryption, read_deck_source};
+use details::{Section, sections};
+use diagram::{clip_line, is_protected};
+use directives::{
+    Directive, centering, emphasized_terms, pause_count, revealed_part, speaker_notes,
+};
+use events::{AppEvent, EventBus};
+use export::ScriptFormat;
+use feedback::Feedback;
+use frontmatter::{Split, Version};
+use goto::GotoInput;
+use highlight::{highlight_terms, highlight_with};
+use images::{Images, image_paragraph};
+use keepalive::{Inhibitor, KeepAlive};
+use macros::MacroPrefix;
+use markdown::mdast::Node;
+use modes::{Action, Mode};
+use numbering::SlideNumbers;
+use overview::Overview;
+use prefetch::AssetCache;
+use presenter::is_presenter_block;
+use profile::{FrameTimer, Profiler};
+use quiz::Quiz;
 use ratatui::{
     Terminal,
-    crossterm::{
-        self,
-        event::{Event, KeyCode, KeyModifiers},
-    },
+    buffer::Buffer,
+    crossterm::event::{Event, KeyCode, KeyModifiers, MouseEventKind},
     layout::{Alignment, Constraint, Layout, Margin, Rect},
     prelude::CrosstermBackend,
-    style::{Color, Style},
-    text::Text,
-    widgets::{Paragraph, Wrap},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, LineGauge, Paragraph, Widget, Wrap},
 };
-use tui_scrollview::{ScrollView, ScrollbarVisibility};
+use rehearsal::{Pace, Rehearsal};
+use reload_diff::Changes;
+use render_cache::{RenderKey, blit};
+use search::Search;
+use share::Server;
+use storage::{SessionStore, Storage};
+use syntax::unknown_languages;
+use table::TableView;
+use theme::{Theme, ThemeChoice};
+use tui_scrollview::ScrollViewState;
+use watch::Watcher;
 
 #[derive(Parser)]
 #[command(name = "markdeck")]
 #[command(about = "A terminal-based markdown presentation viewer", long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Cli {
-    #[arg(help = "Path to the markdown file to present")]
-    file: String,
+    #[arg(required = true, help = "Path to the markdown file to present")]
+    file: Option<String>,
 
-    #[arg(short, long, help = "Path to config file (defaults to ~/.config/markdeck/config.toml)")]
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Path to config file (defaults to ~/.config/markdeck/config.toml)"
+    )]
     config: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "resume",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Start on slide N, counting from 1"
+    )]
+    start_slide: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Start on the slide where the deck was last closed, with its checklist progress"
+    )]
+    resume: bool,
+
+    #[arg(
+        long,
+        help = "Start in the speaker view, with notes and the next slide beside the current one"
+    )]
+    speaker_view: bool,
+
+    #[arg(
+        long,
+        value_name = "THEME",
+        help = "Present with a built-in theme (dark, light, solarized, gruvbox) or a theme file instead of the deck's own theme"
+    )]
+    theme: Option<String>,
+
+    #[arg(
+        long,
+        help = "Keep redraws small for presenting over a slow SSH connection: ASCII borders, no highlights, images or syntax colors"
+    )]
+    low_bandwidth: bool,
+
+    #[arg(
+        long,
+        help = "Present remote images from the cache only, failing before the first slide if one isn't cached"
+    )]
+    offline: bool,
+
+    #[arg(
+        long,
+        help = "Refuse to present a deck whose min_markdeck_version is newer than this markdeck"
+    )]
+    strict: bool,
+
+    #[arg(long, help = "Show per-frame render timings and allocation counts")]
+    profile: bool,
+
+    #[arg(
+        long,
+        help = "Time each slide against the planned timing and print a report on exit"
+    )]
+    rehearse: bool,
+
+    #[arg(
+        long,
+        help = "Run the deck's screenshot commands on load instead of waiting for R"
+    )]
+    capture: bool,
+
+    #[arg(
+        long,
+        help = "Print every slide to stdout instead of presenting; implied when stdout isn't a terminal"
+    )]
+    cat: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Reload the deck whenever the file changes, staying on the current slide"
+    )]
+    watch: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Let viewers follow along with `markdeck attach`, e.g. --serve 127.0.0.1:7070"
+    )]
+    serve: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Follow an editor's buffer and cursor as a live preview, e.g. --listen-editor 127.0.0.1:7071, or - for stdin"
+    )]
+    listen_editor: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Start a new deck from a template, e.g. `markdeck new rust-async`
+    New {
+        #[arg(help = "Name of the talk; written to <name>.md")]
+        name: String,
+
+        #[arg(long, value_enum, default_value = "conference", help = "Kind of talk")]
+        template: template::Template,
+    },
+    /// Bundle a deck and its local images into a single self-contained markdown file
+    Pack {
+        #[arg(help = "Path to the markdown file to pack")]
+        file: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Where to write the packed deck (defaults to <file>.packed.md)"
+        )]
+        output: Option<String>,
+    },
+    /// Convert a reveal.js HTML deck into markdeck markdown, speaker notes included
+    Import {
+        #[arg(help = "Path to the reveal.js HTML file")]
+        file: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Where to write the markdown (defaults to <file>.md, which must not exist)"
+        )]
+        output: Option<String>,
+    },
+    /// Combine decks drafted separately, matching their sections by title
+    Merge {
+        #[arg(required = true, num_args = 2.., help = "Decks to merge, in order")]
+        files: Vec<String>,
+
+        #[arg(
+            short,
+            long,
+            help = "Where to write the merged deck (defaults to stdout)"
+        )]
+        output: Option<String>,
+    },
+    /// Report problems in a deck, such as parts that fail to parse and are shown as errors
+    Check {
+        #[arg(help = "Path to the markdown file to check")]
+        file: String,
+        /// Also render every slide at these terminal widths, e.g. 80,100,120, and report
+        /// the slides that don't fit
+        #[arg(long, value_delimiter = ',')]
+        widths: Vec<u16>,
+        /// Terminal height the widths are checked at
+        #[arg(long, default_value_t = 24, requires = "widths")]
+        height: u16,
+    },
+    /// Print a deck's metadata (title, author, date, tags, sections) as TOML
+    Info {
+        #[arg(help = "Path to the markdown file to describe")]
+        file: String,
+    },
+    /// Export a deck to another format
+    Export {
+        #[command(subcommand)]
+        export: ExportCommand,
+    },
+    /// Work on theme files
+    Theme {
+        #[command(subcommand)]
+        theme: ThemeCommand,
+    },
+    /// Follow a presentation started with --serve from this terminal
+    Attach {
+        #[arg(help = "Address the presenter serves on, e.g. 127.0.0.1:7070")]
+        address: String,
+
+        #[arg(
+            long,
+            required = true,
+            help = "Only view; the presenter moves between slides"
+        )]
+        read_only: bool,
+    },
+    /// Print every command with its keys, the config's bindings merged with the defaults
+    Keys {
+        #[arg(long, value_enum, default_value = "table", help = "Output format")]
+        format: keys::KeysFormat,
+    },
+    /// Report what this terminal supports and which features are degraded
+    Doctor,
+    /// List recently presented decks
+    Recent,
+    /// Delete saved state (resume positions, recent files) and caches
+    Clear {
+        #[arg(long, help = "Only delete saved state")]
+        state: bool,
+
+        #[arg(long, help = "Only delete caches")]
+        cache: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeCommand {
+    /// Show a sample slide with every element in the theme, redrawn whenever it's saved
+    Preview {
+        #[arg(help = "Path to the theme's TOML file")]
+        file: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommand {
+    /// Slide titles and speaker notes in order, formatted for printing
+    Script {
+        #[arg(help = "Path to the markdown file to export")]
+        file: String,
+
+        #[arg(long, value_enum, default_value = "markdown", help = "Output format")]
+        format: ScriptFormat,
+
+        #[arg(short, long, help = "Where to write the script (defaults to stdout)")]
+        output: Option<String>,
+    },
+    /// The deck as versioned JSON for editors, web viewers and other tools
+    Json {
+        #[arg(help = "Path to the markdown file to export")]
+        file: String,
+
+        #[arg(short, long, help = "Where to write the JSON (defaults to stdout)")]
+        output: Option<String>,
+    },
+    /// A recording of a script navigating the deck, as an asciinema cast or a GIF
+    Cast {
+        #[arg(help = "Path to the markdown file to record")]
+        file: String,
+
+        #[arg(
+            long,
+            help = "TOML file with the keys to press and how long to show each step"
+        )]
+        script: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Where to write the recording (defaults to <file>.cast); a .gif is made with agg"
+        )]
+        output: Option<String>,
+    },
+    /// A PowerPoint deck with one slide per markdeck slide, for upload portals
+    Pptx {
+        #[arg(help = "Path to the markdown file to export")]
+        file: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Where to write the deck (defaults to <file>.pptx)"
+        )]
+        output: Option<String>,
+    },
 }
 
 pub fn render(app: &mut App, frame: &mut ratatui::Frame, config: &config::Config) {
-    let area = frame.area();
+    if app.blanked {
+        return;
+    }
+
+    let area = app
+        .canvas
+        .map_or(frame.area(), |canvas| canvas.area(frame.area()));
+    let base = Style::default()
+        .fg(app.theme.colors.text)
+        .bg(app.theme.colors.background);
+    frame.buffer_mut().set_style(area, base);
+    let flashing = config.flash.effect != FlashEffect::Off
+        && app.flash_remaining(config.flash.duration()).is_some();
 
     let vertical = Layout::vertical([
         Constraint::Length(1),
         Constraint::Min(1),
+        Constraint::Length(app.show_progress.into()),
         Constraint::Length(1),
     ]);
-    let [header_area, content_area, footer_area] = vertical.areas(area);
+    let [header_area, content_area, progress_area, footer_area] = vertical.areas(area);
 
-    let slide_indicator = format!("{}/{}", app.current_slide + 1, app.slides.len());
-    let header = Paragraph::new(slide_indicator)
-        .style(Style::default().fg(Color::DarkGray))
+    let mut slide_indicator = app.numbers.indicator(app.current_slide, app.slides.len());
+    if app.is_zoomed_out() {
+        slide_indicator = format!("zoomed out  {}", slide_indicator);
+    }
+    if let Some(slide_loop) = &app.slide_loop {
+        slide_indicator = format!("loop {}  {}", slide_loop.range(), slide_indicator);
+    }
+    if app.presenting {
+        slide_indicator = format!("locked  {}", slide_indicator);
+    }
+    let mut header = vec![];
+    if let Some(rehearsal) = &app.rehearsal {
+        let now = Instant::now();
+        let color = rehearsal
+            .pace(now)
+            .map_or(app.theme.colors.header, Pace::color);
+        header.push(Span::styled(
+            rehearsal.status(now),
+            Style::default().fg(color),
+        ));
+        header.push(Span::raw("  "));
+    }
+    if let Some(timer) = app.timer.status(Instant::now()) {
+        header.push(Span::raw(format!("{}  ", timer)));
+    }
+    if let Some(viewers) = app.viewers {
+        header.push(Span::raw(format!(
+            "{} {}  ",
+            viewers,
+            if viewers == 1 { "viewer" } else { "viewers" }
+        )));
+    }
+    header.push(Span::raw(slide_indicator));
+    let header = Paragraph::new(Line::from(header))
+        .style(Style::default().fg(app.theme.colors.header))
         .alignment(Alignment::Right);
     frame.render_widget(header, header_area);
 
-    let padded_area = content_area.inner(Margin {
-        horizontal: 2,
-        vertical: 1,
-    });
+    let status = if let Some(register) = app.macros.recording_register() {
+        Some(format!("recording @{}", register))
+    } else if let Some(message) = &app.message {
+        Some(message.clone())
+    } else if app.search_highlight {
+        app.search.as_ref().map(|search| search.status(&app.slides))
+    } else {
+        None
+    };
+    if let Some(status) = status {
+        let status = Paragraph::new(status).style(Style::default().fg(app.theme.colors.accent));
+        frame.render_widget(status, header_area);
+    } else if let Some(meta) = app.meta.status() {
+        let meta = Paragraph::new(meta).style(Style::default().fg(app.theme.colors.header));
+        frame.render_widget(meta, header_area);
+    }
+
+    // The speaker view puts notes and the next slide beside the current one
+    let (slide_area, speaker_area) = if app.speaker_view {
+        let [slide_area, speaker_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(content_area);
+        (slide_area, Some(speaker_area))
+    } else {
+        (content_area, None)
+    };
+    let padded_area = app.theme.layout.content_area(slide_area);
 
     app.viewport_height = padded_area.height;
 
-    if let Some(slide) = app.slides.get(app.current_slide) {
-        let mut all_lines = vec![];
-        for node in slide {
-            let mut node_lines = vec![];
-            node_to_lines(node, &mut node_lines, Style::default());
-            all_lines.extend(node_lines);
+    // Rows of the slide hidden below the bottom of the screen
+    let mut below_fold = 0;
+    if let Some(overview) = &mut app.overview {
+        overview.columns = (padded_area.width / overview::CARD_WIDTH).max(1).into();
+    }
+    if let Some(overview) = &app.overview {
+        render_overview(frame, app, overview, padded_area);
+    } else if let Some(slide) = app.slides.get(app.current_slide) {
+        let search = app
+            .search
+            .as_ref()
+            .filter(|_| app.search_highlight)
+            .map(|search| (search.query.clone(), search.options));
+        let key = RenderKey {
+            slide: app.current_slide,
+            width: padded_area.width,
+            search,
+            flash: flashing && config.flash.effect == FlashEffect::Heading,
+            condensed: app.is_zoomed_out(),
+            expanded: app.expanded_details.contains(&app.current_slide),
+            answer: app.quiz_answers.get(&app.current_slide).copied(),
+            ticked: app.checklists.get(&app.current_slide).copied().unwrap_or(0),
+            revealed: app.revealed(),
+            table: app.table_view(),
+        };
+
+        // Scrolling reuses the rendered slide and only copies the visible rows
+        let layout_started = Instant::now();
+        if !app.render_cache.is_valid(&key) {
+            let breadcrumb = app
+                .theme
+                .headings
+                .breadcrumb
+                .then(|| section_title(&app.slides, app.current_slide))
+                .flatten();
+            let (buffer, heading_rows) = render_slide(
+                slide,
+                &key,
+                breadcrumb.as_deref(),
+                app.search.as_ref(),
+                &app.theme,
+                &app.images,
+                app.feedback.as_ref(),
+            );
+            app.render_cache.store(key, buffer, heading_rows);
+            if let Some(diff) = &mut app.reload_diff {
+                diff.compare(app.current_slide, app.render_cache.buffer());
+            }
+        }
+        if let Some(profiler) = &mut app.profiler {
+            profiler.layout = layout_started.elapsed();
         }
 
-        let num_lines = all_lines.len() as u16;
-        let content_width = padded_area.width;
+        // The heading stays put while the rest of the slide scrolls beneath it
+        let pinned = if app.theme.layout.pin_heading {
+            app.render_cache.heading_rows().min(padded_area.height / 2)
+        } else {
+            0
+        };
+        app.pinned_rows = pinned;
+        app.viewport_height = padded_area.height - pinned;
+        app.content_height = app.render_cache.buffer().area.height - pinned;
+        // Centered slides that fit sit in the middle of the screen, others scroll as usual
+        let mut slide_area = padded_area;
+        if centering(slide, app.theme.layout.center).vertical()
+            && app.content_height < app.viewport_height
+        {
+            let top = (app.viewport_height - app.content_height) / 2;
+            slide_area.y += top;
+            slide_area.height -= top;
+        }
+        if let Some(forward) = app.search_pending.take() {
+            app.next_match_row(forward);
+        }
+        blit(
+            app.render_cache.buffer(),
+            pinned,
+            slide_area,
+            frame.buffer_mut(),
+            &mut app.scroll_view_state,
+        );
+        below_fold = app
+            .content_height
+            .saturating_sub(app.scroll_view_state.offset().y + app.viewport_height);
+        if let Some(row) = app.cursor_row().or(app.search_match_row())
+            && let Some(y) = row.checked_sub(app.scroll_view_state.offset().y)
+            && y < app.viewport_height
+        {
+            let line = Rect::new(slide_area.x, slide_area.y + pinned + y, slide_area.width, 1);
+            frame
+                .buffer_mut()
+                .set_style(line, Style::default().bg(app.theme.colors.cursor));
+        }
+        if let Some(diff) = &app.reload_diff
+            && diff.remaining(Instant::now()).is_some()
+            && let Some(changes) = diff.changes(app.current_slide)
+        {
+            render_reload_diff(frame, app, changes, slide_area, pinned);
+        }
+    }
 
-        let mut scroll_view = ScrollView::new((content_width, num_lines).into())
-            .horizontal_scrollbar_visibility(ScrollbarVisibility::Never);
+    if let Some(area) = speaker_area {
+        render_speaker_view(frame, app, area);
+    }
 
-        let text = Text::from(all_lines);
-        let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+    if flashing && config.flash.effect == FlashEffect::Border {
+        let border = bordered(app).border_style(Style::default().fg(app.theme.colors.accent));
+        frame.render_widget(border, content_area);
+    }
 
-        scroll_view.render_widget(paragraph, Rect::new(0, 0, content_width, num_lines));
-        frame.render_stateful_widget(scroll_view, padded_area, &mut app.scroll_view_state);
+    if app.show_progress {
+        let label = format!(
+            "slide {}  ",
+            app.numbers.indicator(app.current_slide, app.slides.len())
+        );
+        let dot_line = (config.progress.style == ProgressStyle::Dots)
+            .then(|| progress_dots(app, &label))
+            .filter(|line| line.width() <= progress_area.width as usize);
+        let mut progress = LineGauge::default()
+            .ratio(app.numbers.progress(app.current_slide))
+            .label(label)
+            .style(Style::default().fg(app.theme.colors.footer))
+            .filled_style(Style::default().fg(app.theme.colors.accent))
+            .unfilled_style(Style::default().fg(app.theme.colors.footer));
+        if app.low_bandwidth {
+            progress = progress
+                .filled_symbol(low_bandwidth::GAUGE_FILLED)
+                .unfilled_symbol(low_bandwidth::GAUGE_UNFILLED);
+        }
+        match dot_line {
+            Some(line) => frame.render_widget(line, progress_area),
+            None => frame.render_widget(progress, progress_area),
+        }
     }
 
-    let controls_text = config.format_help_text();
-    let footer = Paragraph::new(controls_text).style(Style::default().fg(Color::DarkGray));
+    let controls_text = if let Some(input) = &app.search_input {
+        let mut toggles = vec![];
+        for (enabled, name) in [
+            (input.options.regex, "regex"),
+            (input.options.case_sensitive, "case"),
+            (input.options.whole_word, "word"),
+        ] {
+            if enabled {
+                toggles.push(format!("[{}]", name));
+            }
+        }
+        let hint = config.mode_hint(
+            Mode::Search,
+            &[
+                Action::ToggleRegex,
+                Action::ToggleCase,
+                Action::ToggleWholeWord,
+            ],
+            "regex/case/word",
+        );
+        format!(
+            "/{}  {}  {}",
+            input.text,
+            toggles.join(" "),
+            hint.unwrap_or_default()
+        )
+    } else if let Some(input) = &app.goto_input {
+        if input.prompt {
+            format!(":{}  Enter: go to slide  Esc: cancel", input.digits)
+        } else {
+            format!("{}  G: go to slide", input.digits)
+        }
+    } else if app.overview.is_some() {
+        let hints = [
+            (&[Action::SelectPrevious, Action::SelectNext][..], "select"),
+            (&[Action::Confirm], "open"),
+            (&[Action::Cancel], "close"),
+        ];
+        mode_hints(config, Mode::Overview, "type to filter", &hints)
+    } else if let Some(view) = &app.table_view {
+        if view.filtering {
+            let hints = [
+                (&[Action::Confirm][..], "keep"),
+                (&[Action::Cancel], "clear"),
+            ];
+            mode_hints(
+                config,
+                Mode::Table,
+                &format!("filter: {}", view.filter),
+                &hints,
+            )
+        } else {
+            let hints = [
+                (&[Action::PreviousColumn, Action::NextColumn][..], "column"),
+                (&[Action::Sort], "sort"),
+                (&[Action::Filter], "filter"),
+                (&[Action::NextTable], "next table"),
+                (&[Action::Cancel], "done"),
+            ];
+            mode_hints(config, Mode::Table, "", &hints)
+        }
+    } else {
+        config.format_help_text()
+    };
+    let footer = Paragraph::new(controls_text).style(Style::default().fg(app.theme.colors.footer));
     frame.render_widget(footer, footer_area);
+    if below_fold > 0 {
+        let indicator = Paragraph::new(format!(" ▼ +{}", below_fold))
+            .alignment(Alignment::Right)
+            .style(Style::default().fg(app.theme.colors.accent));
+        frame.render_widget(indicator, footer_area);
+    }
+
+    if let Some(output) = &app.command_output {
+        let popup = area.inner(Margin {
+            horizontal: area.width / 10,
+            vertical: area.height / 5,
+        });
+        let block = bordered(app)
+            .title(" Output ")
+            .border_style(Style::default().fg(app.theme.colors.footer));
+        let text = Text::from(parse_ansi(output, Style::default()));
+        frame.render_widget(Clear, popup);
+        frame.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+            popup,
+        );
+    }
+
+    if app.cheat_sheet {
+        render_cheat_sheet(frame, app, config, area);
+    }
+
+    if let Some(profiler) = &app.profiler {
+        render_profile(frame, profiler, content_area);
+    }
+
+    app.features
+        .degrade(frame.buffer_mut(), app.theme.colors.background);
+    app.hyperlinks = if app.features.hyperlinks && app.overview.is_none() {
+        hyperlink::find_links(
+            frame.buffer_mut(),
+            padded_area,
+            &hyperlink::slide_links(&app.slides[app.current_slide]),
+            app.features.color(app.theme.colors.link),
+        )
+    } else {
+        vec![]
+    };
+    app.image_placements = if app.overview.is_none() {
+        app.images.place(frame.buffer_mut(), padded_area)
+    } else {
+        vec![]
+    };
 }
 
-pub fn handle_key(app: &mut App, key_code: KeyCode, modifiers: KeyModifiers, config: &config::Config) {
+/// Renders the revealed part of a slide at full height into a buffer as wide as the
+/// viewport, along with the number of rows through the heading it opens with
+fn render_slide(
+    slide: &[Node],
+    key: &RenderKey,
+    breadcrumb: Option<&str>,
+    search: Option<&Search>,
+    theme: &Theme,
+    images: &Images,
+    feedback: Option<&Feedback>,
+) -> (Buffer, u16) {
+    let accent = Style::default()
+        .fg(theme.colors.accent)
+        .add_modifier(Modifier::BOLD);
+
+    // Columns are laid out for the whole slide, so revealing one doesn't narrow the others
+    let column_count = columns::count(slide);
+    let column_width = columns::width(key.width, column_count);
+    let (lead_nodes, column_nodes) = columns::split(revealed_part(slide, key.revealed));
+    let parts = std::iter::once(lead_nodes).chain(column_nodes);
+
+    let mut all_lines = vec![];
+    let mut column_lines: Vec<Vec<Line>> = vec![];
+    if let Some(section) = breadcrumb {
+        // Eyebrow naming the H1 section this H2 slide belongs to
+        all_lines.push(Line::styled(
+            format!("{} ›", section),
+            Style::default()
+                .fg(theme.colors.header)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+    let quiz = Quiz::find(slide);
+    // Steps of the slide's checklists rendered so far
+    let mut checklist_items = 0;
+    let mut title_line = None;
+    // Rows through the heading the slide opens with, which can be pinned
+    let mut heading_rows = 0;
+    let lead = all_lines.len();
+    let mut tables = 0;
+    for (part, section) in parts
+        .enumerate()
+        .flat_map(|(part, nodes)| sections(nodes).into_iter().map(move |s| (part, s)))
+    {
+        let width = if part == 0 { key.width } else { column_width };
+        if part > column_lines.len() {
+            column_lines.push(vec![]);
+        }
+        let mut node_lines = vec![];
+        let mut opening_heading = false;
+        let table_index = tables;
+        if let Section::Node(Node::Table(_)) = section {
+            tables += 1;
+        }
+        match section {
+            Section::Node(node) if quiz.as_ref().is_some_and(|q| std::ptr::eq(node, q.options)) => {
+                if let Some(quiz) = &quiz {
+                    node_lines = quiz.option_lines(key.answer, theme);
+                }
+            }
+            // The explanation stays hidden until the question is answered
+            Section::Node(node)
+                if key.answer.is_none()
+                    && quiz
+                        .as_ref()
+                        .and_then(|q| q.explanation)
+                        .is_some_and(|explanation| std::ptr::eq(node, explanation)) => {}
+            // Notes to self are only shown in the speaker view
+            Section::Node(node) if is_presenter_block(node) => {}
+            Section::Node(Node::Html(html))
+                if Directive::parse(&html.value) == Some(Directive::Feedback) =>
+            {
+                node_lines = feedback::lines(feedback, theme);
+            }
+            Section::Node(Node::Table(table))
+                if let Some(view) = key.table.as_ref().filter(|view| view.table == table_index) =>
+            {
+                let rows = view.apply(table_rows(table, Style::default(), theme));
+                node_lines = table::layout(rows, &table.align, Style::default());
+                node_lines.push(Line::raw(""));
+            }
+            Section::Node(node)
+                if let Some(image) = image_paragraph(node)
+                    && let Some((index, _)) = images.find(&image.url) =>
+            {
+                node_lines = images.lines(index, width);
+            }
+            Section::Node(node) if as_checklist(node).is_some() => {
+                if let Some(code) = as_checklist(node) {
+                    node_lines = checklist_lines(code, checklist_items, key.ticked, theme);
+                    checklist_items += node_lines.len() - 1;
+                }
+            }
+            Section::Node(node) => {
+                node_to_lines(node, &mut node_lines, Style::default(), theme);
+                // Wrapping would tear diagrams apart, so they're cut off instead
+                if is_protected(node) {
+                    node_lines = node_lines
+                        .into_iter()
+                        .map(|line| clip_line(line, width.into()))
+                        .collect();
+                }
+                if let Node::Heading(_) = node
+                    && !node_lines.is_empty()
+                {
+                    let title = node_lines.remove(0);
+                    let fitted = fit_heading(title, width as usize, theme.headings.fit);
+                    node_lines.insert(0, fitted);
+                    if part == 0 {
+                        opening_heading = all_lines.len() == lead;
+                        title_line.get_or_insert(all_lines.len());
+                    }
+                }
+            }
+            Section::Fold {
+                summary,
+                body,
+                inline,
+            } => {
+                let marker = if key.expanded { "▾" } else { "▸" };
+                node_lines.push(Line::styled(format!("{} {}", marker, summary), accent));
+                if key.expanded {
+                    node_lines.extend(inline.lines().map(|line| Line::raw(line.to_string())));
+                    for node in body {
+                        node_to_lines(node, &mut node_lines, Style::default(), theme);
+                    }
+                } else {
+                    node_lines.push(Line::raw(""));
+                }
+            }
+        }
+        if key.condensed {
+            node_lines = condense_lines(node_lines);
+        }
+        if part > 0 {
+            column_lines[part - 1].extend(node_lines);
+            continue;
+        }
+        all_lines.extend(node_lines);
+        if opening_heading {
+            heading_rows = all_lines.len() as u16;
+        }
+    }
+
+    let terms = emphasized_terms(slide);
+    for lines in std::iter::once(&mut all_lines).chain(&mut column_lines) {
+        highlight_terms(lines, &terms, accent);
+        if key.search.is_some()
+            && let Some(search) = search
+        {
+            let style = Style::default().fg(Color::Black).bg(theme.colors.search);
+            highlight_with(lines, style, |text| search.find(text));
+        }
+    }
+
+    if key.flash
+        && let Some(i) = title_line
+    {
+        all_lines[i] = all_lines[i].clone().patch_style(Modifier::REVERSED);
+    }
+
+    // Long lines wrap onto several rows, which the slide needs room for to scroll to
+    // its end; the heading may wrap as well
+    let heading_rows = wrapped_rows(&all_lines[..heading_rows as usize], key.width);
+    let lead_rows = wrapped_rows(&all_lines, key.width);
+    let column_rows = column_lines
+        .iter()
+        .map(|lines| wrapped_rows(lines, column_width))
+        .max()
+        .unwrap_or(0);
+    let area = Rect::new(0, 0, key.width, lead_rows + column_rows);
+    let mut buffer = Buffer::empty(area);
+    buffer.set_style(
+        area,
+        Style::default()
+            .fg(theme.colors.text)
+            .bg(theme.colors.background),
+    );
+    let alignment = if centering(slide, theme.layout.center).horizontal() {
+        Alignment::Center
+    } else {
+        Alignment::Left
+    };
+    Paragraph::new(Text::from(all_lines))
+        .wrap(Wrap { trim: false })
+        .alignment(alignment)
+        .render(
+            Rect {
+                height: lead_rows,
+                ..area
+            },
+            &mut buffer,
+        );
+    for (index, lines) in column_lines.into_iter().enumerate() {
+        let column = Rect::new(
+            columns::offset(key.width, column_count, index),
+            lead_rows,
+            column_width,
+            column_rows,
+        );
+        Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: false })
+            .alignment(alignment)
+            .render(column, &mut buffer);
+    }
+    (buffer, heading_rows)
+}
+
+/// The slide counter followed by the progress dots, all filled in the appendix
+fn progress_dots(app: &App, label: &str) -> Line<'static> {
+    let main = app.numbers.main_slides();
+    let symbols = if app.low_bandwidth {
+        &low_bandwidth::DOTS
+    } else {
+        &dots::SYMBOLS
+    };
+    let footer = Style::default().fg(app.theme.colors.footer);
+    let mut line = dots::line(
+        &dots::sections(&app.slides, main),
+        app.current_slide.min(main.saturating_sub(1)),
+        symbols,
+        Style::default().fg(app.theme.colors.accent),
+        footer,
+    );
+    line.spans
+        .insert(0, Span::styled(label.to_string(), footer));
+    line
+}
+
+/// Rows lines take once wrapped to `width`, the way the slide's paragraph wraps them
+fn wrapped_rows(lines: &[Line], width: u16) -> u16 {
+    Paragraph::new(lines.to_vec())
+        .wrap(Wrap { trim: false })
+        .line_count(width) as u16
+}
+
+/// A bordered block, in ASCII for `--low-bandwidth`
+fn bordered(app: &App) -> Block<'static> {
+    let block = Block::bordered();
+    if app.low_bandwidth {
+        block.border_set(low_bandwidth::BORDER)
+    } else {
+        block
+    }
+}
+
+/// A mode's footer, with the keys its actions are bound to
+fn mode_hints(
+    config: &config::Config,
+    mode: Mode,
+    prefix: &str,
+    hints: &[(&[Action], &str)],
+) -> String {
+    let hints = hints
+        .iter()
+        .filter_map(|(actions, label)| config.mode_hint(mode, actions, label));
+    std::iter::once(prefix.to_string())
+        .filter(|prefix| !prefix.is_empty())
+        .chain(hints)
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Every bound key and what it does, in as many columns as the screen needs
+fn render_cheat_sheet(frame: &mut ratatui::Frame, app: &App, config: &config::Config, area: Rect) {
+    let block = bordered(app)
+        .title(" Keys ")
+        .border_style(Style::default().fg(app.theme.colors.footer));
+    let inner = block.inner(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+
+    let entries: Vec<_> = config
+        .key_table()
+        .into_iter()
+        .filter(|(keys, _)| !keys.is_empty())
+        .collect();
+    let height = inner.height.max(1) as usize;
+    let width = inner.width / entries.len().div_ceil(height).max(1) as u16;
+    for (i, column) in entries.chunks(height).enumerate() {
+        let keys_width = column.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = column
+            .iter()
+            .map(|(keys, command)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(" {:>width$}  ", keys, width = keys_width),
+                        Style::default().fg(app.theme.colors.accent),
+                    ),
+                    Span::raw(command.description()),
+                ])
+            })
+            .collect();
+        let x = inner.x + i as u16 * width;
+        frame.render_widget(
+            Paragraph::new(lines),
+            Rect::new(x, inner.y, width, inner.height),
+        );
+    }
+}
+
+/// Marks the rows a live reload changed with `+` or `-` in the margin, and colors the
+/// added ones
+fn render_reload_diff(
+    frame: &mut ratatui::Frame,
+    app: &App,
+    changes: &Changes,
+    area: Rect,
+    pinned: u16,
+) {
+    let offset = app.scroll_view_state.offset().y;
+    let screen_row = |row: u16| {
+        if row < pinned {
+            return Some(area.y + row);
+        }
+        let y = (row - pinned).checked_sub(offset)?;
+        (y < app.viewport_height).then_some(area.y + pinned + y)
+    };
+    let gutter = area
+        .x
+        .checked_sub(1)
+        .filter(|_| app.theme.layout.margin_x > 0);
+    let buffer = frame.buffer_mut();
+    for (rows, mark, color) in [
+        (&changes.removed, "-", app.theme.colors.incorrect),
+        (&changes.added, "+", app.theme.colors.correct),
+    ] {
+        for y in rows.iter().filter_map(|&row| screen_row(row)) {
+            if let Some(x) = gutter {
+                buffer.set_string(x, y, mark, Style::default().fg(color));
+            }
+            if mark == "+" {
+                buffer.set_style(
+                    Rect::new(area.x, y, area.width, 1),
+                    Style::default().fg(color),
+                );
+            }
+        }
+    }
+}
+
+/// Notes of the current slide above a preview of the next one
+fn render_speaker_view(frame: &mut ratatui::Frame, app: &App, area: Rect) {
+    let [notes_area, next_area] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(area);
+    let border = Style::default().fg(app.theme.colors.footer);
+
+    let notes = speaker_notes(&app.slides[app.current_slide]);
+    let notes = if notes.is_empty() {
+        Text::styled("No notes for this slide", border)
+    } else {
+        Text::from(notes.join("\n\n"))
+    };
+    frame.render_widget(
+        Paragraph::new(notes)
+            .wrap(Wrap { trim: false })
+            .block(bordered(app).title(" Notes ").border_style(border)),
+        notes_area,
+    );
+
+    let block = bordered(app).title(" Next ").border_style(border);
+    let preview_area = block.inner(next_area);
+    frame.render_widget(block, next_area);
+    let Some(next) = app.slides.get(app.current_slide + 1) else {
+        frame.render_widget(Paragraph::new("End of deck").style(border), preview_area);
+        return;
+    };
+    let key = RenderKey {
+        slide: app.current_slide + 1,
+        width: preview_area.width,
+        search: None,
+        flash: false,
+        condensed: false,
+        expanded: false,
+        answer: None,
+        ticked: 0,
+        revealed: usize::MAX,
+        table: None,
+    };
+    // Images are left to the current slide; the preview shows their alt text
+    let (buffer, _) = render_slide(
+        next,
+        &key,
+        None,
+        None,
+        &app.theme,
+        &Images::default(),
+        app.feedback.as_ref(),
+    );
+    blit(
+        &buffer,
+        0,
+        preview_area,
+        frame.buffer_mut(),
+        &mut ScrollViewState::default(),
+    );
+}
+
+/// Timings of the previous frame in the top right corner
+fn render_profile(frame: &mut ratatui::Frame, profiler: &Profiler, area: Rect) {
+    let lines: Vec<Line> = profiler.lines().into_iter().map(Line::raw).collect();
+    let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 2;
+    let height = lines.len() as u16 + 2;
+    let overlay = Rect::new(
+        area.right().saturating_sub(width),
+        area.y,
+        width.min(area.width),
+        height.min(area.height),
+    );
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" profile ")),
+        overlay,
+    );
+}
+
+fn render_overview(frame: &mut ratatui::Frame, app: &App, overview: &Overview, area: Rect) {
+    let matches = overview.matches(&app.slides);
+    let [query_area, grid_area] =
+        Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+    frame.render_widget(
+        Paragraph::new(format!("/{}", overview.query))
+            .style(Style::default().fg(app.theme.colors.accent)),
+        query_area,
+    );
+    if matches.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No matching slides")
+                .style(Style::default().fg(app.theme.colors.footer)),
+            grid_area,
+        );
+        return;
+    }
+
+    // Rows scroll to keep the selected card in view
+    let columns = overview.columns.max(1);
+    let card_width = grid_area.width / columns as u16;
+    let rows = (grid_area.height / overview::CARD_HEIGHT).max(1) as usize;
+    let first_row = (overview.selected / columns).saturating_sub(rows - 1);
+    for (i, &slide) in matches
+        .iter()
+        .enumerate()
+        .skip(first_row * columns)
+        .take(rows * columns)
+    {
+        let card = Rect::new(
+            grid_area.x + (i % columns) as u16 * card_width,
+            grid_area.y + (i / columns - first_row) as u16 * overview::CARD_HEIGHT,
+            card_width,
+            overview::CARD_HEIGHT,
+        )
+        .intersection(grid_area);
+        let number = app
+            .numbers
+            .number(slide)
+            .map_or(String::new(), |n| format!("{} ", n));
+        let title = format!(" {}{} ", number, slide_title(&app.slides[slide]));
+        let block = if i == overview.selected {
+            bordered(app)
+                .title(Span::styled(
+                    title,
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ))
+                .border_style(Style::default().fg(app.theme.colors.accent))
+        } else {
+            bordered(app)
+                .title(title)
+                .border_style(Style::default().fg(app.theme.colors.footer))
+        };
+        frame.render_widget(
+            Paragraph::new(thumbnail(&app.slides[slide], &app.theme)).block(block),
+            card,
+        );
+    }
+}
+
+/// The start of a slide as shown on it, without the heading the card's title repeats
+fn thumbnail<'a>(slide: &'a [Node], theme: &Theme) -> Vec<Line<'a>> {
+    let heading = usize::from(matches!(slide.first(), Some(Node::Heading(_))));
+    let mut lines = vec![];
+    for node in slide[heading..]
+        .iter()
+        .filter(|node| !matches!(node, Node::Html(_)) && !is_presenter_block(node))
+    {
+        node_to_lines(node, &mut lines, Style::default(), theme);
+        if lines.len() >= overview::CARD_HEIGHT as usize * 2 {
+            break;
+        }
+    }
+    lines.retain(|line| line.width() > 0);
+    lines
+}
+
+/// Keys typed after `:`, which take digits until Enter jumps to the slide
+fn handle_goto_key(app: &mut App, key_code: KeyCode) {
+    let Some(input) = app.goto_input.as_mut() else {
+        return;
+    };
+
+    match key_code {
+        KeyCode::Char(c) if c.is_ascii_digit() => input.digits.push(c),
+        KeyCode::Backspace => {
+            input.digits.pop();
+        }
+        KeyCode::Enter => {
+            if let Some(number) = app.goto_input.take().and_then(|input| input.number()) {
+                let cmd = Command::GotoSlide(number);
+                app.macros.record(&cmd);
+                cmd.execute(app);
+            }
+        }
+        _ => app.goto_input = None,
+    }
+}
+
+fn handle_table_key(
+    app: &mut App,
+    key_code: KeyCode,
+    modifiers: KeyModifiers,
+    config: &config::Config,
+) {
+    let tables = table::tables(&app.slides[app.current_slide]).len();
+    let Some(view) = app.table_view.as_mut() else {
+        return;
+    };
+    let action = config.get_mode_action(Mode::Table, key_code, modifiers);
+
+    if view.filtering {
+        match (action, key_code) {
+            (Some(Action::DeleteChar), _) => {
+                view.filter.pop();
+            }
+            (Some(Action::Confirm), _) => view.filtering = false,
+            (Some(Action::Cancel), _) => {
+                view.filter.clear();
+                view.filtering = false;
+            }
+            (None, KeyCode::Char(c)) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                view.filter.push(c)
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let columns = table::tables(&app.slides[app.current_slide])
+        .get(view.table)
+        .and_then(|table| table.children.first())
+        .map_or(0, |header| header.children().map_or(0, Vec::len));
+    match action {
+        Some(Action::PreviousColumn) => view.column = view.column.saturating_sub(1),
+        Some(Action::NextColumn) => {
+            view.column = (view.column + 1).min(columns.saturating_sub(1));
+        }
+        Some(Action::Sort) => view.cycle_sort(),
+        Some(Action::Filter) => view.filtering = true,
+        Some(Action::NextTable) if tables > 0 => {
+            *view = TableView {
+                table: (view.table + 1) % tables,
+                ..TableView::new(view.slide)
+            };
+        }
+        Some(Action::ScrollDown) => Command::ScrollDown.execute(app),
+        Some(Action::ScrollUp) => Command::ScrollUp.execute(app),
+        Some(Action::Cancel) => app.table_view = None,
+        _ => {}
+    }
+}
+
+fn handle_search_key(
+    app: &mut App,
+    key_code: KeyCode,
+    modifiers: KeyModifiers,
+    config: &config::Config,
+) {
+    let Some(input) = app.search_input.as_mut() else {
+        return;
+    };
+
+    match (
+        config.get_mode_action(Mode::Search, key_code, modifiers),
+        key_code,
+    ) {
+        (Some(Action::Cancel), _) => app.search_input = None,
+        (Some(Action::Confirm), _) => {
+            let input = app.search_input.take().unwrap_or_default();
+            if input.text.is_empty() {
+                return;
+            }
+            match Search::parse(&input.text, input.options) {
+                Ok(search) => {
+                    if search.count(&app.slides[app.current_slide]) == 0 {
+                        match search.next_slide(&app.slides, app.current_slide, true) {
+                            Some(slide) => app.jump_to_slide(slide),
+                            None => {
+                                app.message = Some(format!("Pattern not found: {}", search.query))
+                            }
+                        }
+                    }
+                    app.search = Some(search);
+                    app.search_highlight = true;
+                    app.search_match = None;
+                    app.search_pending = Some(true);
+                }
+                Err(e) => app.message = Some(e.to_string()),
+            }
+        }
+        (Some(Action::DeleteChar), _) => {
+            input.text.pop();
+        }
+        (Some(Action::ToggleRegex), _) => input.options.regex = !input.options.regex,
+        (Some(Action::ToggleCase), _) => {
+            input.options.case_sensitive = !input.options.case_sensitive;
+        }
+        (Some(Action::ToggleWholeWord), _) => {
+            input.options.whole_word = !input.options.whole_word;
+        }
+        (None, KeyCode::Char(c)) if !modifiers.contains(KeyModifiers::CONTROL) => {
+            input.text.push(c)
+        }
+        _ => {}
+    }
+}
+
+fn handle_overview_key(
+    app: &mut App,
+    key_code: KeyCode,
+    modifiers: KeyModifiers,
+    config: &config::Config,
+) {
+    let Some(overview) = app.overview.as_mut() else {
+        return;
+    };
+
+    match (
+        config.get_mode_action(Mode::Overview, key_code, modifiers),
+        key_code,
+    ) {
+        (Some(Action::Cancel), _) => {
+            app.overview = None;
+            app.flash_started = Some(Instant::now());
+        }
+        (Some(Action::Confirm), _) => {
+            if let Some(slide) = overview.selected_slide(&app.slides) {
+                app.jump_to_slide(slide);
+            }
+            app.overview = None;
+            app.flash_started = Some(Instant::now());
+        }
+        (Some(Action::SelectNext), _) => {
+            let count = overview.matches(&app.slides).len();
+            overview.select_next(count);
+        }
+        (Some(Action::SelectPrevious), _) => overview.select_previous(),
+        (Some(Action::SelectBelow), _) => {
+            let count = overview.matches(&app.slides).len();
+            overview.select_below(count);
+        }
+        (Some(Action::SelectAbove), _) => overview.select_above(),
+        (Some(Action::DeleteChar), _) => overview.pop_char(),
+        (None, KeyCode::Char(c)) if !modifiers.contains(KeyModifiers::CONTROL) => {
+            overview.push_char(c)
+        }
+        _ => {}
+    }
+}
+
+pub fn handle_key(
+    app: &mut App,
+    key_code: KeyCode,
+    modifiers: KeyModifiers,
+    config: &config::Config,
+) {
+    // Any key dismisses shell command output and the cheat sheet
+    if app.command_output.take().is_some() || std::mem::take(&mut app.cheat_sheet) {
+        return;
+    }
+    app.message = None;
+    let confirming_quit = std::mem::take(&mut app.confirm_quit);
+
+    if key_code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL) {
+        app.confirm_quit = confirming_quit;
+        Command::Quit.execute(app);
+        return;
+    }
+
+    if app.search_input.is_some() {
+        handle_search_key(app, key_code, modifiers, config);
+        return;
+    }
+
+    if app.overview.is_some() {
+        handle_overview_key(app, key_code, modifiers, config);
+        return;
+    }
+
+    if app.goto_input.as_ref().is_some_and(|input| input.prompt) {
+        handle_goto_key(app, key_code);
+        return;
+    }
+
+    if app.table_view.is_some() {
+        handle_table_key(app, key_code, modifiers, config);
+        return;
+    }
+
+    if let Some(prefix) = app.pending_macro.take() {
+        if let KeyCode::Char(register) = key_code {
+            match prefix {
+                MacroPrefix::Record if register.is_ascii_lowercase() => {
+                    app.macros.start_recording(register);
+                }
+                MacroPrefix::Replay if register.is_ascii_lowercase() || register == '@' => {
+                    for cmd in app.macros.replay(register) {
+                        app.macros.record(&cmd);
+                        cmd.execute(app);
+                    }
+                }
+                _ => {}
+            }
+        }
+        return;
+    }
+
+    if modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+        match key_code {
+            KeyCode::Char('q') => {
+                if app.macros.recording_register().is_some() {
+                    app.macros.stop_recording();
+                } else {
+                    app.pending_macro = Some(MacroPrefix::Record);
+                }
+                return;
+            }
+            KeyCode::Char('@') => {
+                app.pending_macro = Some(MacroPrefix::Replay);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    // Number keys answer the quiz on the current slide, and count up a slide number
+    // too: the answer is taken back if the count is used, as in `12G`
+    if let KeyCode::Char(c @ '1'..='9') = key_code
+        && modifiers.is_empty()
+        && let Some(quiz) = Quiz::find(&app.slides[app.current_slide])
+    {
+        let option = c as usize - '0' as usize;
+        if option <= quiz.option_count() {
+            let replaced = app.quiz_answers.insert(app.current_slide, option);
+            let input = app.goto_input.get_or_insert_default();
+            input.digits.push(c);
+            input.replaced_answer.get_or_insert(replaced);
+            return;
+        }
+    }
+
+    // Digits count up a slide number for `G`, e.g. `12G`
+    if modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+        match key_code {
+            KeyCode::Char(':') => {
+                app.goto_input = Some(GotoInput::prompt());
+                return;
+            }
+            KeyCode::Char(c @ '0'..='9') if c != '0' || app.goto_input.is_some() => {
+                app.goto_input.get_or_insert_default().digits.push(c);
+                return;
+            }
+            _ => {}
+        }
+    }
+    let input = app.goto_input.take();
+    let count = input.as_ref().and_then(GotoInput::number);
+
     if let Some(cmd) = config.get_command(key_code, modifiers) {
+        let (cmd, counted) = match (cmd, count) {
+            (Command::JumpToBottom, Some(number)) => (&Command::GotoSlide(number), true),
+            (Command::RunCode(_), Some(number)) => (&Command::RunCode(number), true),
+            (Command::CopyCode(_), Some(number)) => (&Command::CopyCode(number), true),
+            _ => (cmd, false),
+        };
+        if counted && let Some(replaced) = input.and_then(|input| input.replaced_answer) {
+            restore_quiz_answer(app, replaced);
+        }
+        if matches!(cmd, Command::Quit) {
+            app.confirm_quit = confirming_quit;
+        }
+        app.macros.record(cmd);
         cmd.execute(app);
     }
 }
 
-pub fn run_app(term: &mut Terminal<CrosstermBackend<Stdout>>, file_path: &str, config: config::Config) -> Result<()> {
-    let slides = load_slides(file_path)?;
-    let mut app = App::new(slides);
+/// Takes back the answers digits gave the current slide's quiz once they're used as a
+/// count
+fn restore_quiz_answer(app: &mut App, replaced: Option<usize>) {
+    match replaced {
+        Some(answer) => app.quiz_answers.insert(app.current_slide, answer),
+        None => app.quiz_answers.remove(&app.current_slide),
+    };
+}
+
+/// Clicks and the wheel run their bound command, but only over the slides themselves:
+/// prompts, the overview and table mode are driven from the keyboard
+pub fn handle_mouse(app: &mut App, kind: MouseEventKind, config: &config::Config) {
+    let Some(cmd) = config.get_mouse_command(kind) else {
+        return;
+    };
+    if app.command_output.take().is_some() || std::mem::take(&mut app.cheat_sheet) {
+        return;
+    }
+    if app.search_input.is_some()
+        || app.overview.is_some()
+        || app.goto_input.as_ref().is_some_and(|input| input.prompt)
+        || app.table_view.is_some()
+        || app.pending_macro.is_some()
+    {
+        return;
+    }
+    app.message = None;
+    app.goto_input = None;
+    let confirming_quit = std::mem::take(&mut app.confirm_quit);
+    if matches!(cmd, Command::Quit) {
+        app.confirm_quit = confirming_quit;
+    }
+    app.macros.record(cmd);
+    cmd.execute(app);
+}
+
+/// Applies an event to the app state; rendering and other side effects stay in the loop.
+/// Returns whether the event swapped in a new version of the deck
+pub fn update(app: &mut App, event: &AppEvent, config: &config::Config) -> bool {
+    match event {
+        AppEvent::Terminal(Event::Key(key)) if key.is_press() => {
+            handle_key(app, key.code, key.modifiers, config);
+        }
+        AppEvent::Terminal(Event::Mouse(mouse)) => handle_mouse(app, mouse.kind, config),
+        AppEvent::Follow(slide) => app.goto_slide(*slide),
+        AppEvent::Disconnected => {
+            app.message = Some("The presenter ended the session".to_string());
+        }
+        AppEvent::Viewers(viewers) => app.viewers = Some(*viewers),
+        AppEvent::Responses(count) => {
+            if let Some(feedback) = &mut app.feedback {
+                feedback.count = Some(*count);
+            }
+            // The count is drawn into the slide, which the cache can't tell changed
+            app.render_cache.invalidate();
+        }
+        AppEvent::Reload(source) => match parse_deck_with(source, &config.split) {
+            Ok(deck) => {
+                app.reload(deck);
+                return true;
+            }
+            // Keep showing the last good version while the deck is mid-edit
+            Err(e) => app.message = Some(format!("Reload failed: {}", e)),
+        },
+        AppEvent::Cursor(line) => app.goto_slide(slide_at_line(&app.slides, *line)),
+        AppEvent::Theme(source) => match Theme::parse(source) {
+            // The edited theme is shown over any cycled to
+            Ok(theme) => {
+                app.theme_choice.forced = Some(theme);
+                app.theme_choice.cycled = None;
+                app.apply_theme();
+                app.message = None;
+            }
+            Err(e) => app.message = Some(format!("Theme reload failed: {}", e.root_cause())),
+        },
+        _ => {}
+    }
+    false
+}
+
+/// Sends viewers the deck when a reload was applied, then the slide the presenter is on;
+/// a save that failed to parse stays with the presenter, who keeps the last good one
+fn publish(server: &Server, app: &App, event: &AppEvent, reloaded: bool) {
+    if reloaded && let AppEvent::Reload(source) = event {
+        server.publish_deck(source);
+    }
+    server.publish(app.current_slide);
+}
+
+pub async fn run_app(
+    term: &mut Terminal<CrosstermBackend<Stdout>>,
+    mut app: App,
+    mut events: EventBus,
+    server: Option<Server>,
+    config: config::Config,
+) -> Result<App> {
+    events.spawn_terminal_reader();
+    let _mouse = config
+        .binds_mouse()
+        .then(mouse::Capture::start)
+        .transpose()?;
+    let mut keepalive = KeepAlive::new(config.keepalive.interval());
+    let mut inhibitor = None;
 
     loop {
-        term.draw(|f| render(&mut app, f, &config))?;
-        let event = crossterm::event::read()?;
-        if let Event::Key(key) = event
-            && key.is_press()
+        if let Some(rehearsal) = &mut app.rehearsal {
+            rehearsal.observe(app.current_slide, Instant::now());
+        }
+        let frame = FrameTimer::start();
+        let screen = term.draw(|f| render(&mut app, f, &config))?.area;
+        hyperlink::write(term.backend_mut(), &app.hyperlinks)?;
+        let placements = std::mem::take(&mut app.image_placements);
+        app.images.draw(term.backend_mut(), placements, screen)?;
+        if let Some(profiler) = &mut app.profiler {
+            frame.finish(profiler);
+        }
+
+        // The screensaver is held off for exactly as long as the deck is locked
+        if config.keepalive.inhibit && app.presenting != inhibitor.is_some() {
+            inhibitor = app.presenting.then(Inhibitor::start);
+        }
+
+        // Wake up without input when the flash or a reload's highlight runs out, a
+        // keep-alive is due or a timer ticks
+        let now = Instant::now();
+        let mut deadline = app
+            .flash_remaining(config.flash.duration())
+            .map(|remaining| now + remaining);
+        if app.presenting
+            && let Some(remaining) = keepalive.remaining(now)
         {
-            if let KeyCode::Char('q') = key.code {
-                return Ok(());
+            deadline = Some(deadline.map_or(now + remaining, |d| d.min(now + remaining)));
+        }
+        if let Some(rehearsal) = &app.rehearsal {
+            let tick = rehearsal.next_tick(now);
+            deadline = Some(deadline.map_or(tick, |d| d.min(tick)));
+        }
+        if let Some(tick) = app.timer.next_tick(now) {
+            deadline = Some(deadline.map_or(tick, |d| d.min(tick)));
+        }
+        if let Some(remaining) = app.reload_diff.as_ref().and_then(|d| d.remaining(now)) {
+            deadline = Some(deadline.map_or(now + remaining, |d| d.min(now + remaining)));
+        }
+
+        let Some(event) = events.next(deadline).await else {
+            return Ok(app);
+        };
+        let now = Instant::now();
+        match &event {
+            AppEvent::Tick if app.presenting && keepalive.is_due(now) => {
+                keepalive.ping(term.backend_mut(), now)?;
             }
-            handle_key(&mut app, key.code, key.modifiers, &config);
+            AppEvent::Terminal(_) => keepalive.touch(now),
+            _ => {}
+        }
+
+        let reloaded = update(&mut app, &event, &config);
+        if let Some(server) = &server {
+            publish(server, &app, &event, reloaded);
+        }
+        if app.should_quit {
+            return Ok(app);
+        }
+    }
+}
+
+fn run_pack(file: &str, output: Option<&str>) -> Result<()> {
+    let path = Path::new(file);
+    let packed = pack::pack_deck(path)?;
+    let output = output.map_or_else(|| pack::default_output(path), PathBuf::from);
+    std::fs::write(&output, packed.markdown)
+        .with_context(|| format!("Failed to write packed deck: {}", output.display()))?;
+    println!(
+        "Packed {} {} into {}",
+        packed.assets,
+        if packed.assets == 1 {
+            "asset"
+        } else {
+            "assets"
+        },
+        output.display()
+    );
+    Ok(())
+}
+
+fn run_merge(files: &[String], output: Option<&str>, config: &config::Config) -> Result<()> {
+    let merged = merge::merge_files(files, &config.split)?;
+    let Some(output) = output else {
+        print!("{}", merged.markdown);
+        return Ok(());
+    };
+    std::fs::write(output, &merged.markdown)
+        .with_context(|| format!("Failed to write merged deck: {}", output))?;
+    println!("Merged {} slides into {}", merged.slides, output);
+    if !merged.skipped.is_empty() {
+        println!(
+            "Kept the first version of duplicate slides: {}",
+            merged.skipped.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn run_new(name: &str, template: template::Template) -> Result<()> {
+    let path = template::output_path(name);
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    std::fs::write(&path, template::scaffold(&path, template))
+        .with_context(|| format!("Failed to write deck: {}", path.display()))?;
+    println!(
+        "Created {}; present it with `markdeck {}`",
+        path.display(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn run_check(file: &str, config: &config::Config, widths: &[u16], height: u16) -> Result<()> {
+    let mut deck = load_deck(file, &config.split)?;
+    let mut problems = std::mem::take(&mut deck.problems);
+    problems.extend(unknown_languages(&deck.slides, &config.code));
+    if !widths.is_empty() {
+        let mut app = App::from_deck(deck);
+        app.languages = config.code.clone();
+        app.layout = config.layout.clone();
+        app.apply_theme();
+        for &width in widths {
+            problems.extend(fit::overflows(&mut app, config, width, height)?);
         }
     }
+    problems.sort_by_key(|problem| problem.line);
+    for problem in &problems {
+        println!("{}:{}", file, problem);
+    }
+    match problems.len() {
+        0 => Ok(()),
+        1 => bail!("Found 1 problem"),
+        count => bail!("Found {} problems", count),
+    }
+}
+
+/// Lists the slides with characters the GIF's font would draw as boxes
+fn check_glyphs(
+    file: &str,
+    app: &mut App,
+    config: &config::Config,
+    script: &cast::Script,
+) -> Result<()> {
+    let families = cast::font_families(&config.font);
+    let Some(coverage) = fonts::coverage(families)? else {
+        eprintln!("Warning: fc-match not found; the font isn't checked for missing glyphs");
+        return Ok(());
+    };
+    let problems = fonts::missing_glyphs(app, config, &coverage, script.width, script.height)?;
+    for problem in &problems {
+        println!("{}:{}", file, problem);
+    }
+    if !problems.is_empty() {
+        bail!(
+            "{} can't draw every character; add a font that can to family under [font]",
+            families
+        );
+    }
+    Ok(())
+}
+
+fn run_import(file: &str, output: Option<&str>) -> Result<()> {
+    let path = Path::new(file);
+    let imported = import::import_file(path)?;
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let output = import::default_output(path);
+            if output.exists() {
+                bail!(
+                    "{} already exists; choose another path with --output",
+                    output.display()
+                );
+            }
+            output
+        }
+    };
+    std::fs::write(&output, imported.markdown)
+        .with_context(|| format!("Failed to write deck: {}", output.display()))?;
+    println!(
+        "Imported {} slides into {}",
+        imported.slides,
+        output.display()
+    );
+    if imported.external > 0 {
+        println!(
+            "Skipped {} {} loading markdown from another file; import those files by hand",
+            imported.external,
+            if imported.external == 1 {
+                "section"
+            } else {
+                "sections"
+            }
+        );
+    }
+    Ok(())
+}
+
+fn run_export(export: &ExportCommand, config: &config::Config) -> Result<()> {
+    match export {
+        ExportCommand::Script {
+            file,
+            format,
+            output,
+        } => {
+            let script = export::script(&load_deck(file, &config.split)?, *format);
+            match output {
+                Some(output) => std::fs::write(output, script)
+                    .with_context(|| format!("Failed to write script: {}", output)),
+                None => {
+                    print!("{}", script);
+                    Ok(())
+                }
+            }
+        }
+        ExportCommand::Json { file, output } => {
+            let source = read_deck_source(Path::new(file))?;
+            let deck = parse_deck_with(&source, &config.split)?;
+            let json = serde_json::to_string_pretty(&export::model(&deck, &source))?;
+            match output {
+                Some(output) => std::fs::write(output, json + "\n")
+                    .with_context(|| format!("Failed to write JSON: {}", output)),
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            }
+        }
+        ExportCommand::Cast {
+            file,
+            script,
+            output,
+        } => {
+            let deck = load_deck(file, &config.split)?;
+            let script = cast::Script::load(Path::new(script))?;
+            let output = output
+                .as_deref()
+                .map_or_else(|| cast::default_output(Path::new(file)), PathBuf::from);
+            let title = deck.meta.title.clone();
+            let mut app = App::from_deck(deck);
+            app.languages = config.code.clone();
+            app.layout = config.layout.clone();
+            app.apply_theme();
+            let recording = cast::record(&mut app, config, &script, title.as_deref())?;
+            if output
+                .extension()
+                .is_some_and(|extension| extension == "gif")
+            {
+                check_glyphs(file, &mut app, config, &script)?;
+                let recorded =
+                    std::env::temp_dir().join(format!("markdeck-{}.cast", std::process::id()));
+                std::fs::write(&recorded, recording)?;
+                let converted = cast::to_gif(&recorded, &output, &config.font);
+                let _ = std::fs::remove_file(&recorded);
+                converted?;
+            } else {
+                std::fs::write(&output, recording)
+                    .with_context(|| format!("Failed to write recording: {}", output.display()))?;
+            }
+            println!(
+                "Recorded {} steps to {}",
+                script.steps.len(),
+                output.display()
+            );
+            Ok(())
+        }
+        ExportCommand::Pptx { file, output } => {
+            let deck = load_deck(file, &config.split)?;
+            let output = output
+                .as_deref()
+                .map_or_else(|| pptx::default_output(Path::new(file)), PathBuf::from);
+            std::fs::write(&output, pptx::pptx(&deck)?)
+                .with_context(|| format!("Failed to write deck: {}", output.display()))?;
+            println!(
+                "Exported {} slides to {}",
+                deck.slides.len(),
+                output.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn run_theme_preview(file: &str, cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let path = Path::new(file);
+    let split = Split {
+        headings: false,
+        ..Split::default()
+    };
+    let mut app = App::from_deck(parse_deck_with(theme::PREVIEW_DECK, &split)?);
+    app.theme_choice.forced = Some(Theme::load(path)?);
+    app.apply_theme();
+    app.features = Capabilities::guess().enabled(&config.features);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let events = EventBus::new();
+    {
+        let _guard = runtime.enter();
+        Watcher::new(path).spawn(events.sender(), AppEvent::Theme);
+    }
+    ratatui::run(|term| runtime.block_on(run_app(term, app, events, None, config)))?;
+    Ok(())
+}
+
+/// Shows a presentation served by another markdeck, following its slide changes
+fn run_attach(address: &str, cli: &Cli) -> Result<()> {
+    let config = config::Config::load(cli.config.as_deref())?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let viewer = runtime.block_on(share::attach(address))?;
+    let mut app = App::from_deck(parse_deck_with(&viewer.source, &config.split)?);
+    app.read_only = true;
+    app.show_progress = config.progress.show;
+    app.features = Capabilities::guess().enabled(&config.features);
+
+    let events = EventBus::new();
+    let _guard = runtime.enter();
+    viewer.follow(events.sender());
+    ratatui::run(|term| runtime.block_on(run_app(term, app, events, None, config)))?;
+    Ok(())
+}
+
+/// Prints every slide with the presentation's styling as ANSI text, separated by rules
+/// with the slide number
+fn run_cat(deck: &app::Deck, theme: &Theme, config: &config::Config) -> Result<()> {
+    let width = match deck.frontmatter.canvas {
+        Some(canvas) => canvas.width,
+        None => ratatui::crossterm::terminal::size().map_or(80, |(columns, _)| columns),
+    };
+    let width = theme
+        .layout
+        .max_width
+        .map_or(width, |max_width| width.min(max_width));
+    let features = Capabilities::guess().enabled(&config.features);
+    let numbers = SlideNumbers::new(&deck.slides, deck.frontmatter.number_from.unwrap_or(1));
+    let mut out = String::new();
+    for (index, slide) in deck.slides.iter().enumerate() {
+        let label = format!("── {} ", numbers.indicator(index, deck.slides.len()));
+        let mut rule = Buffer::empty(Rect::new(0, 0, width, 1));
+        rule.set_string(
+            0,
+            0,
+            label + &"─".repeat(width.into()),
+            Style::default().fg(theme.colors.header),
+        );
+
+        // Slides are printed the way they end up once every pause has been passed
+        let key = RenderKey {
+            slide: index,
+            width,
+            search: None,
+            flash: false,
+            condensed: false,
+            expanded: true,
+            answer: None,
+            ticked: 0,
+            revealed: pause_count(slide),
+            table: None,
+        };
+        let breadcrumb = theme
+            .headings
+            .breadcrumb
+            .then(|| section_title(&deck.slides, index))
+            .flatten();
+        let (mut buffer, _) = render_slide(
+            slide,
+            &key,
+            breadcrumb.as_deref(),
+            None,
+            theme,
+            &Images::default(),
+            deck.frontmatter.feedback.as_ref(),
+        );
+        features.degrade(&mut rule, theme.colors.background);
+        features.degrade(&mut buffer, theme.colors.background);
+        if index > 0 {
+            out.push('\n');
+        }
+        out.push_str(&write_ansi(&rule));
+        out.push_str(&write_ansi(&buffer));
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+fn run_clear(storage: &Storage, state: bool, cache: bool) -> Result<()> {
+    // Without flags both are cleared
+    let both = !state && !cache;
+    let cleared = storage.clear(state || both, cache || both)?;
+    if cleared.is_empty() {
+        println!("Nothing to clear");
+    }
+    for dir in cleared {
+        println!("Removed {}", dir.display());
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let config = config::Config::load(cli.config.as_deref())?;
-    ratatui::run(|term| run_app(term, &cli.file, config))
+    match &cli.command {
+        Some(CliCommand::New { name, template }) => return run_new(name, *template),
+        Some(CliCommand::Pack { file, output }) => return run_pack(file, output.as_deref()),
+        Some(CliCommand::Import { file, output }) => return run_import(file, output.as_deref()),
+        Some(CliCommand::Merge { files, output }) => {
+            let config = config::Config::load(cli.config.as_deref())?;
+            return run_merge(files, output.as_deref(), &config);
+        }
+        Some(CliCommand::Check {
+            file,
+            widths,
+            height,
+        }) => {
+            let config = config::Config::load(cli.config.as_deref())?;
+            return run_check(file, &config, widths, *height);
+        }
+        Some(CliCommand::Info { file }) => {
+            let config = config::Config::load(cli.config.as_deref())?;
+            print!(
+                "{}",
+                toml::to_string(&load_deck(file, &config.split)?.meta)?
+            );
+            return Ok(());
+        }
+        Some(CliCommand::Export { export }) => {
+            return run_export(export, &config::Config::load(cli.config.as_deref())?);
+        }
+        Some(CliCommand::Theme {
+            theme: ThemeCommand::Preview { file },
+        }) => return run_theme_preview(file, &cli),
+        Some(CliCommand::Attach { address, .. }) => return run_attach(address, &cli),
+        Some(CliCommand::Keys { format }) => {
+            let config = config::Config::load(cli.config.as_deref())?;
+            print!("{}", keys::cheat_sheet(&config, *format));
+            return Ok(());
+        }
+        Some(CliCommand::Doctor) => {
+            let config = config::Config::load(cli.config.as_deref())?;
+            print!("{}", Capabilities::detect()?.report(&config.features));
+            return Ok(());
+        }
+        Some(CliCommand::Recent) => {
+            for file in Storage::new()?.recent_files() {
+                println!("{}", file.display());
+            }
+            return Ok(());
+        }
+        Some(CliCommand::Clear { state, cache }) => {
+            return run_clear(&Storage::new()?, *state, *cache);
+        }
+        None => {}
+    }
+
+    let file = cli.file.expect("clap requires a file without a subcommand");
+    let mut config = config::Config::load(cli.config.as_deref())?;
+    // Reloading would have to prompt for the passphrase on every save
+    if cli.watch && Encryption::detect(Path::new(&file)).is_some() {
+        bail!("Cannot watch an encrypted deck: {}", file);
+    }
+    if cli.listen_editor.as_deref() == Some("-") && std::io::stdin().is_terminal() {
+        bail!("--listen-editor - reads the editor's messages from stdin, which is a terminal");
+    }
+    // Load before entering the TUI so encrypted decks can prompt for a passphrase
+    let load_started = Instant::now();
+    let source = read_deck_source(Path::new(&file))?;
+    let deck = parse_deck_with(&source, &config.split)?;
+    let version_warning = deck.frontmatter.version_warning(Version::current());
+    if let Some(warning) = &version_warning {
+        if cli.strict {
+            bail!("{}", warning);
+        }
+        if cli.cat || !std::io::stdout().is_terminal() {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+    let theme_choice =
+        ThemeChoice::load(cli.theme.as_deref().map(Path::new), config.theme.as_deref())?;
+    if cli.cat || !std::io::stdout().is_terminal() {
+        let mut theme = theme_choice.pick(deck.frontmatter.theme.clone());
+        theme.code.languages = config.code.clone();
+        config.layout.apply(&mut theme.layout);
+        return run_cat(&deck, &theme, &config);
+    }
+    let profiler = cli.profile.then(|| Profiler::new(load_started.elapsed()));
+
+    let storage = Storage::new()?;
+    let path = Path::new(&file);
+    let mut session = storage.load(path);
+    let start_slide = match cli.start_slide {
+        Some(n) if n as usize > deck.slides.len() => {
+            bail!(
+                "Cannot start on slide {}: the deck has {} slides",
+                n,
+                deck.slides.len()
+            )
+        }
+        Some(n) => n as usize - 1,
+        None if cli.resume => session.slide.unwrap_or(0),
+        None => 0,
+    };
+
+    let problems = deck.problems.len() + unknown_languages(&deck.slides, &config.code).len();
+    let rehearsal = cli
+        .rehearse
+        .then(|| Rehearsal::new(&deck.slides, &deck.frontmatter.timing, Instant::now()));
+    let mut app = App::from_deck(deck);
+    app.theme_choice = theme_choice;
+    app.low_bandwidth = cli.low_bandwidth;
+    app.languages = config.code.clone();
+    app.layout = config.layout.clone();
+    app.apply_theme();
+    app.features = Capabilities::guess().enabled(&config.features);
+    if cli.low_bandwidth {
+        app.features = low_bandwidth::features(app.features);
+        config.flash.effect = FlashEffect::Off;
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        app.base_dir = dir.to_path_buf();
+    }
+    if let Some(protocol) = app.features.images {
+        // Fetched before the first slide, so a flaky network shows up now rather than
+        // as missing images mid-talk
+        app.assets = AssetCache::new(storage.asset_dir());
+        let prefetched = prefetch::prefetch(
+            &prefetch::remote_urls(&app.slides),
+            &app.assets,
+            cli.offline,
+        )?;
+        if let Some((url, error)) = prefetched.failed.first() {
+            app.message = Some(format!(
+                "Could not fetch {} of {} remote images, e.g. {}: {}",
+                prefetched.failed.len(),
+                prefetched.failed.len() + prefetched.fetched + prefetched.cached,
+                url,
+                error
+            ));
+        }
+        app.images = Images::load(&app.slides, &app.base_dir, &app.assets, protocol);
+    }
+    if cli.capture {
+        for slide in &mut app.slides {
+            screenshot::capture(slide, &app.base_dir);
+        }
+    }
+    app.profiler = profiler;
+    app.rehearsal = rehearsal;
+    app.speaker_view = cli.speaker_view;
+    app.show_progress = config.progress.show;
+    app.copy_target = config.copy.target();
+    if config.timer.start == TimerStart::Launch {
+        app.timer.toggle(Instant::now());
+    }
+    if cli.resume {
+        app.checklists = session.checklists.clone().into_iter().collect();
+    }
+    app.goto_slide(start_slide);
+    // A failed prefetch is more pressing to fix before the talk
+    if app.message.is_none() {
+        app.message = match problems {
+            0 => version_warning,
+            1 => Some("1 problem in the deck; see markdeck check".to_string()),
+            count => Some(format!(
+                "{} problems in the deck; see markdeck check",
+                count
+            )),
+        };
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let events = EventBus::new();
+    if cli.watch {
+        let _guard = runtime.enter();
+        Watcher::new(path).spawn(events.sender(), AppEvent::Reload);
+    }
+    match cli.listen_editor.as_deref() {
+        Some("-") => editor::spawn_stdin(events.sender()),
+        Some(address) => runtime.block_on(editor::listen(address, events.sender()))?,
+        None => {}
+    }
+    if let Some(url) = app
+        .feedback
+        .as_ref()
+        .and_then(|feedback| feedback.responses.clone())
+    {
+        feedback::spawn_poller(url, events.sender());
+    }
+    let server = match &cli.serve {
+        Some(address) => {
+            app.viewers = Some(0);
+            let server = runtime.block_on(Server::bind(address, source, events.sender()))?;
+            server.publish(app.current_slide);
+            Some(server)
+        }
+        None => None,
+    };
+    let mut app =
+        ratatui::run(|term| runtime.block_on(run_app(term, app, events, server, config)))?;
+
+    if let Some(rehearsal) = &mut app.rehearsal {
+        print!("{}", rehearsal.report(Instant::now()));
+        session.timings = rehearsal.seconds();
+    }
+    storage.add_recent_file(path)?;
+    session.slide = Some(app.current_slide);
+    // Progress is only loaded with --resume, and isn't wiped by a session without it
+    if cli.resume {
+        session.checklists = app
+            .checklists
+            .into_iter()
+            .filter(|(_, ticked)| *ticked > 0)
+            .collect();
+    }
+    storage.save(path, &session)
 }
 
 #[cfg(test)]
@@ -202,6 +2351,309 @@ mod tests {
         handle_key(&mut app, KeyCode::Char('G'), KeyModifiers::SHIFT, &config);
     }
 
+    #[test]
+    fn test_any_key_dismisses_command_output() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![], vec![]]);
+        app.command_output = Some("done".to_string());
+        handle_key(&mut app, KeyCode::Char('l'), KeyModifiers::NONE, &config);
+        assert!(app.command_output.is_none());
+        assert_eq!(app.current_slide, 0);
+    }
+
+    fn press(app: &mut App, config: &config::Config, keys: &str) {
+        for c in keys.chars() {
+            handle_key(app, KeyCode::Char(c), KeyModifiers::NONE, config);
+        }
+    }
+
+    #[test]
+    fn test_only_applied_reloads_are_sent_to_viewers() {
+        let config = config::Config::default();
+        let mut app = App::from_deck(app::parse_deck("# A\n").unwrap());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let address = std::net::TcpListener::bind("127.0.0.1:0")
+                .and_then(|listener| listener.local_addr())
+                .unwrap()
+                .to_string();
+            let (events, _) = tokio::sync::mpsc::unbounded_channel();
+            let server = Server::bind(&address, "# A\n".to_string(), events)
+                .await
+                .unwrap();
+            let (events, mut received) = tokio::sync::mpsc::unbounded_channel();
+            share::attach(&address).await.unwrap().follow(events);
+            assert!(matches!(received.recv().await, Some(AppEvent::Follow(0))));
+
+            // Parsing hardly ever fails, as problems are shown on the slides instead, so
+            // a failed reload is stood in for by one that wasn't applied
+            let broken = AppEvent::Reload("# Broken\n".to_string());
+            publish(&server, &app, &broken, false);
+
+            let fixed = AppEvent::Reload("# A\n# B\n".to_string());
+            let reloaded = update(&mut app, &fixed, &config);
+            assert!(reloaded);
+            publish(&server, &app, &fixed, reloaded);
+            assert!(matches!(
+                received.recv().await,
+                Some(AppEvent::Reload(source)) if source == "# A\n# B\n"
+            ));
+        });
+    }
+
+    #[test]
+    fn test_reload_marks_changed_rows() {
+        let config = config::Config::default();
+        let mut app = App::from_deck(app::parse_deck("# A\n\nold\n\nkept\n").unwrap());
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(30, 10)).unwrap();
+        terminal
+            .draw(|frame| render(&mut app, frame, &config))
+            .unwrap();
+        app.reload(app::parse_deck("# A\n\nnew\n\nkept\n").unwrap());
+        let frame = terminal
+            .draw(|frame| render(&mut app, frame, &config))
+            .unwrap();
+        // Slides start two rows down and two columns in, the margin beside them
+        assert_eq!(frame.buffer[(1, 4)].symbol(), "+");
+        assert_eq!(frame.buffer[(2, 4)].fg, app.theme.colors.correct);
+        assert_eq!(frame.buffer[(1, 6)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_numeric_goto() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 12]);
+        press(&mut app, &config, "12G");
+        assert_eq!(app.current_slide, 11);
+        assert!(app.goto_input.is_none());
+
+        press(&mut app, &config, ":3");
+        assert_eq!(app.goto_input.as_ref().unwrap().digits, "3");
+        handle_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &config);
+        assert_eq!(app.current_slide, 2);
+
+        press(&mut app, &config, "20G");
+        assert_eq!(app.current_slide, 2);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("No slide 20: the deck has 12 slides")
+        );
+
+        // Numbers are the ones on screen, going on into the appendix
+        let deck = "# Title\n<!-- unnumbered -->\n# A\n# B\n# Backup\n<!-- appendix -->\n";
+        let mut numbered = App::from_deck(app::parse_deck(deck).unwrap());
+        press(&mut numbered, &config, "2G");
+        assert_eq!(numbered.current_slide, 2);
+        press(&mut numbered, &config, ":3");
+        handle_key(&mut numbered, KeyCode::Enter, KeyModifiers::NONE, &config);
+        assert_eq!(numbered.current_slide, 3);
+        press(&mut numbered, &config, "4G");
+        assert_eq!(
+            numbered.message.as_deref(),
+            Some("No slide 4: the deck has 4 slides")
+        );
+
+        // A count before any other key is dropped
+        press(&mut app, &config, "5l");
+        assert_eq!(app.current_slide, 3);
+        press(&mut app, &config, "G");
+        assert_eq!(app.current_slide, 3);
+
+        // On a quiz slide digits answer, and are taken back when they're a count
+        let deck = "# Quiz\n<!-- quiz -->\n- [ ] A\n- [x] B\n".to_string() + &"# Next\n".repeat(11);
+        let mut quiz = App::from_deck(app::parse_deck(&deck).unwrap());
+        press(&mut quiz, &config, "12G");
+        assert_eq!(quiz.current_slide, 11);
+        assert!(quiz.quiz_answers.is_empty());
+        press(&mut quiz, &config, "1G1G");
+        assert_eq!(quiz.current_slide, 0);
+        assert!(quiz.quiz_answers.is_empty());
+        press(&mut quiz, &config, "2l");
+        assert_eq!(quiz.quiz_answers.get(&0), Some(&2));
+    }
+
+    #[test]
+    fn test_macro_records_and_replays_commands() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 5]);
+        press(&mut app, &config, "qall");
+        assert_eq!(app.macros.recording_register(), Some('a'));
+        press(&mut app, &config, "q");
+        assert_eq!(app.current_slide, 2);
+
+        press(&mut app, &config, "@a");
+        assert_eq!(app.current_slide, 4);
+    }
+
+    #[test]
+    fn test_double_at_replays_last_macro() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 5]);
+        press(&mut app, &config, "qblq@b@@");
+        assert_eq!(app.current_slide, 3);
+    }
+
+    #[test]
+    fn test_shift_q_quits() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]]);
+        handle_key(&mut app, KeyCode::Char('Q'), KeyModifiers::SHIFT, &config);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_p_toggles_progress_footer() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 4]);
+        press(&mut app, &config, "lp");
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(40, 10)).unwrap();
+        let frame = terminal
+            .draw(|frame| render(&mut app, frame, &config))
+            .unwrap();
+        let progress: String = (0..40)
+            .map(|x| frame.buffer[(x, 8)].symbol().to_string())
+            .collect();
+        assert!(progress.starts_with("slide 2/4  "));
+
+        press(&mut app, &config, "p");
+        assert!(!app.show_progress);
+    }
+
+    #[test]
+    fn test_theme_preview_redraws_on_theme_changes() {
+        let config = config::Config::default();
+        let split = Split {
+            headings: false,
+            ..Split::default()
+        };
+        let deck = parse_deck_with(theme::PREVIEW_DECK, &split).unwrap();
+        assert_eq!(deck.slides.len(), 1);
+        assert!(
+            deck.slides[0]
+                .iter()
+                .any(|node| matches!(node, Node::Table(_)))
+        );
+
+        let mut app = App::from_deck(deck);
+        app.low_bandwidth = true;
+        app.theme_choice.cycle();
+        let source = "[colors]\nheading = \"magenta\"".to_string();
+        update(&mut app, &AppEvent::Theme(source), &config);
+        assert_eq!(app.theme.colors.heading, Color::Magenta);
+        // Applied like any other theme, so a slow link still goes without syntax colors
+        assert_eq!(app.theme.code.syntax_theme, "none");
+
+        update(&mut app, &AppEvent::Theme("[colors".to_string()), &config);
+        assert_eq!(app.theme.colors.heading, Color::Magenta);
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .starts_with("Theme reload failed")
+        );
+    }
+
+    #[test]
+    fn test_overview_filter_and_open() {
+        let config = config::Config::default();
+        let deck = app::parse_deck("# Intro\n\n# Ownership\n\n# Async\n").unwrap();
+        let mut app = App::new(deck.slides);
+        press(&mut app, &config, "o");
+        assert!(app.overview.is_some());
+
+        press(&mut app, &config, "asy");
+        handle_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &config);
+        assert!(app.overview.is_none());
+        assert_eq!(app.current_slide, 2);
+        assert!(app.flash_started.is_some());
+    }
+
+    #[test]
+    fn test_overview_escape_keeps_current_slide() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 3]);
+        press(&mut app, &config, "o");
+        handle_key(&mut app, KeyCode::Down, KeyModifiers::NONE, &config);
+        handle_key(&mut app, KeyCode::Esc, KeyModifiers::NONE, &config);
+        assert!(app.overview.is_none());
+        assert_eq!(app.current_slide, 0);
+    }
+
+    #[test]
+    fn test_search_prompt_jumps_to_first_match() {
+        let config = config::Config::default();
+        let deck = app::parse_deck("# Intro\n\n# Ownership\nborrow\n\n# Async\nborrow\n").unwrap();
+        let mut app = App::new(deck.slides);
+        press(&mut app, &config, "/borrow");
+        handle_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &config);
+        assert!(app.search_input.is_none());
+        assert_eq!(app.current_slide, 1);
+
+        press(&mut app, &config, "n");
+        assert_eq!(app.current_slide, 2);
+        press(&mut app, &config, "N");
+        assert_eq!(app.current_slide, 1);
+    }
+
+    #[test]
+    fn test_search_highlight_persists_across_navigation() {
+        let config = config::Config::default();
+        let deck = app::parse_deck("# Intro\nborrow\n\n# Next\nborrow\n").unwrap();
+        let mut app = App::new(deck.slides);
+        press(&mut app, &config, "/borrow");
+        handle_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &config);
+        press(&mut app, &config, "l");
+        assert!(app.search_highlight);
+
+        handle_key(&mut app, KeyCode::Esc, KeyModifiers::NONE, &config);
+        assert!(!app.search_highlight);
+    }
+
+    #[test]
+    fn test_search_prompt_toggles_and_reports_invalid_regex() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]]);
+        press(&mut app, &config, "/(");
+        handle_key(&mut app, KeyCode::Char('r'), KeyModifiers::ALT, &config);
+        assert!(app.search_input.as_ref().unwrap().options.regex);
+        handle_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &config);
+        assert!(app.search.is_none());
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .contains("Invalid search pattern")
+        );
+    }
+
+    #[test]
+    fn test_ctrl_c_quits_unless_presenting() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]]);
+        handle_key(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL, &config);
+        assert!(app.should_quit);
+
+        let mut app = App::new(vec![vec![]]);
+        press(&mut app, &config, "P");
+        handle_key(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL, &config);
+        assert!(!app.should_quit);
+        handle_key(&mut app, KeyCode::Char('c'), KeyModifiers::CONTROL, &config);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_quit_confirmation_is_cancelled_by_other_keys() {
+        let config = config::Config::default();
+        let mut app = App::new(vec![vec![]; 2]);
+        press(&mut app, &config, "PQlQ");
+        assert!(!app.should_quit);
+        press(&mut app, &config, "Q");
+        assert!(app.should_quit);
+    }
+
     #[test]
     fn test_unrecognized_key_does_nothing() {
         let config = config::Config::default();
@@ -210,4 +2662,37 @@ mod tests {
         handle_key(&mut app, KeyCode::Char('x'), KeyModifiers::NONE, &config);
         assert_eq!(app.current_slide, initial_slide);
     }
+
+    #[test]
+    fn test_only_an_opening_heading_can_be_pinned() {
+        let heading_rows = |markdown: &str, breadcrumb| {
+            let deck = app::parse_deck(markdown).unwrap();
+            let key = RenderKey {
+                slide: 0,
+                width: 40,
+                search: None,
+                flash: false,
+                condensed: false,
+                expanded: false,
+                answer: None,
+                ticked: 0,
+                revealed: 0,
+                table: None,
+            };
+            let theme = Theme::default();
+            render_slide(
+                &deck.slides[0],
+                &key,
+                breadcrumb,
+                None,
+                &theme,
+                &Images::default(),
+                None,
+            )
+            .1
+        };
+        assert_eq!(heading_rows("## Title\ntext\n", None), 2);
+        assert_eq!(heading_rows("## Title\ntext\n", Some("Part")), 3);
+        assert_eq!(heading_rows("text\n\n## Title\n", None), 0);
+    }
 }
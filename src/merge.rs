@@ -0,0 +1,229 @@
+//! `markdeck merge`: combines decks drafted by co-presenters into one, slide by slide
+//!
+//! Slides are copied as written. Sections, which start at an H1 slide, are matched
+//! by title: a section both decks have gets the later deck's slides appended, and a
+//! section only the later deck has goes after the section it follows there. A slide
+//! whose title already appears in its section is left out, keeping the earlier
+//! deck's version.
+
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use markdown::mdast::Node;
+
+use crate::app::{parse_markdown, slide_title};
+use crate::frontmatter::{Frontmatter, Split};
+
+#[derive(Debug)]
+pub struct Merged {
+    pub markdown: String,
+    pub slides: usize,
+    /// Titles of the slides left out as duplicates
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Slide {
+    title: String,
+    source: String,
+}
+
+#[derive(Debug)]
+struct Section {
+    /// Title of the H1 slide opening the section; none before the first one
+    title: Option<String>,
+    slides: Vec<Slide>,
+}
+
+/// A deck cut into sections, with its frontmatter block and effective split
+#[derive(Debug)]
+struct SplitDeck {
+    frontmatter: Option<String>,
+    split: Split,
+    sections: Vec<Section>,
+}
+
+pub fn merge_files(paths: &[impl AsRef<Path>], split: &Split) -> Result<Merged> {
+    let sources = paths
+        .iter()
+        .map(|path| {
+            let path = path.as_ref();
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read deck: {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    merge(&sources, split)
+}
+
+/// Merges decks in order, the first one providing the frontmatter
+pub fn merge(sources: &[String], split: &Split) -> Result<Merged> {
+    let mut decks = sources
+        .iter()
+        .map(|source| split_deck(source, split))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter();
+    let Some(mut merged) = decks.next() else {
+        return Ok(Merged {
+            markdown: String::new(),
+            slides: 0,
+            skipped: vec![],
+        });
+    };
+
+    let mut skipped = vec![];
+    for deck in decks {
+        // Sections new to the merged deck go after the one they follow in their deck
+        let mut insert_at = merged.sections.len();
+        for section in deck.sections {
+            let Some(index) = merged
+                .sections
+                .iter()
+                .position(|s| s.title == section.title)
+            else {
+                merged.sections.insert(insert_at, section);
+                insert_at += 1;
+                continue;
+            };
+            let existing = &mut merged.sections[index];
+            for slide in section.slides {
+                if existing.slides.iter().any(|s| s.title == slide.title) {
+                    skipped.push(slide.title);
+                } else {
+                    existing.slides.push(slide);
+                }
+            }
+            insert_at = index + 1;
+        }
+    }
+
+    let separator = match (&merged.split.marker, merged.split.rules) {
+        (Some(marker), _) => format!("\n\n{}\n\n", marker.trim()),
+        (None, true) => "\n\n---\n\n".to_string(),
+        (None, false) => "\n\n".to_string(),
+    };
+    let slides: Vec<&str> = merged
+        .sections
+        .iter()
+        .flat_map(|section| &section.slides)
+        .map(|slide| slide.source.as_str())
+        .collect();
+    let mut markdown = merged.frontmatter.map_or_else(String::new, |f| f + "\n\n");
+    markdown.push_str(&slides.join(&separator));
+    markdown.push('\n');
+    Ok(Merged {
+        markdown,
+        slides: slides.len(),
+        skipped,
+    })
+}
+
+fn split_deck(source: &str, split: &Split) -> Result<SplitDeck> {
+    let mdast = parse_markdown(source)?;
+    let mut deck = SplitDeck {
+        frontmatter: None,
+        split: split.clone(),
+        sections: vec![],
+    };
+    let mut slides: Vec<(Vec<&Node>, Range<usize>)> = vec![];
+    let mut current: Option<(Vec<&Node>, Range<usize>)> = None;
+    for node in mdast.children().into_iter().flatten() {
+        let Some(position) = node.position() else {
+            continue;
+        };
+        let range = position.start.offset..position.end.offset;
+        let declared = match node {
+            Node::Toml(toml) => Some(Frontmatter::from_toml(&toml.value)?),
+            Node::Yaml(yaml) => Some(Frontmatter::from_yaml(&yaml.value)?),
+            _ => None,
+        };
+        if let Some(declared) = declared {
+            deck.frontmatter = Some(source[range].to_string());
+            if let Some(split) = declared.split {
+                deck.split = split;
+            }
+            continue;
+        }
+
+        let delimiter = deck.split.is_delimiter(node);
+        if delimiter || deck.split.starts_slide(node) {
+            slides.extend(current.take());
+        }
+        if !delimiter {
+            let (nodes, slide) = current.get_or_insert_with(|| (vec![], range.clone()));
+            nodes.push(node);
+            slide.end = range.end;
+        }
+    }
+    slides.extend(current);
+
+    for (nodes, range) in slides {
+        let opens_section = matches!(nodes.first(), Some(Node::Heading(h)) if h.depth == 1);
+        let nodes: Vec<Node> = nodes.into_iter().cloned().collect();
+        let title = slide_title(&nodes);
+        if opens_section || deck.sections.is_empty() {
+            deck.sections.push(Section {
+                title: opens_section.then(|| title.clone()),
+                slides: vec![],
+            });
+        }
+        if let Some(section) = deck.sections.last_mut() {
+            section.slides.push(Slide {
+                title,
+                source: source[range].to_string(),
+            });
+        }
+    }
+    Ok(deck)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    fn titles(markdown: &str) -> Vec<String> {
+        let deck = parse_deck(markdown).unwrap();
+        deck.slides.iter().map(|slide| slide_title(slide)).collect()
+    }
+
+    #[test]
+    fn test_sections_interleave_and_duplicates_are_skipped() {
+        let a = "+++\ntitle = \"Rust\"\n+++\n\n# Intro\n\n## Agenda\n\n# Ownership\n\nBorrowing\n\n## Questions\n";
+        let b = "# Intro\n\n## Agenda\n\nTheirs\n\n## Speakers\n\n# Async\n\n# Ownership\n\n## Lifetimes\n\n# Wrap-up\n";
+        let merged = merge(&[a.to_string(), b.to_string()], &Split::default()).unwrap();
+        assert!(
+            merged
+                .markdown
+                .starts_with("+++\ntitle = \"Rust\"\n+++\n\n# Intro\n")
+        );
+        assert_eq!(
+            titles(&merged.markdown),
+            [
+                "Intro",
+                "Agenda",
+                "Speakers",
+                "Async",
+                "Ownership",
+                "Questions",
+                "Lifetimes",
+                "Wrap-up"
+            ]
+        );
+        assert_eq!(merged.slides, 8);
+        assert_eq!(merged.skipped, ["Intro", "Agenda", "Ownership"]);
+    }
+
+    #[test]
+    fn test_rule_delimiters_are_kept_between_slides() {
+        let split = Split {
+            headings: false,
+            rules: true,
+            marker: None,
+        };
+        let a = "One\n\n---\n\nTwo\n".to_string();
+        let b = "Three\n\n---\n".to_string();
+        let merged = merge(&[a, b], &split).unwrap();
+        assert_eq!(merged.markdown, "One\n\n---\n\nTwo\n\n---\n\nThree\n");
+    }
+}
@@ -0,0 +1,116 @@
+use markdown::mdast::Node;
+use serde::Serialize;
+
+use crate::app::slide_title;
+use crate::frontmatter::Frontmatter;
+
+/// Facts about a deck that every subsystem reports the same way
+///
+/// Built once from the frontmatter and the slide headings; the title falls back to
+/// the first H1 when the frontmatter doesn't set one.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct DeckMeta {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    pub tags: Vec<String>,
+    pub slides: usize,
+    pub sections: Vec<SectionMeta>,
+}
+
+/// A slide that opens with an H1 or H2
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionMeta {
+    pub title: String,
+    pub depth: u8,
+    /// 1-based slide number
+    pub slide: usize,
+}
+
+impl DeckMeta {
+    pub fn new(frontmatter: &Frontmatter, slides: &[Vec<Node>]) -> Self {
+        let sections: Vec<SectionMeta> = slides
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slide)| match slide.first() {
+                Some(Node::Heading(heading)) => Some(SectionMeta {
+                    title: slide_title(slide),
+                    depth: heading.depth,
+                    slide: i + 1,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let title = frontmatter.title.clone().or_else(|| {
+            sections
+                .iter()
+                .find(|section| section.depth == 1)
+                .map(|section| section.title.clone())
+        });
+
+        DeckMeta {
+            title,
+            author: frontmatter.author.clone(),
+            date: frontmatter.date.clone(),
+            tags: frontmatter.tags.clone(),
+            slides: slides.len(),
+            sections,
+        }
+    }
+
+    /// Short description for the status bar, e.g. `Ownership · Jane Doe`
+    pub fn status(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.title, &self.author]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_meta_from_frontmatter_and_headings() {
+        let deck = parse_deck(
+            "+++\ntitle = \"Ownership\"\nauthor = \"Jane Doe\"\ndate = 2026-10-16\ntags = [\"rust\"]\n+++\n# Intro\n\n## Moves\n\nLoose slide\n",
+        )
+        .unwrap();
+        let meta = &deck.meta;
+        assert_eq!(meta.title.as_deref(), Some("Ownership"));
+        assert_eq!(meta.date.as_deref(), Some("2026-10-16"));
+        assert_eq!(meta.tags, vec!["rust"]);
+        assert_eq!(meta.slides, 2);
+        assert_eq!(
+            meta.sections,
+            vec![
+                SectionMeta {
+                    title: "Intro".to_string(),
+                    depth: 1,
+                    slide: 1
+                },
+                SectionMeta {
+                    title: "Moves".to_string(),
+                    depth: 2,
+                    slide: 2
+                },
+            ]
+        );
+        assert_eq!(meta.status().as_deref(), Some("Ownership · Jane Doe"));
+    }
+
+    #[test]
+    fn test_title_falls_back_to_first_h1() {
+        let deck = parse_deck("## Warmup\n\n# Main topic\n").unwrap();
+        assert_eq!(deck.meta.title.as_deref(), Some("Main topic"));
+        assert_eq!(deck.meta.author, None);
+
+        let deck = parse_deck("Just text\n").unwrap();
+        assert_eq!(deck.meta.status(), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Versioned deck model: a deck as markdeck splits it into slides
+//!
+//! This is a stable contract for editor preview plugins, web viewers and other
+//! tooling. Within a [`VERSION`] fields are only ever added, so readers should ignore
+//! fields they don't know, and unknown block kinds or directives read as `Other`.
+//! Removing, renaming or changing the meaning of anything bumps [`VERSION`].
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the model written by this markdeck
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Deck {
+    /// [`VERSION`] of the model the deck was written with
+    pub version: u32,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub slides: Vec<Slide>,
+}
+
+impl Deck {
+    /// Whether a reader built for this [`VERSION`] understands the deck
+    pub fn is_supported(&self) -> bool {
+        self.version == VERSION
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Slide {
+    /// Position in the deck, counting from 1 as markdeck shows it
+    pub number: usize,
+    /// First heading, or the first line when the slide has none
+    pub title: String,
+    /// Parts of the slide revealed one after another; a slide shown at once has one
+    pub fragments: Vec<Fragment>,
+    /// Instructions such as speaker notes, which aren't shown as content
+    #[serde(default)]
+    pub directives: Vec<Directive>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fragment {
+    pub blocks: Vec<Block>,
+}
+
+/// A top-level markdown element of a slide
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub kind: BlockKind,
+    /// Markdown source of the block, or its text for generated blocks such as a
+    /// table of contents
+    pub markdown: String,
+    /// Column the block is laid out in beside the others, counting from 1; none for
+    /// blocks spanning the slide
+    #[serde(default)]
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Heading,
+    Paragraph,
+    List,
+    Code,
+    Quote,
+    Table,
+    Rule,
+    /// Raw HTML other than directives
+    Html,
+    #[serde(other)]
+    Other,
+}
+
+/// Presentation instructions written as HTML comments, e.g. `<!-- notes: ... -->`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Directive {
+    /// Terms highlighted wherever they appear on the slide
+    Emphasize { terms: Vec<String> },
+    /// Replaced by the deck's table of contents
+    Toc,
+    /// The slide's first list holds answer options
+    Quiz,
+    /// Speaker notes
+    Notes { text: String },
+    /// Shell command whose output is shown as a code block
+    Screenshot { command: String },
+    /// The slide's content is centered; both ways in decks written before the ways
+    /// were added
+    Center {
+        #[serde(default = "yes")]
+        horizontal: bool,
+        #[serde(default = "yes")]
+        vertical: bool,
+    },
+    /// The slide is left out of the slide numbers
+    Unnumbered,
+    /// The first of the backup slides, which the talk itself skips
+    Appendix,
+    /// Shows a QR code of the deck's feedback link
+    Feedback,
+    #[serde(other)]
+    Other,
+}
+
+fn yes() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_kinds_and_fields_are_tolerated() {
+        let json = r#"{
+            "version": 1,
+            "title": "Ownership",
+            "author": null,
+            "date": null,
+            "theme": "dark",
+            "slides": [{
+                "number": 1,
+                "title": "Intro",
+                "fragments": [{"blocks": [{"kind": "diagram", "markdown": "a -> b"}]}],
+                "directives": [{"kind": "notes", "text": "Hi"}, {"kind": "countdown", "secs": 5}, {"kind": "center"}]
+            }]
+        }"#;
+        let deck: Deck = serde_json::from_str(json).unwrap();
+        assert!(deck.is_supported());
+        let slide = &deck.slides[0];
+        assert_eq!(slide.fragments[0].blocks[0].kind, BlockKind::Other);
+        assert_eq!(
+            slide.directives,
+            vec![
+                Directive::Notes {
+                    text: "Hi".to_string()
+                },
+                Directive::Other,
+                Directive::Center {
+                    horizontal: true,
+                    vertical: true
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! The modes that take over the keyboard, whose keys are bound per mode in
+//! `[keymaps.overview]`, `[keymaps.search]` and `[keymaps.table]`
+//!
+//! Typing text into the overview filter, the search prompt or a table filter stays
+//! wired to the keys no action is bound to.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mode {
+    Overview,
+    Search,
+    Table,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 3] = [Mode::Overview, Mode::Search, Mode::Table];
+
+    /// Name of the mode's `[keymaps.<name>]` table
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Overview => "overview",
+            Mode::Search => "search",
+            Mode::Table => "table",
+        }
+    }
+
+    /// The command that opens the mode, which its table can bind as `open` since
+    /// TOML can't have both `overview = [...]` and `[keymaps.overview]`
+    pub fn command(self) -> &'static str {
+        match self {
+            Mode::Overview => "overview",
+            Mode::Search => "search",
+            Mode::Table => "table_mode",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Mode> {
+        Mode::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// The mode's actions with their default keys
+    pub fn actions(self) -> &'static [(Action, &'static [&'static str])] {
+        match self {
+            Mode::Overview => &[
+                (Action::Confirm, &["Enter"]),
+                (Action::Cancel, &["Esc"]),
+                (Action::SelectNext, &["Right"]),
+                (Action::SelectPrevious, &["Left"]),
+                (Action::SelectBelow, &["Down"]),
+                (Action::SelectAbove, &["Up"]),
+                (Action::DeleteChar, &["Backspace"]),
+            ],
+            Mode::Search => &[
+                (Action::Confirm, &["Enter"]),
+                (Action::Cancel, &["Esc"]),
+                (Action::ToggleRegex, &["A-r"]),
+                (Action::ToggleCase, &["A-c"]),
+                (Action::ToggleWholeWord, &["A-w"]),
+                (Action::DeleteChar, &["Backspace"]),
+            ],
+            Mode::Table => &[
+                (Action::PreviousColumn, &["h", "Left"]),
+                (Action::NextColumn, &["l", "Right"]),
+                (Action::Sort, &["s"]),
+                (Action::Filter, &["/"]),
+                (Action::NextTable, &["Tab"]),
+                (Action::ScrollDown, &[]),
+                (Action::ScrollUp, &[]),
+                (Action::Confirm, &["Enter"]),
+                (Action::Cancel, &["Esc"]),
+                (Action::DeleteChar, &["Backspace"]),
+            ],
+        }
+    }
+
+    pub fn action(self, name: &str) -> Option<Action> {
+        self.actions()
+            .iter()
+            .map(|(action, _)| *action)
+            .find(|action| action.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Confirm,
+    Cancel,
+    DeleteChar,
+    SelectNext,
+    SelectPrevious,
+    SelectBelow,
+    SelectAbove,
+    ToggleRegex,
+    ToggleCase,
+    ToggleWholeWord,
+    PreviousColumn,
+    NextColumn,
+    Sort,
+    Filter,
+    NextTable,
+    ScrollDown,
+    ScrollUp,
+}
+
+impl Action {
+    /// Name of the action's entry in its mode's table
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::DeleteChar => "delete_char",
+            Action::SelectNext => "select_next",
+            Action::SelectPrevious => "select_previous",
+            Action::SelectBelow => "select_below",
+            Action::SelectAbove => "select_above",
+            Action::ToggleRegex => "toggle_regex",
+            Action::ToggleCase => "toggle_case",
+            Action::ToggleWholeWord => "toggle_whole_word",
+            Action::PreviousColumn => "previous_column",
+            Action::NextColumn => "next_column",
+            Action::Sort => "sort",
+            Action::Filter => "filter",
+            Action::NextTable => "next_table",
+            Action::ScrollDown => "scroll_down",
+            Action::ScrollUp => "scroll_up",
+        }
+    }
+
+    pub fn description(self, mode: Mode) -> &'static str {
+        match (self, mode) {
+            (Action::Confirm, Mode::Overview) => "Jump to the selected slide",
+            (Action::Confirm, Mode::Search) => "Search for the query",
+            (Action::Confirm, Mode::Table) => "Keep the filter being typed",
+            (Action::Cancel, Mode::Overview) => "Close the overview",
+            (Action::Cancel, Mode::Search) => "Close the search prompt",
+            (Action::Cancel, Mode::Table) => "Clear the filter being typed, or leave table mode",
+            (Action::DeleteChar, _) => "Delete the last character typed",
+            (Action::SelectNext, _) => "Select the next slide",
+            (Action::SelectPrevious, _) => "Select the previous slide",
+            (Action::SelectBelow, _) => "Select the slide below",
+            (Action::SelectAbove, _) => "Select the slide above",
+            (Action::ToggleRegex, _) => "Toggle regex search",
+            (Action::ToggleCase, _) => "Toggle case-sensitive search",
+            (Action::ToggleWholeWord, _) => "Toggle whole-word search",
+            (Action::PreviousColumn, _) => "Select the previous column",
+            (Action::NextColumn, _) => "Select the next column",
+            (Action::Sort, _) => "Sort by the column: ascending, descending, unsorted",
+            (Action::Filter, _) => "Type a filter for the rows",
+            (Action::NextTable, _) => "Select the next table on the slide",
+            (Action::ScrollDown, _) => "Scroll down",
+            (Action::ScrollUp, _) => "Scroll up",
+        }
+    }
+
+    /// The command whose keys the action keeps unless its mode's table binds it
+    pub fn inherits(self) -> Option<&'static str> {
+        match self {
+            Action::ScrollDown => Some("scroll_down"),
+            Action::ScrollUp => Some("scroll_up"),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! The mouse wheel and buttons, bound to commands in `[keymaps]` like keys
+
+use std::io::stdout;
+
+use anyhow::Result;
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, MouseButton, MouseEventKind},
+    execute,
+};
+
+/// Names of the mouse actions, as written in `[keymaps]`
+pub const BINDINGS: [&str; 5] = [
+    "WheelUp",
+    "WheelDown",
+    "LeftClick",
+    "MiddleClick",
+    "RightClick",
+];
+
+/// The `[keymaps]` name of a mouse event, if it's one that can be bound
+pub fn binding(kind: MouseEventKind) -> Option<&'static str> {
+    let name = match kind {
+        MouseEventKind::ScrollUp => "WheelUp",
+        MouseEventKind::ScrollDown => "WheelDown",
+        MouseEventKind::Down(MouseButton::Left) => "LeftClick",
+        MouseEventKind::Down(MouseButton::Middle) => "MiddleClick",
+        MouseEventKind::Down(MouseButton::Right) => "RightClick",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Reports mouse events for as long as it lives; selecting text in the terminal then
+/// takes holding Shift in most terminals
+pub struct Capture;
+
+impl Capture {
+    pub fn start() -> Result<Self> {
+        execute!(stdout(), EnableMouseCapture)?;
+        Ok(Capture)
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), DisableMouseCapture);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindable_mouse_events() {
+        assert_eq!(binding(MouseEventKind::ScrollDown), Some("WheelDown"));
+        assert_eq!(
+            binding(MouseEventKind::Down(MouseButton::Right)),
+            Some("RightClick")
+        );
+        assert_eq!(binding(MouseEventKind::Up(MouseButton::Left)), None);
+        assert_eq!(binding(MouseEventKind::Moved), None);
+    }
+}
@@ -0,0 +1,151 @@
+use markdown::mdast::Node;
+
+use crate::directives::{is_appendix, is_unnumbered};
+
+/// Slide numbers as shown on screen, which skip `unnumbered` slides such as title
+/// slides or section dividers
+///
+/// Slides from the one marked `appendix` on are backup slides: they're numbered on
+/// their own and left out of the progress, and moving forward stops before them.
+#[derive(Debug, Default)]
+pub struct SlideNumbers {
+    numbers: Vec<Option<usize>>,
+    /// Number of the last numbered slide
+    total: usize,
+    /// Index of the first backup slide
+    appendix: Option<usize>,
+}
+
+impl SlideNumbers {
+    /// Numbers the slides from `start`, usually 1
+    pub fn new(slides: &[Vec<Node>], start: usize) -> Self {
+        let appendix = slides.iter().position(|slide| is_appendix(slide));
+        let mut next = start;
+        let numbers: Vec<_> = slides[..appendix.unwrap_or(slides.len())]
+            .iter()
+            .map(|slide| {
+                if is_unnumbered(slide) {
+                    return None;
+                }
+                next += 1;
+                Some(next - 1)
+            })
+            .collect();
+        let total = numbers.iter().flatten().max().copied().unwrap_or(0);
+        SlideNumbers {
+            numbers,
+            total,
+            appendix,
+        }
+    }
+
+    pub fn number(&self, index: usize) -> Option<usize> {
+        self.numbers.get(index).copied().flatten()
+    }
+
+    /// What the slide at `index` is called on screen: `3`, or `appendix 1` for a backup
+    /// slide; unnumbered slides have no label
+    pub fn label(&self, index: usize) -> Option<String> {
+        match self.appendix {
+            Some(appendix) if index >= appendix => {
+                Some(format!("appendix {}", index - appendix + 1))
+            }
+            _ => self.number(index).map(|number| number.to_string()),
+        }
+    }
+
+    /// Index of the slide with this label
+    pub fn find_label(&self, label: &str) -> Option<usize> {
+        match label.strip_prefix("appendix ") {
+            Some(position) => {
+                let position = position.parse::<usize>().ok()?.checked_sub(1)?;
+                Some(self.appendix? + position)
+            }
+            None => {
+                let number = label.parse().ok()?;
+                self.numbers.iter().position(|n| *n == Some(number))
+            }
+        }
+    }
+
+    /// Index of the slide shown as `number`, as typed with `12G`; numbers past the
+    /// main deck go on into the appendix, which may not have that many slides
+    pub fn index(&self, number: usize) -> Option<usize> {
+        match self.numbers.iter().position(|n| *n == Some(number)) {
+            Some(index) => Some(index),
+            None => Some(self.appendix? + number.checked_sub(self.total + 1)?),
+        }
+    }
+
+    /// Slides before the appendix
+    pub fn main_slides(&self) -> usize {
+        self.numbers.len()
+    }
+
+    /// Whether moving forward from the slide at `index` would enter the appendix
+    pub fn ends_main_deck(&self, index: usize) -> bool {
+        self.appendix == Some(index + 1)
+    }
+
+    /// Share of the main deck shown up to the slide at `index`, full in the appendix
+    pub fn progress(&self, index: usize) -> f64 {
+        let main = self.numbers.len().max(1);
+        (index + 1).min(main) as f64 / main as f64
+    }
+
+    /// `3/12` for the slide at `index`, `-/12` on an unnumbered one, or
+    /// `appendix 1/2` on a backup slide
+    pub fn indicator(&self, index: usize, slides: usize) -> String {
+        match (self.appendix, self.number(index)) {
+            (Some(appendix), _) if index >= appendix => {
+                format!("appendix {}/{}", index - appendix + 1, slides - appendix)
+            }
+            (_, Some(number)) => format!("{}/{}", number, self.total),
+            (_, None) => format!("-/{}", self.total),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_unnumbered_slides_are_skipped() {
+        let deck = parse_deck(
+            "# Title\n<!-- unnumbered -->\n\n# A\n\n# B\n\n# Backup\n<!-- unnumbered -->\n",
+        )
+        .unwrap();
+        let numbers = SlideNumbers::new(&deck.slides, 1);
+        assert_eq!(numbers.indicator(0, 4), "-/2");
+        assert_eq!(numbers.indicator(1, 4), "1/2");
+        assert_eq!(numbers.indicator(2, 4), "2/2");
+        assert_eq!(numbers.number(3), None);
+
+        let numbers = SlideNumbers::new(&deck.slides, 0);
+        assert_eq!(numbers.indicator(2, 4), "1/1");
+    }
+
+    #[test]
+    fn test_appendix_is_numbered_on_its_own() {
+        let deck = parse_deck("# A\n\n# B\n\n# Backup\n<!-- appendix -->\n\n# More\n").unwrap();
+        let numbers = SlideNumbers::new(&deck.slides, 1);
+        assert_eq!(numbers.indicator(1, 4), "2/2");
+        assert_eq!(numbers.indicator(2, 4), "appendix 1/2");
+        assert_eq!(numbers.indicator(3, 4), "appendix 2/2");
+        assert!(numbers.ends_main_deck(1));
+        assert!(!numbers.ends_main_deck(2));
+        assert_eq!(numbers.progress(0), 0.5);
+        assert_eq!(numbers.progress(3), 1.0);
+
+        assert_eq!(numbers.label(1).as_deref(), Some("2"));
+        assert_eq!(numbers.label(3).as_deref(), Some("appendix 2"));
+        assert_eq!(numbers.find_label("appendix 2"), Some(3));
+        assert_eq!(numbers.find_label("2"), Some(1));
+        assert_eq!(numbers.find_label("3"), None);
+        assert_eq!(numbers.index(2), Some(1));
+        assert_eq!(numbers.index(4), Some(3));
+        assert_eq!(numbers.index(0), None);
+    }
+}
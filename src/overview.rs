@@ -0,0 +1,142 @@
+use markdown::mdast::Node;
+
+use crate::app::{slide_text, slide_title};
+
+/// Width the grid's cards take at least, borders included
+pub const CARD_WIDTH: u16 = 28;
+/// Height of a card: the title in the top border and a few lines of the slide
+pub const CARD_HEIGHT: u16 = 7;
+
+/// State of the slide overview: a filter query and the selected match in a grid of
+/// slide cards
+#[derive(Debug, Default)]
+pub struct Overview {
+    pub query: String,
+    /// Index into the slides matching the query
+    pub selected: usize,
+    /// Cards per row of the grid, as last rendered
+    pub columns: usize,
+}
+
+impl Overview {
+    /// Slide indices whose title or content contains the query, ignoring case
+    pub fn matches(&self, slides: &[Vec<Node>]) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        slides
+            .iter()
+            .enumerate()
+            .filter(|(_, slide)| {
+                query.is_empty()
+                    || slide_title(slide).to_lowercase().contains(&query)
+                    || slide_text(slide).to_lowercase().contains(&query)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        // Jump to the first match of the refined query
+        self.selected = 0;
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self, match_count: usize) {
+        if self.selected + 1 < match_count {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Moves to the card below, or the last one when the row below is shorter
+    pub fn select_below(&mut self, match_count: usize) {
+        let columns = self.columns.max(1);
+        if self.selected / columns + 1 < match_count.div_ceil(columns) {
+            self.selected = (self.selected + columns).min(match_count - 1);
+        }
+    }
+
+    pub fn select_above(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(self.columns.max(1))
+            .unwrap_or(self.selected);
+    }
+
+    /// Slide index of the current selection
+    pub fn selected_slide(&self, slides: &[Vec<Node>]) -> Option<usize> {
+        self.matches(slides).get(self.selected).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    fn slides() -> Vec<Vec<Node>> {
+        parse_deck("# Intro\nWelcome\n\n# Ownership\nBorrowing rules\n\n# Async\nFutures\n")
+            .unwrap()
+            .slides
+    }
+
+    #[test]
+    fn test_empty_query_matches_every_slide() {
+        let overview = Overview::default();
+        assert_eq!(overview.matches(&slides()), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_query_filters_by_title_and_content() {
+        let mut overview = Overview::default();
+        overview.push_char('b');
+        overview.push_char('o');
+        overview.push_char('r');
+        assert_eq!(overview.matches(&slides()), vec![1]);
+
+        overview.query = "ASYNC".to_string();
+        assert_eq!(overview.matches(&slides()), vec![2]);
+    }
+
+    #[test]
+    fn test_selection_stays_within_matches() {
+        let slides = slides();
+        let mut overview = Overview::default();
+        overview.select_next(3);
+        overview.select_next(3);
+        overview.select_next(3);
+        assert_eq!(overview.selected_slide(&slides), Some(2));
+
+        overview.push_char('i');
+        assert_eq!(overview.selected_slide(&slides), Some(0));
+    }
+
+    #[test]
+    fn test_grid_moves_by_rows() {
+        let mut overview = Overview {
+            columns: 3,
+            ..Overview::default()
+        };
+        overview.select_next(5);
+        overview.select_below(5);
+        assert_eq!(overview.selected, 4);
+        overview.select_below(5);
+        assert_eq!(overview.selected, 4);
+        overview.select_above();
+        assert_eq!(overview.selected, 1);
+        overview.select_above();
+        assert_eq!(overview.selected, 1);
+
+        // From the last full row down to the shorter one below
+        overview.selected = 2;
+        overview.select_below(4);
+        assert_eq!(overview.selected, 3);
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use markdown::mdast::Node;
+
+use crate::app::parse_markdown;
+
+/// A packed deck: self-contained markdown plus the number of assets embedded in it
+#[derive(Debug)]
+pub struct Packed {
+    pub markdown: String,
+    pub assets: usize,
+}
+
+/// Bundles a deck into a single markdown file by embedding its local images as
+/// base64 `data:` URIs. The theme travels along in the frontmatter as is.
+pub fn pack_deck(path: &Path) -> Result<Packed> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read deck: {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    pack_str(&content, base_dir)
+}
+
+pub fn pack_str(content: &str, base_dir: &Path) -> Result<Packed> {
+    let mdast = parse_markdown(content)?;
+    let mut image_labels = HashSet::new();
+    collect_image_labels(&mdast, &mut image_labels);
+    let mut references = vec![];
+    collect_asset_references(&mdast, content, &image_labels, &mut references);
+
+    // Replace back to front so earlier offsets stay valid
+    references.sort_by_key(|(range, _)| range.start);
+    let mut markdown = content.to_string();
+    for (range, url) in references.iter().rev() {
+        let asset = base_dir.join(url);
+        let bytes = std::fs::read(&asset)
+            .with_context(|| format!("Failed to read asset: {}", asset.display()))?;
+        let data_uri = format!(
+            "data:{};base64,{}",
+            mime_type(&asset),
+            STANDARD.encode(bytes)
+        );
+        markdown.replace_range(range.clone(), &data_uri);
+    }
+
+    Ok(Packed {
+        markdown,
+        assets: references.len(),
+    })
+}
+
+/// Default output path: `talk.md` packs into `talk.packed.md` next to it
+pub fn default_output(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.packed.md", stem))
+}
+
+/// Identifiers of the definitions images refer to, like `d` in `![diagram][d]`
+fn collect_image_labels(node: &Node, labels: &mut HashSet<String>) {
+    if let Node::ImageReference(reference) = node {
+        labels.insert(reference.identifier.clone());
+    }
+    for child in node.children().into_iter().flatten() {
+        collect_image_labels(child, labels);
+    }
+}
+
+/// Source byte ranges of local image URLs, both inline and in the reference
+/// definitions of `image_labels`, leaving the definitions only links use alone
+fn collect_asset_references(
+    node: &Node,
+    source: &str,
+    image_labels: &HashSet<String>,
+    references: &mut Vec<(Range<usize>, String)>,
+) {
+    let url = match node {
+        Node::Image(image) => Some(&image.url),
+        Node::Definition(definition) if image_labels.contains(&definition.identifier) => {
+            Some(&definition.url)
+        }
+        _ => None,
+    };
+    if let Some(url) = url
+        && is_local(url)
+        && let Some(position) = node.position()
+    {
+        // The URL follows the alt text or label, so search from the end of the node
+        let start = position.start.offset;
+        if let Some(i) = source[start..position.end.offset].rfind(url.as_str()) {
+            references.push((start + i..start + i + url.len(), url.clone()));
+        }
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_asset_references(child, source, image_labels, references);
+        }
+    }
+}
+
+fn is_local(url: &str) -> bool {
+    !url.is_empty() && !url.contains("://") && !url.starts_with("data:") && !url.starts_with('#')
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_images_are_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("logo.png"), b"png").unwrap();
+
+        let packed = pack_str("# Hi\n\n![logo.png](logo.png \"Logo\")\n", dir.path()).unwrap();
+        assert_eq!(packed.assets, 1);
+        assert_eq!(
+            packed.markdown,
+            "# Hi\n\n![logo.png](data:image/png;base64,cG5n \"Logo\")\n"
+        );
+    }
+
+    #[test]
+    fn test_reference_definitions_are_embedded() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.svg"), b"<svg/>").unwrap();
+
+        let packed = pack_str("![diagram][d]\n\n[d]: a.svg\n", dir.path()).unwrap();
+        assert_eq!(
+            packed.markdown,
+            "![diagram][d]\n\n[d]: data:image/svg+xml;base64,PHN2Zy8+\n"
+        );
+    }
+
+    #[test]
+    fn test_link_definitions_are_left_alone() {
+        let content =
+            "See the [notes] and [missing].\n\n[notes]: ./notes.pdf\n[missing]: gone.pdf\n";
+        let packed = pack_str(content, Path::new("")).unwrap();
+        assert_eq!(packed.assets, 0);
+        assert_eq!(packed.markdown, content);
+    }
+
+    #[test]
+    fn test_remote_and_embedded_images_are_left_alone() {
+        let content = "![a](https://example.com/a.png)\n![b](data:image/png;base64,AA==)\n";
+        let packed = pack_str(content, Path::new("")).unwrap();
+        assert_eq!(packed.assets, 0);
+        assert_eq!(packed.markdown, content);
+    }
+
+    #[test]
+    fn test_missing_asset_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(pack_str("![x](missing.png)\n", dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_default_output() {
+        assert_eq!(
+            default_output(Path::new("talks/rust.md")),
+            PathBuf::from("talks/rust.packed.md")
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! PowerPoint export: a minimal OOXML deck with one slide per markdeck slide
+//!
+//! Each slide gets its first heading as the title, its text as paragraphs and bullets,
+//! and every code block as a monospaced text box. Fonts and colors are left to
+//! PowerPoint's defaults.
+
+use std::fmt::Write as _;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use markdown::mdast::Node;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::app::Deck;
+use crate::presenter::is_presenter_block;
+
+/// 16:9 slides, in EMU (914400 per inch)
+const SLIDE_WIDTH: i64 = 12192000;
+const SLIDE_HEIGHT: i64 = 6858000;
+const MARGIN: i64 = 457200;
+const TITLE_HEIGHT: i64 = 914400;
+/// Line heights of 20pt text and 14pt code
+const TEXT_LINE: i64 = 304800;
+const CODE_LINE: i64 = 215900;
+
+const NAMESPACES: &str = r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#;
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+#[derive(Debug, PartialEq)]
+struct Paragraph {
+    text: String,
+    /// Nesting of a list item, or `None` for plain text
+    bullet: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Block {
+    Text(Vec<Paragraph>),
+    Code(String),
+}
+
+/// Default output path: `talk.md` exports to `talk.pptx` next to it
+pub fn default_output(path: &Path) -> PathBuf {
+    path.with_extension("pptx")
+}
+
+pub fn pptx(deck: &Deck) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let count = deck.slides.len();
+
+    let mut files = vec![
+        ("[Content_Types].xml".to_string(), content_types(count)),
+        ("_rels/.rels".to_string(), ROOT_RELS.to_string()),
+        ("docProps/core.xml".to_string(), core(deck)),
+        ("ppt/presentation.xml".to_string(), presentation(count)),
+        (
+            "ppt/_rels/presentation.xml.rels".to_string(),
+            presentation_rels(count),
+        ),
+        (
+            "ppt/slideMasters/slideMaster1.xml".to_string(),
+            SLIDE_MASTER.replace("{ns}", NAMESPACES),
+        ),
+        (
+            "ppt/slideMasters/_rels/slideMaster1.xml.rels".to_string(),
+            SLIDE_MASTER_RELS.to_string(),
+        ),
+        (
+            "ppt/slideLayouts/slideLayout1.xml".to_string(),
+            SLIDE_LAYOUT.replace("{ns}", NAMESPACES),
+        ),
+        (
+            "ppt/slideLayouts/_rels/slideLayout1.xml.rels".to_string(),
+            SLIDE_LAYOUT_RELS.to_string(),
+        ),
+        ("ppt/theme/theme1.xml".to_string(), THEME.to_string()),
+    ];
+    for (i, slide) in deck.slides.iter().enumerate() {
+        let (title, blocks) = slide_blocks(slide);
+        files.push((
+            format!("ppt/slides/slide{}.xml", i + 1),
+            slide_xml(title, &blocks),
+        ));
+        files.push((
+            format!("ppt/slides/_rels/slide{}.xml.rels", i + 1),
+            SLIDE_RELS.to_string(),
+        ));
+    }
+
+    for (name, content) in files {
+        zip.start_file(name, options)?;
+        zip.write_all(XML_HEADER.as_bytes())?;
+        zip.write_all(content.as_bytes())?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The slide's first heading, then its content as text and code blocks
+fn slide_blocks(slide: &[Node]) -> (Option<String>, Vec<Block>) {
+    let mut title = None;
+    let mut blocks = vec![];
+    let mut paragraphs = vec![];
+    for node in slide {
+        match node {
+            Node::Heading(_) if title.is_none() => title = Some(node.to_string()),
+            _ if is_presenter_block(node) => {}
+            Node::Code(code) => {
+                if !paragraphs.is_empty() {
+                    blocks.push(Block::Text(std::mem::take(&mut paragraphs)));
+                }
+                blocks.push(Block::Code(code.value.clone()));
+            }
+            // Directives, comments and raw HTML don't show on the slide either
+            Node::Html(_) => {}
+            _ => collect_paragraphs(node, None, &mut paragraphs),
+        }
+    }
+    if !paragraphs.is_empty() {
+        blocks.push(Block::Text(paragraphs));
+    }
+    (title, blocks)
+}
+
+fn collect_paragraphs(node: &Node, level: Option<usize>, paragraphs: &mut Vec<Paragraph>) {
+    match node {
+        Node::List(list) => {
+            let level = level.map_or(0, |level| level + 1);
+            for item in &list.children {
+                collect_paragraphs(item, Some(level), paragraphs);
+            }
+        }
+        Node::ListItem(item) => {
+            for (i, child) in item.children.iter().enumerate() {
+                match child {
+                    Node::List(_) => collect_paragraphs(child, level, paragraphs),
+                    // Only the item's first paragraph carries the bullet
+                    _ => paragraphs.push(Paragraph {
+                        text: child.to_string(),
+                        bullet: level.filter(|_| i == 0),
+                    }),
+                }
+            }
+        }
+        Node::Table(table) => {
+            for row in &table.children {
+                let cells: Vec<String> = row
+                    .children()
+                    .into_iter()
+                    .flatten()
+                    .map(|cell| cell.to_string())
+                    .collect();
+                paragraphs.push(Paragraph {
+                    text: cells.join(" | "),
+                    bullet: None,
+                });
+            }
+        }
+        Node::ThematicBreak(_) => {}
+        _ => {
+            let text = node.to_string();
+            if !text.trim().is_empty() {
+                paragraphs.push(Paragraph { text, bullet: None });
+            }
+        }
+    }
+}
+
+fn slide_xml(title: Option<String>, blocks: &[Block]) -> String {
+    let mut shapes = String::new();
+    let width = SLIDE_WIDTH - 2 * MARGIN;
+    if let Some(title) = title {
+        let paragraph = format!(
+            r#"<a:p><a:r><a:rPr lang="en-US" sz="3600" b="1"/><a:t>{}</a:t></a:r></a:p>"#,
+            escape(&title)
+        );
+        shapes.push_str(&text_box(
+            2,
+            "Title",
+            MARGIN,
+            MARGIN,
+            width,
+            TITLE_HEIGHT,
+            &paragraph,
+            false,
+        ));
+    }
+
+    let mut y = MARGIN + TITLE_HEIGHT;
+    for (i, block) in blocks.iter().enumerate() {
+        let id = i + 3;
+        let (paragraphs, height, code) = match block {
+            Block::Text(paragraphs) => {
+                let xml: String = paragraphs.iter().map(paragraph_xml).collect();
+                (xml, paragraphs.len() as i64 * TEXT_LINE, false)
+            }
+            Block::Code(code) => {
+                let xml: String = code.lines().map(code_line_xml).collect();
+                (xml, code.lines().count().max(1) as i64 * CODE_LINE, true)
+            }
+        };
+        let height = height + 2 * 91440;
+        let name = if code { "Code" } else { "Text" };
+        shapes.push_str(&text_box(
+            id,
+            name,
+            MARGIN,
+            y,
+            width,
+            height,
+            &paragraphs,
+            code,
+        ));
+        y += height + 91440;
+    }
+
+    format!(
+        r#"<p:sld {}><p:cSld><p:spTree><p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr/>{}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#,
+        NAMESPACES, shapes
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn text_box(
+    id: usize,
+    name: &str,
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+    paragraphs: &str,
+    shaded: bool,
+) -> String {
+    let fill = if shaded {
+        r#"<a:solidFill><a:srgbClr val="F2F2F2"/></a:solidFill>"#
+    } else {
+        ""
+    };
+    format!(
+        r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="{name} {id}"/><p:cNvSpPr txBox="1"/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{width}" cy="{height}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom>{fill}</p:spPr><p:txBody><a:bodyPr wrap="square"><a:normAutofit/></a:bodyPr><a:lstStyle/>{paragraphs}</p:txBody></p:sp>"#
+    )
+}
+
+fn paragraph_xml(paragraph: &Paragraph) -> String {
+    let properties = match paragraph.bullet {
+        Some(level) => format!(
+            r#"<a:pPr lvl="{}" marL="{}" indent="-285750"><a:buChar char="•"/></a:pPr>"#,
+            level,
+            342900 * (level + 1)
+        ),
+        None => "<a:pPr><a:buNone/></a:pPr>".to_string(),
+    };
+    format!(
+        r#"<a:p>{}<a:r><a:rPr lang="en-US" sz="2000"/><a:t>{}</a:t></a:r></a:p>"#,
+        properties,
+        escape(&paragraph.text)
+    )
+}
+
+fn code_line_xml(line: &str) -> String {
+    if line.is_empty() {
+        return r#"<a:p><a:endParaRPr lang="en-US" sz="1400"/></a:p>"#.to_string();
+    }
+    format!(
+        r#"<a:p><a:r><a:rPr lang="en-US" sz="1400"><a:latin typeface="Courier New"/></a:rPr><a:t>{}</a:t></a:r></a:p>"#,
+        escape(line)
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Line breaks inside a paragraph, e.g. from soft-wrapped markdown
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn content_types(count: usize) -> String {
+    let mut slides = String::new();
+    for i in 1..=count {
+        let _ = write!(
+            slides,
+            r#"<Override PartName="/ppt/slides/slide{}.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slide+xml"/>"#,
+            i
+        );
+    }
+    format!(
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/ppt/presentation.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml"/><Override PartName="/ppt/slideMasters/slideMaster1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideMaster+xml"/><Override PartName="/ppt/slideLayouts/slideLayout1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideLayout+xml"/><Override PartName="/ppt/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/><Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>{}</Types>"#,
+        slides
+    )
+}
+
+fn core(deck: &Deck) -> String {
+    let optional = |tag: &str, value: &Option<String>| {
+        value
+            .as_ref()
+            .map(|value| format!("<{tag}>{}</{tag}>", escape(value)))
+            .unwrap_or_default()
+    };
+    format!(
+        r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/">{}{}</cp:coreProperties>"#,
+        optional("dc:title", &deck.meta.title),
+        optional("dc:creator", &deck.meta.author)
+    )
+}
+
+fn presentation(count: usize) -> String {
+    let mut slides = String::new();
+    for i in 0..count {
+        let _ = write!(slides, r#"<p:sldId id="{}" r:id="rId{}"/>"#, 256 + i, i + 3);
+    }
+    format!(
+        r#"<p:presentation {}><p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst><p:sldIdLst>{}</p:sldIdLst><p:sldSz cx="{}" cy="{}"/><p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#,
+        NAMESPACES, slides, SLIDE_WIDTH, SLIDE_HEIGHT
+    )
+}
+
+fn presentation_rels(count: usize) -> String {
+    let mut relationships = String::from(
+        r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideMaster" Target="slideMasters/slideMaster1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme" Target="theme/theme1.xml"/>"#,
+    );
+    for i in 0..count {
+        let _ = write!(
+            relationships,
+            r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide{}.xml"/>"#,
+            i + 3,
+            i + 1
+        );
+    }
+    format!(
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}</Relationships>"#,
+        relationships
+    )
+}
+
+const ROOT_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="ppt/presentation.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#;
+
+const SLIDE_MASTER: &str = r#"<p:sldMaster {ns}><p:cSld><p:spTree><p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr/></p:spTree></p:cSld><p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" accent6="accent6" hlink="hlink" folHlink="folHlink"/><p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst></p:sldMaster>"#;
+
+const SLIDE_MASTER_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme" Target="../theme/theme1.xml"/></Relationships>"#;
+
+const SLIDE_LAYOUT: &str = r#"<p:sldLayout {ns} type="blank"><p:cSld name="Blank"><p:spTree><p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr/></p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#;
+
+const SLIDE_LAYOUT_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideMaster" Target="../slideMasters/slideMaster1.xml"/></Relationships>"#;
+
+const SLIDE_RELS: &str = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/></Relationships>"#;
+
+/// The smallest theme PowerPoint accepts: colors, fonts and three of each style
+const THEME: &str = r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="markdeck"><a:themeElements><a:clrScheme name="markdeck"><a:dk1><a:srgbClr val="000000"/></a:dk1><a:lt1><a:srgbClr val="FFFFFF"/></a:lt1><a:dk2><a:srgbClr val="1F2937"/></a:dk2><a:lt2><a:srgbClr val="F3F4F6"/></a:lt2><a:accent1><a:srgbClr val="0891B2"/></a:accent1><a:accent2><a:srgbClr val="C026D3"/></a:accent2><a:accent3><a:srgbClr val="16A34A"/></a:accent3><a:accent4><a:srgbClr val="CA8A04"/></a:accent4><a:accent5><a:srgbClr val="2563EB"/></a:accent5><a:accent6><a:srgbClr val="DC2626"/></a:accent6><a:hlink><a:srgbClr val="2563EB"/></a:hlink><a:folHlink><a:srgbClr val="7C3AED"/></a:folHlink></a:clrScheme><a:fontScheme name="markdeck"><a:majorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont><a:minorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont></a:fontScheme><a:fmtScheme name="markdeck"><a:fillStyleLst><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:fillStyleLst><a:lnStyleLst><a:ln w="6350"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln><a:ln w="12700"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln><a:ln w="19050"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln></a:lnStyleLst><a:effectStyleLst><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle></a:effectStyleLst><a:bgFillStyleLst><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:bgFillStyleLst></a:fmtScheme></a:themeElements></a:theme>"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+    use std::io::Read;
+
+    const DECK: &str = "+++\ntitle = \"Ownership & Borrowing\"\n+++\n# Intro\n<!-- notes: hi -->\nWhy <Rust>?\n\n- fast\n  - really\n- safe\n\n```rust\nfn main() {}\n\n```\n\n# Done\n";
+
+    #[test]
+    fn test_slide_blocks() {
+        let deck = parse_deck(DECK).unwrap();
+        let (title, blocks) = slide_blocks(&deck.slides[0]);
+        assert_eq!(title.as_deref(), Some("Intro"));
+        let bullet = |text: &str, level| Paragraph {
+            text: text.to_string(),
+            bullet: Some(level),
+        };
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Text(vec![
+                    Paragraph {
+                        text: "Why <Rust>?".to_string(),
+                        bullet: None
+                    },
+                    bullet("fast", 0),
+                    bullet("really", 1),
+                    bullet("safe", 0),
+                ]),
+                Block::Code("fn main() {}\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pptx_package() {
+        let deck = parse_deck(DECK).unwrap();
+        let bytes = pptx(&deck).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut read = |name: &str| {
+            let mut content = String::new();
+            archive
+                .by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+
+        assert!(read("[Content_Types].xml").contains("/ppt/slides/slide2.xml"));
+        assert!(read("ppt/presentation.xml").contains(r#"<p:sldId id="257" r:id="rId4"/>"#));
+        assert!(
+            read("docProps/core.xml").contains("<dc:title>Ownership &amp; Borrowing</dc:title>")
+        );
+        let slide = read("ppt/slides/slide1.xml");
+        assert!(slide.contains("<a:t>Intro</a:t>"));
+        assert!(slide.contains("<a:t>Why &lt;Rust&gt;?</a:t>"));
+        assert!(
+            slide.contains(r#"<a:latin typeface="Courier New"/></a:rPr><a:t>fn main() {}</a:t>"#)
+        );
+        assert!(!slide.contains("notes:"));
+    }
+
+    #[test]
+    fn test_default_output() {
+        assert_eq!(
+            default_output(Path::new("talks/rust.md")),
+            PathBuf::from("talks/rust.pptx")
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Remote images downloaded into the cache before presenting, so the venue's wifi
+//! can't take them away mid-talk
+//!
+//! Downloads go through `curl`, like copying goes through the platform's clipboard
+//! tool. `--offline` skips the network and refuses to start while anything is
+//! missing from the cache.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Result, bail};
+use markdown::mdast::Node;
+
+use crate::images::image_paragraph;
+
+/// Longest a single download may take before it's given up on
+const TIMEOUT_SECS: &str = "20";
+
+/// Downloaded assets, one file per URL
+#[derive(Debug, Default, Clone)]
+pub struct AssetCache {
+    /// None keeps remote images showing their alt text
+    dir: Option<PathBuf>,
+}
+
+impl AssetCache {
+    pub fn new(dir: PathBuf) -> Self {
+        AssetCache { dir: Some(dir) }
+    }
+
+    /// The cached download of a URL
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.path(url)?).ok()
+    }
+
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(format!("{:016x}", fnv1a(url))))
+    }
+}
+
+/// What a prefetch did, for the warnings printed before presenting
+#[derive(Debug, Default, PartialEq)]
+pub struct Prefetched {
+    pub fetched: usize,
+    pub cached: usize,
+    /// URLs that couldn't be downloaded, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Remote URLs of the images the deck shows, in order and without duplicates
+pub fn remote_urls(slides: &[Vec<Node>]) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    for image in slides.iter().flatten().filter_map(image_paragraph) {
+        let remote = image.url.starts_with("http://") || image.url.starts_with("https://");
+        if remote && !urls.contains(&image.url) {
+            urls.push(image.url.clone());
+        }
+    }
+    urls
+}
+
+/// Downloads the URLs missing from the cache; offline, fails on the first missing one
+pub fn prefetch(urls: &[String], cache: &AssetCache, offline: bool) -> Result<Prefetched> {
+    let mut prefetched = Prefetched::default();
+    for url in urls {
+        let Some(path) = cache.path(url) else {
+            continue;
+        };
+        if path.exists() {
+            prefetched.cached += 1;
+            continue;
+        }
+        if offline {
+            bail!("Not cached for --offline: {}; run once online first", url);
+        }
+        match download(url, &path) {
+            Ok(()) => prefetched.fetched += 1,
+            Err(e) => prefetched.failed.push((url.clone(), e.to_string())),
+        }
+    }
+    Ok(prefetched)
+}
+
+/// Downloads to a temporary file first, so an interrupted download isn't cached
+fn download(url: &str, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("part");
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", TIMEOUT_SECS, "--output"])
+        .arg(&partial)
+        .arg(url)
+        .stdin(Stdio::null())
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(_) => bail!("curl not found"),
+    };
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&partial);
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// A hash that stays the same across Rust versions, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_remote_urls_of_image_slides() {
+        let deck = parse_deck(
+            "![a](https://x.example/a.png)\n\n![b](b.png)\n\n# Two\n\n![a](https://x.example/a.png)\n\n![c](http://x.example/c.png)\n\nInline ![d](https://x.example/d.png)\n",
+        )
+        .unwrap();
+        assert_eq!(
+            remote_urls(&deck.slides),
+            ["https://x.example/a.png", "http://x.example/c.png"]
+        );
+    }
+
+    #[test]
+    fn test_offline_uses_the_cache_and_fails_on_anything_else() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AssetCache::new(dir.path().to_path_buf());
+        let url = "https://x.example/a.png".to_string();
+        std::fs::write(cache.path(&url).unwrap(), b"png").unwrap();
+
+        let prefetched = prefetch(std::slice::from_ref(&url), &cache, true).unwrap();
+        assert_eq!(prefetched.cached, 1);
+        assert_eq!(cache.get(&url).unwrap(), b"png");
+
+        let missing = "https://x.example/b.png".to_string();
+        let error = prefetch(&[url, missing], &cache, true).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Not cached for --offline: https://x.example/b.png; run once online first"
+        );
+    }
+}
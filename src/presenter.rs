@@ -0,0 +1,84 @@
+//! Notes to self written in the slide, in a `:::presenter` container or a fenced
+//! `presenter` block
+//!
+//! They're shown in the speaker view with the notes and kept in the script export, but
+//! the audience never sees them and other exports leave them out.
+
+use std::borrow::Cow;
+
+use markdown::mdast::{Code, Node};
+
+const LANG: &str = "presenter";
+
+/// Rewrites `:::presenter` containers into `~~~presenter` fences, which markdown
+/// parses, keeping every line its length so source positions stay valid
+pub fn desugar(source: &str) -> Cow<'_, str> {
+    if !source.contains(":::") {
+        return Cow::Borrowed(source);
+    }
+
+    let mut desugared = String::with_capacity(source.len());
+    let mut fence: Option<&str> = None;
+    let mut in_container = false;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        let container = trimmed.strip_prefix(":::");
+        if in_container && trimmed == ":::" {
+            in_container = false;
+        } else if !in_container
+            && fence.is_none()
+            && container.is_some_and(|rest| rest.trim() == LANG)
+        {
+            in_container = true;
+        } else {
+            // Containers shown inside code blocks are left alone
+            if !in_container {
+                let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+                match (fence, marker) {
+                    (None, Some(marker)) => fence = Some(marker),
+                    (Some(open), _) if trimmed == open => fence = None,
+                    _ => {}
+                }
+            }
+            desugared.push_str(line);
+            continue;
+        }
+        desugared.push_str("~~~");
+        desugared.push_str(&line[3..]);
+    }
+    Cow::Owned(desugared)
+}
+
+pub fn as_presenter_block(node: &Node) -> Option<&Code> {
+    match node {
+        Node::Code(code) if code.lang.as_deref() == Some(LANG) => Some(code),
+        _ => None,
+    }
+}
+
+pub fn is_presenter_block(node: &Node) -> bool {
+    as_presenter_block(node).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_containers_become_presenter_blocks() {
+        let source = "# A\n\n:::presenter\nMention the **demo**\n:::\n\n```md\n:::presenter\n```\n";
+        let desugared = desugar(source);
+        assert_eq!(desugared.len(), source.len());
+        assert!(desugared.contains("~~~presenter\nMention the **demo**\n~~~\n"));
+        assert!(desugared.contains("```md\n:::presenter\n```"));
+
+        let deck = parse_deck(source).unwrap();
+        let blocks: Vec<&str> = deck.slides[0]
+            .iter()
+            .filter_map(as_presenter_block)
+            .map(|code| code.value.as_str())
+            .collect();
+        assert_eq!(blocks, ["Mention the **demo**"]);
+    }
+}
@@ -0,0 +1,116 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// System allocator that counts allocations for the `--profile` overlay
+pub struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Allocations made by the process so far
+pub fn allocation_count() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// Timings of the last frame, shown in the `--profile` overlay
+#[derive(Debug, Default)]
+pub struct Profiler {
+    /// Reading and parsing the deck, measured once at startup
+    pub load: Duration,
+    /// Turning the slide into styled lines
+    pub layout: Duration,
+    /// The whole frame, layout included
+    pub frame: Duration,
+    pub allocations: usize,
+    pub frames: u64,
+}
+
+impl Profiler {
+    pub fn new(load: Duration) -> Self {
+        Profiler {
+            load,
+            ..Profiler::default()
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("load    {:>8.2}ms", millis(self.load)),
+            format!("layout  {:>8.2}ms", millis(self.layout)),
+            format!(
+                "draw    {:>8.2}ms",
+                millis(self.frame.saturating_sub(self.layout))
+            ),
+            format!("allocs  {:>10}", self.allocations),
+            format!("frame   {:>10}", self.frames),
+        ]
+    }
+}
+
+/// Measures one frame from `start` until `finish`
+pub struct FrameTimer {
+    started: Instant,
+    allocations: usize,
+}
+
+impl FrameTimer {
+    pub fn start() -> Self {
+        FrameTimer {
+            started: Instant::now(),
+            allocations: allocation_count(),
+        }
+    }
+
+    pub fn finish(self, profiler: &mut Profiler) {
+        profiler.frame = self.started.elapsed();
+        profiler.allocations = allocation_count() - self.allocations;
+        profiler.frames += 1;
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocations_are_counted() {
+        let before = allocation_count();
+        std::hint::black_box(Vec::<u8>::with_capacity(64));
+        assert!(allocation_count() > before);
+    }
+
+    #[test]
+    fn test_frame_timer_records_frame() {
+        let mut profiler = Profiler::new(Duration::from_millis(3));
+        let timer = FrameTimer::start();
+        std::hint::black_box(String::from("frame"));
+        timer.finish(&mut profiler);
+
+        assert_eq!(profiler.frames, 1);
+        assert!(profiler.allocations >= 1);
+        assert_eq!(profiler.lines()[0], "load        3.00ms");
+    }
+}
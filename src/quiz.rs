@@ -0,0 +1,139 @@
+use markdown::mdast::{List, Node};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::directives::{Directive, slide_directives};
+use crate::theme::Theme;
+
+/// A question asked with the `quiz` directive
+///
+/// The first list after the directive holds the options, with the correct ones
+/// checked (`- [x]`), and the first blockquote after the list is the explanation
+/// revealed once an answer is picked.
+#[derive(Debug)]
+pub struct Quiz<'a> {
+    pub options: &'a Node,
+    pub explanation: Option<&'a Node>,
+}
+
+impl<'a> Quiz<'a> {
+    pub fn find(slide: &'a [Node]) -> Option<Self> {
+        if !slide_directives(slide).contains(&Directive::Quiz) {
+            return None;
+        }
+        let list = slide
+            .iter()
+            .position(|node| matches!(node, Node::List(_)))?;
+        let explanation = slide[list..]
+            .iter()
+            .find(|node| matches!(node, Node::Blockquote(_)));
+        Some(Quiz {
+            options: &slide[list],
+            explanation,
+        })
+    }
+
+    fn list(&self) -> &'a List {
+        match self.options {
+            Node::List(list) => list,
+            _ => unreachable!("quiz options are always a list"),
+        }
+    }
+
+    pub fn option_count(&self) -> usize {
+        self.list().children.len()
+    }
+
+    /// Whether the 1-based option is marked as correct
+    pub fn is_correct(&self, option: usize) -> bool {
+        matches!(
+            self.list().children.get(option.wrapping_sub(1)),
+            Some(Node::ListItem(item)) if item.checked == Some(true)
+        )
+    }
+
+    /// Numbered options, marking the picked answer and the correct ones once answered
+    pub fn option_lines(&self, answer: Option<usize>, theme: &Theme) -> Vec<Line<'static>> {
+        let mut lines = vec![];
+        for (i, item) in self.list().children.iter().enumerate() {
+            let option = i + 1;
+            let correct = self.is_correct(option);
+            let (marker, style) = match answer {
+                Some(picked) if correct => (
+                    if picked == option { "✔" } else { " " },
+                    Style::default().fg(theme.colors.correct),
+                ),
+                Some(picked) if picked == option => {
+                    ("✘", Style::default().fg(theme.colors.incorrect))
+                }
+                _ => (" ", Style::default()),
+            };
+            let style = if answer == Some(option) {
+                style.add_modifier(Modifier::BOLD)
+            } else {
+                style
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} {}) ", marker, option), style),
+                Span::styled(item.to_string(), style),
+            ]));
+        }
+        lines.push(Line::raw(""));
+        if answer.is_none() {
+            lines.push(Line::styled(
+                format!("Press 1-{} to answer", self.option_count()),
+                Style::default().fg(theme.colors.footer),
+            ));
+            lines.push(Line::raw(""));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    const QUIZ: &str = "# Quiz\n<!-- quiz -->\nWhich type owns its data?\n\n- [ ] &str\n- [x] String\n\n> String owns a heap buffer.\n";
+
+    #[test]
+    fn test_quiz_options_and_explanation() {
+        let deck = parse_deck(QUIZ).unwrap();
+        let quiz = Quiz::find(&deck.slides[0]).unwrap();
+        assert_eq!(quiz.option_count(), 2);
+        assert!(!quiz.is_correct(1));
+        assert!(quiz.is_correct(2));
+        assert!(!quiz.is_correct(0));
+        assert_eq!(
+            quiz.explanation.unwrap().to_string(),
+            "String owns a heap buffer."
+        );
+    }
+
+    #[test]
+    fn test_lists_without_directive_are_not_quizzes() {
+        let deck = parse_deck("# A\n- [x] done\n").unwrap();
+        assert!(Quiz::find(&deck.slides[0]).is_none());
+    }
+
+    #[test]
+    fn test_answer_marks_options() {
+        let deck = parse_deck(QUIZ).unwrap();
+        let quiz = Quiz::find(&deck.slides[0]).unwrap();
+        let text = |answer| -> Vec<String> {
+            quiz.option_lines(answer, &Theme::default())
+                .iter()
+                .map(|line| line.to_string())
+                .collect()
+        };
+        assert_eq!(
+            text(None),
+            vec!["  1) &str", "  2) String", "", "Press 1-2 to answer", ""]
+        );
+        assert_eq!(text(Some(1)), vec!["✘ 1) &str", "  2) String", ""]);
+        assert_eq!(text(Some(2)), vec!["  1) &str", "✔ 2) String", ""]);
+    }
+}
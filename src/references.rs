@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use markdown::mdast::{Image, Link, Node};
+
+/// Link, image and footnote definitions of the whole deck, keyed by identifier
+#[derive(Debug, Default)]
+pub struct Definitions {
+    links: HashMap<String, (String, Option<String>)>,
+    footnotes: HashMap<String, Node>,
+}
+
+impl Definitions {
+    /// Takes the definitions out of the top-level nodes, wherever they appear in the file
+    pub fn extract(nodes: &mut Vec<Node>) -> Self {
+        let mut definitions = Definitions::default();
+        nodes.retain(|node| match node {
+            Node::Definition(definition) => {
+                definitions
+                    .links
+                    .entry(definition.identifier.clone())
+                    .or_insert((definition.url.clone(), definition.title.clone()));
+                false
+            }
+            Node::FootnoteDefinition(footnote) => {
+                definitions
+                    .footnotes
+                    .entry(footnote.identifier.clone())
+                    .or_insert_with(|| node.clone());
+                false
+            }
+            _ => true,
+        });
+        definitions
+    }
+
+    /// Turns reference-style links and images into regular ones, so they render the
+    /// same no matter which slide their definition ended up on
+    pub fn resolve(&self, node: &mut Node) {
+        match node {
+            Node::LinkReference(reference) => {
+                if let Some((url, title)) = self.links.get(&reference.identifier) {
+                    *node = Node::Link(Link {
+                        children: std::mem::take(&mut reference.children),
+                        position: reference.position.take(),
+                        url: url.clone(),
+                        title: title.clone(),
+                    });
+                }
+            }
+            Node::ImageReference(reference) => {
+                if let Some((url, title)) = self.links.get(&reference.identifier) {
+                    *node = Node::Image(Image {
+                        position: reference.position.take(),
+                        alt: std::mem::take(&mut reference.alt),
+                        url: url.clone(),
+                        title: title.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(children) = node.children_mut() {
+            for child in children {
+                self.resolve(child);
+            }
+        }
+    }
+
+    /// Appends the footnotes referenced on a slide to the end of that slide
+    pub fn attach_footnotes(&self, slide: &mut Vec<Node>) {
+        let mut identifiers = vec![];
+        for node in slide.iter() {
+            footnote_references(node, &mut identifiers);
+        }
+        for identifier in identifiers {
+            if let Some(footnote) = self.footnotes.get(&identifier) {
+                slide.push(footnote.clone());
+            }
+        }
+    }
+}
+
+/// Identifiers of footnotes referenced within `node`, in order of first use
+fn footnote_references(node: &Node, identifiers: &mut Vec<String>) {
+    if let Node::FootnoteReference(reference) = node
+        && !identifiers.contains(&reference.identifier)
+    {
+        identifiers.push(reference.identifier.clone());
+    }
+    if let Some(children) = node.children() {
+        for child in children {
+            footnote_references(child, identifiers);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::parse_deck;
+    use markdown::mdast::Node;
+
+    #[test]
+    fn test_reference_links_resolve_across_slides() {
+        let deck = parse_deck(
+            "# A\nSee [the docs][docs] and ![logo][img]\n\n# B\nText\n\n[docs]: https://example.com \"Docs\"\n[img]: logo.png\n",
+        )
+        .unwrap();
+
+        let Node::Paragraph(paragraph) = &deck.slides[0][1] else {
+            panic!("expected a paragraph");
+        };
+        assert!(matches!(
+            &paragraph.children[1],
+            Node::Link(link) if link.url == "https://example.com" && link.title.as_deref() == Some("Docs")
+        ));
+        assert!(matches!(
+            &paragraph.children[3],
+            Node::Image(image) if image.url == "logo.png" && image.alt == "logo"
+        ));
+        // The definitions themselves aren't slide content
+        assert_eq!(deck.slides[1].len(), 2);
+    }
+
+    #[test]
+    fn test_footnotes_are_attached_to_the_slides_using_them() {
+        let deck = parse_deck(
+            "# A\nClaim[^1] and again[^1]\n\n# B\nOther[^note]\n\n# C\nNone\n\n[^1]: Source one\n[^note]: Source two\n",
+        )
+        .unwrap();
+
+        let footnotes = |slide: &[Node]| -> Vec<String> {
+            slide
+                .iter()
+                .filter(|node| matches!(node, Node::FootnoteDefinition(_)))
+                .map(|node| node.to_string())
+                .collect()
+        };
+        assert_eq!(footnotes(&deck.slides[0]), vec!["Source one"]);
+        assert_eq!(footnotes(&deck.slides[1]), vec!["Source two"]);
+        assert!(footnotes(&deck.slides[2]).is_empty());
+    }
+
+    #[test]
+    fn test_undefined_reference_is_left_alone() {
+        let deck = parse_deck("# A\n[missing][nope]\n").unwrap();
+        assert_eq!(deck.slides[0][1].to_string(), "[missing][nope]");
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use markdown::mdast::Node;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::app::slide_title;
+
+/// Planned time per slide, declared in the frontmatter's `[timing]` table
+#[derive(Debug, Default, Deserialize)]
+pub struct Timing {
+    /// Time planned for slides not listed in `slides`
+    pub default_secs: Option<u64>,
+    /// Planned seconds keyed by slide title or 1-based slide number
+    #[serde(default)]
+    pub slides: HashMap<String, u64>,
+}
+
+impl Timing {
+    pub fn planned(&self, slides: &[Vec<Node>]) -> Vec<Option<Duration>> {
+        slides
+            .iter()
+            .enumerate()
+            .map(|(index, slide)| {
+                self.slides
+                    .get(&slide_title(slide))
+                    .or_else(|| self.slides.get(&(index + 1).to_string()))
+                    .copied()
+                    .or(self.default_secs)
+                    .map(Duration::from_secs)
+            })
+            .collect()
+    }
+}
+
+/// How the time spent on a slide compares to its plan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    OnTime,
+    /// Past 80% of the planned time
+    Closing,
+    Over,
+}
+
+impl Pace {
+    pub fn color(self) -> Color {
+        match self {
+            Pace::OnTime => Color::Green,
+            Pace::Closing => Color::Yellow,
+            Pace::Over => Color::Red,
+        }
+    }
+}
+
+/// Stopwatch for a `--rehearse` run, keeping the time spent on every slide
+#[derive(Debug)]
+pub struct Rehearsal {
+    titles: Vec<String>,
+    planned: Vec<Option<Duration>>,
+    actual: Vec<Duration>,
+    slide: usize,
+    since: Instant,
+}
+
+impl Rehearsal {
+    pub fn new(slides: &[Vec<Node>], timing: &Timing, now: Instant) -> Self {
+        Rehearsal {
+            titles: slides.iter().map(|slide| slide_title(slide)).collect(),
+            planned: timing.planned(slides),
+            actual: vec![Duration::ZERO; slides.len()],
+            slide: 0,
+            since: now,
+        }
+    }
+
+    /// Follows an edited deck, keeping the time spent on the slides at the same
+    /// positions
+    pub fn reload(&mut self, slides: &[Vec<Node>], timing: &Timing) {
+        self.titles = slides.iter().map(|slide| slide_title(slide)).collect();
+        self.planned = timing.planned(slides);
+        self.actual.resize(slides.len(), Duration::ZERO);
+        self.slide = self.slide.min(slides.len().saturating_sub(1));
+    }
+
+    /// Books the time since the last call to the slide that was shown until now
+    pub fn observe(&mut self, slide: usize, now: Instant) {
+        if slide != self.slide {
+            self.actual[self.slide] += now - self.since;
+            self.slide = slide;
+            self.since = now;
+        }
+    }
+
+    /// Time spent on the current slide, earlier visits included
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.actual[self.slide] + now.saturating_duration_since(self.since)
+    }
+
+    pub fn pace(&self, now: Instant) -> Option<Pace> {
+        let planned = self.planned[self.slide]?;
+        let elapsed = self.elapsed(now);
+        Some(if elapsed > planned {
+            Pace::Over
+        } else if elapsed * 5 > planned * 4 {
+            Pace::Closing
+        } else {
+            Pace::OnTime
+        })
+    }
+
+    /// Live timer for the header, e.g. `0:42 / 1:00`
+    pub fn status(&self, now: Instant) -> String {
+        let elapsed = clock(self.elapsed(now));
+        match self.planned[self.slide] {
+            Some(planned) => format!("{} / {}", elapsed, clock(planned)),
+            None => elapsed,
+        }
+    }
+
+    /// When the timer next shows a different second
+    pub fn next_tick(&self, now: Instant) -> Instant {
+        now + Duration::from_secs(1) - Duration::from_nanos(self.elapsed(now).subsec_nanos().into())
+    }
+
+    /// Planned versus actual time per slide, printed once the rehearsal ends
+    pub fn report(&mut self, now: Instant) -> String {
+        self.actual[self.slide] += now.saturating_duration_since(self.since);
+        self.since = now;
+
+        let width = self
+            .titles
+            .iter()
+            .map(|title| title.chars().count())
+            .fold("Total".len(), usize::max);
+        let mut report = format!(
+            "{:>3}  {:<width$}  {:>7}  {:>7}  {:>7}\n",
+            "#", "Slide", "Planned", "Actual", "Diff"
+        );
+        for (index, title) in self.titles.iter().enumerate() {
+            let planned = self.planned[index];
+            let actual = self.actual[index];
+            let _ = writeln!(
+                report,
+                "{:>3}  {:<width$}  {:>7}  {:>7}  {:>7}",
+                index + 1,
+                title,
+                planned.map_or("-".to_string(), clock),
+                clock(actual),
+                planned.map_or(String::new(), |planned| difference(actual, planned)),
+            );
+        }
+        let planned: Duration = self.planned.iter().flatten().sum();
+        let actual: Duration = self.actual.iter().sum();
+        let _ = writeln!(
+            report,
+            "{:>3}  {:<width$}  {:>7}  {:>7}  {:>7}",
+            "",
+            "Total",
+            clock(planned),
+            clock(actual),
+            difference(actual, planned),
+        );
+        report
+    }
+
+    /// Whole seconds spent on each slide, as booked by the last `report`
+    pub fn seconds(&self) -> Vec<u64> {
+        self.actual.iter().map(Duration::as_secs).collect()
+    }
+}
+
+/// Minutes and seconds, e.g. `12:05`
+pub fn clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn difference(actual: Duration, planned: Duration) -> String {
+    if actual >= planned {
+        format!("+{}", clock(actual - planned))
+    } else {
+        format!("-{}", clock(planned - actual))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    const DECK: &str = "+++\n[timing]\ndefault_secs = 60\n[timing.slides]\nIntro = 30\n3 = 120\n+++\n# Intro\n# Body\n# End\n";
+
+    #[test]
+    fn test_planned_times_by_title_number_and_default() {
+        let deck = parse_deck(DECK).unwrap();
+        let planned = deck.frontmatter.timing.planned(&deck.slides);
+        assert_eq!(
+            planned,
+            vec![
+                Some(Duration::from_secs(30)),
+                Some(Duration::from_secs(60)),
+                Some(Duration::from_secs(120)),
+            ]
+        );
+        assert_eq!(Timing::default().planned(&deck.slides), vec![None; 3]);
+    }
+
+    #[test]
+    fn test_time_is_booked_per_slide_and_paced() {
+        let deck = parse_deck(DECK).unwrap();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut rehearsal = Rehearsal::new(&deck.slides, &deck.frontmatter.timing, start);
+
+        assert_eq!(rehearsal.pace(at(10)), Some(Pace::OnTime));
+        assert_eq!(rehearsal.pace(at(27)), Some(Pace::Closing));
+        assert_eq!(rehearsal.pace(at(31)), Some(Pace::Over));
+        assert_eq!(rehearsal.status(at(31)), "0:31 / 0:30");
+
+        rehearsal.observe(1, at(40));
+        rehearsal.observe(0, at(50));
+        // Returning to a slide keeps counting from its earlier visit
+        assert_eq!(rehearsal.status(at(50)), "0:40 / 0:30");
+    }
+
+    #[test]
+    fn test_report_compares_planned_and_actual() {
+        let deck = parse_deck(DECK).unwrap();
+        let start = Instant::now();
+        let mut rehearsal = Rehearsal::new(&deck.slides, &deck.frontmatter.timing, start);
+        rehearsal.observe(1, start + Duration::from_secs(45));
+        rehearsal.observe(2, start + Duration::from_secs(95));
+
+        let report = rehearsal.report(start + Duration::from_secs(155));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "  #  Slide  Planned   Actual     Diff");
+        assert_eq!(lines[1], "  1  Intro     0:30     0:45    +0:15");
+        assert_eq!(lines[2], "  2  Body      1:00     0:50    -0:10");
+        assert_eq!(lines[3], "  3  End       2:00     1:00    -1:00");
+        assert_eq!(lines[4], "     Total     3:30     2:35    -0:55");
+    }
+}
@@ -0,0 +1,140 @@
+//! Highlights what a live reload changed on the current slide, for a second after it
+//!
+//! The rows of the slide as rendered before the reload are compared with the rows
+//! rendered after it: new or edited rows are marked as added, and the row that took
+//! the place of deleted ones as removed.
+
+use std::time::{Duration, Instant};
+
+use ratatui::buffer::Buffer;
+
+/// How long the changes stay highlighted
+pub const DURATION: Duration = Duration::from_secs(1);
+
+/// Rows of the reloaded slide, in its rendered buffer
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub added: Vec<u16>,
+    /// Rows that took the place of deleted ones, or the last row after deleting the end
+    pub removed: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub struct ReloadDiff {
+    slide: usize,
+    before: Vec<String>,
+    started: Instant,
+    /// Set once the reloaded slide was rendered
+    changes: Option<Changes>,
+}
+
+impl ReloadDiff {
+    /// Remembers the slide at `slide` as rendered into `before`, ahead of a reload
+    pub fn new(slide: usize, before: &Buffer, now: Instant) -> Self {
+        ReloadDiff {
+            slide,
+            before: rows(before),
+            started: now,
+            changes: None,
+        }
+    }
+
+    /// Compares the slide rendered after the reload with the one before, once
+    pub fn compare(&mut self, slide: usize, after: &Buffer) {
+        if slide == self.slide && self.changes.is_none() {
+            self.changes = Some(diff_rows(&self.before, &rows(after)));
+        }
+    }
+
+    /// Changes to highlight on the slide at `slide`
+    pub fn changes(&self, slide: usize) -> Option<&Changes> {
+        self.changes.as_ref().filter(|_| slide == self.slide)
+    }
+
+    /// Time left on the highlight
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        DURATION
+            .checked_sub(now.duration_since(self.started))
+            .filter(|d| !d.is_zero())
+    }
+}
+
+fn rows(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Rows of `after` missing from `before`, by their longest common subsequence
+pub fn diff_rows(before: &[String], after: &[String]) -> Changes {
+    // common[i][j]: length of the longest common subsequence of before[i..] and after[j..]
+    let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    // Deletions go first, so an edited row is marked as both removed and added
+    let mut changes = Changes::default();
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            i += 1;
+            j += 1;
+        } else if j < after.len() && (i == before.len() || common[i][j + 1] > common[i + 1][j]) {
+            changes.added.push(j as u16);
+            j += 1;
+        } else {
+            // Deleted at the end of the slide, the mark goes on its last row
+            let row = j.min(after.len().saturating_sub(1)) as u16;
+            if changes.removed.last() != Some(&row) {
+                changes.removed.push(row);
+            }
+            i += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_added_and_removed_rows() {
+        let before = lines("# Title\n\nold line\nkept\ndropped\ndropped too");
+        let after = lines("# Title\n\nnew line\nkept");
+        let changes = diff_rows(&before, &after);
+        assert_eq!(changes.added, [2]);
+        assert_eq!(changes.removed, [2, 3]);
+        assert_eq!(diff_rows(&after, &after), Changes::default());
+    }
+
+    #[test]
+    fn test_changes_are_compared_once_on_the_same_slide() {
+        let now = Instant::now();
+        let mut diff = ReloadDiff::new(1, &Buffer::with_lines(["a", "b"]), now);
+        diff.compare(0, &Buffer::with_lines(["x"]));
+        assert_eq!(diff.changes(1), None);
+        diff.compare(1, &Buffer::with_lines(["a", "c", "b"]));
+        diff.compare(1, &Buffer::with_lines(["a"]));
+        assert_eq!(diff.changes(1).unwrap().added, [1]);
+        assert_eq!(diff.changes(0), None);
+        assert!(diff.remaining(now).is_some());
+        assert_eq!(diff.remaining(now + DURATION), None);
+    }
+}
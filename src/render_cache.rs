@@ -0,0 +1,204 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget},
+};
+use tui_scrollview::ScrollViewState;
+
+use crate::search::SearchOptions;
+use crate::table::TableView;
+
+/// Everything a rendered slide depends on besides the deck and theme
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderKey {
+    pub slide: usize,
+    pub width: u16,
+    /// Query and options of the highlighted search
+    pub search: Option<(String, SearchOptions)>,
+    /// Whether the title is flashed
+    pub flash: bool,
+    /// Whether the slide is zoomed out
+    pub condensed: bool,
+    /// Whether `<details>` sections are expanded
+    pub expanded: bool,
+    /// Option picked on a quiz slide
+    pub answer: Option<usize>,
+    /// Checklist steps ticked off on the slide
+    pub ticked: usize,
+    /// Pauses passed on the slide, see `directives::revealed_part`
+    pub revealed: usize,
+    /// Sorting and filter of a table in table mode
+    pub table: Option<TableView>,
+}
+
+/// The current slide rendered at full height, so scrolling only copies the visible rows
+///
+/// Any change to the key re-renders the slide; changes it can't see, such as a new
+/// theme or reloaded slides, have to `invalidate` the cache.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    key: Option<RenderKey>,
+    buffer: Buffer,
+    heading_rows: u16,
+}
+
+impl RenderCache {
+    pub fn is_valid(&self, key: &RenderKey) -> bool {
+        self.key.as_ref() == Some(key)
+    }
+
+    pub fn store(&mut self, key: RenderKey, buffer: Buffer, heading_rows: u16) {
+        self.key = Some(key);
+        self.buffer = buffer;
+        self.heading_rows = heading_rows;
+    }
+
+    /// Slide the buffer was rendered from, unless invalidated
+    pub fn slide(&self) -> Option<usize> {
+        self.key.as_ref().map(|key| key.slide)
+    }
+
+    pub fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Rows through the heading the slide opens with
+    pub fn heading_rows(&self) -> u16 {
+        self.heading_rows
+    }
+}
+
+/// Copies the rows of `content` visible at the current offset into `area`, with a
+/// scrollbar when the content is taller than the area
+///
+/// The first `pinned` rows stay at the top of `area` and the rest scrolls beneath
+/// them. Clamps the offset the same way `tui_scrollview::ScrollView` does.
+pub fn blit(
+    content: &Buffer,
+    pinned: u16,
+    area: Rect,
+    buf: &mut Buffer,
+    state: &mut ScrollViewState,
+) {
+    let pinned = pinned.min(content.area.height).min(area.height);
+    for row in 0..pinned {
+        for column in 0..area.width.min(content.area.width) {
+            buf[(area.x + column, area.y + row)] = content[(column, row)].clone();
+        }
+    }
+    let area = Rect {
+        y: area.y + pinned,
+        height: area.height - pinned,
+        ..area
+    };
+
+    let height = content.area.height - pinned;
+    let mut offset = state.offset();
+    offset.x = 0;
+    offset.y = if height < area.height {
+        0
+    } else {
+        offset
+            .y
+            .min(height.saturating_sub(area.height.saturating_sub(1)))
+    };
+    state.set_offset(offset);
+
+    let mut width = area.width.min(content.area.width);
+    if height > area.height {
+        let mut scrollbar_state =
+            ScrollbarState::new((height - area.height) as usize).position(offset.y as usize);
+        Scrollbar::new(ScrollbarOrientation::VerticalRight).render(area, buf, &mut scrollbar_state);
+        width = width.min(area.width.saturating_sub(1));
+    }
+
+    for row in 0..area.height {
+        let y = offset.y + row;
+        if y >= height {
+            break;
+        }
+        for column in 0..width {
+            buf[(area.x + column, area.y + row)] = content[(column, pinned + y)].clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Position;
+
+    fn content(rows: &[&str]) -> Buffer {
+        Buffer::with_lines(rows.iter().copied())
+    }
+
+    fn key(slide: usize) -> RenderKey {
+        RenderKey {
+            slide,
+            width: 80,
+            search: None,
+            flash: false,
+            condensed: false,
+            expanded: false,
+            answer: None,
+            ticked: 0,
+            revealed: 0,
+            table: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_is_keyed_and_can_be_invalidated() {
+        let mut cache = RenderCache::default();
+        assert!(!cache.is_valid(&key(0)));
+
+        cache.store(key(0), content(&["a"]), 0);
+        assert!(cache.is_valid(&key(0)));
+        assert!(!cache.is_valid(&key(1)));
+
+        cache.invalidate();
+        assert!(!cache.is_valid(&key(0)));
+    }
+
+    #[test]
+    fn test_blit_copies_visible_rows() {
+        let content = content(&["one", "two", "three", "four"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+        let mut state = ScrollViewState::with_offset(Position::new(0, 1));
+        blit(&content, 0, buf.area, &mut buf, &mut state);
+
+        assert_eq!(buf[(0, 0)].symbol(), "t");
+        assert_eq!(buf[(2, 0)].symbol(), "o");
+        assert_eq!(buf[(2, 1)].symbol(), "r");
+    }
+
+    #[test]
+    fn test_blit_clamps_offset() {
+        let content = content(&["one", "two", "three"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+        let mut state = ScrollViewState::with_offset(Position::new(0, 50));
+        blit(&content, 0, buf.area, &mut buf, &mut state);
+        assert_eq!(state.offset().y, 2);
+
+        let mut tall = Buffer::empty(Rect::new(0, 0, 6, 10));
+        blit(&content, 0, tall.area, &mut tall, &mut state);
+        assert_eq!(state.offset().y, 0);
+    }
+
+    #[test]
+    fn test_blit_keeps_pinned_rows_in_place() {
+        let content = content(&["title", "", "one", "two", "three"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 3));
+        let mut state = ScrollViewState::with_offset(Position::new(0, 1));
+        blit(&content, 2, buf.area, &mut buf, &mut state);
+
+        assert_eq!(buf[(0, 0)].symbol(), "t");
+        assert_eq!(buf[(1, 0)].symbol(), "i");
+        assert_eq!(buf[(0, 2)].symbol(), "t");
+        assert_eq!(buf[(1, 2)].symbol(), "w");
+    }
+}
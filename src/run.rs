@@ -0,0 +1,231 @@
+//! Running a slide's code blocks live: `x` pipes a block's code to the interpreter
+//! set for its language under `[code.interpreters]`, and shows what it printed in a
+//! panel under the block until the deck is reloaded
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use markdown::mdast::{Code, Node};
+
+use crate::syntax::Languages;
+
+/// Marks the code blocks holding a run's output
+const META: &str = "output";
+
+/// How long a block may run before it's killed, as the slides wait for it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interpreters for the languages that read a program from stdin out of the box
+const DEFAULT_INTERPRETERS: [(&str, &str); 5] = [
+    ("bash", "bash"),
+    ("sh", "sh"),
+    ("python", "python3"),
+    ("javascript", "node"),
+    ("ruby", "ruby"),
+];
+
+/// The command a fence language runs with, configured first by the language as
+/// written, then by what it's an alias of
+pub fn interpreter<'a>(languages: &'a Languages, lang: &str) -> Option<&'a str> {
+    let resolved = languages.resolve(lang);
+    languages
+        .interpreters
+        .get(lang)
+        .or_else(|| languages.interpreters.get(resolved))
+        .map(String::as_str)
+        .or_else(|| {
+            DEFAULT_INTERPRETERS
+                .iter()
+                .find(|(language, _)| *language == resolved)
+                .map(|(_, command)| *command)
+        })
+}
+
+pub fn is_output(node: &Node) -> bool {
+    matches!(node, Node::Code(code) if code.meta.as_deref() == Some(META))
+}
+
+/// Runs the slide's `nth` code block that has an interpreter, counting from 1, in
+/// `dir`, putting its output under it in place of the previous run's
+pub fn run(slide: &mut Vec<Node>, nth: usize, languages: &Languages, dir: &Path) -> Result<()> {
+    let runnable: Vec<(usize, String, &str)> = slide
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| match node {
+            Node::Code(code) if code.meta.as_deref() != Some(META) => {
+                let command = interpreter(languages, code.lang.as_deref()?)?;
+                Some((index, code.value.clone(), command))
+            }
+            _ => None,
+        })
+        .collect();
+    if runnable.is_empty() {
+        bail!("No code block on this slide has an interpreter; set one under [code.interpreters]");
+    }
+    let Some((index, code, command)) = runnable.get(nth.saturating_sub(1)) else {
+        bail!("This slide has {} code blocks to run", runnable.len());
+    };
+    let output = execute(command, code, dir, TIMEOUT);
+    let block = Node::Code(Code {
+        value: match output.trim_end() {
+            "" => "(no output)".to_string(),
+            output => output.to_string(),
+        },
+        position: None,
+        lang: None,
+        meta: Some(META.to_string()),
+    });
+    match slide.get(index + 1) {
+        Some(next) if is_output(next) => slide[index + 1] = block,
+        _ => slide.insert(index + 1, block),
+    }
+    Ok(())
+}
+
+/// Stdout, then stderr, then the exit status if it failed, like `run_shell`; a
+/// program still running after `timeout` is killed, keeping what it printed so far
+fn execute(command: &str, code: &str, dir: &Path, timeout: Duration) -> String {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => return format!("Failed to run `{}`: {}", command, e),
+    };
+    let deadline = Instant::now() + timeout;
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+    if let Some(mut stdin) = child.stdin.take() {
+        // Written from a thread of its own, so a program that doesn't read all of a big
+        // block can't hold up the deadline; exiting without reading it is no error, and
+        // one waiting for more input gets none once stdin is dropped
+        let code = code.to_string();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(code.as_bytes());
+        });
+    }
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Err(e) => return format!("Failed to run `{}`: {}", command, e),
+        }
+    };
+    // Pipes still held open by a killed program's children aren't waited for
+    let read = |output: &Arc<Mutex<Vec<u8>>>| {
+        if status.is_some() {
+            let until = Instant::now() + Duration::from_millis(100);
+            while Arc::strong_count(output) > 1 && Instant::now() < until {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+        String::from_utf8_lossy(&output.lock().unwrap()).into_owned()
+    };
+    let mut text = read(&stdout);
+    text.push_str(&read(&stderr));
+    match status {
+        Some(status) if !status.success() => text.push_str(&format!("\n[{}]", status)),
+        Some(_) => {}
+        None => text.push_str(&format!("\n[stopped after {:?}]", timeout)),
+    }
+    text
+}
+
+/// Collects what a pipe delivers as it arrives, so it can be taken at any time
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> Arc<Mutex<Vec<u8>>> {
+    let output = Arc::new(Mutex::new(vec![]));
+    if let Some(mut pipe) = pipe {
+        let output = Arc::clone(&output);
+        std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = pipe.read(&mut buffer) {
+                output.lock().unwrap().extend_from_slice(&buffer[..read]);
+            }
+        });
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_interpreter_by_language() {
+        let mut languages = Languages::default();
+        assert_eq!(interpreter(&languages, "python3"), Some("python3"));
+        assert_eq!(interpreter(&languages, "console"), Some("bash"));
+        assert_eq!(interpreter(&languages, "rust"), None);
+        languages
+            .interpreters
+            .insert("rust".to_string(), "runner".to_string());
+        languages
+            .interpreters
+            .insert("python".to_string(), "uv run -".to_string());
+        assert_eq!(interpreter(&languages, "rust"), Some("runner"));
+        assert_eq!(interpreter(&languages, "python3"), Some("uv run -"));
+    }
+
+    #[test]
+    fn test_output_goes_under_the_block() {
+        let mut deck = parse_deck(
+            "# Demo\n\n```rust\nfn main() {}\n```\n\n```sh\necho one\n```\n\n```sh\necho two >&2; exit 3\n```\n\nAfter\n",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let languages = Languages::default();
+        let slide = &mut deck.slides[0];
+
+        run(slide, 1, &languages, dir.path()).unwrap();
+        run(slide, 1, &languages, dir.path()).unwrap();
+        assert_eq!(slide.len(), 6);
+        assert!(is_output(&slide[3]));
+        assert_eq!(slide[3].to_string(), "one");
+
+        run(slide, 2, &languages, dir.path()).unwrap();
+        assert_eq!(slide[5].to_string(), "two\n\n[exit status: 3]");
+        assert_eq!(slide[6].to_string(), "After");
+
+        let error = run(slide, 3, &languages, dir.path()).unwrap_err();
+        assert_eq!(error.to_string(), "This slide has 2 code blocks to run");
+    }
+
+    #[test]
+    fn test_runaway_block_is_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let started = Instant::now();
+        let output = execute(
+            "sh",
+            "echo started; sleep 10",
+            dir.path(),
+            Duration::from_millis(300),
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(output, "started\n\n[stopped after 300ms]");
+    }
+
+    #[test]
+    fn test_unread_input_doesnt_hold_up_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let started = Instant::now();
+        let code = "x".repeat(1 << 20);
+        let output = execute("sleep 10", &code, dir.path(), Duration::from_millis(300));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(output, "\n[stopped after 300ms]");
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use markdown::mdast::{Code, Node};
+
+use crate::commands::run_shell;
+use crate::directives::Directive;
+
+/// Marks the code blocks holding a `screenshot` directive's output
+const META: &str = "screenshot";
+
+fn screenshot_command(node: &Node) -> Option<String> {
+    match node {
+        Node::Html(html) => match Directive::parse(&html.value)? {
+            Directive::Screenshot(command) => Some(command),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_output(node: Option<&Node>) -> bool {
+    matches!(node, Some(Node::Code(code)) if code.meta.as_deref() == Some(META))
+}
+
+fn output_block(value: String) -> Node {
+    Node::Code(Code {
+        value,
+        position: None,
+        lang: None,
+        meta: Some(META.to_string()),
+    })
+}
+
+/// Puts a placeholder block after every `screenshot` directive until its command is run
+pub fn insert_placeholders(slides: &mut [Vec<Node>]) {
+    for slide in slides {
+        let mut i = 0;
+        while i < slide.len() {
+            if let Some(command) = screenshot_command(&slide[i]) {
+                let placeholder = format!("$ {}\n(not captured yet, press R to run)", command);
+                slide.insert(i + 1, output_block(placeholder));
+                i += 1;
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Runs the commands of a slide's `screenshot` directives in `dir` and shows their
+/// output in place of the previous capture, returning how many ran
+pub fn capture(slide: &mut Vec<Node>, dir: &Path) -> usize {
+    let mut captured = 0;
+    let mut i = 0;
+    while i < slide.len() {
+        if let Some(command) = screenshot_command(&slide[i]) {
+            let output = run_shell(&command, Some(dir));
+            let block = output_block(format!("$ {}\n{}", command, output.trim_end()));
+            if is_output(slide.get(i + 1)) {
+                slide[i + 1] = block;
+            } else {
+                slide.insert(i + 1, block);
+            }
+            captured += 1;
+            i += 1;
+        }
+        i += 1;
+    }
+    captured
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_placeholder_until_captured() {
+        let deck = parse_deck("# Demo\n<!-- screenshot: echo hi -->\n\nAfter\n").unwrap();
+        assert_eq!(deck.slides[0].len(), 4);
+        assert_eq!(
+            deck.slides[0][2].to_string(),
+            "$ echo hi\n(not captured yet, press R to run)"
+        );
+    }
+
+    #[test]
+    fn test_capture_replaces_previous_output() {
+        let mut deck =
+            parse_deck("# Demo\n<!-- screenshot: printf 'a\\033[32mb' -->\n\nAfter\n").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(capture(&mut deck.slides[0], dir.path()), 1);
+        assert_eq!(capture(&mut deck.slides[0], dir.path()), 1);
+        assert_eq!(deck.slides[0].len(), 4);
+        assert_eq!(
+            deck.slides[0][2].to_string(),
+            "$ printf 'a\\033[32mb'\na\x1b[32mb"
+        );
+        assert_eq!(deck.slides[0][3].to_string(), "After");
+    }
+
+    #[test]
+    fn test_command_runs_in_deck_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("marker.txt"), "").unwrap();
+        let mut deck = parse_deck("<!-- screenshot: ls -->\n").unwrap();
+        capture(&mut deck.slides[0], dir.path());
+        assert!(deck.slides[0][1].to_string().contains("marker.txt"));
+    }
+}
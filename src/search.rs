@@ -0,0 +1,217 @@
+use std::ops::Range;
+
+use anyhow::{Context, Result};
+use markdown::mdast::Node;
+use ratatui::buffer::Buffer;
+use regex::{Regex, RegexBuilder};
+
+use crate::app::slide_text;
+
+/// How a search query is matched against slide text
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchOptions {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+/// Query being typed at the `/` prompt
+#[derive(Debug, Default)]
+pub struct SearchInput {
+    pub text: String,
+    pub options: SearchOptions,
+}
+
+/// A compiled search query
+#[derive(Debug)]
+pub struct Search {
+    pub query: String,
+    pub options: SearchOptions,
+    matcher: Regex,
+}
+
+impl Search {
+    /// Parses prompt input, where a trailing `/flags` suffix (`r` regex, `c` case
+    /// sensitive, `w` whole word) turns options on, e.g. `fn \w+/rc`
+    pub fn parse(input: &str, mut options: SearchOptions) -> Result<Self> {
+        let mut query = input;
+        if let Some((pattern, flags)) = input.rsplit_once('/')
+            && !flags.is_empty()
+            && flags.chars().all(|c| matches!(c, 'r' | 'c' | 'w'))
+        {
+            query = pattern;
+            options.regex |= flags.contains('r');
+            options.case_sensitive |= flags.contains('c');
+            options.whole_word |= flags.contains('w');
+        }
+
+        let mut pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        if options.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        let matcher = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .with_context(|| format!("Invalid search pattern: {}", query))?;
+
+        Ok(Search {
+            query: query.to_string(),
+            options,
+            matcher,
+        })
+    }
+
+    pub fn find(&self, text: &str) -> Vec<Range<usize>> {
+        self.matcher
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    pub fn count(&self, slide: &[Node]) -> usize {
+        self.find(&slide_text(slide)).len()
+    }
+
+    /// Rows of a rendered slide from `first` on that hold a match, counted from `first`
+    pub fn match_rows(&self, content: &Buffer, first: u16) -> Vec<u16> {
+        (first..content.area.height)
+            .filter(|&y| {
+                let text: String = (0..content.area.width)
+                    .map(|x| content[(x, y)].symbol())
+                    .collect();
+                !self.find(&text).is_empty()
+            })
+            .map(|y| y - first)
+            .collect()
+    }
+
+    /// Next slide after `from` containing a match, wrapping around the deck
+    pub fn next_slide(&self, slides: &[Vec<Node>], from: usize, forward: bool) -> Option<usize> {
+        let len = slides.len();
+        (1..=len)
+            .map(|step| {
+                if forward {
+                    (from + step) % len
+                } else {
+                    (from + len * 2 - step) % len
+                }
+            })
+            .find(|&i| self.count(&slides[i]) > 0)
+    }
+
+    /// Status line text, e.g. `/lifetimes  5 matches on 2 slides`
+    pub fn status(&self, slides: &[Vec<Node>]) -> String {
+        let counts: Vec<usize> = slides.iter().map(|slide| self.count(slide)).collect();
+        let total: usize = counts.iter().sum();
+        let on_slides = counts.iter().filter(|&&count| count > 0).count();
+
+        let mut flags = String::new();
+        if self.options.regex {
+            flags.push('r');
+        }
+        if self.options.case_sensitive {
+            flags.push('c');
+        }
+        if self.options.whole_word {
+            flags.push('w');
+        }
+        let flags = if flags.is_empty() {
+            flags
+        } else {
+            format!("/{}", flags)
+        };
+
+        format!(
+            "/{}{}  {} {} on {} {}",
+            self.query,
+            flags,
+            total,
+            if total == 1 { "match" } else { "matches" },
+            on_slides,
+            if on_slides == 1 { "slide" } else { "slides" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    fn parse(input: &str) -> Search {
+        Search::parse(input, SearchOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_plain_query_is_literal_and_case_insensitive() {
+        let search = parse("a.b");
+        assert_eq!(search.find("A.B axb"), vec![0..3]);
+    }
+
+    #[test]
+    fn test_flag_suffix_enables_options() {
+        let search = parse(r"fn \w+/rc");
+        assert_eq!(search.query, r"fn \w+");
+        assert!(search.options.regex && search.options.case_sensitive);
+        assert_eq!(search.find("fn main FN other"), vec![0..7]);
+    }
+
+    #[test]
+    fn test_whole_word() {
+        let search = parse("own/w");
+        assert_eq!(search.find("own owner owned own"), vec![0..3, 16..19]);
+    }
+
+    #[test]
+    fn test_unknown_flags_are_part_of_the_query() {
+        let search = parse("a/b");
+        assert_eq!(search.query, "a/b");
+    }
+
+    #[test]
+    fn test_prompt_toggles_are_applied() {
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        let search = Search::parse("Rust", options).unwrap();
+        assert_eq!(search.find("rust Rust"), vec![5..9]);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_an_error() {
+        assert!(Search::parse("(/r", SearchOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_next_slide_wraps_in_both_directions() {
+        let deck = parse_deck("# A\nfoo\n\n# B\n\n# C\nfoo\n").unwrap();
+        let search = parse("foo");
+        assert_eq!(search.next_slide(&deck.slides, 0, true), Some(2));
+        assert_eq!(search.next_slide(&deck.slides, 2, true), Some(0));
+        assert_eq!(search.next_slide(&deck.slides, 0, false), Some(2));
+        assert_eq!(parse("zzz").next_slide(&deck.slides, 0, true), None);
+    }
+
+    #[test]
+    fn test_match_rows_skip_pinned_rows() {
+        let search = Search::parse("borrow", SearchOptions::default()).unwrap();
+        let content = Buffer::with_lines(["# Borrowing", "", "a borrow", "none", "Borrow"]);
+        assert_eq!(search.match_rows(&content, 0), vec![0, 2, 4]);
+        assert_eq!(search.match_rows(&content, 1), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_status_reports_match_counts() {
+        let deck = parse_deck("# A\nfoo foo\n\n# B\n\n# C\nfoo\n").unwrap();
+        assert_eq!(
+            parse("foo/w").status(&deck.slides),
+            "/foo/w  3 matches on 2 slides"
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Lets viewers follow a presentation from their own terminals
+//!
+//! The presenter serves the deck with `--serve`; `markdeck attach --read-only` receives
+//! the deck source and then every slide change. The protocol is line based:
+//! `deck <bytes>\n` followed by the markdown, again whenever the deck is reloaded, and
+//! `slide <index>\n` whenever the presenter moves. Viewers never send anything, so
+//! they can't steer the deck.
+//!
+//! Speaker notes and presenter blocks are cut from the source before it's sent, so
+//! they never reach the audience's machines.
+
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result, bail};
+use markdown::mdast::Node;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+
+use crate::app::parse_markdown;
+use crate::directives::Directive;
+use crate::events::AppEvent;
+use crate::fallback::parse_in_pieces;
+use crate::presenter::is_presenter_block;
+
+/// The presenter's end, publishing the deck and the current slide to every attached
+/// viewer
+pub struct Server {
+    deck: watch::Sender<Arc<str>>,
+    slide: watch::Sender<usize>,
+}
+
+impl Server {
+    /// Starts accepting viewers on `address`, reporting the viewer count as
+    /// `AppEvent::Viewers`
+    pub async fn bind(
+        address: &str,
+        source: String,
+        events: UnboundedSender<AppEvent>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(address)
+            .await
+            .with_context(|| format!("Failed to listen on {}", address))?;
+        let (deck, deck_receiver) = watch::channel(Arc::from(audience_source(&source)));
+        let (slide, receiver) = watch::channel(0);
+        tokio::spawn(accept(listener, deck_receiver, receiver, events));
+        Ok(Server { deck, slide })
+    }
+
+    /// Sends a reloaded deck to the viewers, before the slide they're on in it
+    pub fn publish_deck(&self, source: &str) {
+        self.deck.send_replace(Arc::from(audience_source(source)));
+    }
+
+    pub fn publish(&self, slide: usize) {
+        self.slide.send_if_modified(|current| {
+            let changed = *current != slide;
+            *current = slide;
+            changed
+        });
+    }
+}
+
+/// The deck as viewers get it, without speaker notes or presenter blocks
+pub fn audience_source(source: &str) -> String {
+    fn collect(node: &Node, hidden: &mut Vec<Range<usize>>) {
+        let is_notes = matches!(
+            node,
+            Node::Html(html) if matches!(Directive::parse(&html.value), Some(Directive::Notes(_)))
+        );
+        if is_notes || is_presenter_block(node) {
+            if let Some(position) = node.position() {
+                hidden.push(position.start.offset..position.end.offset);
+            }
+            return;
+        }
+        for child in node.children().into_iter().flatten() {
+            collect(child, hidden);
+        }
+    }
+
+    // Presenter containers are parsed as fences of the same length, so positions in
+    // the parsed deck are positions in the source
+    let root = parse_markdown(source)
+        .unwrap_or_else(|_| parse_in_pieces(source, parse_markdown, &mut vec![]));
+    let mut hidden = vec![];
+    collect(&root, &mut hidden);
+    let mut audience = String::with_capacity(source.len());
+    let mut from = 0;
+    for range in hidden {
+        audience.push_str(&source[from..range.start]);
+        from = range.end;
+    }
+    audience.push_str(&source[from..]);
+    audience
+}
+
+async fn accept(
+    listener: TcpListener,
+    deck: watch::Receiver<Arc<str>>,
+    slide: watch::Receiver<usize>,
+    events: UnboundedSender<AppEvent>,
+) {
+    let viewers = Arc::new(AtomicUsize::new(0));
+    while let Ok((stream, _)) = listener.accept().await {
+        let (deck, slide, events, viewers) =
+            (deck.clone(), slide.clone(), events.clone(), viewers.clone());
+        tokio::spawn(async move {
+            let _ = events.send(AppEvent::Viewers(
+                viewers.fetch_add(1, Ordering::Relaxed) + 1,
+            ));
+            let (reader, writer) = stream.into_split();
+            let _ = serve_viewer(reader, writer, deck, slide).await;
+            let _ = events.send(AppEvent::Viewers(
+                viewers.fetch_sub(1, Ordering::Relaxed) - 1,
+            ));
+        });
+    }
+}
+
+async fn serve_viewer(
+    mut reader: impl AsyncRead + Unpin,
+    mut writer: impl AsyncWrite + Unpin,
+    mut deck: watch::Receiver<Arc<str>>,
+    mut slide: watch::Receiver<usize>,
+) -> std::io::Result<()> {
+    let mut buf = [0; 64];
+    let mut send_deck = true;
+    loop {
+        if std::mem::take(&mut send_deck) {
+            let source = deck.borrow_and_update().clone();
+            writer
+                .write_all(format!("deck {}\n{}", source.len(), source).as_bytes())
+                .await?;
+        }
+        let current = *slide.borrow_and_update();
+        writer
+            .write_all(format!("slide {}\n", current).as_bytes())
+            .await?;
+        tokio::select! {
+            // A new deck goes out first, so its slide index isn't read against the old one
+            biased;
+            changed = deck.changed() => match changed {
+                Ok(()) => send_deck = true,
+                Err(_) => return Ok(()),
+            },
+            changed = slide.changed() => if changed.is_err() {
+                return Ok(());
+            },
+            // Viewers only ever close the connection
+            read = reader.read(&mut buf) => if matches!(read, Ok(0) | Err(_)) {
+                return Ok(());
+            },
+        }
+    }
+}
+
+/// A viewer's connection to a presenter, with the deck already received
+pub struct Viewer<R> {
+    pub source: String,
+    reader: BufReader<R>,
+}
+
+/// Connects to a presenter started with `--serve`
+pub async fn attach(address: &str) -> Result<Viewer<TcpStream>> {
+    let stream = TcpStream::connect(address)
+        .await
+        .with_context(|| format!("Failed to connect to {}", address))?;
+    Viewer::handshake(stream).await
+}
+
+impl<R: AsyncRead + Unpin + Send + 'static> Viewer<R> {
+    async fn handshake(stream: R) -> Result<Self> {
+        let mut reader = BufReader::new(stream);
+        let mut header = String::new();
+        reader.read_line(&mut header).await?;
+        let Some(source) = read_deck(&mut reader, &header).await? else {
+            bail!("Not a markdeck presenter");
+        };
+        Ok(Viewer { source, reader })
+    }
+
+    /// Forwards the presenter's slide changes as `AppEvent::Follow` until the
+    /// presenter goes away
+    pub fn follow(mut self, events: UnboundedSender<AppEvent>) {
+        tokio::spawn(async move {
+            let mut line = String::new();
+            while matches!(self.reader.read_line(&mut line).await, Ok(n) if n > 0) {
+                if let Some(slide) = line
+                    .strip_prefix("slide ")
+                    .and_then(|slide| slide.trim().parse().ok())
+                {
+                    let _ = events.send(AppEvent::Follow(slide));
+                } else if let Ok(Some(source)) = read_deck(&mut self.reader, &line).await {
+                    let _ = events.send(AppEvent::Reload(source));
+                }
+                line.clear();
+            }
+            let _ = events.send(AppEvent::Disconnected);
+        });
+    }
+}
+
+/// The deck following a `deck <bytes>` header line, or `None` for any other line
+async fn read_deck(reader: &mut (impl AsyncRead + Unpin), header: &str) -> Result<Option<String>> {
+    let Some(length) = header
+        .strip_prefix("deck ")
+        .and_then(|length| length.trim().parse::<usize>().ok())
+    else {
+        return Ok(None);
+    };
+    let mut source = vec![0; length];
+    reader.read_exact(&mut source).await?;
+    Ok(Some(
+        String::from_utf8(source).context("Deck is not valid UTF-8")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_viewer_receives_deck_and_slide_changes() {
+        runtime().block_on(async {
+            let (presenter, viewer) = tokio::io::duplex(1024);
+            let (deck, deck_receiver) = watch::channel(Arc::from("# Ünïcode\n# B\n"));
+            let (slide, receiver) = watch::channel(2);
+            let (reader, writer) = tokio::io::split(presenter);
+            tokio::spawn(
+                async move { serve_viewer(reader, writer, deck_receiver, receiver).await },
+            );
+
+            let viewer = Viewer::handshake(viewer).await.unwrap();
+            assert_eq!(viewer.source, "# Ünïcode\n# B\n");
+
+            let (events, mut received) = unbounded_channel();
+            viewer.follow(events);
+            assert!(matches!(received.recv().await, Some(AppEvent::Follow(2))));
+            slide.send(1).unwrap();
+            assert!(matches!(received.recv().await, Some(AppEvent::Follow(1))));
+            deck.send(Arc::from("# Edited\n")).unwrap();
+            assert!(matches!(
+                received.recv().await,
+                Some(AppEvent::Reload(source)) if source == "# Edited\n"
+            ));
+            assert!(matches!(received.recv().await, Some(AppEvent::Follow(1))));
+            drop(slide);
+            assert!(matches!(
+                received.recv().await,
+                Some(AppEvent::Disconnected)
+            ));
+        });
+    }
+
+    #[test]
+    fn test_server_counts_viewers() {
+        runtime().block_on(async {
+            let (events, mut received) = unbounded_channel();
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let (_deck, deck_receiver) = watch::channel(Arc::from("# A\n"));
+            let (_slide, receiver) = watch::channel(0);
+            tokio::spawn(accept(listener, deck_receiver, receiver, events));
+
+            let viewer = attach(&address).await.unwrap();
+            assert_eq!(viewer.source, "# A\n");
+            assert!(matches!(received.recv().await, Some(AppEvent::Viewers(1))));
+            drop(viewer);
+            assert!(matches!(received.recv().await, Some(AppEvent::Viewers(0))));
+        });
+    }
+
+    #[test]
+    fn test_audience_source_leaves_out_notes() {
+        let source = "# A\n<!-- notes: Tell the story -->\n\nText\n\n:::presenter\nMention the demo\n:::\n\n```presenter\nSecret\n```\n\n# B\n\n```md\n<!-- notes: shown as code -->\n```\n";
+        let audience = audience_source(source);
+        assert!(!audience.contains("Tell the story"));
+        assert!(!audience.contains("Mention the demo"));
+        assert!(!audience.contains("Secret"));
+        assert!(audience.contains("<!-- notes: shown as code -->"));
+
+        let deck = crate::app::parse_deck(&audience).unwrap();
+        assert_eq!(deck.slides.len(), 2);
+        assert!(crate::directives::speaker_notes(&deck.slides[0]).is_empty());
+    }
+
+    #[test]
+    fn test_attach_rejects_other_servers() {
+        runtime().block_on(async {
+            let (mut other, viewer) = tokio::io::duplex(64);
+            other.write_all(b"HTTP/1.1 400\n").await.unwrap();
+            assert!(Viewer::handshake(viewer).await.is_err());
+        });
+    }
+}
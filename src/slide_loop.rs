@@ -0,0 +1,73 @@
+//! A range of slides the slide keys go round in, to rehearse a tricky section again and
+//! again without paging back to its start each time
+
+/// Slides from `first` through `last`, by index; `last` is unset while the loop is
+/// still being marked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlideLoop {
+    pub first: usize,
+    pub last: Option<usize>,
+}
+
+impl SlideLoop {
+    /// A loop starting at the current slide, to be closed at another
+    pub fn mark(current: usize) -> Self {
+        SlideLoop {
+            first: current,
+            last: None,
+        }
+    }
+
+    /// Closes the loop at the current slide, which may come before the first one marked
+    pub fn close(self, current: usize) -> Self {
+        SlideLoop {
+            first: self.first.min(current),
+            last: Some(self.first.max(current)),
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.last
+            .is_some_and(|last| (self.first..=last).contains(&index))
+    }
+
+    /// Where the next slide key goes from the loop's last slide
+    pub fn after(&self, current: usize) -> Option<usize> {
+        (self.last == Some(current)).then_some(self.first)
+    }
+
+    /// Where the previous slide key goes from the loop's first slide
+    pub fn before(&self, current: usize) -> Option<usize> {
+        self.last.filter(|_| current == self.first)
+    }
+
+    /// The slides counted from 1, as `:12` jumps to them, e.g. `10–15`
+    pub fn range(&self) -> String {
+        match self.last {
+            Some(last) => format!("{}–{}", self.first + 1, last + 1),
+            None => format!("{}–", self.first + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop_wraps_at_either_end() {
+        let marking = SlideLoop::mark(14);
+        assert_eq!(marking.after(14), None);
+        assert_eq!(marking.range(), "15–");
+
+        // Closed on a slide before the one marked first
+        let slide_loop = marking.close(9);
+        assert_eq!(slide_loop.range(), "10–15");
+        assert_eq!(slide_loop.after(14), Some(9));
+        assert_eq!(slide_loop.after(12), None);
+        assert_eq!(slide_loop.before(9), Some(14));
+        assert_eq!(slide_loop.before(10), None);
+        assert!(slide_loop.contains(12));
+        assert!(!slide_loop.contains(15));
+    }
+}
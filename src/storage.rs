@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+const MAX_RECENT_FILES: usize = 20;
+
+/// Runtime data kept between sessions
+///
+/// State that should survive (deck sessions, recent files) lives under the XDG
+/// state directory, `~/.local/state/markdeck` on Linux and the local app data folder
+/// on macOS and Windows. Anything that can be rebuilt goes under the cache directory.
+#[derive(Debug)]
+pub struct Storage {
+    state_dir: PathBuf,
+    cache_dir: PathBuf,
+}
+
+/// What markdeck remembers of one deck between sessions
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// The deck's canonical path, to tell the saved sessions apart by hand
+    pub deck: PathBuf,
+    /// Slide the deck was left on
+    pub slide: Option<usize>,
+    /// Ticked checklist steps per slide index
+    pub checklists: BTreeMap<usize, usize>,
+    /// Seconds spent on each slide in the last `--rehearse` run
+    pub timings: Vec<u64>,
+}
+
+/// Where deck sessions are kept, so the features that remember something share one
+/// way of saving it
+pub trait SessionStore {
+    /// The deck's session, empty if it was never saved or can't be read
+    fn load(&self, deck: &Path) -> Session;
+    fn save(&self, deck: &Path, session: &Session) -> Result<()>;
+}
+
+/// Slides left on per canonical deck path, as saved before sessions
+#[derive(Debug, Default, Deserialize)]
+struct LegacyPositions {
+    #[serde(default)]
+    slides: BTreeMap<String, usize>,
+}
+
+/// Ticked checklist steps per slide index per canonical deck path, as saved before
+/// sessions
+#[derive(Debug, Default, Deserialize)]
+struct LegacyChecklists {
+    #[serde(default)]
+    decks: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Recent {
+    /// Most recently opened first
+    #[serde(default)]
+    files: Vec<PathBuf>,
+}
+
+impl Storage {
+    pub fn new() -> Result<Self> {
+        let state_dir = dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .ok_or_else(|| anyhow!("Could not determine state directory"))?;
+        let cache_dir =
+            dirs::cache_dir().ok_or_else(|| anyhow!("Could not determine cache directory"))?;
+        Ok(Storage::at(
+            state_dir.join("markdeck"),
+            cache_dir.join("markdeck"),
+        ))
+    }
+
+    pub fn at(state_dir: PathBuf, cache_dir: PathBuf) -> Self {
+        Storage {
+            state_dir,
+            cache_dir,
+        }
+    }
+
+    pub fn recent_files(&self) -> Vec<PathBuf> {
+        let recent: Recent = self.read("recent.toml");
+        recent.files
+    }
+
+    pub fn add_recent_file(&self, deck: &Path) -> Result<()> {
+        let deck = PathBuf::from(deck_key(deck));
+        let mut recent: Recent = self.read("recent.toml");
+        recent.files.retain(|file| *file != deck);
+        recent.files.insert(0, deck);
+        recent.files.truncate(MAX_RECENT_FILES);
+        self.write("recent.toml", &recent)
+    }
+
+    /// Where remote images are downloaded to before presenting
+    pub fn asset_dir(&self) -> PathBuf {
+        self.cache_dir.join("assets")
+    }
+
+    /// Deletes the state and/or cache directories, returning the ones that existed
+    pub fn clear(&self, state: bool, cache: bool) -> Result<Vec<&Path>> {
+        let mut cleared = vec![];
+        for (dir, selected) in [(&self.state_dir, state), (&self.cache_dir, cache)] {
+            if selected && dir.exists() {
+                std::fs::remove_dir_all(dir)
+                    .with_context(|| format!("Failed to remove {}", dir.display()))?;
+                cleared.push(dir.as_path());
+            }
+        }
+        Ok(cleared)
+    }
+
+    /// Missing or unreadable state starts out empty rather than failing the session
+    fn read<T: Default + for<'de> Deserialize<'de>>(&self, name: &str) -> T {
+        std::fs::read_to_string(self.state_dir.join(name))
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write<T: Serialize>(&self, name: &str, value: &T) -> Result<()> {
+        create_state_dir(&self.state_dir)?;
+        let path = self.state_dir.join(name);
+        std::fs::write(&path, toml::to_string(value)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// A deck never saved as a session starts from what older versions kept of it, so
+    /// upgrading doesn't lose resume positions and checklist progress
+    fn legacy_session(&self, deck: &Path) -> Session {
+        let key = deck_key(deck);
+        let positions: LegacyPositions = self.read("positions.toml");
+        let checklists: LegacyChecklists = self.read("checklists.toml");
+        Session {
+            slide: positions.slides.get(&key).copied(),
+            checklists: checklists
+                .decks
+                .get(&key)
+                .into_iter()
+                .flatten()
+                .filter_map(|(slide, ticked)| Some((slide.parse().ok()?, *ticked)))
+                .collect(),
+            deck: PathBuf::from(key),
+            ..Session::default()
+        }
+    }
+
+    /// One JSON file per deck, named by a hash of its canonical path
+    fn session_path(&self, deck: &Path) -> PathBuf {
+        self.state_dir
+            .join("sessions")
+            .join(format!("{:016x}.json", deck_hash(&deck_key(deck))))
+    }
+}
+
+impl SessionStore for Storage {
+    fn load(&self, deck: &Path) -> Session {
+        std::fs::read_to_string(self.session_path(deck))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| self.legacy_session(deck))
+    }
+
+    fn save(&self, deck: &Path, session: &Session) -> Result<()> {
+        let path = self.session_path(deck);
+        if let Some(dir) = path.parent() {
+            create_state_dir(dir)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(session)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn create_state_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create state directory: {}", dir.display()))
+}
+
+fn deck_key(deck: &Path) -> String {
+    deck.canonicalize()
+        .unwrap_or_else(|_| deck.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// FNV-1a, which unlike the standard library's hasher stays the same across Rust
+/// releases and so keeps finding the sessions saved by older builds
+fn deck_hash(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage(dir: &Path) -> Storage {
+        Storage::at(dir.join("state"), dir.join("cache"))
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(dir.path());
+        let deck = dir.path().join("talk.md");
+        std::fs::write(&deck, "# Hi").unwrap();
+
+        let mut session = storage.load(&deck);
+        assert_eq!(session.deck, deck.canonicalize().unwrap());
+        assert_eq!(session.slide, None);
+        session.slide = Some(4);
+        session.checklists.insert(2, 3);
+        session.timings = vec![30, 95];
+        storage.save(&deck, &session).unwrap();
+
+        assert_eq!(storage.load(&deck), session);
+        assert_eq!(storage.load(&dir.path().join("other.md")).slide, None);
+        // The file is named by the deck's hash, not its path
+        let saved = std::fs::read_dir(dir.path().join("state/sessions")).unwrap();
+        let name = saved
+            .map(|entry| entry.unwrap().file_name())
+            .next()
+            .unwrap();
+        assert_eq!(name.len(), "0123456789abcdef.json".len());
+    }
+
+    #[test]
+    fn test_sessions_start_from_legacy_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(dir.path());
+        let deck = dir.path().join("talk.md");
+        std::fs::write(&deck, "# Hi").unwrap();
+        let key = deck.canonicalize().unwrap();
+        std::fs::create_dir_all(dir.path().join("state")).unwrap();
+        std::fs::write(
+            dir.path().join("state/positions.toml"),
+            format!("[slides]\n{:?} = 7\n", key),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("state/checklists.toml"),
+            format!("[decks.{:?}]\n2 = 3\n", key),
+        )
+        .unwrap();
+
+        let session = storage.load(&deck);
+        assert_eq!(session.slide, Some(7));
+        assert_eq!(session.checklists, BTreeMap::from([(2, 3)]));
+
+        // Once saved, the session wins over the old files
+        storage
+            .save(
+                &deck,
+                &Session {
+                    slide: Some(1),
+                    ..session
+                },
+            )
+            .unwrap();
+        assert_eq!(storage.load(&deck).slide, Some(1));
+    }
+
+    #[test]
+    fn test_recent_files_are_deduplicated_most_recent_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(dir.path());
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+
+        storage.add_recent_file(&a).unwrap();
+        storage.add_recent_file(&b).unwrap();
+        storage.add_recent_file(&a).unwrap();
+        assert_eq!(storage.recent_files(), vec![a, b]);
+    }
+
+    #[test]
+    fn test_clear_removes_selected_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(dir.path());
+        storage.add_recent_file(Path::new("a.md")).unwrap();
+        std::fs::create_dir_all(dir.path().join("cache")).unwrap();
+
+        let cleared = storage.clear(true, false).unwrap();
+        assert_eq!(cleared, vec![dir.path().join("state").as_path()]);
+        assert!(storage.recent_files().is_empty());
+        assert!(dir.path().join("cache").exists());
+    }
+}
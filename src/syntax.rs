@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use markdown::mdast::Node;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use serde::Deserialize;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{self, FontStyle, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::fallback::Problem;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Fence languages common in other highlighters, mapped to the closest syntax syntect
+/// ships with; `[code.aliases]` in the config takes precedence
+const DEFAULT_ALIASES: [(&str, &str); 15] = [
+    ("console", "bash"),
+    ("shell", "bash"),
+    ("shell-session", "bash"),
+    ("jsx", "javascript"),
+    ("ts", "javascript"),
+    ("tsx", "javascript"),
+    ("typescript", "javascript"),
+    ("json5", "json"),
+    ("jsonc", "json"),
+    ("golang", "go"),
+    ("csharp", "cs"),
+    ("python3", "python"),
+    ("scss", "css"),
+    ("svelte", "html"),
+    ("vue", "html"),
+];
+
+/// Fences markdeck gives a meaning of its own, or that are plain on purpose
+const OWN_LANGUAGES: [&str; 6] = [
+    "checklist",
+    "presenter",
+    "text",
+    "txt",
+    "plain",
+    "plaintext",
+];
+
+/// How fence languages are looked up, set under `[code]` in the config
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Languages {
+    /// Fence languages to highlight as another, e.g. `mdx = "markdown"`
+    pub aliases: BTreeMap<String, String>,
+    pub unknown_language: UnknownLanguage,
+    /// Numbers the lines of every code block, as `{numbers}` after a fence's language
+    /// does for one
+    pub line_numbers: bool,
+    /// Commands that run code in a language, reading it from stdin, e.g.
+    /// `python = "python3"`
+    pub interpreters: BTreeMap<String, String>,
+}
+
+/// What happens to code in a language no syntax is found for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownLanguage {
+    /// Keep the flat code color
+    #[default]
+    Plain,
+    /// Go by the first line, such as a shebang or `<?xml`
+    Guess,
+    /// Keep the flat color and report the block in `markdeck check`
+    Warn,
+}
+
+impl Languages {
+    fn syntax(&self, lang: &str, code: &str) -> Option<&'static SyntaxReference> {
+        let lang = self.resolve(lang);
+        SYNTAXES
+            .find_syntax_by_token(lang)
+            .or_else(|| match self.unknown_language {
+                UnknownLanguage::Guess => SYNTAXES.find_syntax_by_first_line(code),
+                UnknownLanguage::Plain | UnknownLanguage::Warn => None,
+            })
+    }
+
+    /// The language a fence stands for, after aliases
+    pub fn resolve<'a>(&'a self, lang: &'a str) -> &'a str {
+        self.aliases
+            .get(lang)
+            .map(String::as_str)
+            .or_else(|| {
+                DEFAULT_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == lang)
+                    .map(|(_, target)| *target)
+            })
+            .unwrap_or(lang)
+    }
+
+    fn is_known(&self, lang: &str) -> bool {
+        OWN_LANGUAGES.contains(&lang) || SYNTAXES.find_syntax_by_token(self.resolve(lang)).is_some()
+    }
+}
+
+/// Code blocks in a language nothing highlights, reported when `unknown_language`
+/// is `warn`
+pub fn unknown_languages(slides: &[Vec<Node>], languages: &Languages) -> Vec<Problem> {
+    fn collect(node: &Node, languages: &Languages, problems: &mut Vec<Problem>) {
+        if let Node::Code(code) = node
+            && let Some(lang) = code.lang.as_deref()
+            && !languages.is_known(lang)
+        {
+            problems.push(Problem {
+                line: code.position.as_ref().map_or(0, |p| p.start.line),
+                message: format!(
+                    "No highlighting for code in \"{}\"; alias it under [code.aliases]",
+                    lang
+                ),
+            });
+        }
+        for child in node.children().into_iter().flatten() {
+            collect(child, languages, problems);
+        }
+    }
+
+    let mut problems = vec![];
+    if languages.unknown_language == UnknownLanguage::Warn {
+        for node in slides.iter().flatten() {
+            collect(node, languages, &mut problems);
+        }
+    }
+    problems
+}
+
+/// Colors the tokens of a fenced code block by its language
+///
+/// Returns `None` for languages or syntax themes syntect doesn't know, so the
+/// caller can fall back to a flat color.
+pub fn highlight_code<'a>(
+    code: &'a str,
+    lang: &str,
+    syntax_theme: &str,
+    languages: &Languages,
+) -> Option<Vec<Line<'a>>> {
+    let syntax = languages.syntax(lang, code)?;
+    let theme = THEMES.themes.get(syntax_theme)?;
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    let mut lines = vec![];
+    for line in LinesWithEndings::from(code) {
+        let tokens = highlighter.highlight_line(line, &SYNTAXES).ok()?;
+        let spans: Vec<Span<'a>> = tokens
+            .into_iter()
+            .map(|(style, text)| Span::styled(text.trim_end_matches(['\n', '\r']), to_style(style)))
+            .filter(|span| !span.content.is_empty())
+            .collect();
+        lines.push(Line::from(spans));
+    }
+    Some(lines)
+}
+
+/// Foreground and font style only, so code sits on the slide's own background
+fn to_style(style: highlighting::Style) -> Style {
+    let highlighting::Color { r, g, b, .. } = style.foreground;
+    let mut converted = Style::default().fg(Color::Rgb(r, g, b));
+    for (font, modifier) in [
+        (FontStyle::BOLD, Modifier::BOLD),
+        (FontStyle::ITALIC, Modifier::ITALIC),
+        (FontStyle::UNDERLINE, Modifier::UNDERLINED),
+    ] {
+        if style.font_style.contains(font) {
+            converted = converted.add_modifier(modifier);
+        }
+    }
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THEME: &str = "base16-ocean.dark";
+
+    #[test]
+    fn test_tokens_get_different_colors() {
+        let lines = highlight_code(
+            "fn main() {\n    let x = 1;\n}\n",
+            "rust",
+            THEME,
+            &Languages::default(),
+        )
+        .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].to_string(), "    let x = 1;");
+
+        let keyword = lines[0].spans.iter().find(|s| s.content == "fn").unwrap();
+        let name = lines[0].spans.iter().find(|s| s.content == "main").unwrap();
+        assert_ne!(keyword.style.fg, name.style.fg);
+        assert!(
+            lines
+                .iter()
+                .flat_map(|l| &l.spans)
+                .all(|s| s.style.bg.is_none())
+        );
+    }
+
+    #[test]
+    fn test_languages_by_name_or_extension() {
+        for lang in ["python", "py", "json", "rs"] {
+            assert!(
+                highlight_code("x", lang, THEME, &Languages::default()).is_some(),
+                "{}",
+                lang
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_language_or_theme_falls_back() {
+        assert!(highlight_code("x", "klingon", THEME, &Languages::default()).is_none());
+        assert!(highlight_code("x", "rust", "no-such-theme", &Languages::default()).is_none());
+        assert!(THEMES.themes.contains_key(THEME));
+    }
+
+    #[test]
+    fn test_aliases_and_unknown_language_policies() {
+        let mut languages = Languages::default();
+        assert!(highlight_code("ls", "console", THEME, &languages).is_some());
+        assert!(highlight_code("x", "mdx", THEME, &languages).is_none());
+
+        languages
+            .aliases
+            .insert("mdx".to_string(), "markdown".to_string());
+        assert!(highlight_code("x", "mdx", THEME, &languages).is_some());
+
+        let script = "#!/bin/bash\necho hi\n";
+        assert!(highlight_code(script, "run", THEME, &languages).is_none());
+        languages.unknown_language = UnknownLanguage::Guess;
+        assert!(highlight_code(script, "run", THEME, &languages).is_some());
+    }
+
+    #[test]
+    fn test_unknown_languages_are_reported_when_warned() {
+        let deck = crate::app::parse_deck(
+            "# Slide\n\n```klingon\nx\n```\n\n```checklist\nStep\n```\n\n- ```jsx\n  <A/>\n  ```\n",
+        )
+        .unwrap();
+        let mut languages = Languages::default();
+        assert!(unknown_languages(&deck.slides, &languages).is_empty());
+
+        languages.unknown_language = UnknownLanguage::Warn;
+        let problems = unknown_languages(&deck.slides, &languages);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 3);
+        assert!(problems[0].message.contains("\"klingon\""));
+    }
+}
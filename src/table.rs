@@ -0,0 +1,256 @@
+//! GFM tables, laid out as aligned columns with a rule under the header row, and
+//! sorted or filtered in table mode
+
+use std::cmp::Ordering;
+
+use markdown::mdast::{AlignKind, Node, Table};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::details::{Section, sections};
+
+const SEPARATOR: &str = " │ ";
+
+/// The slide's tables outside `<details>` sections, the ones table mode can sort
+pub fn tables(slide: &[Node]) -> Vec<&Table> {
+    sections(slide)
+        .into_iter()
+        .filter_map(|section| match section {
+            Section::Node(Node::Table(table)) => Some(table),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+/// A table on the current slide being sorted and filtered, e.g. during Q&A
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableView {
+    pub slide: usize,
+    /// Which of the slide's `tables`
+    pub table: usize,
+    /// Column that `s` sorts by
+    pub column: usize,
+    pub sort: Option<(usize, Order)>,
+    /// Only rows with a cell containing this, ignoring case, are shown
+    pub filter: String,
+    /// Whether keys are typed into the filter
+    pub filtering: bool,
+}
+
+impl TableView {
+    pub fn new(slide: usize) -> Self {
+        TableView {
+            slide,
+            ..TableView::default()
+        }
+    }
+
+    /// Sorts by the selected column ascending, then descending, then as written
+    pub fn cycle_sort(&mut self) {
+        self.sort = match self.sort {
+            Some((column, Order::Ascending)) if column == self.column => {
+                Some((column, Order::Descending))
+            }
+            Some((column, Order::Descending)) if column == self.column => None,
+            _ => Some((self.column, Order::Ascending)),
+        };
+    }
+
+    /// Filters and sorts the rendered rows below the header, marking the selected
+    /// column and the sort order in the header
+    pub fn apply<'a>(&self, mut rows: Vec<Vec<Line<'a>>>) -> Vec<Vec<Line<'a>>> {
+        if rows.is_empty() {
+            return rows;
+        }
+        let mut header = rows.remove(0);
+        let text =
+            |row: &[Line], column: usize| row.get(column).map(Line::to_string).unwrap_or_default();
+
+        let filter = self.filter.to_lowercase();
+        rows.retain(|row| {
+            row.iter()
+                .any(|cell| cell.to_string().to_lowercase().contains(&filter))
+        });
+        if let Some((column, order)) = self.sort {
+            rows.sort_by(|a, b| {
+                let ordering = compare_cells(&text(a, column), &text(b, column));
+                match order {
+                    Order::Ascending => ordering,
+                    Order::Descending => ordering.reverse(),
+                }
+            });
+            if let Some(cell) = header.get_mut(column) {
+                let arrow = match order {
+                    Order::Ascending => " ▲",
+                    Order::Descending => " ▼",
+                };
+                cell.spans.push(Span::raw(arrow));
+            }
+        }
+        if let Some(cell) = header.get_mut(self.column) {
+            for span in &mut cell.spans {
+                span.style = span.style.add_modifier(Modifier::REVERSED);
+            }
+        }
+        rows.insert(0, header);
+        rows
+    }
+}
+
+/// Numbers, with units or thousands separators, by value; anything else as text
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// The number a cell starts with, like `12.5` in `12.5 ms` or `1200` in `$1,200`
+fn number(cell: &str) -> Option<f64> {
+    let cell = cell.trim().trim_start_matches(['$', '€', '£', '¥']);
+    let digits: String = cell
+        .chars()
+        .filter(|&c| c != ',' && c != '_')
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'))
+        .collect();
+    digits.parse().ok()
+}
+
+/// Lays out rendered cells, the header row first, padding each column to its widest
+/// cell and aligning it as the table's delimiter row asks
+pub fn layout<'a>(rows: Vec<Vec<Line<'a>>>, align: &[AlignKind], style: Style) -> Vec<Line<'a>> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0; columns];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let mut lines = vec![];
+    for (i, row) in rows.into_iter().enumerate() {
+        let mut spans = vec![];
+        let mut cells = row.into_iter();
+        for (column, &width) in widths.iter().enumerate() {
+            if column > 0 {
+                spans.push(Span::styled(SEPARATOR, style));
+            }
+            let mut cell = cells.next().unwrap_or_default();
+            if i == 0 {
+                for span in &mut cell.spans {
+                    span.style = span.style.add_modifier(Modifier::BOLD);
+                }
+            }
+            let padding = width - cell.width();
+            let before = match align.get(column) {
+                Some(AlignKind::Right) => padding,
+                Some(AlignKind::Center) => padding / 2,
+                _ => 0,
+            };
+            spans.push(Span::styled(" ".repeat(before), style));
+            spans.extend(cell.spans);
+            spans.push(Span::styled(" ".repeat(padding - before), style));
+        }
+        lines.push(Line::from(spans));
+        if i == 0 {
+            let rule: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+            lines.push(Line::styled(rule.join("─┼─"), style));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(rows: &[Vec<Line>]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(Line::to_string).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_view_sorts_numbers_by_value_and_filters_rows() {
+        let rows = || {
+            [
+                ["Server", "Latency"],
+                ["tokio", "12 ms"],
+                ["actix", "9 ms"],
+                ["Axum", "1,200 ms"],
+            ]
+            .map(|row| row.map(Line::raw).to_vec())
+            .to_vec()
+        };
+        let mut view = TableView::new(0);
+        view.column = 1;
+        view.cycle_sort();
+        assert_eq!(
+            text(&view.apply(rows())),
+            [
+                ["Server", "Latency ▲"],
+                ["actix", "9 ms"],
+                ["tokio", "12 ms"],
+                ["Axum", "1,200 ms"],
+            ]
+        );
+
+        view.cycle_sort();
+        assert_eq!(text(&view.apply(rows()))[1], ["Axum", "1,200 ms"]);
+        view.cycle_sort();
+        assert_eq!(view.sort, None);
+
+        view.filter = "TOK".to_string();
+        assert_eq!(
+            text(&view.apply(rows())),
+            [["Server", "Latency"], ["tokio", "12 ms"]]
+        );
+        let header = &view.apply(rows())[0];
+        assert!(
+            header[1].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::REVERSED)
+        );
+        assert!(
+            !header[0].spans[0]
+                .style
+                .add_modifier
+                .contains(Modifier::REVERSED)
+        );
+    }
+
+    #[test]
+    fn test_columns_are_padded_and_aligned() {
+        let rows = vec![
+            vec![Line::raw("Crate"), Line::raw("Downloads")],
+            vec![Line::raw("serde"), Line::raw("12")],
+            vec![Line::raw("markdown-rs")],
+        ];
+        let lines = layout(rows, &[AlignKind::None, AlignKind::Right], Style::default());
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "Crate       │ Downloads",
+                "────────────┼──────────",
+                "serde       │        12",
+                "markdown-rs │          ",
+            ]
+        );
+        assert!(
+            lines[0].spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! `markdeck new`: starts a deck from a template that shows off the directives, so
+//! the first deck works before anything in it is changed
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Template {
+    /// Title slide, agenda, sections with a demo, and backup slides for the Q&A
+    Conference,
+    /// Setup checklist, labs, a quiz and hints tucked away in details
+    Workshop,
+    /// A handful of single-point slides planned for five minutes
+    Lightning,
+}
+
+impl Template {
+    fn source(self) -> &'static str {
+        match self {
+            Template::Conference => include_str!("../templates/conference.md"),
+            Template::Workshop => include_str!("../templates/workshop.md"),
+            Template::Lightning => include_str!("../templates/lightning.md"),
+        }
+    }
+}
+
+/// `talk-name` is written to `talk-name.md`; a name ending in `.md` is kept as is
+pub fn output_path(name: &str) -> PathBuf {
+    if name.ends_with(".md") {
+        PathBuf::from(name)
+    } else {
+        PathBuf::from(format!("{}.md", name))
+    }
+}
+
+/// The template with a title made from the file name, e.g. `Rust Async` for
+/// `rust-async.md`
+pub fn scaffold(path: &Path, template: Template) -> String {
+    template.source().replace("{title}", &title(path))
+}
+
+fn title(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    stem.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        // Keeps the frontmatter's TOML string valid
+        .replace(['"', '\\'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parse_deck;
+
+    #[test]
+    fn test_every_template_is_a_working_deck() {
+        for template in Template::value_variants() {
+            let markdown = scaffold(Path::new("talks/rust-async.md"), *template);
+            let deck = parse_deck(&markdown).unwrap();
+            assert_eq!(deck.frontmatter.title.as_deref(), Some("Rust Async"));
+            assert!(deck.slides.len() > 3, "{:?}", template);
+            assert!(!markdown.contains("{title}"));
+        }
+    }
+
+    #[test]
+    fn test_output_path_and_title() {
+        assert_eq!(output_path("rust-async"), PathBuf::from("rust-async.md"));
+        assert_eq!(output_path("talk.md"), PathBuf::from("talk.md"));
+        assert_eq!(title(Path::new("my_first__talk.md")), "My First Talk");
+    }
+}
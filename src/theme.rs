@@ -0,0 +1,481 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ratatui::layout::{Margin, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::directives::Center;
+use crate::syntax::Languages;
+
+/// Slide shown by `markdeck theme preview`, using every element a theme styles
+pub const PREVIEW_DECK: &str = r#"# Theme preview
+<!-- emphasize: accent -->
+
+## Text
+
+Plain, **bold**, *italic*, `inline code`, a [link](https://example.com) and an
+accent term, with a footnote[^1].
+
+- Unordered item
+- Another item with `code`
+
+1. Ordered item
+2. Another item
+
+> A blockquote, with **bold** text
+
+| Element | Styled by       |
+|---------|----------------:|
+| Heading | `colors.heading` |
+| Link    | `colors.link`    |
+
+```rust
+fn main() {
+    println!("Hello, markdeck!");
+}
+```
+
+[^1]: Footnotes use the link color.
+"#;
+
+/// Themes compiled into the binary, by name, in the order `cycle_theme` goes through
+const BUILTIN: [(&str, &str); 4] = [
+    ("dark", include_str!("../themes/dark.toml")),
+    ("light", include_str!("../themes/light.toml")),
+    ("solarized", include_str!("../themes/solarized.toml")),
+    ("gruvbox", include_str!("../themes/gruvbox.toml")),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Theme {
+    #[serde(default)]
+    pub colors: Colors,
+    #[serde(default)]
+    pub headings: Headings,
+    #[serde(default)]
+    pub bullets: Bullets,
+    #[serde(default)]
+    pub layout: Layout,
+    #[serde(default)]
+    pub code: Code,
+}
+
+impl Theme {
+    /// Reads a theme file, laid out like the `theme` table of a deck's frontmatter
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read theme: {}", path.display()))?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).context("Failed to parse theme")
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (_, source) = BUILTIN.iter().find(|(builtin, _)| *builtin == name)?;
+        Some(Self::parse(source).expect("built-in themes parse"))
+    }
+
+    /// A built-in theme by name, or else a theme file
+    pub fn named(spec: &Path) -> Result<Self> {
+        match spec.to_str().and_then(Self::builtin) {
+            Some(theme) => Ok(theme),
+            None => Self::load(spec),
+        }
+    }
+}
+
+pub fn is_builtin(spec: &Path) -> bool {
+    BUILTIN.iter().any(|(name, _)| spec.as_os_str() == *name)
+}
+
+/// Themes picked outside the deck: a built-in theme cycled to while presenting wins,
+/// then `--theme` over the deck's own theme, and the config's `theme` applies to
+/// decks without one
+#[derive(Debug, Clone, Default)]
+pub struct ThemeChoice {
+    pub forced: Option<Theme>,
+    pub fallback: Theme,
+    /// Index of the built-in theme cycled to, if any
+    pub cycled: Option<usize>,
+}
+
+impl ThemeChoice {
+    pub fn load(forced: Option<&Path>, fallback: Option<&Path>) -> Result<Self> {
+        Ok(ThemeChoice {
+            forced: forced.map(Theme::named).transpose()?,
+            fallback: fallback.map(Theme::named).transpose()?.unwrap_or_default(),
+            cycled: None,
+        })
+    }
+
+    /// The theme to present a deck with, given the one in its frontmatter
+    pub fn pick(&self, deck: Option<Theme>) -> Theme {
+        if let Some((_, source)) = self.cycled.and_then(|i| BUILTIN.get(i)) {
+            return Theme::parse(source).expect("built-in themes parse");
+        }
+        match (&self.forced, deck) {
+            (Some(forced), _) => forced.clone(),
+            (None, Some(deck)) => deck,
+            (None, None) => self.fallback.clone(),
+        }
+    }
+
+    /// Moves on to the next built-in theme, and back to the usual one after the last;
+    /// returns the name of the theme now in use
+    pub fn cycle(&mut self) -> &'static str {
+        self.cycled = match self.cycled {
+            None => Some(0),
+            Some(i) if i + 1 < BUILTIN.len() => Some(i + 1),
+            Some(_) => None,
+        };
+        self.cycled.map_or("original", |i| BUILTIN[i].0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub heading: Color,
+    pub code: Color,
+    pub inline_code: Color,
+    pub link: Color,
+    pub blockquote: Color,
+    /// Terms highlighted by the `emphasize` directive
+    pub accent: Color,
+    /// Background of search matches
+    pub search: Color,
+    pub header: Color,
+    pub footer: Color,
+    /// Quiz answers
+    pub correct: Color,
+    pub incorrect: Color,
+    /// Background of the presenter cursor's line
+    pub cursor: Color,
+    /// Gutter of numbered code blocks
+    pub line_number: Color,
+    /// Background of code lines picked out with e.g. `{3,5-7}`
+    pub line_highlight: Color,
+    /// Behind everything else; `reset` keeps the terminal's own
+    pub background: Color,
+    /// Text without a color of its own
+    pub text: Color,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            heading: Color::Cyan,
+            code: Color::Gray,
+            inline_code: Color::Green,
+            link: Color::Blue,
+            blockquote: Color::Yellow,
+            accent: Color::LightMagenta,
+            search: Color::Yellow,
+            header: Color::DarkGray,
+            footer: Color::DarkGray,
+            correct: Color::Green,
+            incorrect: Color::Red,
+            cursor: Color::DarkGray,
+            line_number: Color::DarkGray,
+            line_highlight: Color::DarkGray,
+            background: Color::Reset,
+            text: Color::Reset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Headings {
+    pub fit: TitleFit,
+    /// Show the enclosing H1 above slides that start at an H2
+    pub breadcrumb: bool,
+    pub h1: HeadingStyle,
+    pub h2: HeadingStyle,
+    pub h3: HeadingStyle,
+    pub h4: HeadingStyle,
+    pub h5: HeadingStyle,
+    pub h6: HeadingStyle,
+}
+
+impl Headings {
+    /// How headings of `depth`, from 1 to 6, look
+    pub fn level(&self, depth: u8) -> &HeadingStyle {
+        match depth {
+            1 => &self.h1,
+            2 => &self.h2,
+            3 => &self.h3,
+            4 => &self.h4,
+            5 => &self.h5,
+            _ => &self.h6,
+        }
+    }
+}
+
+/// Look of one heading level, e.g. `[headings.h3]`; what's left unset keeps the `#`
+/// markers and the bold `heading` color
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HeadingStyle {
+    pub color: Option<Color>,
+    /// Replaces the `#` markers, e.g. `"▌ "`, or `""` for none
+    pub prefix: Option<String>,
+    /// Replaces bold, e.g. `["dim", "italic"]`
+    pub modifiers: Option<Vec<TextModifier>>,
+    /// Centers the heading whatever the slide's centering
+    pub center: bool,
+}
+
+impl HeadingStyle {
+    pub fn style(&self, colors: &Colors) -> Style {
+        let modifiers = match &self.modifiers {
+            Some(modifiers) => modifiers
+                .iter()
+                .fold(Modifier::empty(), |all, modifier| all | modifier.modifier()),
+            None => Modifier::BOLD,
+        };
+        Style::default()
+            .fg(self.color.unwrap_or(colors.heading))
+            .add_modifier(modifiers)
+    }
+
+    pub fn prefix(&self, depth: u8) -> String {
+        match &self.prefix {
+            Some(prefix) => prefix.clone(),
+            None => "#".repeat(depth as usize) + " ",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextModifier {
+    Bold,
+    Dim,
+    Italic,
+    Underlined,
+    Reversed,
+}
+
+impl TextModifier {
+    fn modifier(self) -> Modifier {
+        match self {
+            TextModifier::Bold => Modifier::BOLD,
+            TextModifier::Dim => Modifier::DIM,
+            TextModifier::Italic => Modifier::ITALIC,
+            TextModifier::Underlined => Modifier::UNDERLINED,
+            TextModifier::Reversed => Modifier::REVERSED,
+        }
+    }
+}
+
+/// How a heading wider than the viewport is displayed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleFit {
+    /// Wrap onto following lines like any other text
+    Wrap,
+    /// Drop the `#` markers, then truncate with an ellipsis if still too wide
+    #[default]
+    Shrink,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Bullets {
+    /// Marker placed before unordered list items
+    pub unordered: String,
+    /// Suffix placed after the number of ordered list items
+    pub ordered: String,
+}
+
+impl Default for Bullets {
+    fn default() -> Self {
+        Bullets {
+            unordered: "-".to_string(),
+            ordered: ".".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    pub margin_x: u16,
+    pub margin_y: u16,
+    /// Widest the content gets, centered on wider screens so lines stay readable
+    pub max_width: Option<u16>,
+    /// Keeps the heading a slide opens with at the top while the rest scrolls
+    pub pin_heading: bool,
+    /// How slides without a `center` directive of their own are centered
+    pub center: Center,
+}
+
+impl Layout {
+    /// Where the slide's content goes within `area`
+    pub fn content_area(&self, area: Rect) -> Rect {
+        let area = area.inner(Margin {
+            horizontal: self.margin_x,
+            vertical: self.margin_y,
+        });
+        match self.max_width {
+            Some(max_width) if max_width < area.width => Rect {
+                x: area.x + (area.width - max_width) / 2,
+                width: max_width,
+                ..area
+            },
+            _ => area,
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            margin_x: 2,
+            margin_y: 1,
+            max_width: None,
+            pin_heading: false,
+            center: Center::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Code {
+    /// Syntect theme coloring fenced code blocks with a known language, or `"none"`
+    /// to keep them in the flat `code` color
+    pub syntax_theme: String,
+    /// Set from the config's `[code]` rather than the theme file
+    #[serde(skip)]
+    pub languages: Languages,
+}
+
+impl Default for Code {
+    fn default() -> Self {
+        Code {
+            syntax_theme: "base16-ocean.dark".to_string(),
+            languages: Languages::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_theme_keeps_defaults() {
+        let theme: Theme = toml::from_str("[colors]\nheading = \"magenta\"").unwrap();
+        assert_eq!(theme.colors.heading, Color::Magenta);
+        assert_eq!(theme.colors.link, Color::Blue);
+        assert_eq!(theme.bullets, Bullets::default());
+    }
+
+    #[test]
+    fn test_content_area_keeps_margins_and_max_width() {
+        let mut layout = Layout::default();
+        assert_eq!(
+            layout.content_area(Rect::new(0, 1, 200, 40)),
+            Rect::new(2, 2, 196, 38)
+        );
+        layout.max_width = Some(100);
+        assert_eq!(
+            layout.content_area(Rect::new(0, 1, 200, 40)),
+            Rect::new(50, 2, 100, 38)
+        );
+        assert_eq!(
+            layout.content_area(Rect::new(0, 1, 80, 40)),
+            Rect::new(2, 2, 76, 38)
+        );
+    }
+
+    #[test]
+    fn test_theme_parses_hex_colors_and_layout() {
+        let content =
+            "[colors]\ncode = \"#ff8800\"\n[bullets]\nunordered = \"•\"\n[layout]\nmargin_x = 6";
+        let theme: Theme = toml::from_str(content).unwrap();
+        assert_eq!(theme.colors.code, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.bullets.unordered, "•");
+        assert_eq!(theme.layout.margin_x, 6);
+        assert_eq!(theme.layout.margin_y, 1);
+    }
+
+    #[test]
+    fn test_title_fit_policy() {
+        let theme: Theme = toml::from_str("[headings]\nfit = \"wrap\"").unwrap();
+        assert_eq!(theme.headings.fit, TitleFit::Wrap);
+        assert_eq!(Theme::default().headings.fit, TitleFit::Shrink);
+    }
+
+    #[test]
+    fn test_forced_theme_wins_over_the_deck_and_the_fallback() {
+        let deck = Theme::parse("[colors]\nheading = \"red\"").unwrap();
+        let fallback = Theme::parse("[colors]\nbackground = \"black\"").unwrap();
+        let mut choice = ThemeChoice {
+            forced: None,
+            fallback: fallback.clone(),
+            cycled: None,
+        };
+        assert_eq!(choice.pick(Some(deck.clone())), deck);
+        assert_eq!(choice.pick(None), fallback);
+        assert_eq!(Theme::default().colors.background, Color::Reset);
+
+        choice.forced = Some(Theme::default());
+        assert_eq!(choice.pick(Some(deck)), Theme::default());
+    }
+
+    #[test]
+    fn test_builtin_themes_parse_and_cycle_back_to_the_original() {
+        for (name, _) in BUILTIN {
+            let theme = Theme::named(Path::new(name)).unwrap();
+            assert_ne!(theme.colors.background, Color::Reset, "{}", name);
+        }
+        assert!(Theme::named(Path::new("no-such-theme")).is_err());
+
+        let deck = Theme::parse("[colors]\nheading = \"red\"").unwrap();
+        let mut choice = ThemeChoice::default();
+        let names: Vec<&str> = (0..5).map(|_| choice.cycle()).collect();
+        assert_eq!(names, ["dark", "light", "solarized", "gruvbox", "original"]);
+        assert_eq!(choice.pick(Some(deck.clone())), deck);
+        choice.cycle();
+        assert_eq!(choice.pick(Some(deck)), Theme::builtin("dark").unwrap());
+    }
+
+    #[test]
+    fn test_heading_levels_are_styled_on_their_own() {
+        let theme: Theme = toml::from_str(
+            "[headings.h1]\ncenter = true\nprefix = \"\"\n[headings.h3]\ncolor = \"gray\"\nmodifiers = [\"dim\", \"italic\"]",
+        )
+        .unwrap();
+        let h1 = theme.headings.level(1);
+        assert!(h1.center);
+        assert_eq!(h1.prefix(1), "");
+        assert_eq!(
+            h1.style(&theme.colors),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        );
+        let h3 = theme.headings.level(3);
+        assert_eq!(h3.prefix(3), "### ");
+        assert_eq!(
+            h3.style(&theme.colors),
+            Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::DIM | Modifier::ITALIC)
+        );
+    }
+
+    #[test]
+    fn test_breadcrumb_is_opt_in() {
+        assert!(!Theme::default().headings.breadcrumb);
+        let theme: Theme = toml::from_str("[headings]\nbreadcrumb = true").unwrap();
+        assert!(theme.headings.breadcrumb);
+        assert_eq!(theme.headings.fit, TitleFit::Shrink);
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use crate::rehearsal::clock;
+
+/// Stopwatch for the whole talk, shown in the header once started
+#[derive(Debug, Default)]
+pub struct Timer {
+    /// Time counted before the last pause
+    banked: Duration,
+    /// Start of the stretch being counted, unless paused
+    running_since: Option<Instant>,
+}
+
+impl Timer {
+    /// Starts the timer, or pauses and resumes it once running
+    pub fn toggle(&mut self, now: Instant) {
+        match self.running_since.take() {
+            Some(since) => self.banked += now.saturating_duration_since(since),
+            None => self.running_since = Some(now),
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.banked
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+
+    /// Elapsed time for the header, e.g. `12:05` or `12:05 paused`; nothing before
+    /// the timer was first started
+    pub fn status(&self, now: Instant) -> Option<String> {
+        let elapsed = clock(self.elapsed(now));
+        match self.running_since {
+            Some(_) => Some(elapsed),
+            None if self.banked > Duration::ZERO => Some(format!("{} paused", elapsed)),
+            None => None,
+        }
+    }
+
+    /// When a running timer next shows a different second
+    pub fn next_tick(&self, now: Instant) -> Option<Instant> {
+        self.running_since?;
+        let into_second = self.elapsed(now).subsec_nanos();
+        Some(now + Duration::from_secs(1) - Duration::from_nanos(into_second.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_pauses_and_resumes() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut timer = Timer::default();
+        assert_eq!(timer.status(start), None);
+        assert_eq!(timer.next_tick(start), None);
+
+        timer.toggle(start);
+        assert_eq!(timer.status(at(65)).as_deref(), Some("1:05"));
+        assert_eq!(timer.next_tick(at(65)), Some(at(66)));
+
+        timer.toggle(at(70));
+        assert_eq!(timer.status(at(100)).as_deref(), Some("1:10 paused"));
+        assert_eq!(timer.next_tick(at(100)), None);
+
+        timer.toggle(at(100));
+        assert_eq!(timer.elapsed(at(105)), Duration::from_secs(75));
+    }
+}
@@ -0,0 +1,99 @@
+//! Hot reload for `--watch` and theme previews: the file is polled and its new source
+//! published once a change has settled
+//!
+//! Polling keeps this free of platform APIs and catches editors that save by renaming
+//! a temporary file over the deck.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::events::AppEvent;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification time and length, which together tell a saved file apart
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[derive(Debug)]
+pub struct Watcher {
+    path: PathBuf,
+    loaded: Stamp,
+    polled: Stamp,
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> Self {
+        let loaded = stamp(path);
+        Watcher {
+            path: path.to_path_buf(),
+            loaded,
+            polled: loaded,
+        }
+    }
+
+    /// True once the file differs from the loaded one and stayed the same since the
+    /// last poll, so a save still in progress isn't reloaded half written
+    pub fn poll(&mut self) -> bool {
+        let current = stamp(&self.path);
+        let settled = current == self.polled;
+        self.polled = current;
+        if settled && current.is_some() && current != self.loaded {
+            self.loaded = current;
+            return true;
+        }
+        false
+    }
+
+    /// Publishes the file's source after every change as the event `changed` makes of
+    /// it, e.g. `AppEvent::Reload`, until the loop stops listening
+    pub fn spawn(mut self, events: UnboundedSender<AppEvent>, changed: fn(String) -> AppEvent) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                if !self.poll() {
+                    continue;
+                }
+                let event = match std::fs::read_to_string(&self.path) {
+                    Ok(source) => changed(source),
+                    Err(_) => continue,
+                };
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_are_reported_once_settled() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("talk.md");
+        std::fs::write(&path, "# One\n").unwrap();
+        let mut watcher = Watcher::new(&path);
+        assert!(!watcher.poll());
+
+        std::fs::write(&path, "# One\n\n# Two\n").unwrap();
+        // The first poll sees the change, the next one that it has settled
+        assert!(!watcher.poll());
+        assert!(watcher.poll());
+        assert!(!watcher.poll());
+
+        // A deleted file is waited out rather than reloaded
+        std::fs::remove_file(&path).unwrap();
+        assert!(!watcher.poll());
+        assert!(!watcher.poll());
+    }
+}